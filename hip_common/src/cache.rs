@@ -1,6 +1,7 @@
 use itertools::Itertools;
 use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
 use rusqlite::Connection;
+use std::borrow::Cow;
 use std::ffi::{CStr, CString};
 use std::fmt::Display;
 use std::marker::PhantomData;
@@ -11,8 +12,12 @@ pub trait KernelExtendedData {
     const INPUT_COLUMNS: &'static [[&'static str; 2]];
 }
 
+// Used when ZLUDA_CACHE_MAX_SIZE is not set
+pub const DEFAULT_MAX_SIZE: u64 = 2 * 1024 * 1024 * 1024;
+
 pub struct KernelRepository<T: KernelExtendedData> {
     cache_file: Option<PathBuf>,
+    max_size: Option<u64>,
     insert_kernel: String,
     update_last_used: String,
     select_kernel: String,
@@ -104,6 +109,7 @@ impl<T: KernelExtendedData> KernelRepository<T> {
         };
         let result = Self {
             cache_file,
+            max_size: max_size_from_env(),
             insert_kernel,
             update_last_used,
             select_kernel,
@@ -163,6 +169,9 @@ impl<T: KernelExtendedData> KernelRepository<T> {
             insert_kernel.execute(rusqlite::params_from_iter(
                 IntoIterator::into_iter([common_values, input_values]).flatten(),
             ))?;
+            if let Some(max_size) = self.max_size {
+                evict_least_recently_used(&tx, max_size)?;
+            }
         }
         tx.commit()
     }
@@ -235,6 +244,91 @@ impl<T: KernelExtendedData> KernelRepository<T> {
     }
 }
 
+// ZLUDA_CACHE_MAX_SIZE is in MiB, 0 disables the limit
+pub fn max_size_from_env() -> Option<u64> {
+    match std::env::var("ZLUDA_CACHE_MAX_SIZE")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+    {
+        Some(0) => None,
+        Some(mebibytes) => Some(mebibytes.saturating_mul(1024 * 1024)),
+        None => Some(DEFAULT_MAX_SIZE),
+    }
+}
+
+pub fn total_size(connection: &Connection) -> rusqlite::Result<u64> {
+    connection.query_row(
+        "SELECT value FROM globals WHERE key = 'total_binary_size';",
+        [],
+        |row| row.get(0),
+    )
+}
+
+// Removes least recently used binaries until the total size fits in max_size,
+// returns the number of removed binaries
+pub fn evict_least_recently_used(
+    connection: &Connection,
+    max_size: u64,
+) -> rusqlite::Result<usize> {
+    if total_size(connection)? <= max_size {
+        return Ok(0);
+    }
+    connection.execute(
+        "
+        DELETE FROM
+            kernels
+        WHERE
+            id IN (
+                SELECT
+                    id
+                FROM
+                    (
+                        SELECT
+                            id,
+                            SUM(length(binary)) OVER (ORDER BY last_used DESC, id DESC) AS kept_size
+                        FROM
+                            kernels
+                    )
+                WHERE
+                    kept_size > ?1
+            );",
+        rusqlite::params![max_size],
+    )
+}
+
+pub fn evict_other_versions(
+    connection: &Connection,
+    compiler_version: &str,
+    git_hash: &str,
+) -> rusqlite::Result<usize> {
+    connection.execute(
+        "
+        DELETE FROM
+            kernels
+        WHERE
+            compiler_version != ?1 OR git_hash != ?2;",
+        rusqlite::params![compiler_version, git_hash],
+    )
+}
+
+// zluda.db holds kernels compiled for CUDA applications. Both ZLUDA and
+// the offline compiler (when warming up the cache) must agree on its key
+pub const COMPUTE_CACHE_FILE: &'static str = "zluda.db";
+
+pub struct ComputeExtendedData;
+
+impl KernelExtendedData for ComputeExtendedData {
+    const INPUT_COLUMNS: &'static [[&'static str; 2]] = &[["compilation_mode", "INTEGER NOT NULL"]];
+}
+
+pub fn compute_hash(ptx_modules: &[Cow<'_, str>]) -> String {
+    let mut hasher = blake3::Hasher::new();
+    for module in ptx_modules {
+        hasher.update(module.as_bytes());
+    }
+    hasher.finalize().to_hex().to_string()
+}
+
 pub struct SqlCStrRef<'a>(pub &'a CStr);
 
 impl<'a> rusqlite::ToSql for SqlCStrRef<'a> {
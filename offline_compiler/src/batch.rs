@@ -0,0 +1,265 @@
+use crate::{compile, CompileError};
+use comgr::Comgr;
+use goblin::elf::Elf;
+use hip_common::CompilationMode;
+use ptx::{ast, ModuleParserExt};
+use serde::Serialize;
+use std::ffi::CStr;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use std::{fs, io, thread};
+
+#[derive(Serialize)]
+struct Report {
+    compiler_version: String,
+    git_hash: &'static str,
+    total: usize,
+    succeeded: usize,
+    failed: usize,
+    modules: Vec<ModuleReport>,
+}
+
+#[derive(Serialize)]
+struct ModuleReport {
+    path: PathBuf,
+    isa: String,
+    mode: u8,
+    success: bool,
+    // One of: "io", "parse", "translate", "comgr", "panic"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_kind: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    compile_time_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    binary_size: Option<usize>,
+    kernels: Vec<KernelReport>,
+}
+
+#[derive(Serialize)]
+struct KernelReport {
+    name: String,
+    // Kernel is considered compiled if its descriptor (<name>.kd) is present
+    // in the final binary
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code_size: Option<u64>,
+}
+
+struct Job<'a> {
+    path: &'a Path,
+    isa: &'a CStr,
+    mode: CompilationMode,
+}
+
+// Compiles every PTX file found in inputs for every (ISA, compilation mode)
+// on `jobs` threads and writes JSON summary to `report_path`. Compilation
+// failures (including panics in the compiler) are recorded, not propagated
+pub(crate) fn run(
+    comgr: &Comgr,
+    report_path: &Path,
+    inputs: &[PathBuf],
+    isas: &[&CStr],
+    modes: &[CompilationMode],
+    jobs: usize,
+) -> io::Result<()> {
+    let mut files = Vec::new();
+    for input in inputs {
+        collect_ptx_files(input, &mut files)?;
+    }
+    files.sort();
+    let work = files
+        .iter()
+        .flat_map(|path| {
+            isas.iter().flat_map(move |isa| {
+                modes.iter().map(move |mode| Job {
+                    path,
+                    isa,
+                    mode: *mode,
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+    let next_job = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(work.len()));
+    thread::scope(|scope| {
+        for _ in 0..jobs.max(1).min(work.len()) {
+            scope.spawn(|| loop {
+                let index = next_job.fetch_add(1, Ordering::Relaxed);
+                let job = match work.get(index) {
+                    Some(job) => job,
+                    None => break,
+                };
+                let module_report = compile_job(comgr, job);
+                eprintln!(
+                    "[{}/{}] {} {} mode {}: {}",
+                    index + 1,
+                    work.len(),
+                    module_report.path.display(),
+                    module_report.isa,
+                    module_report.mode,
+                    if module_report.success {
+                        "ok"
+                    } else {
+                        "FAILED"
+                    }
+                );
+                results.lock().unwrap().push((index, module_report));
+            });
+        }
+    });
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    let modules = results
+        .into_iter()
+        .map(|(_, module_report)| module_report)
+        .collect::<Vec<_>>();
+    let succeeded = modules.iter().filter(|module| module.success).count();
+    let report = Report {
+        compiler_version: comgr.version().unwrap_or_default(),
+        git_hash: env!("VERGEN_GIT_SHA"),
+        total: modules.len(),
+        succeeded,
+        failed: modules.len() - succeeded,
+        modules,
+    };
+    let file = io::BufWriter::new(fs::File::create(report_path)?);
+    serde_json::to_writer_pretty(file, &report)?;
+    println!(
+        "Compiled: {}, failed: {}, report written to {}",
+        report.succeeded,
+        report.failed,
+        report_path.display()
+    );
+    Ok(())
+}
+
+fn collect_ptx_files(path: &Path, result: &mut Vec<PathBuf>) -> io::Result<()> {
+    if !path.is_dir() {
+        result.push(path.to_path_buf());
+        return Ok(());
+    }
+    for entry in fs::read_dir(path)? {
+        let entry_path = entry?.path();
+        if entry_path.is_dir() {
+            collect_ptx_files(&entry_path, result)?;
+        } else if entry_path.extension().map_or(false, |ext| ext == "ptx") {
+            result.push(entry_path);
+        }
+    }
+    Ok(())
+}
+
+fn compile_job(comgr: &Comgr, job: &Job) -> ModuleReport {
+    let mut module_report = ModuleReport {
+        path: job.path.to_path_buf(),
+        isa: job
+            .isa
+            .to_string_lossy()
+            .trim_start_matches("amdgcn-amd-amdhsa--")
+            .to_string(),
+        mode: job.mode as u8,
+        success: false,
+        error_kind: None,
+        error: None,
+        compile_time_ms: 0.0,
+        binary_size: None,
+        kernels: Vec::new(),
+    };
+    let text = match fs::read_to_string(job.path) {
+        Ok(text) => text,
+        Err(error) => {
+            module_report.error_kind = Some("io");
+            module_report.error = Some(error.to_string());
+            return module_report;
+        }
+    };
+    let kernel_names = match ptx::ModuleParser::parse_checked(&text) {
+        Ok(ast) => kernel_names(&ast),
+        Err(_) => Vec::new(),
+    };
+    let start = Instant::now();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        compile(comgr, job.isa, job.mode, &text)
+    }));
+    module_report.compile_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+    let binary = match result {
+        Ok(Ok(binary)) => binary,
+        Ok(Err(error)) => {
+            module_report.error_kind = Some(match error {
+                CompileError::Parse(_) => "parse",
+                CompileError::Translate(_) => "translate",
+                CompileError::Comgr(_) => "comgr",
+            });
+            module_report.error = Some(error.to_string());
+            module_report.kernels = failed_kernels(kernel_names);
+            return module_report;
+        }
+        Err(panic) => {
+            module_report.error_kind = Some("panic");
+            module_report.error = panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned());
+            module_report.kernels = failed_kernels(kernel_names);
+            return module_report;
+        }
+    };
+    module_report.binary_size = Some(binary.len());
+    module_report.kernels = kernel_reports(kernel_names, &binary);
+    module_report.success = module_report.kernels.iter().all(|kernel| kernel.success);
+    if !module_report.success {
+        module_report.error_kind = Some("comgr");
+        module_report.error = Some("Kernels missing from the compiled binary".to_string());
+    }
+    module_report
+}
+
+fn kernel_names(ast: &ast::Module) -> Vec<String> {
+    ast.directives
+        .iter()
+        .filter_map(|directive| match directive {
+            ast::Directive::Method(_, method) => match method.func_directive.name {
+                ast::MethodName::Kernel(name) if method.body.is_some() => Some(name.to_string()),
+                _ => None,
+            },
+            ast::Directive::Variable(..) => None,
+        })
+        .collect()
+}
+
+fn failed_kernels(kernel_names: Vec<String>) -> Vec<KernelReport> {
+    kernel_names
+        .into_iter()
+        .map(|name| KernelReport {
+            name,
+            success: false,
+            code_size: None,
+        })
+        .collect()
+}
+
+fn kernel_reports(kernel_names: Vec<String>, binary: &[u8]) -> Vec<KernelReport> {
+    let elf = Elf::parse(binary).ok();
+    let symbol = |name: &str| {
+        let elf = elf.as_ref()?;
+        elf.syms
+            .iter()
+            .find(|symbol| elf.strtab.get_at(symbol.st_name) == Some(name))
+    };
+    kernel_names
+        .into_iter()
+        .map(|name| {
+            let success = symbol(&format!("{}.kd", name)).is_some();
+            let code_size = symbol(&name).map(|symbol| symbol.st_size);
+            KernelReport {
+                name,
+                success,
+                code_size,
+            }
+        })
+        .collect()
+}
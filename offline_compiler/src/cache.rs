@@ -0,0 +1,119 @@
+use crate::compile;
+use comgr::Comgr;
+use hip_common::cache::{self, ComputeExtendedData, KernelRepository};
+use hip_common::CompilationMode;
+use std::borrow::Cow;
+use std::convert::TryInto;
+use std::ffi::CStr;
+use std::path::{Path, PathBuf};
+use std::{fs, mem, slice};
+use zluda_dark_api::{FatbinModule, FatbinModuleHandle, ELF_MAGIC, FATBIN_MAGIC};
+
+// Fills zluda.db with binaries for every (module, ISA, compilation mode),
+// keyed exactly like zluda::impl::cache::KernelCache does at runtime
+pub(crate) fn warm(
+    comgr: &Comgr,
+    cache_file: PathBuf,
+    inputs: &[PathBuf],
+    isas: &[&CStr],
+    modes: &[CompilationMode],
+) {
+    let compiler_version = comgr.version().unwrap();
+    let git_hash = env!("VERGEN_GIT_SHA");
+    let repository = KernelRepository::<ComputeExtendedData>::new(Some(cache_file)).unwrap();
+    let (mut compiled, mut cached, mut failed) = (0usize, 0usize, 0usize);
+    for input in inputs {
+        let modules = load_modules(input);
+        if modules.is_empty() {
+            eprintln!("{}: no PTX modules found", input.display());
+        }
+        for (index, ptx) in modules.iter().enumerate() {
+            let ptx_modules = [Cow::Borrowed(ptx.as_str())];
+            let hash = cache::compute_hash(&ptx_modules);
+            for isa in isas {
+                for mode in modes {
+                    let now = KernelRepository::<ComputeExtendedData>::now().unwrap();
+                    let mode_value = *mode as u8;
+                    let input_values = rusqlite::params![mode_value];
+                    if let Ok(Some(_)) = repository.try_load_program(
+                        now,
+                        &hash,
+                        &compiler_version,
+                        git_hash,
+                        isa,
+                        input_values,
+                    ) {
+                        cached += 1;
+                        continue;
+                    }
+                    match compile(comgr, isa, *mode, ptx) {
+                        Ok(binary) => {
+                            repository
+                                .save_program(
+                                    now,
+                                    &hash,
+                                    &compiler_version,
+                                    git_hash,
+                                    isa,
+                                    &binary,
+                                    input_values,
+                                )
+                                .unwrap();
+                            compiled += 1;
+                        }
+                        Err(error) => {
+                            eprintln!(
+                                "{} (module {}, {}, mode {}): {}",
+                                input.display(),
+                                index,
+                                isa.to_string_lossy(),
+                                mode_value,
+                                error
+                            );
+                            failed += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    println!(
+        "Compiled: {}, already cached: {}, failed: {}",
+        compiled, cached, failed
+    );
+}
+
+// Returns PTX text of every module the way ZLUDA would see it at runtime:
+// a fatbin module (whole fatbin file or one element of .nv_fatbin section
+// of an executable) contributes its PTX with the highest sm version.
+// Relocatable device code (__nv_relfatbin) is not supported
+fn load_modules(path: &Path) -> Vec<String> {
+    let data = fs::read(path).unwrap();
+    // Fatbin headers need 8 byte alignment
+    let mut buffer = vec![0u64; (data.len() + mem::size_of::<u64>() - 1) / mem::size_of::<u64>()];
+    let aligned =
+        unsafe { slice::from_raw_parts_mut(buffer.as_mut_ptr().cast::<u8>(), data.len()) };
+    aligned.copy_from_slice(&data);
+    let magic = aligned
+        .get(..mem::size_of::<u32>())
+        .map(|magic| u32::from_ne_bytes(magic.try_into().unwrap()));
+    let fatbins = match magic {
+        Some(ELF_MAGIC) => match hip_common::kernel_metadata::get_section(".nv_fatbin", aligned) {
+            Some(section) => unsafe { FatbinModuleHandle::split_section(section) },
+            None => Vec::new(),
+        },
+        Some(FATBIN_MAGIC) => unsafe { FatbinModuleHandle::split_section(aligned) },
+        _ => return String::from_utf8(data).into_iter().collect(),
+    };
+    fatbins
+        .iter()
+        .filter_map(|fatbin| match unsafe { fatbin.get() } {
+            Ok(FatbinModule::Files(files)) => files
+                .ptx_files()
+                .into_iter()
+                .next()
+                .map(|(text, _)| text.into_owned()),
+            _ => None,
+        })
+        .collect()
+}
@@ -9,15 +9,18 @@ use std::ffi::{c_void, CStr};
 use std::path::Path;
 use std::rc::Rc;
 use std::{ffi::CString, fs, path::PathBuf};
-use std::{iter, ptr};
+use std::{fmt, iter, process, ptr, thread};
+
+mod batch;
+mod cache;
 
 #[derive(FromArgs)]
 /// ZLUDA offline compiler
 struct CompilerArguments {
-    /// LLVM AMDGPU ISA, defaults to "gfx1030"
-    #[argh(option, default = "default_isa()")]
-    isa: String,
-    /// paths to PTX files
+    /// LLVM AMDGPU ISA as reported by HIP (e.g. "gfx90a:sramecc+:xnack-"), can be repeated with --cache or --report, defaults to "gfx1030"
+    #[argh(option)]
+    isa: Vec<String>,
+    /// paths to PTX files, with --cache also fatbinaries, executables and shared libraries, with --report also directories searched recursively for .ptx files
     #[argh(positional)]
     inputs: Vec<PathBuf>,
     /// directory with output, will be created if does not exist
@@ -26,9 +29,18 @@ struct CompilerArguments {
     /// name of an OptiX program, if provided PTX will be compiled in raytracing mode
     #[argh(option)]
     rt_program: Option<String>,
-    /// compilation mode: 1 - Wave32, 2 - Wave32OnWave64, 3 - DoubleWave32OnWave64, defaults to Wave32
+    /// compilation mode: 1 - Wave32, 2 - Wave32OnWave64, 3 - DoubleWave32OnWave64, can be repeated with --cache or --report, defaults to Wave32 (all modes with --report)
     #[argh(option, short = 'm')]
-    mode: Option<u8>,
+    mode: Vec<u8>,
+    /// instead of dumping intermediate files, compile every module for every ISA and mode into this ZLUDA cache file (zluda.db)
+    #[argh(option)]
+    cache: Option<PathBuf>,
+    /// instead of dumping intermediate files, compile every PTX file for every ISA and mode in parallel and write a JSON report of the results to this file
+    #[argh(option)]
+    report: Option<PathBuf>,
+    /// number of compilation threads used by --report, defaults to the number of CPUs
+    #[argh(option, short = 'j')]
+    jobs: Option<usize>,
     /// print LLVM version
     #[argh(switch, short = 'V')]
     version: bool,
@@ -39,23 +51,64 @@ fn default_isa() -> String {
 }
 
 fn main() {
-    let args: CompilerArguments = argh::from_env();
+    let mut args: CompilerArguments = argh::from_env();
     let comgr = comgr::Comgr::find_and_load().unwrap();
     if args.version {
         println!("{}", comgr.version().unwrap());
         return;
     }
+    if args.isa.is_empty() {
+        args.isa.push(default_isa());
+    }
+    let modes = if args.mode.is_empty() && args.report.is_some() {
+        vec![
+            CompilationMode::Wave32,
+            CompilationMode::Wave32OnWave64,
+            CompilationMode::DoubleWave32OnWave64,
+        ]
+    } else if args.mode.is_empty() {
+        vec![CompilationMode::Wave32]
+    } else {
+        args.mode
+            .iter()
+            .map(|mode| CompilationMode::from_u8(*mode).expect("Invalid compilation mode"))
+            .collect::<Vec<_>>()
+    };
+    let isas = args
+        .isa
+        .iter()
+        .map(|isa| CString::new(format!("amdgcn-amd-amdhsa--{}", isa)).unwrap())
+        .collect::<Vec<_>>();
+    let isas = isas.iter().map(CString::as_c_str).collect::<Vec<_>>();
+    if let Some(cache_file) = args.cache {
+        cache::warm(&comgr, cache_file, &args.inputs, &isas, &modes);
+        return;
+    }
+    if let Some(report) = args.report {
+        let jobs = args.jobs.unwrap_or_else(|| {
+            thread::available_parallelism()
+                .map(|jobs| jobs.get())
+                .unwrap_or(1)
+        });
+        if let Err(error) = batch::run(&comgr, &report, &args.inputs, &isas, &modes, jobs) {
+            eprintln!("{}: {}", report.display(), error);
+            process::exit(1);
+        }
+        return;
+    }
     let mut full_isa = "amdgcn-amd-amdhsa--".to_string();
-    full_isa.push_str(&args.isa);
+    full_isa.push_str(&args.isa[0]);
     let main_path = &args.inputs[0];
     let inputs = args
         .inputs
         .iter()
         .map(|input| fs::read_to_string(&input).unwrap())
         .collect::<Vec<_>>();
-    let asts = inputs
+    let asts = args
+        .inputs
         .iter()
-        .map(|input| ptx::ModuleParser::parse_checked(input).unwrap())
+        .zip(inputs.iter())
+        .map(|(path, input)| parse_or_exit(path, input))
         .collect::<Vec<_>>();
     let output_dir = if let Some(output) = args.output {
         fs::create_dir_all(&output).unwrap();
@@ -78,26 +131,103 @@ fn main() {
                 asts.into_iter().next().unwrap(),
             )
         }
-        None => {
-            let mode = args
-                .mode
-                .map(CompilationMode::from_u8)
-                .flatten()
-                .unwrap_or(CompilationMode::Wave32);
-            compile_and_dump(full_isa, output_dir, mode, comgr, main_path, asts)
+        None => compile_and_dump(
+            full_isa,
+            output_dir,
+            modes[0],
+            comgr,
+            &args.inputs,
+            &inputs,
+            asts,
+        ),
+    }
+}
+
+fn parse_or_exit<'input>(path: &Path, text: &'input str) -> ptx::ast::Module<'input> {
+    match ptx::ModuleParser::parse_checked(text) {
+        Ok(ast) => ast,
+        Err(errors) => {
+            let file_name = path.to_string_lossy();
+            for error in errors.iter() {
+                let error =
+                    unsafe { ptx::DisplayParseError::new(error, text) }.with_file_name(&file_name);
+                eprintln!("{}", error);
+            }
+            process::exit(1)
         }
     }
 }
 
+pub(crate) enum CompileError {
+    Parse(String),
+    Translate(String),
+    Comgr(comgr::sys::amd_comgr_status_t),
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::Parse(errors) => write!(f, "{}", errors),
+            CompileError::Translate(error) => write!(f, "{}", error),
+            CompileError::Comgr(status) => write!(f, "Compilation failed: {:?}", status),
+        }
+    }
+}
+
+// Same steps as zluda::impl::module does for a single PTX module at runtime
+pub(crate) fn compile(
+    comgr: &Comgr,
+    isa: &CStr,
+    compilation_mode: CompilationMode,
+    ptx: &str,
+) -> Result<Vec<u8>, CompileError> {
+    let ast = ptx::ModuleParser::parse_checked(ptx).map_err(|errors| {
+        CompileError::Parse(
+            errors
+                .iter()
+                .map(|error| unsafe { ptx::DisplayParseError::new(error, ptx) }.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    })?;
+    let llvm_module = ptx::to_llvm_module(compilation_mode, vec![ast]).map_err(|error| {
+        CompileError::Translate(
+            unsafe { ptx::DisplayTranslateError::new(&error, &[ptx]) }.to_string(),
+        )
+    })?;
+    comgr
+        .compile(
+            compilation_mode,
+            isa,
+            ptx::Module::get_bitcode_multi(iter::once(&llvm_module)).into_iter(),
+            &llvm_module.metadata.to_elf_section(),
+        )
+        .map_err(CompileError::Comgr)
+}
+
 fn compile_and_dump<'input>(
     full_isa: String,
     output_dir: Cow<Path>,
     compilation_mode: CompilationMode,
     comgr: Comgr,
-    main_path: &PathBuf,
+    paths: &[PathBuf],
+    texts: &[String],
     asts: Vec<ptx::ast::Module<'input>>,
 ) {
-    let compiled_module = ptx::to_llvm_module(compilation_mode, asts).unwrap();
+    let main_path = &paths[0];
+    let compiled_module = match ptx::to_llvm_module(compilation_mode, asts) {
+        Ok(compiled_module) => compiled_module,
+        Err(error) => {
+            let file_name = error
+                .span()
+                .map(|span| paths[span.module].to_string_lossy())
+                .unwrap_or_default();
+            let error = unsafe { ptx::DisplayTranslateError::new(&error, texts) }
+                .with_file_name(&file_name);
+            eprintln!("{}", error);
+            process::exit(1)
+        }
+    };
     let mut output_path = output_dir.to_path_buf();
     output_path.push(main_path.file_name().unwrap());
     output_path.set_extension("ll");
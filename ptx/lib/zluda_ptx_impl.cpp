@@ -89,6 +89,7 @@ enum class CompilationMode : uint8_t
 #define PRIVATE_SPACE __attribute__((address_space(5)))
 
 typedef half half4 __attribute__((ext_vector_type(4)));
+typedef half zluda_half2 __attribute__((ext_vector_type(2)));
 
 extern "C" __device__ const CONSTANT_SPACE CompilationMode FUNC_CALL(COMPILATION_MODE);
 extern "C" __device__ const CONSTANT_SPACE bool FUNC_CALL(IS_WINDOWS);
@@ -899,6 +900,142 @@ extern "C"
     tex_a2d_f16(s32, int);
     tex_a2d_f16(f32, float);
 
+#define tex_grad_1d(CHANNEL_TYPE, HIP_CHANNEL_TYPE)                                                                                                                                                    \
+    HIP_CHANNEL_TYPE##4 ::Native_vec_ FUNC(tex_grad_1d_v4_##CHANNEL_TYPE##_f32)(struct textureReference GLOBAL_SPACE * ptr, float1::Native_vec_ x, float1::Native_vec_ dpdx, float1::Native_vec_ dpdy) \
+    {                                                                                                                                                                                                  \
+        hipTextureObject_t textureObject = ptr->textureObject;                                                                                                                                         \
+        return tex1DGrad<HIP_CHANNEL_TYPE##4>(textureObject, x.x, dpdx.x, dpdy.x).data;                                                                                                                \
+    }                                                                                                                                                                                                  \
+    HIP_CHANNEL_TYPE##4 ::Native_vec_ FUNC(tex_grad_indirect_1d_v4_##CHANNEL_TYPE##_f32)(uint64_t texobj, float1::Native_vec_ x, float1::Native_vec_ dpdx, float1::Native_vec_ dpdy)                   \
+    {                                                                                                                                                                                                  \
+        hipTextureObject_t textureObject = (hipTextureObject_t)texobj;                                                                                                                                 \
+        return tex1DGrad<HIP_CHANNEL_TYPE##4>(textureObject, x.x, dpdx.x, dpdy.x).data;                                                                                                                \
+    }
+#define tex_grad_2d(CHANNEL_TYPE, HIP_CHANNEL_TYPE)                                                                                                                                                        \
+    HIP_CHANNEL_TYPE##4 ::Native_vec_ FUNC(tex_grad_2d_v4_##CHANNEL_TYPE##_f32)(struct textureReference GLOBAL_SPACE * ptr, float2::Native_vec_ coord, float2::Native_vec_ dpdx, float2::Native_vec_ dpdy) \
+    {                                                                                                                                                                                                      \
+        hipTextureObject_t textureObject = ptr->textureObject;                                                                                                                                             \
+        return tex2DGrad<HIP_CHANNEL_TYPE##4>(textureObject, coord.x, coord.y, float2(dpdx.x, dpdx.y), float2(dpdy.x, dpdy.y)).data;                                                                       \
+    }                                                                                                                                                                                                      \
+    HIP_CHANNEL_TYPE##4 ::Native_vec_ FUNC(tex_grad_indirect_2d_v4_##CHANNEL_TYPE##_f32)(uint64_t texobj, float2::Native_vec_ coord, float2::Native_vec_ dpdx, float2::Native_vec_ dpdy)                   \
+    {                                                                                                                                                                                                      \
+        hipTextureObject_t textureObject = (hipTextureObject_t)texobj;                                                                                                                                     \
+        return tex2DGrad<HIP_CHANNEL_TYPE##4>(textureObject, coord.x, coord.y, float2(dpdx.x, dpdx.y), float2(dpdy.x, dpdy.y)).data;                                                                       \
+    }
+#define tex_grad_3d(CHANNEL_TYPE, HIP_CHANNEL_TYPE)                                                                                                                                                        \
+    HIP_CHANNEL_TYPE##4 ::Native_vec_ FUNC(tex_grad_3d_v4_##CHANNEL_TYPE##_f32)(struct textureReference GLOBAL_SPACE * ptr, float4::Native_vec_ coord, float4::Native_vec_ dpdx, float4::Native_vec_ dpdy) \
+    {                                                                                                                                                                                                      \
+        hipTextureObject_t textureObject = ptr->textureObject;                                                                                                                                             \
+        return tex3DGrad<HIP_CHANNEL_TYPE##4>(textureObject, coord.x, coord.y, coord.z, float4(dpdx.x, dpdx.y, dpdx.z, dpdx.w), float4(dpdy.x, dpdy.y, dpdy.z, dpdy.w)).data;                              \
+    }                                                                                                                                                                                                      \
+    HIP_CHANNEL_TYPE##4 ::Native_vec_ FUNC(tex_grad_indirect_3d_v4_##CHANNEL_TYPE##_f32)(uint64_t texobj, float4::Native_vec_ coord, float4::Native_vec_ dpdx, float4::Native_vec_ dpdy)                   \
+    {                                                                                                                                                                                                      \
+        hipTextureObject_t textureObject = (hipTextureObject_t)texobj;                                                                                                                                     \
+        return tex3DGrad<HIP_CHANNEL_TYPE##4>(textureObject, coord.x, coord.y, coord.z, float4(dpdx.x, dpdx.y, dpdx.z, dpdx.w), float4(dpdy.x, dpdy.y, dpdy.z, dpdy.w)).data;                              \
+    }
+#define tex_grad_a1d(CHANNEL_TYPE, HIP_CHANNEL_TYPE)                                                                                                                          \
+    HIP_CHANNEL_TYPE##4 ::Native_vec_ FUNC(tex_grad_a1d_v4_##CHANNEL_TYPE##_f32)(struct textureReference GLOBAL_SPACE * ptr, uint32_t layer, float x, float dpdx, float dpdy) \
+    {                                                                                                                                                                         \
+        hipTextureObject_t textureObject = ptr->textureObject;                                                                                                                \
+        return tex1DLayeredGrad<HIP_CHANNEL_TYPE##4>(textureObject, x, int(layer), dpdx, dpdy).data;                                                                          \
+    }                                                                                                                                                                         \
+    HIP_CHANNEL_TYPE##4 ::Native_vec_ FUNC(tex_grad_indirect_a1d_v4_##CHANNEL_TYPE##_f32)(uint64_t texobj, uint32_t layer, float x, float dpdx, float dpdy)                   \
+    {                                                                                                                                                                         \
+        hipTextureObject_t textureObject = (hipTextureObject_t)texobj;                                                                                                        \
+        return tex1DLayeredGrad<HIP_CHANNEL_TYPE##4>(textureObject, x, int(layer), dpdx, dpdy).data;                                                                          \
+    }
+#define tex_grad_a2d(CHANNEL_TYPE, HIP_CHANNEL_TYPE)                                                                                                                                                               \
+    HIP_CHANNEL_TYPE##4 ::Native_vec_ FUNC(tex_grad_a2d_v4_##CHANNEL_TYPE##_f32)(struct textureReference GLOBAL_SPACE * ptr, uint32_t layer, float x, float y, float2::Native_vec_ dpdx, float2::Native_vec_ dpdy) \
+    {                                                                                                                                                                                                              \
+        hipTextureObject_t textureObject = ptr->textureObject;                                                                                                                                                     \
+        return tex2DLayeredGrad<HIP_CHANNEL_TYPE##4>(textureObject, x, y, int(layer), float2(dpdx.x, dpdx.y), float2(dpdy.x, dpdy.y)).data;                                                                        \
+    }                                                                                                                                                                                                              \
+    HIP_CHANNEL_TYPE##4 ::Native_vec_ FUNC(tex_grad_indirect_a2d_v4_##CHANNEL_TYPE##_f32)(uint64_t texobj, uint32_t layer, float x, float y, float2::Native_vec_ dpdx, float2::Native_vec_ dpdy)                   \
+    {                                                                                                                                                                                                              \
+        hipTextureObject_t textureObject = (hipTextureObject_t)texobj;                                                                                                                                             \
+        return tex2DLayeredGrad<HIP_CHANNEL_TYPE##4>(textureObject, x, y, int(layer), float2(dpdx.x, dpdx.y), float2(dpdy.x, dpdy.y)).data;                                                                        \
+    }
+    tex_grad_1d(u32, uint);
+    tex_grad_1d(s32, int);
+    tex_grad_1d(f32, float);
+    tex_grad_2d(u32, uint);
+    tex_grad_2d(s32, int);
+    tex_grad_2d(f32, float);
+    tex_grad_3d(u32, uint);
+    tex_grad_3d(s32, int);
+    tex_grad_3d(f32, float);
+    tex_grad_a1d(u32, uint);
+    tex_grad_a1d(s32, int);
+    tex_grad_a1d(f32, float);
+    tex_grad_a2d(u32, uint);
+    tex_grad_a2d(s32, int);
+    tex_grad_a2d(f32, float);
+
+#define tld4_2d(COMPONENT, COMPONENT_INDEX, CHANNEL_TYPE, HIP_CHANNEL_TYPE)                                                                                      \
+    HIP_CHANNEL_TYPE##4 ::Native_vec_ FUNC(tld4_##COMPONENT##_2d_v4_##CHANNEL_TYPE##_f32)(struct textureReference GLOBAL_SPACE * ptr, float2::Native_vec_ coord) \
+    {                                                                                                                                                            \
+        hipTextureObject_t textureObject = ptr->textureObject;                                                                                                   \
+        return tex2Dgather<HIP_CHANNEL_TYPE##4>(textureObject, coord.x, coord.y, COMPONENT_INDEX).data;                                                          \
+    }                                                                                                                                                            \
+    HIP_CHANNEL_TYPE##4 ::Native_vec_ FUNC(tld4_##COMPONENT##_indirect_2d_v4_##CHANNEL_TYPE##_f32)(uint64_t texobj, float2::Native_vec_ coord)                   \
+    {                                                                                                                                                            \
+        hipTextureObject_t textureObject = (hipTextureObject_t)texobj;                                                                                           \
+        return tex2Dgather<HIP_CHANNEL_TYPE##4>(textureObject, coord.x, coord.y, COMPONENT_INDEX).data;                                                          \
+    }
+    tld4_2d(r, 0, u32, uint);
+    tld4_2d(r, 0, s32, int);
+    tld4_2d(r, 0, f32, float);
+    tld4_2d(g, 1, u32, uint);
+    tld4_2d(g, 1, s32, int);
+    tld4_2d(g, 1, f32, float);
+    tld4_2d(b, 2, u32, uint);
+    tld4_2d(b, 2, s32, int);
+    tld4_2d(b, 2, f32, float);
+    tld4_2d(a, 3, u32, uint);
+    tld4_2d(a, 3, s32, int);
+    tld4_2d(a, 3, f32, float);
+
+    // txq/suq don't know the geometry of the image, but descriptor fields are
+    // at the same offsets for every image type, so we use the most general query
+#define txq(NAME, OCKL_QUERY)                                             \
+    uint32_t FUNC(txq_##NAME)(struct textureReference GLOBAL_SPACE * ptr) \
+    {                                                                     \
+        hipTextureObject_t textureObject = ptr->textureObject;            \
+        TEXTURE_OBJECT_PARAMETERS_INIT;                                   \
+        (void)s;                                                          \
+        return uint32_t(OCKL_QUERY(i));                                   \
+    }                                                                     \
+    uint32_t FUNC(txq_indirect_##NAME)(uint64_t texobj)                   \
+    {                                                                     \
+        hipTextureObject_t textureObject = (hipTextureObject_t)texobj;    \
+        TEXTURE_OBJECT_PARAMETERS_INIT;                                   \
+        (void)s;                                                          \
+        return uint32_t(OCKL_QUERY(i));                                   \
+    }
+    txq(width, __ockl_image_width_3D);
+    txq(height, __ockl_image_height_3D);
+    txq(depth, __ockl_image_depth_3D);
+    txq(channel_data_type, __ockl_image_channel_data_type_3D);
+    txq(channel_order, __ockl_image_channel_order_3D);
+    txq(array_size, __ockl_image_array_size_2Da);
+    txq(num_mipmap_levels, __ockl_image_num_mip_levels_3D);
+
+#define suq(NAME, OCKL_QUERY)                                                                      \
+    uint32_t FUNC(suq_##NAME)(struct textureReference GLOBAL_SPACE * ptr)                          \
+    {                                                                                              \
+        return uint32_t(OCKL_QUERY((unsigned int CONSTANT_SPACE *)get_surface_pointer(ptr)));      \
+    }                                                                                              \
+    uint32_t FUNC(suq_indirect_##NAME)(uint64_t surf_arg)                                          \
+    {                                                                                              \
+        return uint32_t(OCKL_QUERY((unsigned int CONSTANT_SPACE *)get_surface_pointer(surf_arg))); \
+    }
+    suq(width, __ockl_image_width_3D);
+    suq(height, __ockl_image_height_3D);
+    suq(depth, __ockl_image_depth_3D);
+    suq(channel_data_type, __ockl_image_channel_data_type_3D);
+    suq(channel_order, __ockl_image_channel_order_3D);
+    suq(array_size, __ockl_image_array_size_2Da);
+
 #define suld_b_1d_vec(VEC, TYPE, HIP_TYPE)                                                                                      \
     HIP_TYPE::Native_vec_ FUNC(suld_b_1d##VEC##_##TYPE##_trap)(struct textureReference GLOBAL_SPACE * ptr, int1::Native_vec_ x) \
     {                                                                                                                           \
@@ -1683,6 +1820,16 @@ extern "C"
         return __ockl_cyclectr_u64();
     }
 
+    // Filled by the host after the module is loaded. CUDA's cooperative groups
+    // read %envreg1 (low) and %envreg2 (high) as the address of the grid sync
+    // workspace, everything else is left zeroed
+    __device__ uint32_t FUNC_CALL(ENVREG)[32];
+
+    uint32_t FUNC(sreg_envreg)(uchar index)
+    {
+        return FUNC_CALL(ENVREG)[index];
+    }
+
     void FUNC(barrier_sync)(uint32_t)
     {
         // I'm not 100% how should a barrier be defined:
@@ -1704,6 +1851,85 @@ extern "C"
         __builtin_amdgcn_s_barrier();
     }
 
+    // AMD GPUs have no thread block clusters, we behave as if every launch
+    // used an implicit cluster of a single block
+    uint32_t FUNC(sreg_clusterid)(uchar dim)
+    {
+        return FUNC_CALL(sreg_ctaid)(dim);
+    }
+
+    uint32_t FUNC(sreg_nclusterid)(uchar dim)
+    {
+        return FUNC_CALL(sreg_nctaid)(dim);
+    }
+
+    uint32_t FUNC(sreg_cluster_ctaid)(__attribute__((unused)) uchar dim)
+    {
+        return 0;
+    }
+
+    uint32_t FUNC(sreg_cluster_nctaid)(uchar dim)
+    {
+        return dim < 3 ? 1 : 0;
+    }
+
+    uint32_t FUNC(sreg_cluster_ctarank)(void)
+    {
+        return 0;
+    }
+
+    uint32_t FUNC(sreg_cluster_nctarank)(void)
+    {
+        return 1;
+    }
+
+    bool FUNC(sreg_is_explicit_cluster)(void)
+    {
+        return false;
+    }
+
+    void FUNC(barrier_cluster_wait)(void)
+    {
+        FUNC_CALL(barrier_sync)(0);
+    }
+
+    // The only CTA in the cluster is our own, so the address maps to itself
+    uint32_t FUNC(mapa_u32)(uint32_t address, __attribute__((unused)) uint32_t rank)
+    {
+        return address;
+    }
+
+    uint64_t FUNC(mapa_u64)(uint64_t address, __attribute__((unused)) uint32_t rank)
+    {
+        return address;
+    }
+
+    uint32_t FUNC(getctarank_u32)(__attribute__((unused)) uint32_t address)
+    {
+        return 0;
+    }
+
+    uint32_t FUNC(getctarank_u64)(__attribute__((unused)) uint64_t address)
+    {
+        return 0;
+    }
+
+    // All threads in membermask execute elect.sync together, the lowest lane
+    // is a deterministic choice of the leader
+    uint2::Native_vec_ FUNC(elect_sync)(uint32_t membermask)
+    {
+        __builtin_amdgcn_wave_barrier();
+        uint32_t leader = (uint32_t)(__builtin_ffs(membermask) - 1);
+        return uint2(leader, (uint32_t)(FUNC_CALL(sreg_laneid)() == leader)).data;
+    }
+
+    bool FUNC(elect_sync_pred)(uint32_t membermask)
+    {
+        __builtin_amdgcn_wave_barrier();
+        uint32_t leader = (uint32_t)(__builtin_ffs(membermask) - 1);
+        return FUNC_CALL(sreg_laneid)() == leader;
+    }
+
     half __ockl_median3_f16(half, half, half) __attribute__((device));
     half FUNC(cvt_sat_f16_f16)(half x)
     {
@@ -1748,6 +1974,173 @@ extern "C"
     {
         return __ockl_sdot4(a, b, c, false);
     }
+
+    // For .u16/.s16 c is always zero and there are no bits left for it
+#define cvt_pack_sat(NAME, BITS, MIN, MAX)                                                \
+    uint32_t FUNC(cvt_pack_sat_##NAME##_s32_b32)(int32_t a, int32_t b, uint32_t c)      \
+    {                                                                                   \
+        uint32_t mask = (1U << BITS) - 1;                                               \
+        uint32_t a_sat = (uint32_t)min(max(a, MIN), MAX) & mask;                        \
+        uint32_t b_sat = (uint32_t)min(max(b, MIN), MAX) & mask;                        \
+        uint32_t high = (uint32_t)((uint64_t)c << (2 * BITS));                          \
+        return high | (a_sat << BITS) | b_sat;                                          \
+    }
+
+    cvt_pack_sat(u2, 2, 0, 3);
+    cvt_pack_sat(s2, 2, -2, 1);
+    cvt_pack_sat(u4, 4, 0, 15);
+    cvt_pack_sat(s4, 4, -8, 7);
+    cvt_pack_sat(u8, 8, 0, 255);
+    cvt_pack_sat(s8, 8, -128, 127);
+    cvt_pack_sat(u16, 16, 0, 65535);
+    cvt_pack_sat(s16, 16, -32768, 32767);
+
+    float __ocml_tanh_f32(float) __attribute__((device));
+    float FUNC(tanh_approx_f32)(float x)
+    {
+        return __ocml_tanh_f32(x);
+    }
+
+    half __ocml_tanh_f16(half) __attribute__((device));
+    half FUNC(tanh_approx_f16)(half x)
+    {
+        return __ocml_tanh_f16(x);
+    }
+
+    zluda_half2 FUNC(tanh_approx_f16x2)(zluda_half2 x)
+    {
+        zluda_half2 result;
+        result.x = __ocml_tanh_f16(x.x);
+        result.y = __ocml_tanh_f16(x.y);
+        return result;
+    }
+
+    // membermask is ignored, we reduce over all active lanes of the warp.
+    // In double wave mode each half of the wavefront is a separate warp, so we
+    // reduce twice, every time with the other half replaced by the identity
+#define redux_sync(NAME, TYPE, OCKL_NAME, IDENTITY)                                     \
+    TYPE __ockl_wfred_##OCKL_NAME(TYPE) __attribute__((device));                        \
+    TYPE FUNC(redux_sync_##NAME)(TYPE src, __attribute__((unused)) uint32_t membermask) \
+    {                                                                                   \
+        if (FUNC_CALL(COMPILATION_MODE) == CompilationMode::DoubleWave32OnWave64)       \
+        {                                                                               \
+            bool upper = is_upper_warp();                                               \
+            TYPE lower_result = __ockl_wfred_##OCKL_NAME(upper ? (IDENTITY) : src);     \
+            TYPE upper_result = __ockl_wfred_##OCKL_NAME(upper ? src : (IDENTITY));     \
+            return upper ? upper_result : lower_result;                                 \
+        }                                                                               \
+        return __ockl_wfred_##OCKL_NAME(src);                                           \
+    }
+
+    redux_sync(add_u32, uint32_t, add_u32, 0U);
+    redux_sync(add_s32, int32_t, add_i32, 0);
+    redux_sync(min_u32, uint32_t, min_u32, UINT32_MAX);
+    redux_sync(min_s32, int32_t, min_i32, INT32_MAX);
+    redux_sync(max_u32, uint32_t, max_u32, 0U);
+    redux_sync(max_s32, int32_t, max_i32, INT32_MIN);
+    redux_sync(and_b32, uint32_t, and_u32, UINT32_MAX);
+    redux_sync(or_b32, uint32_t, or_u32, 0U);
+    redux_sync(xor_b32, uint32_t, xor_u32, 0U);
+
+    __device__ static inline uint32_t bmsk(uint32_t start, uint32_t width)
+    {
+        return (uint32_t)(((1ULL << width) - 1ULL) << start);
+    }
+
+    uint32_t FUNC(bmsk_wrap_b32)(uint32_t a, uint32_t b)
+    {
+        return bmsk(a & 31U, b & 31U);
+    }
+
+    uint32_t FUNC(bmsk_clamp_b32)(uint32_t a, uint32_t b)
+    {
+        return bmsk(min(a, 32U), min(b, 32U));
+    }
+
+    __device__ static inline uint32_t szext_u32(uint32_t a, uint32_t width)
+    {
+        return (uint32_t)(a & ((1ULL << width) - 1ULL));
+    }
+
+    __device__ static inline int32_t szext_s32(int32_t a, uint32_t width)
+    {
+        if (width == 0)
+            return 0;
+        uint32_t shift = 32U - width;
+        return ((int32_t)((uint32_t)a << shift)) >> shift;
+    }
+
+    uint32_t FUNC(szext_wrap_u32)(uint32_t a, uint32_t b)
+    {
+        return szext_u32(a, b & 31U);
+    }
+
+    uint32_t FUNC(szext_clamp_u32)(uint32_t a, uint32_t b)
+    {
+        return szext_u32(a, min(b, 32U));
+    }
+
+    int32_t FUNC(szext_wrap_s32)(int32_t a, uint32_t b)
+    {
+        return szext_s32(a, b & 31U);
+    }
+
+    int32_t FUNC(szext_clamp_s32)(int32_t a, uint32_t b)
+    {
+        return szext_s32(a, min(b, 32U));
+    }
+
+    uint32_t FUNC(fns_b32)(uint32_t mask, uint32_t base, int32_t offset)
+    {
+        base &= 31U;
+        if (offset == 0)
+            return ((mask >> base) & 1U) ? base : UINT32_MAX;
+        int32_t step = offset > 0 ? 1 : -1;
+        uint32_t remaining = offset > 0 ? (uint32_t)offset : 0U - (uint32_t)offset;
+        for (int32_t i = (int32_t)base; i >= 0 && i < 32; i += step)
+        {
+            if (((mask >> i) & 1U) && --remaining == 0)
+                return (uint32_t)i;
+        }
+        return UINT32_MAX;
+    }
+
+    __device__ static inline uint64_t mul24_product_u32(uint32_t a, uint32_t b)
+    {
+        return (uint64_t)(a & 0xFFFFFFU) * (uint64_t)(b & 0xFFFFFFU);
+    }
+
+    __device__ static inline int64_t mul24_product_s32(int32_t a, int32_t b)
+    {
+        int64_t a24 = ((int32_t)((uint32_t)a << 8)) >> 8;
+        int64_t b24 = ((int32_t)((uint32_t)b << 8)) >> 8;
+        return a24 * b24;
+    }
+
+    // PTX mul24.hi returns bits 16..47 of the product, unlike v_mul_hi_u32_u24
+#define mul24(SUFFIX, TYPE)                                                          \
+    TYPE FUNC(mul24_lo_##SUFFIX)(TYPE a, TYPE b)                                     \
+    {                                                                                \
+        return (TYPE)mul24_product_##SUFFIX(a, b);                                   \
+    }                                                                                \
+                                                                                     \
+    TYPE FUNC(mul24_hi_##SUFFIX)(TYPE a, TYPE b)                                     \
+    {                                                                                \
+        return (TYPE)(mul24_product_##SUFFIX(a, b) >> 16);                           \
+    }                                                                                \
+                                                                                     \
+    TYPE FUNC(mad24_lo_##SUFFIX)(TYPE a, TYPE b, TYPE c)                             \
+    {                                                                                \
+        return (TYPE)((uint32_t)mul24_product_##SUFFIX(a, b) + (uint32_t)c);         \
+    }                                                                                \
+                                                                                     \
+    TYPE FUNC(mad24_hi_##SUFFIX)(TYPE a, TYPE b, TYPE c)                             \
+    {                                                                                \
+        return (TYPE)((uint32_t)(mul24_product_##SUFFIX(a, b) >> 16) + (uint32_t)c); \
+    }
+
+    mul24(u32, uint32_t);
+    mul24(s32, int32_t);
 }
 __device__ uint32_t __llvm_amdgcn_ballot_i32(bool) __asm("llvm.amdgcn.ballot.i32");
 __device__ uint64_t __llvm_amdgcn_ballot_i64(bool) __asm("llvm.amdgcn.ballot.i64");
@@ -1880,3 +2273,770 @@ extern "C"
             __builtin_amdgcn_s_sleep(1);
     }
 }
+
+// Warp-level matrix instructions (wmma.*, mma.sync) are emulated with cross-lane shuffles.
+// Elements of wmma fragments are distributed between lanes the same way as in RDNA3 WMMA
+// instructions, so on RDNA3 wmma.mma can be lowered directly to a hardware instruction:
+// * A fragment: lane holds row (lane % 16), B fragment: lane holds column (lane % 16).
+//   Fragments too small for the whole K dimension (everything except .f16) split it:
+//   lane holds the lower half of K and lane + 16 holds the upper half
+// * C and D fragments: lane holds column (lane % 16), rows (2 * i + lane / 16)
+// mma.sync fragments are distributed as described in the PTX documentation
+enum class MatrixType
+{
+    F16,
+    BF16,
+    TF32,
+    S8,
+    U8,
+    F32,
+    S32
+};
+
+enum class MatrixFragment
+{
+    A,
+    B,
+    C
+};
+
+template <uint32_t N>
+using matrix_fragment = uint32_t __attribute__((ext_vector_type(N)));
+
+typedef _Float16 matrix_half16 __attribute__((ext_vector_type(16)));
+typedef float matrix_float8 __attribute__((ext_vector_type(8)));
+
+extern "C" __device__ const CONSTANT_SPACE uint32_t __oclc_ISA_version;
+
+struct matrix_coordinates
+{
+    uint32_t row;
+    uint32_t col;
+};
+
+static __device__ uint32_t matrix_shfl(uint32_t value, uint32_t lane)
+{
+    if (is_upper_warp())
+        lane += 32U;
+    int32_t shfl_width = (FUNC_CALL(COMPILATION_MODE) == CompilationMode::DoubleWave32OnWave64) ? 64 : 32;
+    return __shfl(value, (int32_t)lane, shfl_width);
+}
+
+template <MatrixType type>
+static constexpr uint32_t matrix_element_bits()
+{
+    if constexpr (type == MatrixType::F16 || type == MatrixType::BF16)
+        return 16U;
+    else if constexpr (type == MatrixType::S8 || type == MatrixType::U8)
+        return 8U;
+    else
+        return 32U;
+}
+
+template <uint32_t bits>
+static __device__ uint32_t matrix_register_element(uint32_t reg, uint32_t index)
+{
+    if constexpr (bits == 32U)
+        return reg;
+    else
+        return (reg >> (index * bits)) & ((1U << bits) - 1U);
+}
+
+template <uint32_t bits, uint32_t N>
+static __device__ uint32_t matrix_fragment_get(matrix_fragment<N> fragment, uint32_t index)
+{
+    constexpr uint32_t per_register = 32U / bits;
+    return matrix_register_element<bits>(fragment[index / per_register], index % per_register);
+}
+
+template <uint32_t bits, uint32_t N>
+static __device__ void matrix_fragment_set(matrix_fragment<N> &fragment, uint32_t index, uint32_t value)
+{
+    constexpr uint32_t per_register = 32U / bits;
+    fragment[index / per_register] |= value << ((index % per_register) * bits);
+}
+
+template <MatrixType type, typename Acc>
+static __device__ Acc matrix_decode(uint32_t value)
+{
+    if constexpr (type == MatrixType::F16)
+        return (Acc)std::bit_cast<_Float16>((uint16_t)value);
+    else if constexpr (type == MatrixType::BF16)
+        return std::bit_cast<float>(value << 16U);
+    else if constexpr (type == MatrixType::TF32)
+        return std::bit_cast<float>(value & 0xFFFFE000U);
+    else if constexpr (type == MatrixType::F32)
+        return std::bit_cast<float>(value);
+    else if constexpr (type == MatrixType::S8)
+        return (Acc)(int8_t)value;
+    else if constexpr (type == MatrixType::U8)
+        return (Acc)value;
+    else
+        return (Acc)(int32_t)value;
+}
+
+template <MatrixType type, bool satfinite, typename Acc>
+static __device__ uint32_t matrix_encode(Acc value)
+{
+    if constexpr (type == MatrixType::F16)
+        return (uint32_t)std::bit_cast<uint16_t>((_Float16)value);
+    else if constexpr (type == MatrixType::F32)
+        return std::bit_cast<uint32_t>((float)value);
+    else
+    {
+        if constexpr (satfinite)
+        {
+            if (value > (Acc)INT32_MAX)
+                value = (Acc)INT32_MAX;
+            if (value < (Acc)INT32_MIN)
+                value = (Acc)INT32_MIN;
+        }
+        return (uint32_t)value;
+    }
+}
+
+// Dot product of elements packed into a single register of A and a single register of B
+template <MatrixType a_type, MatrixType b_type, typename Acc>
+static __device__ Acc matrix_dot(uint32_t a, uint32_t b)
+{
+    constexpr uint32_t bits = matrix_element_bits<a_type>();
+    Acc result = 0;
+    for (uint32_t i = 0; i < 32U / bits; i++)
+        result += matrix_decode<a_type, Acc>(matrix_register_element<bits>(a, i)) *
+                  matrix_decode<b_type, Acc>(matrix_register_element<bits>(b, i));
+    return result;
+}
+
+template <MatrixFragment fragment, uint32_t k, uint32_t elements>
+static __device__ matrix_coordinates wmma_coordinates(uint32_t lane, uint32_t index)
+{
+    if constexpr (fragment == MatrixFragment::C)
+    {
+        return {2U * index + lane / 16U, lane % 16U};
+    }
+    else
+    {
+        uint32_t k_index = (elements < k ? (lane / 16U) * elements : 0U) + index;
+        if constexpr (fragment == MatrixFragment::A)
+            return {lane % 16U, k_index};
+        else
+            return {k_index, lane % 16U};
+    }
+}
+
+template <MatrixFragment fragment, uint32_t k, typename T, uint32_t N, bool row_major>
+static __device__ matrix_fragment<N> wmma_load(T *ptr, uint32_t stride)
+{
+    constexpr uint32_t bits = (uint32_t)sizeof(T) * 8U;
+    constexpr uint32_t elements = N * (32U / bits);
+    uint32_t lane = FUNC_CALL(sreg_laneid());
+    matrix_fragment<N> result = {};
+    for (uint32_t i = 0; i < elements; i++)
+    {
+        matrix_coordinates coords = wmma_coordinates<fragment, k, elements>(lane, i);
+        uint32_t offset = row_major ? coords.row * stride + coords.col : coords.col * stride + coords.row;
+        matrix_fragment_set<bits>(result, i, (uint32_t)ptr[offset]);
+    }
+    return result;
+}
+
+template <uint32_t k, typename T, uint32_t N, bool row_major>
+static __device__ void wmma_store(T *ptr, matrix_fragment<N> value, uint32_t stride)
+{
+    constexpr uint32_t bits = (uint32_t)sizeof(T) * 8U;
+    constexpr uint32_t elements = N * (32U / bits);
+    uint32_t lane = FUNC_CALL(sreg_laneid());
+    for (uint32_t i = 0; i < elements; i++)
+    {
+        matrix_coordinates coords = wmma_coordinates<MatrixFragment::C, k, elements>(lane, i);
+        uint32_t offset = row_major ? coords.row * stride + coords.col : coords.col * stride + coords.row;
+        ptr[offset] = (T)matrix_fragment_get<bits>(value, i);
+    }
+}
+
+template <MatrixType a_type, MatrixType b_type, MatrixType c_type, MatrixType d_type, bool satfinite, typename Acc, uint32_t k, uint32_t ab_regs, uint32_t c_regs, uint32_t d_regs>
+static __device__ matrix_fragment<d_regs> wmma_mma(matrix_fragment<ab_regs> a, matrix_fragment<ab_regs> b, matrix_fragment<c_regs> c)
+{
+    constexpr uint32_t elements = ab_regs * (32U / matrix_element_bits<a_type>());
+    constexpr uint32_t parts = k / elements;
+    uint32_t lane = FUNC_CALL(sreg_laneid());
+    uint32_t lane_part = parts == 1U ? 0U : lane / 16U;
+    matrix_fragment<ab_regs> b_other_part;
+    for (uint32_t r = 0; r < ab_regs; r++)
+        b_other_part[r] = matrix_shfl(b[r], lane ^ 16U);
+    matrix_fragment<d_regs> d = {};
+    for (uint32_t i = 0; i < 8U; i++)
+    {
+        uint32_t row = 2U * i + lane / 16U;
+        Acc acc = matrix_decode<c_type, Acc>(matrix_fragment_get<matrix_element_bits<c_type>()>(c, i));
+        for (uint32_t part = 0; part < parts; part++)
+        {
+            for (uint32_t r = 0; r < ab_regs; r++)
+            {
+                uint32_t a_reg = matrix_shfl(a[r], row + 16U * part);
+                uint32_t b_reg = part == lane_part ? b[r] : b_other_part[r];
+                acc += matrix_dot<a_type, b_type, Acc>(a_reg, b_reg);
+            }
+        }
+        matrix_fragment_set<matrix_element_bits<d_type>()>(d, i, matrix_encode<d_type, satfinite>(acc));
+    }
+    return d;
+}
+
+static __device__ bool wmma_has_hardware_support()
+{
+    return FUNC_CALL(COMPILATION_MODE) == CompilationMode::Wave32 && __oclc_ISA_version >= 11000U && __oclc_ISA_version < 12000U;
+}
+
+__attribute__((target("gfx11-insts"))) static __device__ matrix_fragment<8> wmma_mma_f32_f16_gfx11(matrix_fragment<8> a, matrix_fragment<8> b, matrix_fragment<8> c)
+{
+    matrix_float8 d = __builtin_amdgcn_wmma_f32_16x16x16_f16_w32(std::bit_cast<matrix_half16>(a), std::bit_cast<matrix_half16>(b), std::bit_cast<matrix_float8>(c));
+    return std::bit_cast<matrix_fragment<8>>(d);
+}
+
+// m16n8k16 and m16n8k8: registers of A alternate between rows (group) and (group + 8),
+// i-th register of B holds the same K range as registers 2 * i and 2 * i + 1 of A
+template <MatrixType a_type, MatrixType b_type, MatrixType c_type, MatrixType d_type, bool satfinite, typename Acc, uint32_t a_regs, uint32_t b_regs, uint32_t c_regs, uint32_t d_regs>
+static __device__ matrix_fragment<d_regs> mma_m16n8(matrix_fragment<a_regs> a, matrix_fragment<b_regs> b, matrix_fragment<c_regs> c)
+{
+    static_assert(a_regs == 2U * b_regs);
+    uint32_t lane = FUNC_CALL(sreg_laneid());
+    uint32_t group = lane >> 2U;
+    uint32_t thread = lane & 3U;
+    Acc acc[4];
+    for (uint32_t i = 0; i < 4U; i++)
+        acc[i] = matrix_decode<c_type, Acc>(matrix_fragment_get<matrix_element_bits<c_type>()>(c, i));
+    for (uint32_t source = 0; source < 4U; source++)
+    {
+        for (uint32_t r = 0; r < b_regs; r++)
+        {
+            uint32_t a_top = matrix_shfl(a[2U * r], 4U * group + source);
+            uint32_t a_bottom = matrix_shfl(a[2U * r + 1U], 4U * group + source);
+            for (uint32_t j = 0; j < 2U; j++)
+            {
+                uint32_t b_reg = matrix_shfl(b[r], 4U * (2U * thread + j) + source);
+                acc[j] += matrix_dot<a_type, b_type, Acc>(a_top, b_reg);
+                acc[2U + j] += matrix_dot<a_type, b_type, Acc>(a_bottom, b_reg);
+            }
+        }
+    }
+    matrix_fragment<d_regs> d = {};
+    for (uint32_t i = 0; i < 4U; i++)
+        matrix_fragment_set<matrix_element_bits<d_type>()>(d, i, matrix_encode<d_type, satfinite>(acc[i]));
+    return d;
+}
+
+// m8n8k4 is computed independently by each quadpair: lanes 4q..4q+3 hold rows (columns) 0-3
+// and lanes 4q+16..4q+19 hold rows (columns) 4-7 of A (B)
+static __device__ uint32_t mma_m8n8k4_lane(uint32_t quadpair, uint32_t index)
+{
+    return (index < 4U ? 0U : 16U) + 4U * quadpair + (index & 3U);
+}
+
+template <MatrixType cd_type, uint32_t cd_regs>
+static __device__ matrix_fragment<cd_regs> mma_m8n8k4(matrix_fragment<2> a, matrix_fragment<2> b, matrix_fragment<cd_regs> c)
+{
+    uint32_t lane = FUNC_CALL(sreg_laneid());
+    uint32_t quadpair = (lane >> 2U) & 3U;
+    uint32_t upper_rows = lane < 16U ? 0U : 4U;
+    matrix_fragment<cd_regs> d = {};
+    for (uint32_t i = 0; i < 8U; i++)
+    {
+        uint32_t row, col;
+        if constexpr (cd_type == MatrixType::F16)
+        {
+            row = (lane & 3U) + upper_rows;
+            col = i;
+        }
+        else
+        {
+            row = (lane & 1U) + (i & 2U) + upper_rows;
+            col = (i & 4U) + (lane & 2U) + (i & 1U);
+        }
+        uint32_t a_lane = mma_m8n8k4_lane(quadpair, row);
+        uint32_t b_lane = mma_m8n8k4_lane(quadpair, col);
+        float acc = matrix_decode<cd_type, float>(matrix_fragment_get<matrix_element_bits<cd_type>()>(c, i));
+        for (uint32_t r = 0; r < 2U; r++)
+            acc += matrix_dot<MatrixType::F16, MatrixType::F16, float>(matrix_shfl(a[r], a_lane), matrix_shfl(b[r], b_lane));
+        matrix_fragment_set<matrix_element_bits<cd_type>()>(d, i, matrix_encode<cd_type, false>(acc));
+    }
+    return d;
+}
+
+extern "C"
+{
+#define WMMA_LOAD(NAME, FRAGMENT, K, TYPE, REGS, ROW_MAJOR, SPACE)                                 \
+    matrix_fragment<REGS> FUNC(wmma_load_##NAME)(SPACE TYPE * ptr, uint32_t stride)                \
+    {                                                                                              \
+        return wmma_load<MatrixFragment::FRAGMENT, K, TYPE, REGS, ROW_MAJOR>((TYPE *)ptr, stride); \
+    }
+
+#define WMMA_LOAD_SPACES(NAME, FRAGMENT, K, TYPE, REGS, ROW_MAJOR)               \
+    WMMA_LOAD(NAME##_generic, FRAGMENT, K, TYPE, REGS, ROW_MAJOR, GENERIC_SPACE) \
+    WMMA_LOAD(NAME##_global, FRAGMENT, K, TYPE, REGS, ROW_MAJOR, GLOBAL_SPACE)   \
+    WMMA_LOAD(NAME##_shared, FRAGMENT, K, TYPE, REGS, ROW_MAJOR, SHARED_SPACE)
+
+#define WMMA_LOAD_LAYOUTS(FRAGMENT_NAME, SHAPE_TYPE, FRAGMENT, K, TYPE, REGS)         \
+    WMMA_LOAD_SPACES(FRAGMENT_NAME##_row_##SHAPE_TYPE, FRAGMENT, K, TYPE, REGS, true) \
+    WMMA_LOAD_SPACES(FRAGMENT_NAME##_col_##SHAPE_TYPE, FRAGMENT, K, TYPE, REGS, false)
+
+    WMMA_LOAD_LAYOUTS(a, m16n16k16_f16, A, 16, uint16_t, 8)
+    WMMA_LOAD_LAYOUTS(b, m16n16k16_f16, B, 16, uint16_t, 8)
+    WMMA_LOAD_LAYOUTS(a, m16n16k16_bf16, A, 16, uint16_t, 4)
+    WMMA_LOAD_LAYOUTS(b, m16n16k16_bf16, B, 16, uint16_t, 4)
+    WMMA_LOAD_LAYOUTS(a, m16n16k16_s8, A, 16, uint8_t, 2)
+    WMMA_LOAD_LAYOUTS(b, m16n16k16_s8, B, 16, uint8_t, 2)
+    WMMA_LOAD_LAYOUTS(a, m16n16k16_u8, A, 16, uint8_t, 2)
+    WMMA_LOAD_LAYOUTS(b, m16n16k16_u8, B, 16, uint8_t, 2)
+    WMMA_LOAD_LAYOUTS(a, m16n16k8_tf32, A, 8, uint32_t, 4)
+    WMMA_LOAD_LAYOUTS(b, m16n16k8_tf32, B, 8, uint32_t, 4)
+    WMMA_LOAD_LAYOUTS(c, m16n16k16_f16, C, 16, uint16_t, 4)
+    WMMA_LOAD_LAYOUTS(c, m16n16k16_f32, C, 16, uint32_t, 8)
+    WMMA_LOAD_LAYOUTS(c, m16n16k16_s32, C, 16, uint32_t, 8)
+    WMMA_LOAD_LAYOUTS(c, m16n16k8_f32, C, 8, uint32_t, 8)
+
+#define WMMA_STORE(NAME, K, TYPE, REGS, ROW_MAJOR, SPACE)                                          \
+    void FUNC(wmma_store_d_##NAME)(SPACE TYPE * ptr, matrix_fragment<REGS> value, uint32_t stride) \
+    {                                                                                              \
+        wmma_store<K, TYPE, REGS, ROW_MAJOR>((TYPE *)ptr, value, stride);                          \
+    }
+
+#define WMMA_STORE_SPACES(NAME, K, TYPE, REGS, ROW_MAJOR)               \
+    WMMA_STORE(NAME##_generic, K, TYPE, REGS, ROW_MAJOR, GENERIC_SPACE) \
+    WMMA_STORE(NAME##_global, K, TYPE, REGS, ROW_MAJOR, GLOBAL_SPACE)   \
+    WMMA_STORE(NAME##_shared, K, TYPE, REGS, ROW_MAJOR, SHARED_SPACE)
+
+#define WMMA_STORE_LAYOUTS(SHAPE_TYPE, K, TYPE, REGS)        \
+    WMMA_STORE_SPACES(row_##SHAPE_TYPE, K, TYPE, REGS, true) \
+    WMMA_STORE_SPACES(col_##SHAPE_TYPE, K, TYPE, REGS, false)
+
+    WMMA_STORE_LAYOUTS(m16n16k16_f16, 16, uint16_t, 4)
+    WMMA_STORE_LAYOUTS(m16n16k16_f32, 16, uint32_t, 8)
+    WMMA_STORE_LAYOUTS(m16n16k16_s32, 16, uint32_t, 8)
+    WMMA_STORE_LAYOUTS(m16n16k8_f32, 8, uint32_t, 8)
+
+#define WMMA_MMA(NAME, D_TYPE, A_TYPE, B_TYPE, C_TYPE, SATFINITE, ACC, K, AB_REGS, C_REGS, D_REGS)                                                            \
+    matrix_fragment<D_REGS> FUNC(wmma_mma_##NAME)(matrix_fragment<AB_REGS> a, matrix_fragment<AB_REGS> b, matrix_fragment<C_REGS> c)                          \
+    {                                                                                                                                                         \
+        return wmma_mma<MatrixType::A_TYPE, MatrixType::B_TYPE, MatrixType::C_TYPE, MatrixType::D_TYPE, SATFINITE, ACC, K, AB_REGS, C_REGS, D_REGS>(a, b, c); \
+    }
+
+    matrix_fragment<8> FUNC(wmma_mma_m16n16k16_f32_f16_f16_f32)(matrix_fragment<8> a, matrix_fragment<8> b, matrix_fragment<8> c)
+    {
+        if (wmma_has_hardware_support())
+            return wmma_mma_f32_f16_gfx11(a, b, c);
+        return wmma_mma<MatrixType::F16, MatrixType::F16, MatrixType::F32, MatrixType::F32, false, float, 16, 8, 8, 8>(a, b, c);
+    }
+    WMMA_MMA(m16n16k16_f32_f16_f16_f16, F32, F16, F16, F16, false, float, 16, 8, 4, 8)
+    WMMA_MMA(m16n16k16_f16_f16_f16_f32, F16, F16, F16, F32, false, float, 16, 8, 8, 4)
+    WMMA_MMA(m16n16k16_f16_f16_f16_f16, F16, F16, F16, F16, false, float, 16, 8, 4, 4)
+    WMMA_MMA(m16n16k16_f32_bf16_bf16_f32, F32, BF16, BF16, F32, false, float, 16, 4, 8, 8)
+    WMMA_MMA(m16n16k16_s32_s8_s8_s32, S32, S8, S8, S32, false, int64_t, 16, 2, 8, 8)
+    WMMA_MMA(m16n16k16_s32_s8_s8_s32_satfinite, S32, S8, S8, S32, true, int64_t, 16, 2, 8, 8)
+    WMMA_MMA(m16n16k16_s32_u8_u8_s32, S32, U8, U8, S32, false, int64_t, 16, 2, 8, 8)
+    WMMA_MMA(m16n16k16_s32_u8_u8_s32_satfinite, S32, U8, U8, S32, true, int64_t, 16, 2, 8, 8)
+    WMMA_MMA(m16n16k8_f32_tf32_tf32_f32, F32, TF32, TF32, F32, false, float, 8, 4, 8, 8)
+
+#define MMA_M16N8(NAME, D_TYPE, A_TYPE, B_TYPE, C_TYPE, SATFINITE, ACC, A_REGS, B_REGS, C_REGS, D_REGS)                                                            \
+    matrix_fragment<D_REGS> FUNC(mma_##NAME)(matrix_fragment<A_REGS> a, matrix_fragment<B_REGS> b, matrix_fragment<C_REGS> c)                                      \
+    {                                                                                                                                                              \
+        return mma_m16n8<MatrixType::A_TYPE, MatrixType::B_TYPE, MatrixType::C_TYPE, MatrixType::D_TYPE, SATFINITE, ACC, A_REGS, B_REGS, C_REGS, D_REGS>(a, b, c); \
+    }
+
+#define MMA_M16N8K16_INTEGER(A_NAME, B_NAME, A_TYPE, B_TYPE)                                                \
+    MMA_M16N8(m16n8k16_s32_##A_NAME##_##B_NAME##_s32, S32, A_TYPE, B_TYPE, S32, false, int64_t, 2, 1, 4, 4) \
+    MMA_M16N8(m16n8k16_s32_##A_NAME##_##B_NAME##_s32_satfinite, S32, A_TYPE, B_TYPE, S32, true, int64_t, 2, 1, 4, 4)
+
+    MMA_M16N8(m16n8k16_f32_f16_f16_f32, F32, F16, F16, F32, false, float, 4, 2, 4, 4)
+    MMA_M16N8(m16n8k16_f32_f16_f16_f16, F32, F16, F16, F16, false, float, 4, 2, 2, 4)
+    MMA_M16N8(m16n8k16_f16_f16_f16_f32, F16, F16, F16, F32, false, float, 4, 2, 4, 2)
+    MMA_M16N8(m16n8k16_f16_f16_f16_f16, F16, F16, F16, F16, false, float, 4, 2, 2, 2)
+    MMA_M16N8(m16n8k16_f32_bf16_bf16_f32, F32, BF16, BF16, F32, false, float, 4, 2, 4, 4)
+    MMA_M16N8(m16n8k8_f32_tf32_tf32_f32, F32, TF32, TF32, F32, false, float, 4, 2, 4, 4)
+    MMA_M16N8K16_INTEGER(s8, s8, S8, S8)
+    MMA_M16N8K16_INTEGER(s8, u8, S8, U8)
+    MMA_M16N8K16_INTEGER(u8, s8, U8, S8)
+    MMA_M16N8K16_INTEGER(u8, u8, U8, U8)
+
+    matrix_fragment<8> FUNC(mma_m8n8k4_f32_f16_f16_f32)(matrix_fragment<2> a, matrix_fragment<2> b, matrix_fragment<8> c)
+    {
+        return mma_m8n8k4<MatrixType::F32, 8>(a, b, c);
+    }
+
+    matrix_fragment<4> FUNC(mma_m8n8k4_f16_f16_f16_f16)(matrix_fragment<2> a, matrix_fragment<2> b, matrix_fragment<4> c)
+    {
+        return mma_m8n8k4<MatrixType::F16, 4>(a, b, c);
+    }
+}
+
+// cp.async is executed synchronously: by the time cp.async.wait_group would return, the copy has already completed
+template <typename T>
+static __device__ void cp_async(SHARED_SPACE uint8_t *dst, GLOBAL_SPACE uint8_t *src, uint32_t src_size)
+{
+    if (src_size >= sizeof(T))
+    {
+        *(SHARED_SPACE T *)dst = *(GLOBAL_SPACE T *)src;
+    }
+    else
+    {
+        // Bytes past src-size are zero-filled
+        for (uint32_t i = 0; i < sizeof(T); i++)
+            dst[i] = i < src_size ? src[i] : (uint8_t)0;
+    }
+}
+
+/*
+    mbarrier object layout:
+        bits 0..=30:  expected arrival count
+        bits 32..=62: pending arrival count of the current phase
+        bit 63:       current phase parity
+    The state returned by mbarrier.arrive is the phase parity at the time of arrival
+*/
+static __device__ uint64_t mbarrier_new(uint64_t phase, uint64_t pending, uint64_t expected)
+{
+    return (phase << 63) | (pending << 32) | expected;
+}
+
+template <typename T>
+static __device__ void mbarrier_init(T *barrier, uint32_t count)
+{
+    __hip_atomic_store(barrier, mbarrier_new(0, count, count), __ATOMIC_RELAXED, __HIP_MEMORY_SCOPE_WORKGROUP);
+}
+
+template <typename T>
+static __device__ uint64_t mbarrier_arrive(T *barrier, uint32_t count)
+{
+    uint64_t old = __hip_atomic_load(barrier, __ATOMIC_RELAXED, __HIP_MEMORY_SCOPE_WORKGROUP);
+    uint64_t desired;
+    do
+    {
+        uint64_t phase = old >> 63;
+        uint64_t pending = (old >> 32) & 0x7FFFFFFF;
+        uint64_t expected = old & 0x7FFFFFFF;
+        if (pending <= count)
+            desired = mbarrier_new(phase ^ 1, expected, expected);
+        else
+            desired = mbarrier_new(phase, pending - count, expected);
+    } while (!__hip_atomic_compare_exchange_weak(barrier, &old, desired, __ATOMIC_ACQ_REL, __ATOMIC_RELAXED, __HIP_MEMORY_SCOPE_WORKGROUP));
+    return old & (1ULL << 63);
+}
+
+template <typename T>
+static __device__ bool mbarrier_test_wait_parity(T *barrier, uint32_t parity)
+{
+    uint64_t value = __hip_atomic_load(barrier, __ATOMIC_ACQUIRE, __HIP_MEMORY_SCOPE_WORKGROUP);
+    return (value >> 63) != (parity & 1);
+}
+
+extern "C"
+{
+    void FUNC(cp_async_4)(SHARED_SPACE uint8_t *dst, GLOBAL_SPACE uint8_t *src, uint32_t src_size)
+    {
+        cp_async<uint32_t>(dst, src, src_size);
+    }
+
+    void FUNC(cp_async_8)(SHARED_SPACE uint8_t *dst, GLOBAL_SPACE uint8_t *src, uint32_t src_size)
+    {
+        cp_async<uint2::Native_vec_>(dst, src, src_size);
+    }
+
+    void FUNC(cp_async_16)(SHARED_SPACE uint8_t *dst, GLOBAL_SPACE uint8_t *src, uint32_t src_size)
+    {
+        cp_async<uint4::Native_vec_>(dst, src, src_size);
+    }
+
+#define MBARRIER(SPACE_NAME, SPACE)                                                                        \
+    void FUNC(mbarrier_init_b64_##SPACE_NAME)(SPACE uint64_t * barrier, uint32_t count)                    \
+    {                                                                                                      \
+        mbarrier_init(barrier, count);                                                                     \
+    }                                                                                                      \
+                                                                                                           \
+    uint64_t FUNC(mbarrier_arrive_b64_##SPACE_NAME)(SPACE uint64_t * barrier, uint32_t count)              \
+    {                                                                                                      \
+        return mbarrier_arrive(barrier, count);                                                            \
+    }                                                                                                      \
+                                                                                                           \
+    bool FUNC(mbarrier_test_wait_b64_##SPACE_NAME)(SPACE uint64_t * barrier, uint64_t state)               \
+    {                                                                                                      \
+        return mbarrier_test_wait_parity(barrier, (uint32_t)(state >> 63));                                \
+    }                                                                                                      \
+                                                                                                           \
+    bool FUNC(mbarrier_test_wait_parity_b64_##SPACE_NAME)(SPACE uint64_t * barrier, uint32_t phase_parity) \
+    {                                                                                                      \
+        return mbarrier_test_wait_parity(barrier, phase_parity);                                           \
+    }
+
+    MBARRIER(generic, GENERIC_SPACE)
+    MBARRIER(shared, SHARED_SPACE)
+}
+
+enum class ReducedRounding
+{
+    NearestEven,
+    NearestAway,
+    Zero
+};
+
+// .bf16 and .tf32 are f32 with a shorter mantissa, rounding drops the low mantissa bits
+template <uint32_t DroppedBits, ReducedRounding Rounding>
+static __device__ uint32_t round_f32_mantissa(float x)
+{
+    if (__builtin_isnan(x))
+        return 0x7FFFFFFF;
+    uint32_t bits = std::bit_cast<uint32_t>(x);
+    constexpr uint32_t half_ulp = 1u << (DroppedBits - 1);
+    if constexpr (Rounding == ReducedRounding::NearestEven)
+        bits += half_ulp - 1 + ((bits >> DroppedBits) & 1);
+    else if constexpr (Rounding == ReducedRounding::NearestAway)
+        bits += half_ulp;
+    return bits & ~((1u << DroppedBits) - 1);
+}
+
+template <ReducedRounding Rounding>
+static __device__ uint16_t bf16_from_float(float x)
+{
+    return (uint16_t)(round_f32_mantissa<16, Rounding>(x) >> 16);
+}
+
+static __device__ float bf16_to_float(uint16_t x)
+{
+    return std::bit_cast<float>((uint32_t)x << 16);
+}
+
+static __device__ uint32_t pack_16x2(uint16_t lo, uint16_t hi)
+{
+    return (uint32_t)lo | ((uint32_t)hi << 16);
+}
+
+struct fp8_format
+{
+    int mantissa_bits;
+    int bias;
+    uint32_t max_finite;
+    bool has_infinity;
+};
+
+static constexpr fp8_format FP8_E4M3 = {3, 7, 0x7E, false};
+static constexpr fp8_format FP8_E5M2 = {2, 15, 0x7B, true};
+
+// Conversions to fp8 are always .rn.satfinite
+static __device__ uint8_t fp8_from_float(float x, fp8_format format)
+{
+    if (__builtin_isnan(x))
+        return 0x7F;
+    uint32_t sign = std::bit_cast<uint32_t>(x) >> 31 << 7;
+    float magnitude = fabsf(x);
+    if (__builtin_isinf(magnitude))
+        return (uint8_t)(sign | format.max_finite);
+    // Subnormals share the exponent of the smallest normal value, so the encoding below is
+    // continuous across the boundary and mantissa overflow carries into the exponent
+    int exponent = (int)((std::bit_cast<uint32_t>(magnitude) >> 23) & 0xFF) - 127;
+    exponent = max(exponent, 1 - format.bias);
+    uint32_t mantissa = (uint32_t)rintf(ldexpf(magnitude, format.mantissa_bits - exponent));
+    uint32_t encoded = ((uint32_t)(exponent + format.bias - 1) << format.mantissa_bits) + mantissa;
+    return (uint8_t)(sign | min(encoded, format.max_finite));
+}
+
+static __device__ half fp8_to_half(uint8_t x, fp8_format format)
+{
+    float sign = (x & 0x80) ? -1.0f : 1.0f;
+    uint32_t exponent = (x & 0x7Fu) >> format.mantissa_bits;
+    uint32_t mantissa = x & ((1u << format.mantissa_bits) - 1);
+    uint32_t max_exponent = 0x7Fu >> format.mantissa_bits;
+    float result;
+    if (format.has_infinity && exponent == max_exponent)
+        result = mantissa ? __builtin_nanf("") : sign * __builtin_inff();
+    else if (!format.has_infinity && (x & 0x7F) == 0x7F)
+        result = __builtin_nanf("");
+    else if (exponent == 0)
+        result = sign * ldexpf((float)mantissa, 1 - format.bias - format.mantissa_bits);
+    else
+        result = sign * ldexpf((float)((1u << format.mantissa_bits) + mantissa), (int)exponent - format.bias - format.mantissa_bits);
+    return (half)result;
+}
+
+extern "C"
+{
+    uint16_t FUNC(cvt_rn_bf16_f32)(float x)
+    {
+        return bf16_from_float<ReducedRounding::NearestEven>(x);
+    }
+
+    uint16_t FUNC(cvt_rz_bf16_f32)(float x)
+    {
+        return bf16_from_float<ReducedRounding::Zero>(x);
+    }
+
+    float FUNC(cvt_rn_f32_bf16)(uint16_t x)
+    {
+        return bf16_to_float(x);
+    }
+
+    uint32_t FUNC(cvt_rna_tf32_f32)(float x)
+    {
+        return round_f32_mantissa<13, ReducedRounding::NearestAway>(x);
+    }
+
+    uint32_t FUNC(cvt_rn_tf32_f32)(float x)
+    {
+        return round_f32_mantissa<13, ReducedRounding::NearestEven>(x);
+    }
+
+    uint32_t FUNC(cvt_rz_tf32_f32)(float x)
+    {
+        return round_f32_mantissa<13, ReducedRounding::Zero>(x);
+    }
+
+    // For all packing conversions the first source goes into the upper half
+    uint32_t FUNC(cvt_rn_bf16x2_f32)(float a, float b)
+    {
+        return pack_16x2(bf16_from_float<ReducedRounding::NearestEven>(b), bf16_from_float<ReducedRounding::NearestEven>(a));
+    }
+
+    uint32_t FUNC(cvt_rz_bf16x2_f32)(float a, float b)
+    {
+        return pack_16x2(bf16_from_float<ReducedRounding::Zero>(b), bf16_from_float<ReducedRounding::Zero>(a));
+    }
+
+    zluda_half2 FUNC(cvt_rn_f16x2_f32)(float a, float b)
+    {
+        zluda_half2 result;
+        result.x = FUNC_CALL(cvt_rn_f16_f32)(b);
+        result.y = FUNC_CALL(cvt_rn_f16_f32)(a);
+        return result;
+    }
+
+    zluda_half2 FUNC(cvt_rz_f16x2_f32)(float a, float b)
+    {
+        zluda_half2 result;
+        result.x = FUNC_CALL(cvt_rz_f16_f32)(b);
+        result.y = FUNC_CALL(cvt_rz_f16_f32)(a);
+        return result;
+    }
+
+#define CVT_FP8(TYPE, FORMAT)                                                                \
+    uint16_t FUNC(cvt_rn_##TYPE##_f32)(float a, float b)                                     \
+    {                                                                                        \
+        return (uint16_t)(fp8_from_float(b, FORMAT) | (fp8_from_float(a, FORMAT) << 8));     \
+    }                                                                                        \
+                                                                                             \
+    uint16_t FUNC(cvt_rn_##TYPE##_f16x2)(zluda_half2 x)                                      \
+    {                                                                                        \
+        return (uint16_t)(fp8_from_float(x.x, FORMAT) | (fp8_from_float(x.y, FORMAT) << 8)); \
+    }                                                                                        \
+                                                                                             \
+    zluda_half2 FUNC(cvt_rn_f16x2_##TYPE)(uint16_t x)                                        \
+    {                                                                                        \
+        zluda_half2 result;                                                                  \
+        result.x = fp8_to_half((uint8_t)x, FORMAT);                                          \
+        result.y = fp8_to_half((uint8_t)(x >> 8), FORMAT);                                   \
+        return result;                                                                       \
+    }
+
+    CVT_FP8(e4m3x2, FP8_E4M3)
+    CVT_FP8(e5m2x2, FP8_E5M2)
+
+    // There is no bf16 arithmetic on AMD GPUs, compute in f32 and round the result
+#define BF16_BINARY(NAME, EXPR)                                                         \
+    uint16_t FUNC(NAME##_bf16)(uint16_t a_bits, uint16_t b_bits)                        \
+    {                                                                                   \
+        float a = bf16_to_float(a_bits);                                                \
+        float b = bf16_to_float(b_bits);                                                \
+        return bf16_from_float<ReducedRounding::NearestEven>(EXPR);                     \
+    }                                                                                   \
+                                                                                        \
+    uint32_t FUNC(NAME##_bf16x2)(uint32_t a, uint32_t b)                                \
+    {                                                                                   \
+        uint16_t lo = FUNC_CALL(NAME##_bf16)((uint16_t)a, (uint16_t)b);                 \
+        uint16_t hi = FUNC_CALL(NAME##_bf16)((uint16_t)(a >> 16), (uint16_t)(b >> 16)); \
+        return pack_16x2(lo, hi);                                                       \
+    }
+
+    BF16_BINARY(add_rn, a + b)
+    BF16_BINARY(sub_rn, a - b)
+    BF16_BINARY(mul_rn, a * b)
+    BF16_BINARY(min, fminf(a, b))
+    BF16_BINARY(max, fmaxf(a, b))
+    BF16_BINARY(min_nan, (__builtin_isnan(a) || __builtin_isnan(b)) ? __builtin_nanf("") : fminf(a, b))
+    BF16_BINARY(max_nan, (__builtin_isnan(a) || __builtin_isnan(b)) ? __builtin_nanf("") : fmaxf(a, b))
+
+    uint16_t FUNC(fma_rn_bf16)(uint16_t a, uint16_t b, uint16_t c)
+    {
+        return bf16_from_float<ReducedRounding::NearestEven>(fmaf(bf16_to_float(a), bf16_to_float(b), bf16_to_float(c)));
+    }
+
+    uint32_t FUNC(fma_rn_bf16x2)(uint32_t a, uint32_t b, uint32_t c)
+    {
+        uint16_t lo = FUNC_CALL(fma_rn_bf16)((uint16_t)a, (uint16_t)b, (uint16_t)c);
+        uint16_t hi = FUNC_CALL(fma_rn_bf16)((uint16_t)(a >> 16), (uint16_t)(b >> 16), (uint16_t)(c >> 16));
+        return pack_16x2(lo, hi);
+    }
+
+    uint16_t FUNC(neg_bf16)(uint16_t x)
+    {
+        return (uint16_t)(x ^ 0x8000);
+    }
+
+    uint32_t FUNC(neg_bf16x2)(uint32_t x)
+    {
+        return x ^ 0x80008000;
+    }
+
+    uint16_t FUNC(abs_bf16)(uint16_t x)
+    {
+        return (uint16_t)(x & 0x7FFF);
+    }
+
+    uint32_t FUNC(abs_bf16x2)(uint32_t x)
+    {
+        return x & 0x7FFF7FFF;
+    }
+
+    // atom.add and red.add on f16x2, bf16 and bf16x2, implemented as a compare-and-swap loop
+    static __device__ zluda_half2 add_f16x2(zluda_half2 a, zluda_half2 b)
+    {
+        return a + b;
+    }
+
+#define atomic_add_packed(SEMANTICS, SUCCESS, FAILURE, SCOPE_NAME, SCOPE, SPACE_NAME, SPACE, TYPE_NAME, TYPE, BITS, OP)           \
+    TYPE FUNC(atom_##SEMANTICS##_##SCOPE_NAME##_##SPACE_NAME##_add_##TYPE_NAME)(SPACE TYPE * ptr, TYPE value)                     \
+    {                                                                                                                             \
+        BITS expected = *(SPACE BITS *)ptr;                                                                                       \
+        BITS desired;                                                                                                             \
+        do                                                                                                                        \
+        {                                                                                                                         \
+            desired = transmute<BITS>(OP(transmute<TYPE>(expected), value));                                                      \
+        } while (!__hip_atomic_compare_exchange_strong((volatile SPACE BITS *)ptr, &expected, desired, SUCCESS, FAILURE, SCOPE)); \
+        return transmute<TYPE>(expected);                                                                                         \
+    }                                                                                                                             \
+                                                                                                                                  \
+    void FUNC(red_##SEMANTICS##_##SCOPE_NAME##_##SPACE_NAME##_add_##TYPE_NAME)(SPACE TYPE * ptr, TYPE value)                      \
+    {                                                                                                                             \
+        FUNC_CALL(atom_##SEMANTICS##_##SCOPE_NAME##_##SPACE_NAME##_add_##TYPE_NAME)(ptr, value);                                  \
+    }
+
+#define atomic_add_packed_types(SEMANTICS, SUCCESS, FAILURE, SCOPE_NAME, SCOPE, SPACE_NAME, SPACE)                                         \
+    atomic_add_packed(SEMANTICS, SUCCESS, FAILURE, SCOPE_NAME, SCOPE, SPACE_NAME, SPACE, f16x2, zluda_half2, uint32_t, add_f16x2)          \
+    atomic_add_packed(SEMANTICS, SUCCESS, FAILURE, SCOPE_NAME, SCOPE, SPACE_NAME, SPACE, bf16, uint16_t, uint16_t, FUNC_CALL(add_rn_bf16)) \
+    atomic_add_packed(SEMANTICS, SUCCESS, FAILURE, SCOPE_NAME, SCOPE, SPACE_NAME, SPACE, bf16x2, uint32_t, uint32_t, FUNC_CALL(add_rn_bf16x2))
+
+#define atomic_add_packed_spaces(SEMANTICS, SUCCESS, FAILURE, SCOPE_NAME, SCOPE)                    \
+    atomic_add_packed_types(SEMANTICS, SUCCESS, FAILURE, SCOPE_NAME, SCOPE, generic, GENERIC_SPACE) \
+    atomic_add_packed_types(SEMANTICS, SUCCESS, FAILURE, SCOPE_NAME, SCOPE, global, GLOBAL_SPACE)   \
+    atomic_add_packed_types(SEMANTICS, SUCCESS, FAILURE, SCOPE_NAME, SCOPE, shared, SHARED_SPACE)
+
+    atomic_add_packed_spaces(relaxed, __ATOMIC_RELAXED, __ATOMIC_RELAXED, cta, __HIP_MEMORY_SCOPE_WORKGROUP)
+    atomic_add_packed_spaces(acquire, __ATOMIC_ACQUIRE, __ATOMIC_ACQUIRE, cta, __HIP_MEMORY_SCOPE_WORKGROUP)
+    atomic_add_packed_spaces(release, __ATOMIC_RELEASE, __ATOMIC_ACQUIRE, cta, __HIP_MEMORY_SCOPE_WORKGROUP)
+    atomic_add_packed_spaces(acq_rel, __ATOMIC_ACQ_REL, __ATOMIC_ACQUIRE, cta, __HIP_MEMORY_SCOPE_WORKGROUP)
+
+    atomic_add_packed_spaces(relaxed, __ATOMIC_RELAXED, __ATOMIC_RELAXED, gpu, __HIP_MEMORY_SCOPE_AGENT)
+    atomic_add_packed_spaces(acquire, __ATOMIC_ACQUIRE, __ATOMIC_ACQUIRE, gpu, __HIP_MEMORY_SCOPE_AGENT)
+    atomic_add_packed_spaces(release, __ATOMIC_RELEASE, __ATOMIC_ACQUIRE, gpu, __HIP_MEMORY_SCOPE_AGENT)
+    atomic_add_packed_spaces(acq_rel, __ATOMIC_ACQ_REL, __ATOMIC_ACQUIRE, gpu, __HIP_MEMORY_SCOPE_AGENT)
+
+    atomic_add_packed_spaces(relaxed, __ATOMIC_RELAXED, __ATOMIC_RELAXED, sys, __HIP_MEMORY_SCOPE_SYSTEM)
+    atomic_add_packed_spaces(acquire, __ATOMIC_ACQUIRE, __ATOMIC_ACQUIRE, sys, __HIP_MEMORY_SCOPE_SYSTEM)
+    atomic_add_packed_spaces(release, __ATOMIC_RELEASE, __ATOMIC_ACQUIRE, sys, __HIP_MEMORY_SCOPE_SYSTEM)
+    atomic_add_packed_spaces(acq_rel, __ATOMIC_ACQ_REL, __ATOMIC_ACQUIRE, sys, __HIP_MEMORY_SCOPE_SYSTEM)
+}
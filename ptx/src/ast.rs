@@ -16,44 +16,64 @@ pub enum PtxError {
         #[from]
         source: ParseFloatError,
     },
-    #[error("")]
+    #[error("invalid combination of instruction modifiers")]
     SyntaxError,
-    #[error("")]
+    #[error(".ftz modifier is only allowed for .f32 destination")]
     NonF32Ftz,
-    #[error("")]
+    #[error("wrong array type")]
     WrongArrayType,
-    #[error("")]
+    #[error("vector element must be one of .x, .y, .z, .w or .r, .g, .b, .a")]
     WrongVectorElement,
-    #[error("")]
+    #[error("array variable can't be declared with a count suffix (<N>)")]
     MultiArrayVariable,
-    #[error("")]
+    #[error("only the first dimension of an array can be left unspecified")]
     ZeroDimensionArray,
-    #[error("")]
+    #[error("array initializer does not match the array dimensions")]
     ArrayInitializer,
-    #[error("")]
+    #[error("scalar variable can't be initialized with an array")]
     ScalarInitalizer,
-    #[error("")]
+    #[error("array initializer can only contain scalar values")]
     NonScalarArray,
-    #[error("")]
+    #[error("variable can't be declared in this state space")]
     InvalidStateSpace,
-    #[error("")]
+    #[error("variable can't be named `_`")]
     BlankVariableName,
-    #[error("")]
+    #[error(".pred and vector variables can only be declared in .reg state space")]
     NonRegPredVariable,
-    #[error("")]
+    #[error("initializer does not match the type of the variable")]
     InitializerTypeMismatch,
-    #[error("")]
+    #[error("pointer is only allowed in .extern declarations")]
     NonExternPointer,
-    #[error("{start}:{end}")]
+    #[error("unrecognized statement")]
     UnrecognizedStatement { start: usize, end: usize },
-    #[error("{start}:{end}")]
+    #[error("unrecognized directive")]
     UnrecognizedDirective { start: usize, end: usize },
-    #[error("")]
+    #[error(".target directive does not specify a shader model (sm_XX)")]
     NoSmVersion,
-    #[error("")]
+    #[error("module-level variables must be declared one per directive")]
     UnexpectedMultivariable,
-    #[error("")]
+    #[error(".extern declarations can't be defined, other declarations must be complete")]
     ExternDefinition,
+    // Most errors are reported by grammar actions that don't know their
+    // location, so they get a span of the enclosing statement or directive
+    #[error("{source}")]
+    Spanned {
+        start: usize,
+        end: usize,
+        source: Box<PtxError>,
+    },
+}
+
+impl PtxError {
+    // Byte offsets into the module text
+    pub fn span(&self) -> Option<(usize, usize)> {
+        match self {
+            PtxError::UnrecognizedStatement { start, end }
+            | PtxError::UnrecognizedDirective { start, end }
+            | PtxError::Spanned { start, end, .. } => Some((*start, *end)),
+            _ => None,
+        }
+    }
 }
 
 // For some weird reson this is illegal:
@@ -107,8 +127,12 @@ impl<
 }
 
 pub struct Module<'a> {
+    // Major and minor PTX ISA version from the `.version` directive
+    pub version: (u32, u32),
     pub sm_version: u32,
     pub directives: Vec<Directive<'a, ParsedArgParams<'a>>>,
+    // Index and name from every `.file` directive
+    pub files: Vec<(u32, &'a str)>,
 }
 
 pub enum Directive<'a, P: ArgParams> {
@@ -213,6 +237,11 @@ pub enum ScalarType {
     F32,
     F64,
     F16x2,
+    BF16,
+    BF16x2,
+    TF32,
+    E4M3x2,
+    E5M2x2,
     Pred,
 }
 
@@ -235,6 +264,11 @@ impl ScalarType {
             ScalarType::F32 => "f32",
             ScalarType::F64 => "f64",
             ScalarType::F16x2 => "f16x2",
+            ScalarType::BF16 => "bf16",
+            ScalarType::BF16x2 => "bf16x2",
+            ScalarType::TF32 => "tf32",
+            ScalarType::E4M3x2 => "e4m3x2",
+            ScalarType::E5M2x2 => "e5m2x2",
             ScalarType::Pred => "pred",
         }
     }
@@ -259,6 +293,11 @@ impl ScalarType {
             ScalarType::B64 => 8,
             ScalarType::F64 => 8,
             ScalarType::F16x2 => 4,
+            ScalarType::BF16 => 2,
+            ScalarType::BF16x2 => 4,
+            ScalarType::TF32 => 4,
+            ScalarType::E4M3x2 => 2,
+            ScalarType::E5M2x2 => 2,
             ScalarType::Pred => 1,
         }
     }
@@ -291,6 +330,33 @@ pub enum Statement<P: ArgParams> {
     Variable(Vec<MultiVariableDefinition<P::Id>>),
     Instruction(Option<PredAt<P::Id>>, Instruction<P>),
     Block(Vec<Statement<P>>),
+    Location(SourceLocation),
+    Span(SourceSpan),
+}
+
+// Byte offsets of the statement that follows in the module text. Only kept so
+// errors found during translation can point at the source
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+// https://docs.nvidia.com/cuda/parallel-thread-execution/index.html#debugging-directives-loc
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: u32,
+    pub line: u32,
+    pub column: u32,
+    pub inlined_at: Option<InlinedAt>,
+}
+
+// We don't keep the function name, it's a label in .debug_str section
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct InlinedAt {
+    pub file: u32,
+    pub line: u32,
+    pub column: u32,
 }
 
 #[derive(Clone)]
@@ -352,6 +418,28 @@ pub struct BfindDetails {
     pub type_: ScalarType,
 }
 
+#[derive(Copy, Clone)]
+pub struct ReduxDetails {
+    pub op: ReduxOp,
+    pub type_: ScalarType,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum ReduxOp {
+    Add,
+    Min,
+    Max,
+    And,
+    Or,
+    Xor,
+}
+
+#[derive(Copy, Clone)]
+pub struct Mul24Details {
+    pub type_: ScalarType,
+    pub is_hi: bool,
+}
+
 pub enum Instruction<P: ArgParams> {
     Ld(LdDetails, Arg2Ld<P>),
     Mov(MovDetails, Arg2Mov<P>),
@@ -364,6 +452,7 @@ pub enum Instruction<P: ArgParams> {
     Not(ScalarType, Arg2<P>),
     Bra(BraData, Arg1<P>),
     Cvt(CvtDetails, Arg2<P>),
+    CvtPackedFloat(CvtDesc, Arg3<P>),
     Cvta(CvtaDetails, Arg2<P>),
     Shl(ScalarType, Arg3<P>),
     Shr(ScalarType, Arg3<P>),
@@ -415,9 +504,11 @@ pub enum Instruction<P: ArgParams> {
         arg: Arg2<P>,
     },
     Ex2 {
+        typ: ScalarType,
         flush_to_zero: bool,
         arg: Arg2<P>,
     },
+    Tanh(ScalarType, Arg2<P>),
     Clz {
         typ: ScalarType,
         arg: Arg2<P>,
@@ -460,9 +551,17 @@ pub enum Instruction<P: ArgParams> {
     Membar {
         level: MemScope,
     },
+    Fence {
+        semantics: FenceSemantics,
+        scope: MemScope,
+    },
+    FenceProxy(ProxyKind),
     Tex(TexDetails, Arg5Tex<P>),
+    Tld4(Tld4Details, Arg5Tex<P>),
+    Txq(ImageQueryDetails, Arg2Query<P>),
     Suld(SurfaceDetails, Arg5Tex<P>),
     Sust(SurfaceDetails, Arg4Sust<P>),
+    Suq(ImageQueryDetails, Arg2Query<P>),
     Shfl(ShflMode, Arg5Shfl<P>),
     Shf(FunnelShift, Arg4<P>),
     Vote(VoteDetails, Arg3<P>),
@@ -473,11 +572,134 @@ pub enum Instruction<P: ArgParams> {
     Bfind(BfindDetails, Arg2<P>),
     Set(SetData, Arg3<P>),
     Dp4a(ScalarType, Arg4<P>),
+    Copysign(ScalarType, Arg3<P>),
+    Testp(TestpOp, ScalarType, Arg2<P>),
+    CvtPack(CvtPackDetails, Arg4<P>),
     MatchAny(Arg3<P>),
+    Redux(ReduxDetails, Arg3<P>),
+    Lop3 {
+        lut: u8,
+        arg: Arg4<P>,
+    },
+    Bmsk(ShiftNormalization, Arg3<P>),
+    Szext(ShiftNormalization, ScalarType, Arg3<P>),
+    Fns(Arg4<P>),
+    Mul24(Mul24Details, Arg3<P>),
+    Mad24(Mul24Details, Arg4<P>),
     Red(AtomDetails, Arg2St<P>),
     Nanosleep(Arg1<P>),
     Isspacep(StateSpace, Arg2<P>),
     Sad(ScalarType, Arg4<P>),
+    WmmaLoad(WmmaLoadDetails, Arg3<P>),
+    WmmaStore(WmmaStoreDetails, Arg3St<P>),
+    WmmaMma(MmaDetails, Arg4<P>),
+    Mma(MmaDetails, Arg4<P>),
+    CpAsync(CpAsyncDetails, Arg3St<P>),
+    CpAsyncCommitGroup,
+    CpAsyncWaitGroup(u32),
+    MbarrierInit(StateSpace, Arg2St<P>),
+    MbarrierArrive(StateSpace, Arg3<P>),
+    MbarrierTestWait(MbarrierTestWaitDetails, Arg3<P>),
+    Mapa(ScalarType, Arg3<P>),
+    Getctarank(ScalarType, Arg2<P>),
+    BarrierCluster(BarrierClusterOp),
+    ElectSync(Arg2Elect<P>),
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum MatrixShape {
+    M16N16K16,
+    M16N16K8,
+    M16N8K16,
+    M16N8K8,
+    M8N8K4,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum MatrixLayout {
+    Row,
+    Col,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum MatrixType {
+    F16,
+    BF16,
+    TF32,
+    S8,
+    U8,
+    F32,
+    S32,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum MatrixFragment {
+    A,
+    B,
+    C,
+}
+
+#[derive(Copy, Clone)]
+pub struct WmmaLoadDetails {
+    pub fragment: MatrixFragment,
+    pub layout: MatrixLayout,
+    pub shape: MatrixShape,
+    pub state_space: StateSpace,
+    pub type_: MatrixType,
+}
+
+#[derive(Copy, Clone)]
+pub struct WmmaStoreDetails {
+    pub layout: MatrixLayout,
+    pub shape: MatrixShape,
+    pub state_space: StateSpace,
+    pub type_: MatrixType,
+}
+
+#[derive(Copy, Clone)]
+pub struct MmaDetails {
+    pub a_layout: MatrixLayout,
+    pub b_layout: MatrixLayout,
+    pub shape: MatrixShape,
+    pub d_type: MatrixType,
+    pub a_type: MatrixType,
+    pub b_type: MatrixType,
+    pub c_type: MatrixType,
+    pub satfinite: bool,
+}
+
+#[derive(Copy, Clone)]
+pub struct CpAsyncDetails {
+    pub cache_operator: LdCacheOperator,
+    pub cp_size: u32,
+}
+
+#[derive(Copy, Clone)]
+pub struct MbarrierTestWaitDetails {
+    pub state_space: StateSpace,
+    pub parity: bool,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum BarrierClusterOp {
+    Arrive,
+    Wait,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum TestpOp {
+    Finite,
+    Infinite,
+    Number,
+    NotANumber,
+    Normal,
+    Subnormal,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct CvtPackDetails {
+    pub bits: u8,
+    pub signed: bool,
 }
 
 #[derive(Copy, Clone)]
@@ -598,6 +820,17 @@ pub struct Arg2Mov<P: ArgParams> {
     pub src: P::Operand,
 }
 
+pub struct Arg2Query<P: ArgParams> {
+    pub dst: P::Operand,
+    pub image: P::Operand,
+}
+
+pub struct Arg3St<P: ArgParams> {
+    pub src1: P::Operand,
+    pub src2: P::Operand,
+    pub src3: P::Operand,
+}
+
 pub struct Arg3<P: ArgParams> {
     pub dst: P::Operand,
     pub src1: P::Operand,
@@ -611,6 +844,12 @@ pub struct Arg4<P: ArgParams> {
     pub src3: P::Operand,
 }
 
+pub struct Arg2Elect<P: ArgParams> {
+    pub dst1: Option<P::Id>,
+    pub dst2: P::Id,
+    pub src: P::Operand,
+}
+
 pub struct Arg4Setp<P: ArgParams> {
     pub dst1: P::Id,
     pub dst2: Option<P::Id>,
@@ -639,6 +878,8 @@ pub struct Arg5Tex<P: ArgParams> {
     pub layer: Option<P::Operand>,
     pub coordinates: P::Operand,
     pub lod: Option<P::Operand>,
+    // (dPdx, dPdy) of tex.grad
+    pub gradient: Option<(P::Operand, P::Operand)>,
 }
 
 pub struct Arg5Setp<P: ArgParams> {
@@ -794,6 +1035,20 @@ pub enum MemScope {
     Sys,
 }
 
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum FenceSemantics {
+    SequentiallyConsistent,
+    AcquireRelease,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ProxyKind {
+    Alias,
+    Async,
+    AsyncGlobal,
+    AsyncShared,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum LdCacheOperator {
     Cached,
@@ -843,6 +1098,7 @@ pub enum RoundingMode {
     Zero,
     NegativeInf,
     PositiveInf,
+    NearestAway,
 }
 
 pub struct AddIntDesc {
@@ -1068,6 +1324,7 @@ pub struct AtomDetails {
     pub scope: MemScope,
     pub space: StateSpace,
     pub inner: AtomInnerDetails,
+    pub vector: Option<u8>,
 }
 
 #[derive(Copy, Clone)]
@@ -1267,7 +1524,13 @@ impl<'a> NumsOrArrays<'a> {
             ScalarType::F16 => {
                 Self::parse_and_copy_single_t::<f16>(idx, str_val, radix, output)?;
             }
-            ScalarType::F16x2 => todo!(),
+            // Packed and storage-only types are initialized with their bit patterns
+            ScalarType::BF16 | ScalarType::E4M3x2 | ScalarType::E5M2x2 => {
+                Self::parse_and_copy_single_bits::<u16>(idx, str_val, radix, output)?;
+            }
+            ScalarType::F16x2 | ScalarType::BF16x2 | ScalarType::TF32 => {
+                Self::parse_and_copy_single_bits::<u32>(idx, str_val, radix, output)?;
+            }
             ScalarType::F32 => {
                 Self::parse_and_copy_single_t::<f32>(idx, str_val, radix, output)?;
             }
@@ -1297,6 +1560,22 @@ impl<'a> NumsOrArrays<'a> {
         typed_output[idx] = str_val.parse::<T>().map_err(|e| e.into())?;
         Ok(())
     }
+
+    fn parse_and_copy_single_bits<T: Copy + num_traits::Num<FromStrRadixErr = ParseIntError>>(
+        idx: usize,
+        str_val: &str,
+        radix: u32,
+        output: &mut [u8],
+    ) -> Result<(), PtxError> {
+        let typed_output = unsafe {
+            std::slice::from_raw_parts_mut::<T>(
+                output.as_mut_ptr() as *mut _,
+                output.len() / mem::size_of::<T>(),
+            )
+        };
+        typed_output[idx] = T::from_str_radix(str_val, radix)?;
+        Ok(())
+    }
 }
 
 pub enum ArrayOrPointer {
@@ -1352,6 +1631,11 @@ impl ScalarType {
             ScalarType::F32 => ScalarKind::Float,
             ScalarType::F64 => ScalarKind::Float,
             ScalarType::F16x2 => ScalarKind::Float2,
+            ScalarType::BF16 => ScalarKind::Float,
+            ScalarType::BF16x2 => ScalarKind::Float2,
+            ScalarType::TF32 => ScalarKind::Float,
+            ScalarType::E4M3x2 => ScalarKind::Float2,
+            ScalarType::E5M2x2 => ScalarKind::Float2,
             ScalarType::Pred => ScalarKind::Pred,
         }
     }
@@ -1365,6 +1649,38 @@ pub struct TexDetails {
     pub direct: bool,
 }
 
+pub struct Tld4Details {
+    pub component: TextureComponent,
+    pub channel_type: ScalarType,
+    // direct = takes .texref, indirect = takes .u64
+    pub direct: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TextureComponent {
+    R,
+    G,
+    B,
+    A,
+}
+
+pub struct ImageQueryDetails {
+    pub query: ImageQuery,
+    // direct = takes .texref/.surfref, indirect = takes .u64
+    pub direct: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ImageQuery {
+    Width,
+    Height,
+    Depth,
+    ChannelDataType,
+    ChannelOrder,
+    ArraySize,
+    NumMipmapLevels,
+}
+
 pub struct SurfaceDetails {
     pub geometry: TextureGeometry,
     pub vector: Option<u8>,
@@ -1440,4 +1756,20 @@ mod tests {
         let mut dimensions = vec![0u32, 2];
         assert!(inp.to_vec(ScalarType::B8, &mut dimensions).is_err());
     }
+
+    #[test]
+    fn array_of_storage_only_type_is_bit_pattern() {
+        let inp = NumsOrArrays::Nums(vec![("3f80", 16), ("49024", 10)]);
+        let mut dimensions = vec![0u32];
+        assert_eq!(
+            vec![0x80u8, 0x3f, 0x80, 0xbf],
+            inp.to_vec(ScalarType::BF16, &mut dimensions).unwrap()
+        );
+    }
+
+    #[test]
+    fn array_of_storage_only_type_fails_on_float() {
+        let inp = NumsOrArrays::Nums(vec![("1.0", 10)]);
+        assert!(inp.to_vec(ScalarType::TF32, &mut vec![0]).is_err());
+    }
 }
@@ -0,0 +1,274 @@
+use crate::ast::PtxError;
+use crate::{translate, DisplayParseError, ModuleParser, ModuleParserExt, ParseError};
+use hip_common::CompilationMode;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+// Newest PTX ISA version and shader model accepted without a warning, they
+// match CUDA version (12.2) and compute capability (8.8) reported by ZLUDA
+pub const MAX_PTX_VERSION: (u32, u32) = (8, 2);
+pub const MAX_SM_VERSION: u32 = 88;
+
+// Special registers defined by PTX ISA, used to tell a special register we
+// can't map apart from a plain undeclared identifier
+const PTX_SPECIAL_REGISTERS: &[&str] = &[
+    "%aggr_smem_size",
+    "%clock",
+    "%clock64",
+    "%cluster_ctaid",
+    "%cluster_ctarank",
+    "%cluster_nctaid",
+    "%cluster_nctarank",
+    "%clusterid",
+    "%ctaid",
+    "%current_graph_exec",
+    "%dynamic_smem_size",
+    "%globaltimer",
+    "%globaltimer_hi",
+    "%globaltimer_lo",
+    "%gridid",
+    "%is_explicit_cluster",
+    "%laneid",
+    "%lanemask_eq",
+    "%lanemask_ge",
+    "%lanemask_gt",
+    "%lanemask_le",
+    "%lanemask_lt",
+    "%nclusterid",
+    "%nctaid",
+    "%nsmid",
+    "%ntid",
+    "%nwarpid",
+    "%smid",
+    "%tid",
+    "%total_smem_size",
+    "%warpid",
+];
+// Numbered or suffixed families: %envreg0..31, %pm0..7, %pm0_64, ...
+const PTX_SPECIAL_REGISTER_PREFIXES: &[&str] = &["%envreg", "%pm", "%reserved_smem_offset_"];
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+    // Byte offsets into the module text
+    pub span: Option<(usize, usize)>,
+    pub note: Option<String>,
+}
+
+impl Issue {
+    pub fn display<'a>(&'a self, text: &'a str, file_name: Option<&'a str>) -> DisplayIssue<'a> {
+        DisplayIssue {
+            issue: self,
+            text,
+            file_name,
+        }
+    }
+}
+
+pub struct DisplayIssue<'a> {
+    issue: &'a Issue,
+    text: &'a str,
+    file_name: Option<&'a str>,
+}
+
+impl<'a> fmt::Display for DisplayIssue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        crate::fmt_diagnostic(
+            f,
+            self.text,
+            self.file_name,
+            self.issue.severity.as_str(),
+            &self.issue.message,
+            self.issue.span,
+            self.issue.note.as_deref(),
+        )
+    }
+}
+
+// Reports everything that would stop ZLUDA from compiling the module, short
+// of running comgr: parse errors (most often instructions we don't support),
+// identifiers that can't be resolved (including special registers we can't
+// map) and translation errors. Too new .version or .target are warnings
+pub fn check(text: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let (ast, errors) = ModuleParser::parse_unchecked(text);
+    for error in errors.iter() {
+        let display = unsafe { DisplayParseError::new(error, text) };
+        let message = match error {
+            ParseError::User {
+                error: PtxError::UnrecognizedStatement { start, end },
+            } => {
+                let statement = &text[*start..*end];
+                let opcode = statement
+                    .split(|c: char| c.is_whitespace() || c == ';')
+                    .find(|word| !word.is_empty() && !word.starts_with('@'))
+                    .unwrap_or(statement);
+                format!("unsupported or malformed instruction `{}`", opcode)
+            }
+            _ => display.message(),
+        };
+        issues.push(Issue {
+            severity: Severity::Error,
+            message,
+            span: display.span(),
+            note: display.expected(),
+        });
+    }
+    // AST of a module with parse errors is incomplete, further checks would
+    // only report bogus issues
+    if !errors.is_empty() {
+        return issues;
+    }
+    if ast.version > MAX_PTX_VERSION {
+        issues.push(Issue {
+            severity: Severity::Warning,
+            message: format!(
+                "PTX ISA version {}.{} is newer than the supported {}.{}",
+                ast.version.0, ast.version.1, MAX_PTX_VERSION.0, MAX_PTX_VERSION.1
+            ),
+            span: directive_span(text, ".version"),
+            note: None,
+        });
+    }
+    if ast.sm_version > MAX_SM_VERSION {
+        issues.push(Issue {
+            severity: Severity::Warning,
+            message: format!(
+                "target sm_{} is newer than the supported sm_{}",
+                ast.sm_version, MAX_SM_VERSION
+            ),
+            span: directive_span(text, ".target"),
+            note: Some("instructions specific to newer GPUs might be missing".to_string()),
+        });
+    }
+    let unresolved = translate::unresolved_symbols(ast);
+    for name in unresolved.iter() {
+        let message = if is_special_register(name) {
+            format!("special register `{}` is not supported", name)
+        } else {
+            format!("use of an undeclared identifier `{}`", name)
+        };
+        issues.push(Issue {
+            severity: Severity::Error,
+            message,
+            span: identifier_span(text, name),
+            note: None,
+        });
+    }
+    if !unresolved.is_empty() {
+        return issues;
+    }
+    let ast = match ModuleParser::parse_checked(text) {
+        Ok(ast) => ast,
+        Err(_) => return issues,
+    };
+    // In debug builds translation errors panic at the point of creation
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        translate::to_llvm_module(CompilationMode::Wave32, vec![ast])
+            .map(|_| ())
+            .map_err(|error| error.to_string())
+    }));
+    let error = match result {
+        Ok(Ok(())) => return issues,
+        Ok(Err(error)) => Some(error),
+        Err(panic) => panic
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned()),
+    };
+    issues.push(Issue {
+        severity: Severity::Error,
+        message: format!(
+            "PTX translation failed: {}",
+            error.as_deref().unwrap_or("unknown error")
+        ),
+        span: None,
+        note: None,
+    });
+    issues
+}
+
+fn is_special_register(name: &str) -> bool {
+    PTX_SPECIAL_REGISTERS.contains(&name)
+        || PTX_SPECIAL_REGISTER_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+}
+
+fn directive_span(text: &str, directive: &str) -> Option<(usize, usize)> {
+    let start = text.find(directive)?;
+    let line = text[start..].lines().next()?;
+    Some((start, start + line.trim_end().len()))
+}
+
+// AST has no locations, so we point at the first use of the identifier
+fn identifier_span(text: &str, name: &str) -> Option<(usize, usize)> {
+    let is_identifier_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '%');
+    text.match_indices(name)
+        .map(|(start, _)| (start, start + name.len()))
+        .find(|(start, end)| {
+            !text[..*start].ends_with(is_identifier_char)
+                && !text[*end..].starts_with(is_identifier_char)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, Severity};
+
+    #[test]
+    fn reports_unsupported_special_register() {
+        let issues = check(
+            "
+            .version 6.5
+            .target sm_30
+            .address_size 64
+
+            .visible .entry time(.param .u64 output)
+            {
+                .reg .u64 t;
+                mov.u64 t, %globaltimer;
+                st.u64 [undeclared], t;
+                ret;
+            }",
+        );
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().all(|issue| issue.severity == Severity::Error));
+        assert!(issues[0].message.contains("`%globaltimer`"));
+        assert!(issues[1].message.contains("`undeclared`"));
+    }
+
+    #[test]
+    fn warns_about_newer_target() {
+        let issues = check(
+            "
+            .version 9.0
+            .target sm_100
+            .address_size 64
+
+            .visible .entry empty()
+            {
+                ret;
+            }",
+        );
+        assert_eq!(issues.len(), 2);
+        assert!(issues
+            .iter()
+            .all(|issue| issue.severity == Severity::Warning));
+    }
+}
@@ -9,6 +9,7 @@ use std::io::Write;
 use std::ptr::null_mut;
 use std::{iter, mem, ptr};
 use zluda_llvm::core::*;
+use zluda_llvm::debuginfo::*;
 use zluda_llvm::prelude::*;
 use zluda_llvm::zluda::*;
 use zluda_llvm::*;
@@ -38,6 +39,7 @@ struct EmitContext<'a> {
     names: NamedIdGenerator,
     denorm_statistics: FxHashMap<Id, DenormSummary>,
     compilation_mode: CompilationMode,
+    debug_info: Option<DebugInfo>,
 }
 
 impl<'a> EmitContext<'a> {
@@ -49,6 +51,7 @@ impl<'a> EmitContext<'a> {
         directive: &[Directive<'input>],
         denorm_statistics: FxHashMap<Id, DenormSummary>,
         compilation_mode: CompilationMode,
+        source_files: &[&'input str],
     ) -> Self {
         let builder = unsafe { llvm::Builder::create(context.get()) };
         let texref_underlying_type =
@@ -62,6 +65,233 @@ impl<'a> EmitContext<'a> {
             names: NamedIdGenerator::new(id_gen, id_defs, directive),
             denorm_statistics,
             compilation_mode,
+            debug_info: unsafe { DebugInfo::new(context, module, source_files) },
+        }
+    }
+}
+
+// Line tables built from .file and .loc directives. Only methods with at least
+// one .loc get a DISubprogram
+struct DebugInfo {
+    builder: LLVMDIBuilderRef,
+    files: Vec<LLVMMetadataRef>,
+    subroutine_type: LLVMMetadataRef,
+    inlined_subprograms: FxHashMap<u32, LLVMMetadataRef>,
+    method: Option<MethodDebugInfo>,
+}
+
+struct MethodDebugInfo {
+    subprogram: LLVMMetadataRef,
+    file: u32,
+    file_scopes: FxHashMap<u32, LLVMMetadataRef>,
+}
+
+impl DebugInfo {
+    unsafe fn new(
+        context: &llvm::Context,
+        module: &llvm::Module,
+        source_files: &[&str],
+    ) -> Option<Self> {
+        if source_files.is_empty() {
+            return None;
+        }
+        let builder = LLVMCreateDIBuilder(module.get());
+        let files = source_files
+            .iter()
+            .map(|&path| {
+                let (directory, file_name) = path
+                    .rsplit_once(|c: char| c == '/' || c == '\\')
+                    .unwrap_or(("", path));
+                LLVMDIBuilderCreateFile(
+                    builder,
+                    file_name.as_ptr().cast(),
+                    file_name.len(),
+                    directory.as_ptr().cast(),
+                    directory.len(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let producer = "ZLUDA";
+        LLVMDIBuilderCreateCompileUnit(
+            builder,
+            LLVMDWARFSourceLanguage::LLVMDWARFSourceLanguageC_plus_plus,
+            files[0],
+            producer.as_ptr().cast(),
+            producer.len(),
+            1,
+            ptr::null(),
+            0,
+            0,
+            ptr::null(),
+            0,
+            LLVMDWARFEmissionKind::LLVMDWARFEmissionKindLineTablesOnly,
+            0,
+            0,
+            0,
+            ptr::null(),
+            0,
+            ptr::null(),
+            0,
+        );
+        let subroutine_type = LLVMDIBuilderCreateSubroutineType(
+            builder,
+            files[0],
+            ptr::null_mut(),
+            0,
+            LLVMDIFlagZero,
+        );
+        let debug_info_version = b"Debug Info Version";
+        LLVMAddModuleFlag(
+            module.get(),
+            LLVMModuleFlagBehavior::LLVMModuleFlagBehaviorWarning,
+            debug_info_version.as_ptr().cast(),
+            debug_info_version.len(),
+            LLVMValueAsMetadata(LLVMConstInt(
+                LLVMInt32TypeInContext(context.get()),
+                LLVMDebugMetadataVersion() as u64,
+                0,
+            )),
+        );
+        Some(Self {
+            builder,
+            files,
+            subroutine_type,
+            inlined_subprograms: FxHashMap::default(),
+            method: None,
+        })
+    }
+
+    unsafe fn start_method(
+        &mut self,
+        context: LLVMContextRef,
+        builder: LLVMBuilderRef,
+        llvm_method: LLVMValueRef,
+        first_location: ast::SourceLocation,
+    ) {
+        let (file, line) = match first_location.inlined_at {
+            Some(inlined_at) => (inlined_at.file, inlined_at.line),
+            None => (first_location.file, first_location.line),
+        };
+        let file_ref = self.files[file as usize];
+        let mut name_len = 0;
+        let name = LLVMGetValueName2(llvm_method, &mut name_len);
+        let subprogram = LLVMDIBuilderCreateFunction(
+            self.builder,
+            file_ref,
+            name,
+            name_len,
+            name,
+            name_len,
+            file_ref,
+            line,
+            self.subroutine_type,
+            0,
+            1,
+            line,
+            LLVMDIFlagZero,
+            1,
+        );
+        LLVMSetSubprogram(llvm_method, subprogram);
+        self.method = Some(MethodDebugInfo {
+            subprogram,
+            file,
+            file_scopes: FxHashMap::default(),
+        });
+        // Every instruction in a function with a DISubprogram should have a
+        // location, including variable declarations and prologue
+        let location =
+            LLVMDIBuilderCreateDebugLocation(context, line, 0, subprogram, ptr::null_mut());
+        LLVMSetCurrentDebugLocation2(builder, location);
+    }
+
+    unsafe fn end_method(&mut self, builder: LLVMBuilderRef) {
+        if self.method.take().is_some() {
+            LLVMSetCurrentDebugLocation2(builder, ptr::null_mut());
+        }
+    }
+
+    unsafe fn set_location(
+        &mut self,
+        context: LLVMContextRef,
+        builder: LLVMBuilderRef,
+        location: ast::SourceLocation,
+    ) {
+        let (scope, inlined_at) = match location.inlined_at {
+            Some(inlined_at) => {
+                let call_site_scope = match self.method_scope(inlined_at.file) {
+                    Some(scope) => scope,
+                    None => return,
+                };
+                let call_site = LLVMDIBuilderCreateDebugLocation(
+                    context,
+                    inlined_at.line,
+                    inlined_at.column,
+                    call_site_scope,
+                    ptr::null_mut(),
+                );
+                (self.inlined_subprogram(location.file), call_site)
+            }
+            None => match self.method_scope(location.file) {
+                Some(scope) => (scope, ptr::null_mut()),
+                None => return,
+            },
+        };
+        let location = LLVMDIBuilderCreateDebugLocation(
+            context,
+            location.line,
+            location.column,
+            scope,
+            inlined_at,
+        );
+        LLVMSetCurrentDebugLocation2(builder, location);
+    }
+
+    unsafe fn method_scope(&mut self, file: u32) -> Option<LLVMMetadataRef> {
+        let builder = self.builder;
+        let file_ref = self.files[file as usize];
+        let method = self.method.as_mut()?;
+        if method.file == file {
+            return Some(method.subprogram);
+        }
+        let subprogram = method.subprogram;
+        Some(*method.file_scopes.entry(file).or_insert_with(|| {
+            LLVMDIBuilderCreateLexicalBlockFile(builder, subprogram, file_ref, 0)
+        }))
+    }
+
+    // function_name in .loc points to a label in .debug_str section, which we
+    // don't parse, so all code inlined from the same file shares a subprogram
+    unsafe fn inlined_subprogram(&mut self, file: u32) -> LLVMMetadataRef {
+        let builder = self.builder;
+        let file_ref = self.files[file as usize];
+        let subroutine_type = self.subroutine_type;
+        *self.inlined_subprograms.entry(file).or_insert_with(|| {
+            let name = "inlined";
+            LLVMDIBuilderCreateFunction(
+                builder,
+                file_ref,
+                name.as_ptr().cast(),
+                name.len(),
+                ptr::null(),
+                0,
+                file_ref,
+                0,
+                subroutine_type,
+                1,
+                1,
+                0,
+                LLVMDIFlagZero,
+                1,
+            )
+        })
+    }
+}
+
+impl Drop for DebugInfo {
+    fn drop(&mut self) {
+        unsafe {
+            LLVMDIBuilderFinalize(self.builder);
+            LLVMDisposeDIBuilder(self.builder);
         }
     }
 }
@@ -230,6 +460,7 @@ pub(crate) unsafe fn emit_llvm_bitcode_and_linker_module<'input>(
             &module.directives,
             denorm_statistics,
             module.compilation_mode,
+            &module.source_files,
         );
         emit_prelude(&mut emit_ctx);
         for d in module.directives {
@@ -430,6 +661,12 @@ unsafe fn get_llvm_const_scalar(
     constant: ast::ImmediateValue,
 ) -> Result<LLVMValueRef, TranslateError> {
     let llvm_type = get_llvm_type(ctx, &ast::Type::Scalar(scalar_type))?;
+    if scalar_type.is_storage_only() {
+        let bits = constant
+            .as_u64()
+            .ok_or_else(TranslateError::mismatched_type)?;
+        return Ok(LLVMConstInt(llvm_type, bits, 0));
+    }
     Ok(match scalar_type.kind() {
         ast::ScalarKind::Pred
         | ast::ScalarKind::Bit
@@ -537,16 +774,32 @@ fn emit_method<'a, 'input>(
     let starting_bb =
         unsafe { LLVMAppendBasicBlockInContext(ctx.context.get(), llvm_method, LLVM_UNNAMED) };
     unsafe { LLVMPositionBuilderAtEnd(ctx.builder.get(), starting_bb) };
+    let first_location = statements.iter().find_map(|statement| match statement {
+        crate::translate::Statement::Location(location) => Some(*location),
+        _ => None,
+    });
+    if let (Some(debug_info), Some(location)) = (&mut ctx.debug_info, first_location) {
+        unsafe {
+            debug_info.start_method(ctx.context.get(), ctx.builder.get(), llvm_method, location)
+        };
+    }
     for statement in statements.iter() {
         register_basic_blocks(ctx, llvm_method, statement);
     }
+    let mut span = None;
     for statement in statements.into_iter() {
-        emit_statement(ctx, is_kernel, statement)?;
+        if let crate::translate::Statement::Span(statement_span) = statement {
+            span = Some(statement_span);
+        }
+        emit_statement(ctx, is_kernel, statement).map_err(|err| err.with_span(span))?;
     }
     // happens if there is a post-ret trailing label
     terminate_current_block_if_not_terminated(ctx, None);
     unsafe { LLVMPositionBuilderAtEnd(ctx.builder.get(), bb_with_variables) };
     unsafe { LLVMBuildBr(ctx.builder.get(), starting_bb) };
+    if let Some(debug_info) = &mut ctx.debug_info {
+        unsafe { debug_info.end_method(ctx.builder.get()) };
+    }
     Ok(())
 }
 
@@ -625,7 +878,12 @@ fn emit_statement(
     is_kernel: bool,
     statement: crate::translate::ExpandedStatement,
 ) -> Result<(), TranslateError> {
-    if !matches!(statement, crate::translate::Statement::Label(..)) {
+    if !matches!(
+        statement,
+        crate::translate::Statement::Label(..)
+            | crate::translate::Statement::Location(..)
+            | crate::translate::Statement::Span(..)
+    ) {
         start_next_block_if_terminated(ctx);
     }
     Ok(match statement {
@@ -659,6 +917,12 @@ fn emit_statement(
         crate::translate::Statement::AsmVolatile { asm, constraints } => unsafe {
             emit_asm_volatile(ctx, asm, constraints)?
         },
+        crate::translate::Statement::Location(location) => {
+            if let Some(debug_info) = &mut ctx.debug_info {
+                unsafe { debug_info.set_location(ctx.context.get(), ctx.builder.get(), location) };
+            }
+        }
+        crate::translate::Statement::Span(_) => {}
     })
 }
 
@@ -1145,7 +1409,7 @@ fn emit_instruction(
         ast::Instruction::Sin { arg, .. } => emit_inst_sin(ctx, arg)?,
         ast::Instruction::Cos { arg, .. } => emit_inst_cos(ctx, arg)?,
         ast::Instruction::Lg2 { arg, .. } => emit_inst_lg2(ctx, arg)?,
-        ast::Instruction::Ex2 { arg, .. } => emit_inst_ex2(ctx, arg)?,
+        ast::Instruction::Ex2 { typ, arg, .. } => emit_inst_ex2(ctx, *typ, arg)?,
         ast::Instruction::Clz { typ, arg } => emit_inst_clz(ctx, *typ, arg)?,
         ast::Instruction::Bfind(details, arg) => emit_inst_bfind(ctx, details, arg)?,
         ast::Instruction::Brev { typ, arg } => emit_inst_brev(ctx, *typ, arg)?,
@@ -1155,6 +1419,7 @@ fn emit_instruction(
         ast::Instruction::Prmt { control, arg } => emit_inst_prmt(ctx, *control, arg)?,
         ast::Instruction::PrmtSlow { .. } => return Err(TranslateError::unexpected_pattern()),
         ast::Instruction::Membar { level } => emit_inst_membar(ctx, *level),
+        ast::Instruction::Fence { semantics, scope } => emit_inst_fence(ctx, *semantics, *scope),
         ast::Instruction::Shf(details, args) => emit_inst_shf(ctx, details, args)?,
         ast::Instruction::Trap => emit_int_trap(ctx)?,
         ast::Instruction::Brkpt => emit_int_brkpt(ctx)?,
@@ -1164,6 +1429,9 @@ fn emit_instruction(
         ast::Instruction::Red(details, arg) => emit_inst_red(ctx, details, arg)?,
         ast::Instruction::Isspacep(space, arg) => emit_inst_isspacep(ctx, *space, arg)?,
         ast::Instruction::Sad(type_, arg) => emit_inst_sad(ctx, *type_, arg)?,
+        ast::Instruction::Lop3 { lut, arg } => emit_inst_lop3(ctx, *lut, arg)?,
+        ast::Instruction::Copysign(typ, arg) => emit_inst_copysign(ctx, *typ, arg)?,
+        ast::Instruction::Testp(op, typ, arg) => emit_inst_testp(ctx, *op, *typ, arg)?,
         // replaced by function calls or Statement variants
         ast::Instruction::Activemask { .. }
         | ast::Instruction::Bar(..)
@@ -1177,14 +1445,41 @@ fn emit_instruction(
         | ast::Instruction::SubC { .. }
         | ast::Instruction::SubCC { .. }
         | ast::Instruction::Tex(..)
+        | ast::Instruction::Tld4(..)
+        | ast::Instruction::Txq(..)
         | ast::Instruction::Suld(..)
         | ast::Instruction::Sust(..)
+        | ast::Instruction::Suq(..)
         | ast::Instruction::Call(_)
         | ast::Instruction::Vote { .. }
         | ast::Instruction::Shfl(..)
         | ast::Instruction::Dp4a(..)
+        | ast::Instruction::Tanh(..)
+        | ast::Instruction::CvtPack(..)
         | ast::Instruction::Nanosleep(..)
-        | ast::Instruction::MatchAny(..) => return Err(TranslateError::unreachable()),
+        | ast::Instruction::MatchAny(..)
+        | ast::Instruction::Redux(..)
+        | ast::Instruction::Bmsk(..)
+        | ast::Instruction::Szext(..)
+        | ast::Instruction::Fns(..)
+        | ast::Instruction::Mul24(..)
+        | ast::Instruction::Mad24(..)
+        | ast::Instruction::CvtPackedFloat(..)
+        | ast::Instruction::WmmaLoad(..)
+        | ast::Instruction::WmmaStore(..)
+        | ast::Instruction::WmmaMma(..)
+        | ast::Instruction::Mma(..)
+        | ast::Instruction::CpAsync(..)
+        | ast::Instruction::CpAsyncCommitGroup
+        | ast::Instruction::CpAsyncWaitGroup(..)
+        | ast::Instruction::FenceProxy(..)
+        | ast::Instruction::MbarrierInit(..)
+        | ast::Instruction::MbarrierArrive(..)
+        | ast::Instruction::MbarrierTestWait(..)
+        | ast::Instruction::Mapa(..)
+        | ast::Instruction::Getctarank(..)
+        | ast::Instruction::BarrierCluster(..)
+        | ast::Instruction::ElectSync(..) => return Err(TranslateError::unreachable()),
     })
 }
 
@@ -1286,7 +1581,42 @@ fn emit_inst_red(
     details: &ast::AtomDetails,
     arg: &ast::Arg2St<ExpandedArgParams>,
 ) -> Result<(), TranslateError> {
-    emit_inst_atom_impl(ctx, details, None, arg.src1, arg.src2)
+    match details.vector {
+        None => emit_inst_atom_impl(ctx, details, None, arg.src1, arg.src2),
+        Some(len) => emit_inst_red_vector(ctx, details, len, arg),
+    }
+}
+
+// There is no vector atomicrmw, so we emit one atomic per element
+fn emit_inst_red_vector(
+    ctx: &mut EmitContext,
+    details: &ast::AtomDetails,
+    len: u8,
+    arg: &ast::Arg2St<ExpandedArgParams>,
+) -> Result<(), TranslateError> {
+    let builder = ctx.builder.get();
+    let vector_type = get_llvm_type(ctx, &ast::Type::Vector(details.inner.get_type(), len))?;
+    let llvm_i32 = get_llvm_type(ctx, &ast::Type::Scalar(ast::ScalarType::U32))?;
+    let zero_llvm = unsafe { LLVMConstInt(llvm_i32, 0, 0) };
+    let ptr = ctx.names.value(arg.src1)?;
+    let vector = ctx.names.value(arg.src2)?;
+    for i in 0..len {
+        let index_llvm = unsafe { LLVMConstInt(llvm_i32, i as _, 0) };
+        let indices = [zero_llvm, index_llvm];
+        let element_ptr = unsafe {
+            LLVMBuildInBoundsGEP2(
+                builder,
+                vector_type,
+                ptr,
+                indices.as_ptr() as _,
+                2,
+                LLVM_UNNAMED,
+            )
+        };
+        let element = unsafe { LLVMBuildExtractElement(builder, vector, index_llvm, LLVM_UNNAMED) };
+        emit_atomic_rmw(ctx, details, element_ptr, element)?;
+    }
+    Ok(())
 }
 
 fn emit_inst_set(
@@ -1830,6 +2160,123 @@ fn emit_inst_prmt(
     Ok(())
 }
 
+// Truth tables of lop3 inputs a, b and c, as defined in the PTX docs
+const LOP3_INPUTS: [u8; 3] = [0xF0, 0xCC, 0xAA];
+
+#[derive(Clone, Copy)]
+enum Lop3Expr {
+    Input(usize),
+    Constant(bool),
+    Not(u8),
+    And(u8, u8),
+    Or(u8, u8),
+    Xor(u8, u8),
+}
+
+thread_local! {
+    static LOP3_EXPRESSIONS: [Lop3Expr; 256] = lop3_expressions();
+}
+
+// For every lookup table find the smallest and/or/xor/not expression over
+// the inputs. Simple relaxation until nothing changes, there are only 256
+// tables so this is cheap enough to do once per thread
+fn lop3_expressions() -> [Lop3Expr; 256] {
+    let mut costs = [u32::MAX; 256];
+    let mut exprs = [Lop3Expr::Constant(false); 256];
+    for (index, table) in LOP3_INPUTS.iter().copied().enumerate() {
+        costs[table as usize] = 0;
+        exprs[table as usize] = Lop3Expr::Input(index);
+    }
+    costs[0x00] = 0;
+    costs[0xFF] = 0;
+    exprs[0xFF] = Lop3Expr::Constant(true);
+    let mut relax = |costs: &mut [u32; 256], table: u8, cost: u32, expr: Lop3Expr| {
+        if cost < costs[table as usize] {
+            costs[table as usize] = cost;
+            exprs[table as usize] = expr;
+            true
+        } else {
+            false
+        }
+    };
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for f in 0..=255u8 {
+            let f_cost = costs[f as usize];
+            if f_cost == u32::MAX {
+                continue;
+            }
+            changed |= relax(&mut costs, !f, f_cost + 1, Lop3Expr::Not(f));
+            for g in f..=255u8 {
+                let g_cost = costs[g as usize];
+                if g_cost == u32::MAX {
+                    continue;
+                }
+                let cost = f_cost + g_cost + 1;
+                changed |= relax(&mut costs, f & g, cost, Lop3Expr::And(f, g));
+                changed |= relax(&mut costs, f | g, cost, Lop3Expr::Or(f, g));
+                changed |= relax(&mut costs, f ^ g, cost, Lop3Expr::Xor(f, g));
+            }
+        }
+    }
+    exprs
+}
+
+fn emit_inst_lop3(
+    ctx: &mut EmitContext,
+    lut: u8,
+    arg: &ast::Arg4<ExpandedArgParams>,
+) -> Result<(), TranslateError> {
+    let builder = ctx.builder.get();
+    let llvm_i32 = get_llvm_type(ctx, &ast::Type::Scalar(ast::ScalarType::B32))?;
+    let inputs = [
+        ctx.names.value(arg.src1)?,
+        ctx.names.value(arg.src2)?,
+        ctx.names.value(arg.src3)?,
+    ];
+    LOP3_EXPRESSIONS.with(|exprs| match exprs[lut as usize] {
+        // Degenerate tables do not produce an instruction we could name
+        Lop3Expr::Input(_) | Lop3Expr::Constant(_) => {
+            let value = emit_lop3_expr(builder, llvm_i32, exprs, &inputs, lut, LLVM_UNNAMED);
+            ctx.names.register(arg.dst, value);
+        }
+        _ => {
+            ctx.names.register_result(arg.dst, |dst_name| {
+                emit_lop3_expr(builder, llvm_i32, exprs, &inputs, lut, dst_name)
+            });
+        }
+    });
+    Ok(())
+}
+
+fn emit_lop3_expr(
+    builder: LLVMBuilderRef,
+    llvm_i32: LLVMTypeRef,
+    exprs: &[Lop3Expr; 256],
+    inputs: &[LLVMValueRef; 3],
+    table: u8,
+    name: *const i8,
+) -> LLVMValueRef {
+    let operand = |table: u8| emit_lop3_expr(builder, llvm_i32, exprs, inputs, table, LLVM_UNNAMED);
+    match exprs[table as usize] {
+        Lop3Expr::Input(index) => inputs[index],
+        Lop3Expr::Constant(value) => unsafe {
+            LLVMConstInt(llvm_i32, if value { u32::MAX as u64 } else { 0 }, 0)
+        },
+        Lop3Expr::Not(src) => unsafe { LLVMBuildNot(builder, operand(src), name) },
+        Lop3Expr::And(src1, src2) => unsafe {
+            LLVMBuildAnd(builder, operand(src1), operand(src2), name)
+        },
+        Lop3Expr::Or(src1, src2) => unsafe {
+            LLVMBuildOr(builder, operand(src1), operand(src2), name)
+        },
+        Lop3Expr::Xor(src1, src2) => unsafe {
+            LLVMBuildXor(builder, operand(src1), operand(src2), name)
+        },
+    }
+}
+
 fn emit_inst_setp(
     ctx: &mut EmitContext,
     setp: &ast::SetpData,
@@ -2039,6 +2486,24 @@ fn emit_inst_membar(ctx: &mut EmitContext, level: ast::MemScope) {
     };
 }
 
+fn emit_inst_fence(ctx: &mut EmitContext, semantics: ast::FenceSemantics, scope: ast::MemScope) {
+    let scope = get_llvm_scope_for_membar(scope);
+    let ordering = match semantics {
+        ast::FenceSemantics::SequentiallyConsistent => {
+            LLVMAtomicOrdering::LLVMAtomicOrderingSequentiallyConsistent
+        }
+        ast::FenceSemantics::AcquireRelease => LLVMAtomicOrdering::LLVMAtomicOrderingAcquireRelease,
+    };
+    unsafe {
+        LLVMZludaBuildFence(
+            ctx.builder.get(),
+            ordering,
+            scope.as_ptr() as _,
+            b"\0".as_ptr() as _,
+        )
+    };
+}
+
 fn emit_inst_max(
     ctx: &mut EmitContext,
     details: &ast::MinMaxDetails,
@@ -2610,6 +3075,9 @@ fn emit_inst_cvt(
                             intrinsic_fn,
                         )?;
                     }
+                    Some(ast::RoundingMode::NearestAway) => {
+                        return Err(TranslateError::unreachable())
+                    }
                     None => {
                         let src = ctx.names.value(args.src)?;
                         emit_value_copy(ctx, &ast::Type::Scalar(type_), src, args.dst)?;
@@ -2735,6 +3203,11 @@ fn get_min_max(type_: ast::ScalarType) -> Result<(i128, i128), TranslateError> {
         | ast::ScalarType::F32
         | ast::ScalarType::F64
         | ast::ScalarType::F16x2
+        | ast::ScalarType::BF16
+        | ast::ScalarType::BF16x2
+        | ast::ScalarType::TF32
+        | ast::ScalarType::E4M3x2
+        | ast::ScalarType::E5M2x2
         | ast::ScalarType::Pred => return Err(TranslateError::unreachable()),
     })
 }
@@ -3026,19 +3499,74 @@ fn emit_inst_popc(
 
 fn emit_inst_ex2(
     ctx: &mut EmitContext,
+    typ: ast::ScalarType,
     args: &ast::Arg2<crate::translate::ExpandedArgParams>,
 ) -> Result<(), TranslateError> {
-    let function_name = b"llvm.exp2.f32\0";
+    let function_name = format!("llvm.exp2.{}\0", typ.llvm_display());
     let llvm_value = emit_intrinsic_arg2(
         ctx,
-        (ast::ScalarType::F32, Some(args.dst)),
-        (ast::ScalarType::F32, ast::StateSpace::Reg, args.src),
-        function_name,
+        (typ, Some(args.dst)),
+        (typ, ast::StateSpace::Reg, args.src),
+        function_name.as_bytes(),
     )?;
     unsafe { LLVMZludaSetFastMathFlags(llvm_value, FastMathFlags::ApproxFunc) };
     Ok(())
 }
 
+fn emit_inst_copysign(
+    ctx: &mut EmitContext,
+    typ: ast::ScalarType,
+    args: &ast::Arg3<crate::translate::ExpandedArgParams>,
+) -> Result<(), TranslateError> {
+    // PTX copies the sign of the first source onto the second one
+    let function_name = format!("llvm.copysign.{}\0", typ.llvm_display());
+    emit_intrinsic_arg3(
+        ctx,
+        (get_llvm_type(ctx, &ast::Type::Scalar(typ))?, Some(args.dst)),
+        (typ, args.src2),
+        (typ, args.src1),
+        function_name.as_bytes(),
+    )?;
+    Ok(())
+}
+
+fn emit_inst_testp(
+    ctx: &mut EmitContext,
+    op: ast::TestpOp,
+    typ: ast::ScalarType,
+    args: &ast::Arg2<crate::translate::ExpandedArgParams>,
+) -> Result<(), TranslateError> {
+    // Class mask bits: 0 snan, 1 qnan, 2 -inf, 3 -normal, 4 -subnormal,
+    // 5 -zero, 6 +zero, 7 +subnormal, 8 +normal, 9 +inf
+    let mask = match op {
+        ast::TestpOp::Finite => 0x1f8,
+        ast::TestpOp::Infinite => 0x204,
+        ast::TestpOp::Number => 0x3fc,
+        ast::TestpOp::NotANumber => 0x003,
+        ast::TestpOp::Normal => 0x108,
+        ast::TestpOp::Subnormal => 0x090,
+    };
+    let mask = unsafe {
+        LLVMConstInt(
+            get_llvm_type(ctx, &ast::Type::Scalar(ast::ScalarType::U32))?,
+            mask,
+            0,
+        )
+    };
+    let function_name = format!("llvm.amdgcn.class.{}\0", typ.llvm_display());
+    emit_intrinsic_arg3(
+        ctx,
+        (
+            get_llvm_type(ctx, &ast::Type::Scalar(ast::ScalarType::Pred))?,
+            Some(args.dst),
+        ),
+        (typ, args.src),
+        (ast::ScalarType::U32, mask),
+        function_name.as_bytes(),
+    )?;
+    Ok(())
+}
+
 fn emit_inst_lg2(
     ctx: &mut EmitContext,
     args: &ast::Arg2<crate::translate::ExpandedArgParams>,
@@ -3227,6 +3755,23 @@ fn emit_inst_atom_impl(
     src1: Id,
     src2: Id,
 ) -> Result<(), TranslateError> {
+    let ptr = ctx.names.value(src1)?;
+    let val = ctx.names.value(src2)?;
+    let value = emit_atomic_rmw(ctx, details, ptr, val)?;
+    if let Some(dst) = dst {
+        ctx.names.register(dst, value);
+        let name = ctx.names.name(dst);
+        unsafe { LLVMSetValueName2(value, name.as_ptr(), name.len()) };
+    }
+    Ok(())
+}
+
+fn emit_atomic_rmw(
+    ctx: &mut EmitContext,
+    details: &ast::AtomDetails,
+    ptr: LLVMValueRef,
+    val: LLVMValueRef,
+) -> Result<LLVMValueRef, TranslateError> {
     let builder = ctx.builder.get();
     let (atomic_op, type_) = match details.inner {
         ast::AtomInnerDetails::Bit {
@@ -3285,9 +3830,7 @@ fn emit_inst_atom_impl(
     };
     let ordering = get_llvm_ordering(details.semantics);
     let scope = get_llvm_scope(details.scope);
-    let ptr = ctx.names.value(src1)?;
-    let val = ctx.names.value(src2)?;
-    let value = unsafe {
+    Ok(unsafe {
         LLVMZludaBuildAtomicRMW(
             builder,
             atomic_op,
@@ -3297,13 +3840,7 @@ fn emit_inst_atom_impl(
             ordering,
             type_.size_of() as u32,
         )
-    };
-    if let Some(dst) = dst {
-        ctx.names.register(dst, value);
-        let name = ctx.names.name(dst);
-        unsafe { LLVMSetValueName2(value, name.as_ptr(), name.len()) };
-    }
-    Ok(())
+    })
 }
 
 // https://llvm.org/docs/AMDGPUUsage.html#memory-scopes
@@ -3775,6 +4312,14 @@ fn get_llvm_type(ctx: &EmitContext, type_: &ast::Type) -> Result<LLVMTypeRef, Tr
                     get_llvm_type(ctx, &ast::Type::Scalar(ast::ScalarType::F16))?,
                     2,
                 ),
+                // Types without native LLVM arithmetic are kept as raw bits, all operations on
+                // them are replaced by function calls
+                ast::ScalarType::BF16 | ast::ScalarType::E4M3x2 | ast::ScalarType::E5M2x2 => {
+                    LLVMInt16TypeInContext(ctx.context.get())
+                }
+                ast::ScalarType::BF16x2 | ast::ScalarType::TF32 => {
+                    LLVMInt32TypeInContext(ctx.context.get())
+                }
                 ast::ScalarType::Pred => LLVMInt1TypeInContext(ctx.context.get()),
             },
             ast::Type::Vector(scalar_type, count) => LLVMVectorType(
@@ -3985,6 +4530,10 @@ impl Display for ScalarTypeLLVMDisplay {
             ast::ScalarType::F32 => write!(f, "f32"),
             ast::ScalarType::F64 => write!(f, "f64"),
             ast::ScalarType::F16x2 => write!(f, "v2f16"),
+            ast::ScalarType::BF16 | ast::ScalarType::E4M3x2 | ast::ScalarType::E5M2x2 => {
+                write!(f, "i16")
+            }
+            ast::ScalarType::BF16x2 | ast::ScalarType::TF32 => write!(f, "i32"),
             ast::ScalarType::Pred => write!(f, "i1"),
         }
     }
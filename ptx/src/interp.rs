@@ -0,0 +1,2761 @@
+// CPU reference interpreter for translated PTX.
+// It executes a module right after insert_implicit_conversions(...), so it
+// sees the same IR that emit.rs turns into LLVM, but without AMD-specific
+// lowering: warp and block collectives are still plain instructions and
+// special registers are calls to __zluda_ptx_impl__sreg_* declarations.
+// Every value is kept in its raw little-endian bit pattern. Variables,
+// including .reg variables, live in a single flat address space, which
+// makes generic addressing and cvta trivial.
+// Threads of a block are stepped one by one until they either exit or reach
+// a collective (shfl, vote, activemask, match, bar). A warp collective is
+// resolved once every live lane of the warp is suspended, with all lanes
+// sitting at the same instruction forming the member mask. A block barrier
+// is resolved once every live thread of the block is suspended on it.
+// Floating point math is done on the host: rounding modifiers are honored
+// for conversions to and from integers and for conversions to bf16 and tf32,
+// ignored otherwise.
+
+use crate::ast;
+use crate::translate::{
+    self, ConversionKind, ExpandedArgParams, ExpandedStatement, Id, ImplicitConversion, Statement,
+    TranslateError, TranslationDirective, TranslationMethod, TranslationModule, Variable,
+    ZLUDA_PTX_PREFIX,
+};
+use half::{bf16, f16};
+use rustc_hash::FxHashMap;
+use std::collections::BTreeMap;
+use std::num::FpCategory;
+
+const WARP_SIZE: usize = 32;
+// Function pointers must not alias any allocation
+const FUNCTION_ADDRESS_BASE: u64 = 0xFFFF_0000_0000_0000;
+// Start of the heap, low enough that .shared pointers fit in 32 bits
+const HEAP_START: u64 = 0x1000;
+const ALLOCATION_ALIGNMENT: u64 = 16;
+
+#[derive(Debug, thiserror::Error)]
+pub enum InterpreterError {
+    #[error("{0}")]
+    Translate(#[from] TranslateError),
+    #[error("unknown kernel `{0}`")]
+    UnknownKernel(String),
+    #[error("kernel expects {expected} arguments, got {actual}")]
+    ArgumentCount { expected: usize, actual: usize },
+    #[error("kernel argument {index} must be {expected} bytes, got {actual}")]
+    ArgumentSize {
+        index: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("invalid memory access of {size} bytes at {address:#x}")]
+    InvalidMemoryAccess { address: u64, size: usize },
+    #[error("value {0} read before it was written")]
+    UndefinedValue(u32),
+    #[error("unsupported: {0}")]
+    Unsupported(String),
+    #[error("assertion failed: {0}")]
+    AssertionFailed(String),
+    #[error("trap")]
+    Trap,
+    #[error("deadlock, no thread in block {0:?} can make progress")]
+    Deadlock([u32; 3]),
+}
+
+pub struct Interpreter<'input> {
+    program: Program<'input>,
+    memory: Memory,
+}
+
+impl<'input> Interpreter<'input> {
+    pub fn new(asts: Vec<ast::Module<'input>>) -> Result<Self, InterpreterError> {
+        let module = translate::to_interpreter_module(asts)?;
+        let mut memory = Memory::new();
+        let program = Program::new(module, &mut memory)?;
+        Ok(Self { program, memory })
+    }
+
+    // Returns zero-initialized .global memory
+    pub fn alloc(&mut self, size: usize) -> u64 {
+        self.memory
+            .allocate(size, ALLOCATION_ALIGNMENT as usize, ast::StateSpace::Global)
+    }
+
+    pub fn free(&mut self, address: u64) -> Result<(), InterpreterError> {
+        self.memory.free(address)
+    }
+
+    pub fn write(&mut self, address: u64, data: &[u8]) -> Result<(), InterpreterError> {
+        self.memory.write(address, data)
+    }
+
+    pub fn read(&self, address: u64, size: usize) -> Result<Vec<u8>, InterpreterError> {
+        Ok(self.memory.read(address, size)?.to_vec())
+    }
+
+    // Every argument is passed as the raw bytes of the corresponding .param
+    pub fn launch(
+        &mut self,
+        kernel: &str,
+        grid: [u32; 3],
+        block: [u32; 3],
+        dynamic_shared: u32,
+        args: &[&[u8]],
+    ) -> Result<(), InterpreterError> {
+        let program = &self.program;
+        let memory = &mut self.memory;
+        let kernel_id = *program
+            .kernels
+            .get(kernel)
+            .ok_or_else(|| InterpreterError::UnknownKernel(kernel.to_string()))?;
+        let method = program.method(kernel_id)?;
+        if method.input_arguments.len() != args.len() {
+            return Err(InterpreterError::ArgumentCount {
+                expected: method.input_arguments.len(),
+                actual: args.len(),
+            });
+        }
+        let mut params = FxHashMap::default();
+        for (index, (arg, value)) in method.input_arguments.iter().zip(args).enumerate() {
+            let (size, align) = type_layout(&arg.type_, arg.align);
+            if size != value.len() {
+                return Err(InterpreterError::ArgumentSize {
+                    index,
+                    expected: size,
+                    actual: value.len(),
+                });
+            }
+            let address = memory.allocate(size, align, ast::StateSpace::Const);
+            memory.write(address, value)?;
+            params.insert(arg.name, Value::Scalar(address));
+        }
+        let result = (|| {
+            for z in 0..grid[2] {
+                for y in 0..grid[1] {
+                    for x in 0..grid[0] {
+                        let block_state = BlockState::new(
+                            program,
+                            memory,
+                            [x, y, z],
+                            grid,
+                            block,
+                            dynamic_shared,
+                        );
+                        let result = run_block(program, memory, &block_state, kernel_id, &params);
+                        block_state.free(memory)?;
+                        result?;
+                    }
+                }
+            }
+            Ok(())
+        })();
+        for address in params.values() {
+            memory.free(address.as_u64())?;
+        }
+        result
+    }
+}
+
+struct SharedVariable {
+    name: Id,
+    size: usize,
+    align: usize,
+    is_extern: bool,
+}
+
+struct Program<'input> {
+    module: TranslationModule<'input, ExpandedArgParams>,
+    // directive index, definitions take precedence over declarations
+    methods: FxHashMap<Id, usize>,
+    kernels: FxHashMap<String, Id>,
+    labels: FxHashMap<Id, FxHashMap<Id, usize>>,
+    globals: FxHashMap<Id, u64>,
+    shared: Vec<SharedVariable>,
+}
+
+impl<'input> Program<'input> {
+    fn new(
+        module: TranslationModule<'input, ExpandedArgParams>,
+        memory: &mut Memory,
+    ) -> Result<Self, InterpreterError> {
+        let mut methods = FxHashMap::default();
+        let mut kernels = FxHashMap::default();
+        let mut labels = FxHashMap::default();
+        let mut globals = FxHashMap::default();
+        let mut shared = Vec::<SharedVariable>::new();
+        for (index, directive) in module.directives.iter().enumerate() {
+            match directive {
+                TranslationDirective::Method(method) => match method.body {
+                    Some(ref body) => {
+                        methods.insert(method.name, index);
+                        let method_labels = body
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(pc, statement)| match statement {
+                                Statement::Label(label) => Some((*label, pc)),
+                                _ => None,
+                            })
+                            .collect::<FxHashMap<_, _>>();
+                        labels.insert(method.name, method_labels);
+                        if let (true, Some(name)) = (method.is_kernel, &method.source_name) {
+                            kernels.insert(name.to_string(), method.name);
+                        }
+                    }
+                    None => {
+                        methods.entry(method.name).or_insert(index);
+                    }
+                },
+                TranslationDirective::Variable(linking, _, var) => {
+                    let (size, align) = type_layout(&var.type_, var.align);
+                    if var.state_space == ast::StateSpace::Shared {
+                        if !shared.iter().any(|shared_var| shared_var.name == var.name) {
+                            shared.push(SharedVariable {
+                                name: var.name,
+                                size,
+                                align,
+                                is_extern: *linking == ast::LinkingDirective::Extern,
+                            });
+                        }
+                    } else if !globals.contains_key(&var.name) {
+                        globals.insert(var.name, memory.allocate(size, align, var.state_space));
+                    }
+                }
+            }
+        }
+        let program = Program {
+            module,
+            methods,
+            kernels,
+            labels,
+            globals,
+            shared,
+        };
+        for directive in program.module.directives.iter() {
+            if let TranslationDirective::Variable(
+                _,
+                _,
+                Variable {
+                    type_,
+                    name,
+                    state_space,
+                    initializer: Some(initializer),
+                    ..
+                },
+            ) = directive
+            {
+                if *state_space == ast::StateSpace::Shared {
+                    continue;
+                }
+                let bytes = program.initializer_bytes(type_, initializer)?;
+                memory.write(program.globals[name], &bytes)?;
+            }
+        }
+        Ok(program)
+    }
+
+    fn method(
+        &self,
+        id: Id,
+    ) -> Result<&TranslationMethod<'input, ExpandedArgParams>, InterpreterError> {
+        let index = self.methods.get(&id).ok_or(TranslateError::UnknownSymbol)?;
+        match &self.module.directives[*index] {
+            TranslationDirective::Method(method) => Ok(method),
+            TranslationDirective::Variable(..) => Err(TranslateError::UnknownSymbol.into()),
+        }
+    }
+
+    fn label(&self, method: Id, label: Id) -> Result<usize, InterpreterError> {
+        self.labels
+            .get(&method)
+            .and_then(|labels| labels.get(&label))
+            .copied()
+            .ok_or_else(|| TranslateError::UnknownSymbol.into())
+    }
+
+    fn statement(
+        &self,
+        method: Id,
+        pc: usize,
+    ) -> Result<Option<&ExpandedStatement>, InterpreterError> {
+        match self.method(method)?.body {
+            Some(ref body) => Ok(body.get(pc)),
+            None => Err(TranslateError::Unreachable.into()),
+        }
+    }
+
+    fn global_address(&self, id: Id) -> Option<u64> {
+        if let Some(address) = self.globals.get(&id) {
+            return Some(*address);
+        }
+        if self.methods.contains_key(&id) {
+            return Some(FUNCTION_ADDRESS_BASE | id.get() as u64);
+        }
+        None
+    }
+
+    fn function_from_address(&self, address: u64) -> Result<Id, InterpreterError> {
+        if address & FUNCTION_ADDRESS_BASE == FUNCTION_ADDRESS_BASE {
+            if let Some(id) = self
+                .methods
+                .keys()
+                .find(|id| id.get() as u64 == address & !FUNCTION_ADDRESS_BASE)
+            {
+                return Ok(*id);
+            }
+        }
+        Err(InterpreterError::InvalidMemoryAccess { address, size: 0 })
+    }
+
+    fn initializer_bytes(
+        &self,
+        type_: &ast::Type,
+        initializer: &ast::Initializer<Id>,
+    ) -> Result<Vec<u8>, InterpreterError> {
+        let (size, _) = type_layout(type_, None);
+        let mut bytes = match initializer {
+            ast::Initializer::Constant(value) => {
+                let scalar_type = match type_ {
+                    ast::Type::Scalar(scalar_type)
+                    | ast::Type::Vector(scalar_type, _)
+                    | ast::Type::Array(scalar_type, _) => *scalar_type,
+                    _ => ast::ScalarType::B64,
+                };
+                immediate_to_bits(scalar_type, *value)
+                    .to_le_bytes()
+                    .to_vec()
+            }
+            ast::Initializer::Global(_)
+            | ast::Initializer::GenericGlobal(_)
+            | ast::Initializer::Add(_) => self
+                .initializer_address(initializer)?
+                .to_le_bytes()
+                .to_vec(),
+            ast::Initializer::Array(elements) => {
+                let element_type = match type_ {
+                    ast::Type::Array(scalar_type, dimensions) if dimensions.len() > 1 => {
+                        ast::Type::Array(*scalar_type, dimensions[1..].to_vec())
+                    }
+                    ast::Type::Array(scalar_type, _) | ast::Type::Vector(scalar_type, _) => {
+                        ast::Type::Scalar(*scalar_type)
+                    }
+                    _ => return Err(TranslateError::MismatchedType.into()),
+                };
+                let (element_size, _) = type_layout(&element_type, None);
+                let mut result = Vec::with_capacity(size);
+                for element in elements {
+                    let mut element_bytes = self.initializer_bytes(&element_type, element)?;
+                    element_bytes.resize(element_size, 0);
+                    result.extend_from_slice(&element_bytes);
+                }
+                result
+            }
+        };
+        bytes.resize(size, 0);
+        Ok(bytes)
+    }
+
+    fn initializer_address(
+        &self,
+        initializer: &ast::Initializer<Id>,
+    ) -> Result<u64, InterpreterError> {
+        Ok(match initializer {
+            ast::Initializer::Constant(value) => immediate_to_bits(ast::ScalarType::B64, *value),
+            ast::Initializer::Global(id) | ast::Initializer::GenericGlobal(id) => self
+                .global_address(*id)
+                .ok_or(TranslateError::UnknownSymbol)?,
+            ast::Initializer::Add(add) => {
+                let (left, right) = &**add;
+                self.initializer_address(left)?
+                    .wrapping_add(self.initializer_address(right)?)
+            }
+            ast::Initializer::Array(..) => return Err(TranslateError::MismatchedType.into()),
+        })
+    }
+}
+
+struct Allocation {
+    bytes: Vec<u8>,
+    state_space: ast::StateSpace,
+}
+
+// Flat address space, addresses are never reused so that stale pointers fault
+struct Memory {
+    allocations: BTreeMap<u64, Allocation>,
+    next_address: u64,
+}
+
+impl Memory {
+    fn new() -> Self {
+        Self {
+            allocations: BTreeMap::new(),
+            next_address: HEAP_START,
+        }
+    }
+
+    fn allocate(&mut self, size: usize, align: usize, state_space: ast::StateSpace) -> u64 {
+        let align = (align as u64).max(ALLOCATION_ALIGNMENT);
+        let address = (self.next_address + align - 1) / align * align;
+        // Leave a gap after every allocation, so off-by-one accesses are caught
+        self.next_address = address + size as u64 + ALLOCATION_ALIGNMENT;
+        self.allocations.insert(
+            address,
+            Allocation {
+                bytes: vec![0; size],
+                state_space,
+            },
+        );
+        address
+    }
+
+    fn free(&mut self, address: u64) -> Result<(), InterpreterError> {
+        self.allocations
+            .remove(&address)
+            .map(|_| ())
+            .ok_or(InterpreterError::InvalidMemoryAccess { address, size: 0 })
+    }
+
+    fn find(&self, address: u64, size: usize) -> Result<(u64, &Allocation), InterpreterError> {
+        match self.allocations.range(..=address).next_back() {
+            Some((base, allocation))
+                if address + size as u64 <= base + allocation.bytes.len() as u64 =>
+            {
+                Ok((*base, allocation))
+            }
+            _ => Err(InterpreterError::InvalidMemoryAccess { address, size }),
+        }
+    }
+
+    fn read(&self, address: u64, size: usize) -> Result<&[u8], InterpreterError> {
+        let (base, allocation) = self.find(address, size)?;
+        let offset = (address - base) as usize;
+        Ok(&allocation.bytes[offset..offset + size])
+    }
+
+    fn write(&mut self, address: u64, data: &[u8]) -> Result<(), InterpreterError> {
+        let (base, _) = self.find(address, data.len())?;
+        let allocation = self.allocations.get_mut(&base).unwrap();
+        let offset = (address - base) as usize;
+        allocation.bytes[offset..offset + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn read_scalar(&self, address: u64, size: usize) -> Result<u64, InterpreterError> {
+        Ok(bytes_to_u64(self.read(address, size)?))
+    }
+
+    fn write_scalar(
+        &mut self,
+        address: u64,
+        size: usize,
+        value: u64,
+    ) -> Result<(), InterpreterError> {
+        self.write(address, &value.to_le_bytes()[..size])
+    }
+
+    fn state_space(&self, address: u64) -> ast::StateSpace {
+        match self.find(address, 0) {
+            Ok((_, allocation)) => allocation.state_space,
+            Err(_) => ast::StateSpace::Global,
+        }
+    }
+
+    fn read_c_string(&self, address: u64) -> Result<String, InterpreterError> {
+        let (base, allocation) = self.find(address, 0)?;
+        let bytes = &allocation.bytes[(address - base) as usize..];
+        let end = bytes.iter().position(|c| *c == 0).unwrap_or(bytes.len());
+        Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    }
+}
+
+#[derive(Clone)]
+enum Value {
+    Scalar(u64),
+    // vectors, arrays and structs
+    Aggregate(Vec<u8>),
+}
+
+impl Value {
+    fn from_bytes(type_: &ast::Type, bytes: &[u8]) -> Self {
+        match type_ {
+            ast::Type::Scalar(ast::ScalarType::Pred) => Value::Scalar((bytes[0] != 0) as u64),
+            ast::Type::Scalar(_)
+            | ast::Type::Pointer(..)
+            | ast::Type::Texref
+            | ast::Type::Surfref => Value::Scalar(bytes_to_u64(bytes)),
+            ast::Type::Vector(..) | ast::Type::Array(..) | ast::Type::Struct(..) => {
+                Value::Aggregate(bytes.to_vec())
+            }
+        }
+    }
+
+    fn as_u64(&self) -> u64 {
+        match self {
+            Value::Scalar(value) => *value,
+            Value::Aggregate(bytes) => bytes_to_u64(bytes),
+        }
+    }
+
+    fn to_bytes(&self, size: usize) -> Vec<u8> {
+        let mut bytes = match self {
+            Value::Scalar(value) => value.to_le_bytes().to_vec(),
+            Value::Aggregate(bytes) => bytes.clone(),
+        };
+        bytes.resize(size, 0);
+        bytes
+    }
+}
+
+fn bytes_to_u64(bytes: &[u8]) -> u64 {
+    let mut result = [0u8; 8];
+    let len = bytes.len().min(8);
+    result[..len].copy_from_slice(&bytes[..len]);
+    u64::from_le_bytes(result)
+}
+
+struct BlockState {
+    ctaid: [u32; 3],
+    nctaid: [u32; 3],
+    ntid: [u32; 3],
+    shared: FxHashMap<Id, u64>,
+    allocations: Vec<u64>,
+}
+
+impl BlockState {
+    fn new(
+        program: &Program,
+        memory: &mut Memory,
+        ctaid: [u32; 3],
+        nctaid: [u32; 3],
+        ntid: [u32; 3],
+        dynamic_shared: u32,
+    ) -> Self {
+        let mut shared = FxHashMap::default();
+        let mut allocations = Vec::new();
+        let mut dynamic_shared_address = None;
+        for var in program.shared.iter() {
+            let address = if var.is_extern {
+                // All `.extern .shared` arrays alias the dynamic shared memory
+                *dynamic_shared_address.get_or_insert_with(|| {
+                    let address = memory.allocate(
+                        dynamic_shared as usize,
+                        ALLOCATION_ALIGNMENT as usize,
+                        ast::StateSpace::Shared,
+                    );
+                    allocations.push(address);
+                    address
+                })
+            } else {
+                let address = memory.allocate(var.size, var.align, ast::StateSpace::Shared);
+                allocations.push(address);
+                address
+            };
+            shared.insert(var.name, address);
+        }
+        Self {
+            ctaid,
+            nctaid,
+            ntid,
+            shared,
+            allocations,
+        }
+    }
+
+    fn free(&self, memory: &mut Memory) -> Result<(), InterpreterError> {
+        for address in self.allocations.iter() {
+            memory.free(*address)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum ThreadState {
+    Ready,
+    // Suspended on a warp collective
+    Warp,
+    // Suspended on a block barrier
+    Block,
+    Exited,
+}
+
+struct Frame {
+    method: Id,
+    pc: usize,
+    values: FxHashMap<Id, Value>,
+    allocations: Vec<u64>,
+    // Caller ids receiving returned values
+    return_ids: Vec<Id>,
+}
+
+struct Thread {
+    tid: [u32; 3],
+    lane: u32,
+    frames: Vec<Frame>,
+    state: ThreadState,
+    clock: u64,
+}
+
+impl Thread {
+    fn frame(&self) -> &Frame {
+        self.frames.last().unwrap()
+    }
+
+    fn frame_mut(&mut self) -> &mut Frame {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn position(&self) -> (usize, Id, usize) {
+        let frame = self.frame();
+        (self.frames.len(), frame.method, frame.pc)
+    }
+
+    fn value(
+        &self,
+        program: &Program,
+        block: &BlockState,
+        id: Id,
+    ) -> Result<Value, InterpreterError> {
+        if let Some(value) = self.frame().values.get(&id) {
+            return Ok(value.clone());
+        }
+        if let Some(address) = block.shared.get(&id) {
+            return Ok(Value::Scalar(*address));
+        }
+        match program.global_address(id) {
+            Some(address) => Ok(Value::Scalar(address)),
+            None => Err(InterpreterError::UndefinedValue(id.get())),
+        }
+    }
+
+    fn scalar(
+        &self,
+        program: &Program,
+        block: &BlockState,
+        id: Id,
+    ) -> Result<u64, InterpreterError> {
+        Ok(self.value(program, block, id)?.as_u64())
+    }
+
+    fn set(&mut self, id: Id, value: Value) {
+        self.frame_mut().values.insert(id, value);
+    }
+
+    fn resume(&mut self) {
+        self.frame_mut().pc += 1;
+        self.state = ThreadState::Ready;
+    }
+}
+
+fn current_instruction<'a>(
+    program: &'a Program,
+    thread: &Thread,
+) -> Result<&'a ast::Instruction<ExpandedArgParams>, InterpreterError> {
+    let frame = thread.frame();
+    match program.statement(frame.method, frame.pc)? {
+        Some(Statement::Instruction(inst)) => Ok(inst),
+        _ => Err(TranslateError::Unreachable.into()),
+    }
+}
+
+fn run_block(
+    program: &Program,
+    memory: &mut Memory,
+    block: &BlockState,
+    kernel: Id,
+    params: &FxHashMap<Id, Value>,
+) -> Result<(), InterpreterError> {
+    let mut threads = Vec::new();
+    for z in 0..block.ntid[2] {
+        for y in 0..block.ntid[1] {
+            for x in 0..block.ntid[0] {
+                threads.push(Thread {
+                    tid: [x, y, z],
+                    lane: (threads.len() % WARP_SIZE) as u32,
+                    frames: vec![Frame {
+                        method: kernel,
+                        pc: 0,
+                        values: params.clone(),
+                        allocations: Vec::new(),
+                        return_ids: Vec::new(),
+                    }],
+                    state: ThreadState::Ready,
+                    clock: 0,
+                });
+            }
+        }
+    }
+    let result = (|| loop {
+        for thread in threads.iter_mut() {
+            let mut context = ThreadContext {
+                program,
+                memory: &mut *memory,
+                block,
+                thread,
+            };
+            context.run()?;
+        }
+        if threads
+            .iter()
+            .all(|thread| thread.state == ThreadState::Exited)
+        {
+            return Ok(());
+        }
+        let mut progress = false;
+        for warp in threads.chunks_mut(WARP_SIZE) {
+            progress |= resolve_warp_collective(program, block, warp)?;
+        }
+        if progress {
+            continue;
+        }
+        if threads
+            .iter()
+            .all(|thread| matches!(thread.state, ThreadState::Block | ThreadState::Exited))
+        {
+            resolve_block_barrier(program, block, &mut threads)?;
+            continue;
+        }
+        return Err(InterpreterError::Deadlock(block.ctaid));
+    })();
+    for thread in threads.iter() {
+        for frame in thread.frames.iter() {
+            for address in frame.allocations.iter() {
+                memory.free(*address)?;
+            }
+        }
+    }
+    result
+}
+
+fn resolve_warp_collective(
+    program: &Program,
+    block: &BlockState,
+    warp: &mut [Thread],
+) -> Result<bool, InterpreterError> {
+    let position = match warp.iter().find(|thread| thread.state == ThreadState::Warp) {
+        Some(thread) => thread.position(),
+        None => return Ok(false),
+    };
+    let group = warp
+        .iter()
+        .enumerate()
+        .filter(|(_, thread)| thread.state == ThreadState::Warp && thread.position() == position)
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let group_mask = group
+        .iter()
+        .fold(0u32, |mask, index| mask | (1 << warp[*index].lane));
+    let inst = current_instruction(program, &warp[group[0]])?;
+    let mut results = Vec::with_capacity(group.len());
+    match inst {
+        ast::Instruction::Activemask { arg } => {
+            for _ in group.iter() {
+                results.push(vec![(arg.src, group_mask as u64)]);
+            }
+        }
+        ast::Instruction::Shfl(mode, arg) => {
+            let mut sources = [None; WARP_SIZE];
+            for index in group.iter() {
+                let thread = &warp[*index];
+                sources[thread.lane as usize] = Some(thread.scalar(program, block, arg.src1)?);
+            }
+            for index in group.iter() {
+                let thread = &warp[*index];
+                let lane = thread.lane as i64;
+                let b = (thread.scalar(program, block, arg.src2)? & 0x1f) as i64;
+                let c = thread.scalar(program, block, arg.src3)?;
+                let clamp = (c & 0x1f) as i64;
+                let segment_mask = ((c >> 8) & 0x1f) as i64;
+                let max_lane = (lane & segment_mask) | (clamp & !segment_mask);
+                let min_lane = lane & segment_mask;
+                let (source_lane, is_valid) = match mode {
+                    ast::ShflMode::Up => (lane - b, lane - b >= max_lane),
+                    ast::ShflMode::Down => (lane + b, lane + b <= max_lane),
+                    ast::ShflMode::Bfly => (lane ^ b, (lane ^ b) <= max_lane),
+                    ast::ShflMode::Idx => {
+                        let source_lane = min_lane | (b & !segment_mask);
+                        (source_lane, source_lane <= max_lane)
+                    }
+                };
+                let source_lane = if is_valid { source_lane } else { lane };
+                // Reading from an inactive lane is undefined, we return our own value
+                let value = sources
+                    .get(source_lane as usize)
+                    .copied()
+                    .flatten()
+                    .map_or_else(|| thread.scalar(program, block, arg.src1), Ok)?;
+                let mut result = vec![(arg.dst1, value)];
+                if let Some(dst2) = arg.dst2 {
+                    result.push((dst2, is_valid as u64));
+                }
+                results.push(result);
+            }
+        }
+        ast::Instruction::Vote(details, arg) => {
+            let mut predicates = 0u32;
+            for index in group.iter() {
+                let thread = &warp[*index];
+                if (thread.scalar(program, block, arg.src1)? != 0) != details.negate_pred {
+                    predicates |= 1 << thread.lane;
+                }
+            }
+            for index in group.iter() {
+                let thread = &warp[*index];
+                let member_mask = thread.scalar(program, block, arg.src2)? as u32 & group_mask;
+                let active = predicates & member_mask;
+                let value = match details.mode {
+                    ast::VoteMode::Ballot => active as u64,
+                    ast::VoteMode::Any => (active != 0) as u64,
+                    ast::VoteMode::All => (active == member_mask) as u64,
+                    ast::VoteMode::Uni => (active == 0 || active == member_mask) as u64,
+                };
+                results.push(vec![(arg.dst, value)]);
+            }
+        }
+        ast::Instruction::MatchAny(arg) => {
+            let mut values = [None; WARP_SIZE];
+            for index in group.iter() {
+                let thread = &warp[*index];
+                values[thread.lane as usize] =
+                    Some(thread.scalar(program, block, arg.src1)? as u32);
+            }
+            for index in group.iter() {
+                let thread = &warp[*index];
+                let member_mask = thread.scalar(program, block, arg.src2)? as u32 & group_mask;
+                let own_value = values[thread.lane as usize];
+                let matching = values
+                    .iter()
+                    .enumerate()
+                    .filter(|(lane, value)| member_mask & (1 << lane) != 0 && **value == own_value)
+                    .fold(0u32, |mask, (lane, _)| mask | (1 << lane));
+                results.push(vec![(arg.dst, matching as u64)]);
+            }
+        }
+        ast::Instruction::Redux(details, arg) => {
+            let mut values = [None; WARP_SIZE];
+            for index in group.iter() {
+                let thread = &warp[*index];
+                values[thread.lane as usize] =
+                    Some(thread.scalar(program, block, arg.src1)? as u32);
+            }
+            for index in group.iter() {
+                let thread = &warp[*index];
+                let member_mask = thread.scalar(program, block, arg.src2)? as u32 & group_mask;
+                let value = values
+                    .iter()
+                    .enumerate()
+                    .filter(|(lane, _)| member_mask & (1 << lane) != 0)
+                    .filter_map(|(_, value)| *value)
+                    .reduce(|x, y| redux(details, x, y))
+                    .unwrap_or(0);
+                results.push(vec![(arg.dst, value as u64)]);
+            }
+        }
+        ast::Instruction::ElectSync(arg) => {
+            for index in group.iter() {
+                let thread = &warp[*index];
+                let member_mask = thread.scalar(program, block, arg.src)? as u32 & group_mask;
+                let leader = member_mask.trailing_zeros();
+                let mut result = vec![(arg.dst2, (thread.lane == leader) as u64)];
+                if let Some(dst1) = arg.dst1 {
+                    result.push((dst1, leader as u64));
+                }
+                results.push(result);
+            }
+        }
+        _ => return Err(TranslateError::Unreachable.into()),
+    }
+    for (index, result) in group.iter().zip(results) {
+        let thread = &mut warp[*index];
+        for (id, value) in result {
+            thread.set(id, Value::Scalar(value));
+        }
+        thread.resume();
+    }
+    Ok(true)
+}
+
+fn resolve_block_barrier(
+    program: &Program,
+    block: &BlockState,
+    threads: &mut [Thread],
+) -> Result<(), InterpreterError> {
+    let mut all = true;
+    let mut any = false;
+    let mut count = 0u64;
+    for thread in threads.iter() {
+        if thread.state != ThreadState::Block {
+            continue;
+        }
+        if let ast::Instruction::BarRed(_, arg) = current_instruction(program, thread)? {
+            let predicate = thread.scalar(program, block, arg.src2)? != 0;
+            all &= predicate;
+            any |= predicate;
+            count += predicate as u64;
+        }
+    }
+    for thread in threads.iter_mut() {
+        if thread.state != ThreadState::Block {
+            continue;
+        }
+        if let ast::Instruction::BarRed(op, arg) = current_instruction(program, thread)? {
+            let value = match op {
+                ast::ReductionOp::And => all as u64,
+                ast::ReductionOp::Or => any as u64,
+                ast::ReductionOp::Popc => count,
+            };
+            thread.set(arg.dst, Value::Scalar(value));
+        }
+        thread.resume();
+    }
+    Ok(())
+}
+
+enum Flow {
+    Next,
+    Jump(Id),
+    Return(Vec<Value>),
+    Exit,
+    // Thread is suspended, program counter is not advanced
+    Suspend(ThreadState),
+    // Call has already set up program counters
+    Call,
+}
+
+struct ThreadContext<'a, 'input> {
+    program: &'a Program<'input>,
+    memory: &'a mut Memory,
+    block: &'a BlockState,
+    thread: &'a mut Thread,
+}
+
+impl<'a, 'input> ThreadContext<'a, 'input> {
+    fn run(&mut self) -> Result<(), InterpreterError> {
+        while self.thread.state == ThreadState::Ready {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    fn step(&mut self) -> Result<(), InterpreterError> {
+        self.thread.clock += 1;
+        let program = self.program;
+        let (method, pc) = {
+            let frame = self.thread.frame();
+            (frame.method, frame.pc)
+        };
+        let flow = match program.statement(method, pc)? {
+            // Falling off the end of a function is an implicit `ret`
+            None => Flow::Return(Vec::new()),
+            Some(statement) => self.statement(statement)?,
+        };
+        match flow {
+            Flow::Next => self.thread.frame_mut().pc += 1,
+            Flow::Jump(label) => self.thread.frame_mut().pc = program.label(method, label)?,
+            Flow::Return(values) => self.return_(values)?,
+            Flow::Exit => self.exit()?,
+            Flow::Suspend(state) => self.thread.state = state,
+            Flow::Call => {}
+        }
+        Ok(())
+    }
+
+    fn return_(&mut self, values: Vec<Value>) -> Result<(), InterpreterError> {
+        if self.thread.frames.len() == 1 {
+            return self.exit();
+        }
+        let frame = self.thread.frames.pop().unwrap();
+        for address in frame.allocations {
+            self.memory.free(address)?;
+        }
+        if frame.return_ids.len() != values.len() {
+            return Err(TranslateError::MismatchedType.into());
+        }
+        for (id, value) in frame.return_ids.into_iter().zip(values) {
+            self.thread.set(id, value);
+        }
+        Ok(())
+    }
+
+    fn exit(&mut self) -> Result<(), InterpreterError> {
+        for frame in self.thread.frames.drain(..) {
+            for address in frame.allocations {
+                self.memory.free(address)?;
+            }
+        }
+        self.thread.state = ThreadState::Exited;
+        Ok(())
+    }
+
+    fn value(&self, id: Id) -> Result<Value, InterpreterError> {
+        self.thread.value(self.program, self.block, id)
+    }
+
+    fn scalar(&self, id: Id) -> Result<u64, InterpreterError> {
+        self.thread.scalar(self.program, self.block, id)
+    }
+
+    fn set_scalar(&mut self, id: Id, type_: ast::ScalarType, value: u64) {
+        self.thread.set(id, Value::Scalar(truncate(type_, value)));
+    }
+
+    fn statement(&mut self, statement: &ExpandedStatement) -> Result<Flow, InterpreterError> {
+        match statement {
+            Statement::Label(_) => {}
+            Statement::Variable(var) => self.variable(var)?,
+            Statement::Instruction(inst) => return self.instruction(inst),
+            Statement::Conditional(cond) => {
+                return Ok(Flow::Jump(if self.scalar(cond.predicate)? != 0 {
+                    cond.if_true
+                } else {
+                    cond.if_false
+                }))
+            }
+            Statement::Call(call) => {
+                let method = if call.is_indirect {
+                    self.program
+                        .function_from_address(self.scalar(call.name)?)?
+                } else {
+                    call.name
+                };
+                let args = call
+                    .input_arguments
+                    .iter()
+                    .map(|(id, _, _)| self.value(*id))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let return_ids = call
+                    .return_arguments
+                    .iter()
+                    .map(|(id, _, _)| *id)
+                    .collect::<Vec<_>>();
+                return self.call(method, args, return_ids);
+            }
+            Statement::LoadVar(load) => {
+                let mut address = self.scalar(load.arg.src)?;
+                let (size, _) = type_layout(&load.typ, None);
+                if let Some((index, _)) = load.member_index {
+                    address += index as u64 * size as u64;
+                }
+                let value = Value::from_bytes(&load.typ, self.memory.read(address, size)?);
+                self.thread.set(load.arg.dst, value);
+            }
+            Statement::StoreVar(store) => {
+                let mut address = self.scalar(store.arg.src1)?;
+                let size = match (&store.type_, store.member_index) {
+                    (ast::Type::Vector(scalar_type, _), Some(index)) => {
+                        let size = scalar_type.size_of() as usize;
+                        address += (index as usize * size) as u64;
+                        size
+                    }
+                    (type_, _) => type_layout(type_, None).0,
+                };
+                let value = self.value(store.arg.src2)?;
+                self.memory.write(address, &value.to_bytes(size))?;
+            }
+            Statement::Conversion(conv) => {
+                let value = convert(conv, self.value(conv.src)?);
+                self.thread.set(conv.dst, value);
+            }
+            Statement::Constant(constant) => {
+                let value = immediate_to_bits(constant.typ, constant.value);
+                self.set_scalar(constant.dst, constant.typ, value);
+            }
+            Statement::RetValue(_, ids) => {
+                let values = ids
+                    .iter()
+                    .map(|(id, _)| self.value(*id))
+                    .collect::<Result<Vec<_>, _>>()?;
+                return Ok(Flow::Return(values));
+            }
+            Statement::PtrAccess(ptr_access) => {
+                let address = self
+                    .scalar(ptr_access.ptr_src)?
+                    .wrapping_add(self.scalar(ptr_access.offset_src)?);
+                self.thread.set(ptr_access.dst, Value::Scalar(address));
+            }
+            Statement::RepackVector(repack) => {
+                let size = repack.typ.size_of() as usize;
+                if repack.is_extract {
+                    let packed = self
+                        .value(repack.packed)?
+                        .to_bytes(size * repack.unpacked.len());
+                    for (index, id) in repack.unpacked.iter().enumerate() {
+                        let value = bytes_to_u64(&packed[index * size..(index + 1) * size]);
+                        self.set_scalar(*id, repack.typ, value);
+                    }
+                } else {
+                    let mut packed = Vec::with_capacity(size * repack.unpacked.len());
+                    for id in repack.unpacked.iter() {
+                        packed.extend_from_slice(&self.value(*id)?.to_bytes(size));
+                    }
+                    self.thread.set(repack.packed, Value::Aggregate(packed));
+                }
+            }
+            Statement::FunctionPointer(fn_ptr) => {
+                let address = self.scalar(fn_ptr.src)?;
+                self.thread.set(fn_ptr.dst, Value::Scalar(address));
+            }
+            Statement::MadC(madc) => {
+                let arg = &madc.arg;
+                let product = mul_int_part(
+                    madc.type_,
+                    madc.is_hi,
+                    self.scalar(arg.src1)?,
+                    self.scalar(arg.src2)?,
+                );
+                let (value, carry) = add_with_carry(
+                    madc.type_,
+                    product,
+                    self.scalar(arg.src3)?,
+                    self.scalar(arg.carry_in)?,
+                );
+                self.set_scalar(arg.dst, madc.type_, value);
+                if let Some(carry_out) = arg.carry_out {
+                    self.set_scalar(carry_out, ast::ScalarType::Pred, carry as u64);
+                }
+            }
+            Statement::MadCC(madcc) => {
+                let arg = &madcc.arg;
+                let product = mul_int_part(
+                    madcc.type_,
+                    madcc.is_hi,
+                    self.scalar(arg.src1)?,
+                    self.scalar(arg.src2)?,
+                );
+                let (value, carry) =
+                    add_with_carry(madcc.type_, product, self.scalar(arg.src3)?, 0);
+                self.set_scalar(arg.dst, madcc.type_, value);
+                self.set_scalar(arg.carry_out, ast::ScalarType::Pred, carry as u64);
+            }
+            Statement::AddC(type_, arg) => {
+                let (value, carry) = add_with_carry(
+                    *type_,
+                    self.scalar(arg.src1)?,
+                    self.scalar(arg.src2)?,
+                    self.scalar(arg.carry_in)?,
+                );
+                self.set_scalar(arg.dst, *type_, value);
+                if let Some(carry_out) = arg.carry_out {
+                    self.set_scalar(carry_out, ast::ScalarType::Pred, carry as u64);
+                }
+            }
+            Statement::AddCC(type_, arg) => {
+                let (value, carry) =
+                    add_with_carry(*type_, self.scalar(arg.src1)?, self.scalar(arg.src2)?, 0);
+                self.set_scalar(arg.dst, *type_, value);
+                self.set_scalar(arg.carry_flag, ast::ScalarType::Pred, carry as u64);
+            }
+            // Carry flag inversion for subtraction is already made explicit by
+            // insert_hardware_registers(...), here carry is a plain borrow
+            Statement::SubC(type_, arg) => {
+                let (value, borrow) = sub_with_borrow(
+                    *type_,
+                    self.scalar(arg.src1)?,
+                    self.scalar(arg.src2)?,
+                    self.scalar(arg.carry_in)?,
+                );
+                self.set_scalar(arg.dst, *type_, value);
+                if let Some(carry_out) = arg.carry_out {
+                    self.set_scalar(carry_out, ast::ScalarType::Pred, borrow as u64);
+                }
+            }
+            Statement::SubCC(type_, arg) => {
+                let (value, borrow) =
+                    sub_with_borrow(*type_, self.scalar(arg.src1)?, self.scalar(arg.src2)?, 0);
+                self.set_scalar(arg.dst, *type_, value);
+                self.set_scalar(arg.carry_flag, ast::ScalarType::Pred, borrow as u64);
+            }
+            Statement::AsmVolatile { .. } | Statement::Location(_) | Statement::Span(_) => {}
+        }
+        Ok(Flow::Next)
+    }
+
+    fn variable(&mut self, var: &Variable) -> Result<(), InterpreterError> {
+        let address = match self.thread.frame().values.get(&var.name) {
+            // Declarations inside loops reuse the same storage
+            Some(address) => address.as_u64(),
+            None => {
+                let state_space = match var.state_space {
+                    ast::StateSpace::Reg | ast::StateSpace::Param => ast::StateSpace::Local,
+                    space => space,
+                };
+                let (size, align) = type_layout(&var.type_, var.align);
+                let address = self.memory.allocate(size, align, state_space);
+                let frame = self.thread.frame_mut();
+                frame.allocations.push(address);
+                frame.values.insert(var.name, Value::Scalar(address));
+                address
+            }
+        };
+        if let Some(ref initializer) = var.initializer {
+            let bytes = self.program.initializer_bytes(&var.type_, initializer)?;
+            self.memory.write(address, &bytes)?;
+        }
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        method_id: Id,
+        args: Vec<Value>,
+        return_ids: Vec<Id>,
+    ) -> Result<Flow, InterpreterError> {
+        let method = self.program.method(method_id)?;
+        if method.input_arguments.len() != args.len() {
+            return Err(TranslateError::MismatchedType.into());
+        }
+        if method.body.is_none() {
+            let name = method
+                .source_name
+                .as_ref()
+                .ok_or(TranslateError::UnknownSymbol)?;
+            let values = self.call_builtin(name, &args)?;
+            if values.len() != return_ids.len() {
+                return Err(TranslateError::MismatchedType.into());
+            }
+            for (id, value) in return_ids.into_iter().zip(values) {
+                self.thread.set(id, value);
+            }
+            return Ok(Flow::Next);
+        }
+        let values = method
+            .input_arguments
+            .iter()
+            .map(|arg| arg.name)
+            .zip(args)
+            .collect();
+        self.thread.frame_mut().pc += 1;
+        self.thread.frames.push(Frame {
+            method: method_id,
+            pc: 0,
+            values,
+            allocations: Vec::new(),
+            return_ids,
+        });
+        Ok(Flow::Call)
+    }
+
+    fn call_builtin(&mut self, name: &str, args: &[Value]) -> Result<Vec<Value>, InterpreterError> {
+        let unprefixed = match name.strip_prefix(ZLUDA_PTX_PREFIX) {
+            Some(unprefixed) => unprefixed,
+            None => return Err(InterpreterError::Unsupported(format!("call to `{}`", name))),
+        };
+        let dimension = |values: [u32; 3]| -> Result<Vec<Value>, InterpreterError> {
+            let index = args.get(0).ok_or(TranslateError::MismatchedType)?.as_u64() as usize;
+            Ok(vec![Value::Scalar(
+                values.get(index).copied().unwrap_or(0) as u64
+            )])
+        };
+        let lane = self.thread.lane;
+        Ok(match unprefixed {
+            "sreg_tid" => dimension(self.thread.tid)?,
+            "sreg_ntid" => dimension(self.block.ntid)?,
+            "sreg_ctaid" => dimension(self.block.ctaid)?,
+            "sreg_nctaid" => dimension(self.block.nctaid)?,
+            "sreg_clock" => vec![Value::Scalar(self.thread.clock as u32 as u64)],
+            "sreg_clock64" => vec![Value::Scalar(self.thread.clock)],
+            "sreg_laneid" => vec![Value::Scalar(lane as u64)],
+            "sreg_lanemask_lt" => vec![Value::Scalar((1u64 << lane) - 1)],
+            "sreg_lanemask_le" => vec![Value::Scalar((1u64 << (lane + 1)) - 1)],
+            "sreg_lanemask_ge" => vec![Value::Scalar(!((1u64 << lane) - 1) as u32 as u64)],
+            // There's no grid sync workspace, same as a non-cooperative launch on CUDA
+            "sreg_envreg" => vec![Value::Scalar(0)],
+            // Every cluster is a single block
+            "sreg_clusterid" => dimension(self.block.ctaid)?,
+            "sreg_nclusterid" => dimension(self.block.nctaid)?,
+            "sreg_cluster_ctaid" => dimension([0, 0, 0])?,
+            "sreg_cluster_nctaid" => dimension([1, 1, 1])?,
+            "sreg_cluster_ctarank" => vec![Value::Scalar(0)],
+            "sreg_cluster_nctarank" => vec![Value::Scalar(1)],
+            "sreg_is_explicit_cluster" => vec![Value::Scalar(0)],
+            "__assertfail" => {
+                let message = args.get(0).ok_or(TranslateError::MismatchedType)?;
+                return Err(InterpreterError::AssertionFailed(
+                    self.memory.read_c_string(message.as_u64())?,
+                ));
+            }
+            // Output of printf is not emulated
+            "vprintf" => vec![Value::Scalar(0)],
+            "malloc" => {
+                let size = args.get(0).ok_or(TranslateError::MismatchedType)?.as_u64();
+                let address = self.memory.allocate(
+                    size as usize,
+                    ALLOCATION_ALIGNMENT as usize,
+                    ast::StateSpace::Global,
+                );
+                vec![Value::Scalar(address)]
+            }
+            "free" => {
+                let address = args.get(0).ok_or(TranslateError::MismatchedType)?.as_u64();
+                if address != 0 {
+                    self.memory.free(address)?;
+                }
+                Vec::new()
+            }
+            _ => return Err(InterpreterError::Unsupported(format!("call to `{}`", name))),
+        })
+    }
+
+    fn instruction(
+        &mut self,
+        inst: &ast::Instruction<ExpandedArgParams>,
+    ) -> Result<Flow, InterpreterError> {
+        match inst {
+            ast::Instruction::Ld(details, arg) => {
+                let address = self.scalar(arg.src)?;
+                let (size, _) = type_layout(&details.typ, None);
+                let value = Value::from_bytes(&details.typ, self.memory.read(address, size)?);
+                self.thread.set(arg.dst, value);
+            }
+            ast::Instruction::St(details, arg) => {
+                let address = self.scalar(arg.src1)?;
+                let (size, _) = type_layout(&details.typ, None);
+                let value = self.value(arg.src2)?;
+                self.memory.write(address, &value.to_bytes(size))?;
+            }
+            ast::Instruction::Mov(_, arg) => {
+                let value = self.value(arg.src)?;
+                self.thread.set(arg.dst, value);
+            }
+            ast::Instruction::Mul(details, arg) => {
+                let (a, b) = (self.scalar(arg.src1)?, self.scalar(arg.src2)?);
+                let value = match details {
+                    ast::MulDetails::Unsigned(desc) | ast::MulDetails::Signed(desc) => {
+                        mul_int(*desc, a, b).1
+                    }
+                    ast::MulDetails::Float(desc) => {
+                        float_op(desc.typ, ftz(desc.flush_to_zero), &[a, b], |x| {
+                            saturate_if(desc.saturate, x[0] * x[1])
+                        })?
+                    }
+                };
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Mad(details, arg) => {
+                let (a, b, c) = (
+                    self.scalar(arg.src1)?,
+                    self.scalar(arg.src2)?,
+                    self.scalar(arg.src3)?,
+                );
+                let value = match details {
+                    ast::MulDetails::Unsigned(desc) | ast::MulDetails::Signed(desc) => {
+                        let (dst_type, product) = mul_int(*desc, a, b);
+                        truncate(dst_type, product.wrapping_add(c))
+                    }
+                    ast::MulDetails::Float(desc) => {
+                        float_op(desc.typ, ftz(desc.flush_to_zero), &[a, b, c], |x| {
+                            saturate_if(desc.saturate, fma(desc.typ, x[0], x[1], x[2]))
+                        })?
+                    }
+                };
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Fma(details, arg) => {
+                let (a, b, c) = (
+                    self.scalar(arg.src1)?,
+                    self.scalar(arg.src2)?,
+                    self.scalar(arg.src3)?,
+                );
+                let value = float_op(details.typ, ftz(details.flush_to_zero), &[a, b, c], |x| {
+                    saturate_if(details.saturate, fma(details.typ, x[0], x[1], x[2]))
+                })?;
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Add(details, arg) => {
+                let value = self.add_sub(details, arg, false)?;
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Sub(details, arg) => {
+                let value = self.add_sub(details, arg, true)?;
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Setp(details, arg) => {
+                let (a, b) = (self.scalar(arg.src1)?, self.scalar(arg.src2)?);
+                let (p, q) = setp(details, a, b)?;
+                self.set_scalar(arg.dst1, ast::ScalarType::Pred, p as u64);
+                if let Some(dst2) = arg.dst2 {
+                    self.set_scalar(dst2, ast::ScalarType::Pred, q as u64);
+                }
+            }
+            ast::Instruction::SetpBool(details, arg) => {
+                let (a, b) = (self.scalar(arg.src1)?, self.scalar(arg.src2)?);
+                let c = (self.scalar(arg.src3)? != 0) != details.negate_src3;
+                let (p, q) = setp(&details.base, a, b)?;
+                let combine = |x: bool| match details.bool_op {
+                    ast::SetpBoolPostOp::And => x && c,
+                    ast::SetpBoolPostOp::Or => x || c,
+                    ast::SetpBoolPostOp::Xor => x != c,
+                };
+                self.set_scalar(arg.dst1, ast::ScalarType::Pred, combine(p) as u64);
+                if let Some(dst2) = arg.dst2 {
+                    self.set_scalar(dst2, ast::ScalarType::Pred, combine(q) as u64);
+                }
+            }
+            ast::Instruction::Not(type_, arg) => {
+                let value = !self.scalar(arg.src)?;
+                self.set_scalar(arg.dst, *type_, value);
+            }
+            ast::Instruction::And(type_, arg) => {
+                let value = self.scalar(arg.src1)? & self.scalar(arg.src2)?;
+                self.set_scalar(arg.dst, *type_, value);
+            }
+            ast::Instruction::Or(type_, arg) => {
+                let value = self.scalar(arg.src1)? | self.scalar(arg.src2)?;
+                self.set_scalar(arg.dst, *type_, value);
+            }
+            ast::Instruction::Xor { typ, arg } => {
+                let value = self.scalar(arg.src1)? ^ self.scalar(arg.src2)?;
+                self.set_scalar(arg.dst, *typ, value);
+            }
+            ast::Instruction::Bra(_, arg) => return Ok(Flow::Jump(arg.src)),
+            ast::Instruction::Cvt(details, arg) => {
+                let value = cvt(details, self.scalar(arg.src)?)?;
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::CvtPackedFloat(desc, arg) => {
+                let (a, b) = (self.scalar(arg.src1)?, self.scalar(arg.src2)?);
+                // First source goes into the upper half
+                let x = [to_float(desc.src, false, b)?, to_float(desc.src, false, a)?];
+                let value = pack_lanes(desc.dst, desc.rounding, x)?;
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Cvta(details, arg) => {
+                let value = self.scalar(arg.src)?;
+                let value = match details.size {
+                    ast::CvtaSize::U32 => value as u32 as u64,
+                    ast::CvtaSize::U64 => value,
+                };
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Shl(type_, arg) => {
+                let (a, b) = (self.scalar(arg.src1)?, self.scalar(arg.src2)? as u32);
+                let value = if b >= bit_width(*type_) { 0 } else { a << b };
+                self.set_scalar(arg.dst, *type_, value);
+            }
+            ast::Instruction::Shr(type_, arg) => {
+                let (a, b) = (self.scalar(arg.src1)?, self.scalar(arg.src2)? as u32);
+                let value = if type_.kind() == ast::ScalarKind::Signed {
+                    (sign_extend(*type_, a) >> b.min(bit_width(*type_) - 1)) as u64
+                } else if b >= bit_width(*type_) {
+                    0
+                } else {
+                    truncate(*type_, a) >> b
+                };
+                self.set_scalar(arg.dst, *type_, value);
+            }
+            ast::Instruction::Ret(_) => return Ok(Flow::Return(Vec::new())),
+            ast::Instruction::Exit => return Ok(Flow::Exit),
+            ast::Instruction::Abs(details, arg) => {
+                let a = self.scalar(arg.src)?;
+                let value = match details.typ.kind() {
+                    ast::ScalarKind::Float | ast::ScalarKind::Float2 => {
+                        float_op(details.typ, ftz(details.flush_to_zero), &[a], |x| {
+                            x[0].abs()
+                        })?
+                    }
+                    _ => truncate(
+                        details.typ,
+                        sign_extend(details.typ, a).wrapping_abs() as u64,
+                    ),
+                };
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Neg(details, arg) => {
+                let a = self.scalar(arg.src)?;
+                let value = match details.typ.kind() {
+                    ast::ScalarKind::Float | ast::ScalarKind::Float2 => {
+                        float_op(details.typ, ftz(details.flush_to_zero), &[a], |x| -x[0])?
+                    }
+                    _ => truncate(details.typ, a.wrapping_neg()),
+                };
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Min(details, arg) => {
+                let value = min_max(
+                    details,
+                    self.scalar(arg.src1)?,
+                    self.scalar(arg.src2)?,
+                    true,
+                )?;
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Max(details, arg) => {
+                let value = min_max(
+                    details,
+                    self.scalar(arg.src1)?,
+                    self.scalar(arg.src2)?,
+                    false,
+                )?;
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Rcp(details, arg) => {
+                let a = self.scalar(arg.src)?;
+                let value = float_op(details.type_, ftz(details.flush_to_zero), &[a], |x| {
+                    1.0 / x[0]
+                })?;
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Sqrt(details, arg) => {
+                let a = self.scalar(arg.src)?;
+                let value = float_op(details.type_, ftz(details.flush_to_zero), &[a], |x| {
+                    x[0].sqrt()
+                })?;
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Rsqrt(details, arg) => {
+                let a = self.scalar(arg.src)?;
+                let value = float_op(details.typ, details.flush_to_zero, &[a], |x| {
+                    1.0 / x[0].sqrt()
+                })?;
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Sin { flush_to_zero, arg } => {
+                self.float_unary(ast::ScalarType::F32, *flush_to_zero, arg, f64::sin)?
+            }
+            ast::Instruction::Cos { flush_to_zero, arg } => {
+                self.float_unary(ast::ScalarType::F32, *flush_to_zero, arg, f64::cos)?
+            }
+            ast::Instruction::Lg2 { flush_to_zero, arg } => {
+                self.float_unary(ast::ScalarType::F32, *flush_to_zero, arg, f64::log2)?
+            }
+            ast::Instruction::Ex2 {
+                typ,
+                flush_to_zero,
+                arg,
+            } => self.float_unary(*typ, *flush_to_zero, arg, f64::exp2)?,
+            ast::Instruction::Tanh(typ, arg) => self.float_unary(*typ, false, arg, f64::tanh)?,
+            ast::Instruction::Copysign(typ, arg) => {
+                let sign_bit = 1u64 << (typ.size_of() as u32 * 8 - 1);
+                let (a, b) = (self.scalar(arg.src1)?, self.scalar(arg.src2)?);
+                self.set_scalar(arg.dst, *typ, (a & sign_bit) | (b & !sign_bit));
+            }
+            ast::Instruction::Testp(op, typ, arg) => {
+                let a = self.scalar(arg.src)?;
+                let category = if *typ == ast::ScalarType::F32 {
+                    f32::from_bits(a as u32).classify()
+                } else {
+                    f64::from_bits(a).classify()
+                };
+                let value = match op {
+                    ast::TestpOp::Finite => {
+                        category != FpCategory::Nan && category != FpCategory::Infinite
+                    }
+                    ast::TestpOp::Infinite => category == FpCategory::Infinite,
+                    ast::TestpOp::Number => category != FpCategory::Nan,
+                    ast::TestpOp::NotANumber => category == FpCategory::Nan,
+                    ast::TestpOp::Normal => category == FpCategory::Normal,
+                    ast::TestpOp::Subnormal => category == FpCategory::Subnormal,
+                };
+                self.thread.set(arg.dst, Value::Scalar(value as u64));
+            }
+            ast::Instruction::Selp(type_, arg) => {
+                let value = if self.scalar(arg.src3)? != 0 {
+                    self.scalar(arg.src1)?
+                } else {
+                    self.scalar(arg.src2)?
+                };
+                self.set_scalar(arg.dst, *type_, value);
+            }
+            ast::Instruction::Atom(details, arg) => {
+                let address = self.scalar(arg.src1)?;
+                let b = self.scalar(arg.src2)?;
+                let (type_, size) = match details.inner {
+                    ast::AtomInnerDetails::Bit { typ, .. }
+                    | ast::AtomInnerDetails::Unsigned { typ, .. }
+                    | ast::AtomInnerDetails::Signed { typ, .. }
+                    | ast::AtomInnerDetails::Float { typ, .. } => (typ, typ.size_of() as usize),
+                };
+                let old = self.memory.read_scalar(address, size)?;
+                let new = atom(details.inner, old, b)?;
+                self.memory.write_scalar(address, size, new)?;
+                self.set_scalar(arg.dst, type_, old);
+            }
+            ast::Instruction::Red(details, arg) => {
+                let address = self.scalar(arg.src1)?;
+                let size = match details.inner {
+                    ast::AtomInnerDetails::Bit { typ, .. }
+                    | ast::AtomInnerDetails::Unsigned { typ, .. }
+                    | ast::AtomInnerDetails::Signed { typ, .. }
+                    | ast::AtomInnerDetails::Float { typ, .. } => typ.size_of() as usize,
+                };
+                let len = details.vector.unwrap_or(1) as usize;
+                let src = self.value(arg.src2)?.to_bytes(size * len);
+                for (i, b) in src.chunks(size).enumerate() {
+                    let address = address + (i * size) as u64;
+                    let old = self.memory.read_scalar(address, size)?;
+                    let new = atom(details.inner, old, bytes_to_u64(b))?;
+                    self.memory.write_scalar(address, size, new)?;
+                }
+            }
+            ast::Instruction::CpAsync(details, arg) => {
+                let dst = self.scalar(arg.src1)?;
+                let src = self.scalar(arg.src2)?;
+                let cp_size = details.cp_size as usize;
+                let src_size = (self.scalar(arg.src3)? as usize).min(cp_size);
+                let mut data = self.memory.read(src, src_size)?.to_vec();
+                data.resize(cp_size, 0);
+                self.memory.write(dst, &data)?;
+            }
+            // Copies are executed synchronously
+            ast::Instruction::CpAsyncCommitGroup | ast::Instruction::CpAsyncWaitGroup(..) => {}
+            ast::Instruction::AtomCas(details, arg) => {
+                let address = self.scalar(arg.src1)?;
+                let size = details.typ.size_of() as usize;
+                let old = self.memory.read_scalar(address, size)?;
+                if old == truncate(details.typ, self.scalar(arg.src2)?) {
+                    let new = self.scalar(arg.src3)?;
+                    self.memory.write_scalar(address, size, new)?;
+                }
+                self.set_scalar(arg.dst, details.typ, old);
+            }
+            ast::Instruction::Div(details, arg) => {
+                let (a, b) = (self.scalar(arg.src1)?, self.scalar(arg.src2)?);
+                let value = match details {
+                    ast::DivDetails::Unsigned(type_) => {
+                        let (a, b) = (truncate(*type_, a), truncate(*type_, b));
+                        // Division by zero is undefined, we return all ones like AMD GPUs do
+                        truncate(*type_, a.checked_div(b).unwrap_or(u64::MAX))
+                    }
+                    ast::DivDetails::Signed(type_) => {
+                        let (a, b) = (sign_extend(*type_, a), sign_extend(*type_, b));
+                        let value = if b == 0 { -1 } else { a.wrapping_div(b) };
+                        truncate(*type_, value as u64)
+                    }
+                    ast::DivDetails::Float(desc) => {
+                        float_op(desc.typ, ftz(desc.flush_to_zero), &[a, b], |x| x[0] / x[1])?
+                    }
+                };
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Rem { typ, arg } => {
+                let (a, b) = (self.scalar(arg.src1)?, self.scalar(arg.src2)?);
+                let value = if typ.kind() == ast::ScalarKind::Signed {
+                    let (a, b) = (sign_extend(*typ, a), sign_extend(*typ, b));
+                    (if b == 0 { a } else { a.wrapping_rem(b) }) as u64
+                } else {
+                    let (a, b) = (truncate(*typ, a), truncate(*typ, b));
+                    a.checked_rem(b).unwrap_or(a)
+                };
+                self.set_scalar(arg.dst, *typ, value);
+            }
+            ast::Instruction::Clz { typ, arg } => {
+                let a = truncate(*typ, self.scalar(arg.src)?);
+                let value = a.leading_zeros() - (64 - bit_width(*typ));
+                self.thread.set(arg.dst, Value::Scalar(value as u64));
+            }
+            ast::Instruction::Popc { typ, arg } => {
+                let value = truncate(*typ, self.scalar(arg.src)?).count_ones();
+                self.thread.set(arg.dst, Value::Scalar(value as u64));
+            }
+            ast::Instruction::Brev { typ, arg } => {
+                let value = self.scalar(arg.src)?.reverse_bits() >> (64 - bit_width(*typ));
+                self.set_scalar(arg.dst, *typ, value);
+            }
+            ast::Instruction::Bfind(details, arg) => {
+                let type_ = details.type_;
+                let mut a = truncate(type_, self.scalar(arg.src)?);
+                if type_.kind() == ast::ScalarKind::Signed && sign_extend(type_, a) < 0 {
+                    a = truncate(type_, !a);
+                }
+                let value = if a == 0 {
+                    u32::MAX
+                } else {
+                    let msb = 63 - a.leading_zeros();
+                    if details.shift {
+                        bit_width(type_) - 1 - msb
+                    } else {
+                        msb
+                    }
+                };
+                self.thread.set(arg.dst, Value::Scalar(value as u64));
+            }
+            ast::Instruction::Bfe { typ, arg } => {
+                let value = bfe(
+                    *typ,
+                    self.scalar(arg.src1)?,
+                    self.scalar(arg.src2)? as u32,
+                    self.scalar(arg.src3)? as u32,
+                );
+                self.set_scalar(arg.dst, *typ, value);
+            }
+            ast::Instruction::Bfi { typ, arg } => {
+                let value = bfi(
+                    *typ,
+                    self.scalar(arg.src1)?,
+                    self.scalar(arg.src2)?,
+                    self.scalar(arg.src3)? as u32,
+                    self.scalar(arg.src4)? as u32,
+                );
+                self.set_scalar(arg.dst, *typ, value);
+            }
+            ast::Instruction::Prmt { control, arg } => {
+                let value = prmt(*control, self.scalar(arg.src1)?, self.scalar(arg.src2)?);
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::PrmtSlow { control, arg } => {
+                let control = self.scalar(*control)? as u16;
+                let value = prmt(control, self.scalar(arg.src1)?, self.scalar(arg.src2)?);
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Shf(details, arg) => {
+                let (a, b, c) = (
+                    self.scalar(arg.src1)? as u32 as u64,
+                    self.scalar(arg.src2)? as u32 as u64,
+                    self.scalar(arg.src3)? as u32,
+                );
+                let n = match details.mode {
+                    ast::ShiftNormalization::Wrap => c & 31,
+                    ast::ShiftNormalization::Clamp => c.min(32),
+                };
+                let joined = (b << 32) | a;
+                let value = match details.direction {
+                    ast::FunnelDirection::Left => (joined << n) >> 32,
+                    ast::FunnelDirection::Right => (joined >> n) as u32 as u64,
+                };
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Vshr(arg) => {
+                let a = self.scalar(arg.src1)? as u32 as u64;
+                let b = (self.scalar(arg.src2)? as u32).min(32);
+                let c = self.scalar(arg.src3)?;
+                let value = (a >> b).wrapping_add(c) as u32;
+                self.thread.set(arg.dst, Value::Scalar(value as u64));
+            }
+            ast::Instruction::Set(details, arg) => {
+                let (a, b) = (self.scalar(arg.src1)?, self.scalar(arg.src2)?);
+                let value = set(details, a, b)?;
+                self.thread.set(arg.dst, Value::Scalar(value));
+            }
+            ast::Instruction::Dp4a(type_, arg) => {
+                let (a, b, c) = (
+                    self.scalar(arg.src1)?,
+                    self.scalar(arg.src2)?,
+                    self.scalar(arg.src3)?,
+                );
+                let signed = type_.kind() == ast::ScalarKind::Signed;
+                let byte = |x: u64, i: u32| {
+                    let x = (x >> (i * 8)) as u8;
+                    if signed {
+                        x as i8 as i64
+                    } else {
+                        x as i64
+                    }
+                };
+                let sum = (0..4).fold(c as i64, |sum, i| sum.wrapping_add(byte(a, i) * byte(b, i)));
+                self.thread.set(arg.dst, Value::Scalar(sum as u32 as u64));
+            }
+            ast::Instruction::CvtPack(details, arg) => {
+                let (a, b, c) = (
+                    self.scalar(arg.src1)?,
+                    self.scalar(arg.src2)?,
+                    self.scalar(arg.src3)?,
+                );
+                let bits = details.bits as u32;
+                let (min, max) = if details.signed {
+                    (-(1i64 << (bits - 1)), (1i64 << (bits - 1)) - 1)
+                } else {
+                    (0, (1i64 << bits) - 1)
+                };
+                let mask = (1u64 << bits) - 1;
+                let sat = |x: u64| (x as u32 as i32 as i64).clamp(min, max) as u64 & mask;
+                // Bits above the packed pair come from c, which is zero for 16-bit types
+                let value = (c << (2 * bits)) | (sat(a) << bits) | sat(b);
+                self.set_scalar(arg.dst, ast::ScalarType::B32, value);
+            }
+            ast::Instruction::Sad(type_, arg) => {
+                let (a, b, c) = (
+                    self.scalar(arg.src1)?,
+                    self.scalar(arg.src2)?,
+                    self.scalar(arg.src3)?,
+                );
+                let difference = if type_.kind() == ast::ScalarKind::Signed {
+                    (sign_extend(*type_, a) as i128 - sign_extend(*type_, b) as i128).unsigned_abs()
+                        as u64
+                } else {
+                    (truncate(*type_, a) as i128 - truncate(*type_, b) as i128).unsigned_abs()
+                        as u64
+                };
+                self.set_scalar(arg.dst, *type_, c.wrapping_add(difference));
+            }
+            ast::Instruction::Lop3 { lut, arg } => {
+                let value = lop3(
+                    *lut,
+                    self.scalar(arg.src1)?,
+                    self.scalar(arg.src2)?,
+                    self.scalar(arg.src3)?,
+                );
+                self.set_scalar(arg.dst, ast::ScalarType::B32, value);
+            }
+            ast::Instruction::Bmsk(mode, arg) => {
+                let (a, b) = (self.scalar(arg.src1)? as u32, self.scalar(arg.src2)? as u32);
+                let (start, width) = match mode {
+                    ast::ShiftNormalization::Wrap => (a & 31, b & 31),
+                    ast::ShiftNormalization::Clamp => (a.min(32), b.min(32)),
+                };
+                let mask = ((1u64 << width) - 1) << start;
+                self.set_scalar(arg.dst, ast::ScalarType::B32, mask);
+            }
+            ast::Instruction::Szext(mode, type_, arg) => {
+                let (a, b) = (self.scalar(arg.src1)?, self.scalar(arg.src2)? as u32);
+                let width = match mode {
+                    ast::ShiftNormalization::Wrap => b & 31,
+                    ast::ShiftNormalization::Clamp => b.min(32),
+                };
+                let value = if width == 0 {
+                    0
+                } else if type_.kind() == ast::ScalarKind::Signed {
+                    (((a as u32) << (32 - width)) as i32 >> (32 - width)) as u64
+                } else {
+                    a & ((1u64 << width) - 1)
+                };
+                self.set_scalar(arg.dst, *type_, value);
+            }
+            ast::Instruction::Fns(arg) => {
+                let value = fns(
+                    self.scalar(arg.src1)? as u32,
+                    self.scalar(arg.src2)? as u32,
+                    self.scalar(arg.src3)? as i32,
+                );
+                self.set_scalar(arg.dst, ast::ScalarType::B32, value as u64);
+            }
+            ast::Instruction::Mul24(details, arg) => {
+                let value = mul24(details, self.scalar(arg.src1)?, self.scalar(arg.src2)?);
+                self.set_scalar(arg.dst, details.type_, value);
+            }
+            ast::Instruction::Mad24(details, arg) => {
+                let value = mul24(details, self.scalar(arg.src1)?, self.scalar(arg.src2)?);
+                let c = self.scalar(arg.src3)?;
+                self.set_scalar(arg.dst, details.type_, value.wrapping_add(c));
+            }
+            ast::Instruction::Isspacep(space, arg) => {
+                let actual = self.memory.state_space(self.scalar(arg.src)?);
+                let value = match space {
+                    ast::StateSpace::Global => {
+                        !matches!(actual, ast::StateSpace::Local | ast::StateSpace::Shared)
+                    }
+                    ast::StateSpace::Shared => actual == ast::StateSpace::Shared,
+                    ast::StateSpace::Local => actual == ast::StateSpace::Local,
+                    _ => return Err(TranslateError::Unreachable.into()),
+                };
+                self.set_scalar(arg.dst, ast::ScalarType::Pred, value as u64);
+            }
+            // Every cluster is a single block
+            ast::Instruction::Mapa(type_, arg) => {
+                let value = self.scalar(arg.src1)?;
+                self.set_scalar(arg.dst, *type_, value);
+            }
+            ast::Instruction::Getctarank(_, arg) => {
+                self.set_scalar(arg.dst, ast::ScalarType::U32, 0);
+            }
+            ast::Instruction::Activemask { .. }
+            | ast::Instruction::Shfl(..)
+            | ast::Instruction::Vote(..)
+            | ast::Instruction::MatchAny(..)
+            | ast::Instruction::Redux(..)
+            | ast::Instruction::ElectSync(..) => return Ok(Flow::Suspend(ThreadState::Warp)),
+            ast::Instruction::Bar(..)
+            | ast::Instruction::BarRed(..)
+            | ast::Instruction::BarrierCluster(ast::BarrierClusterOp::Wait) => {
+                return Ok(Flow::Suspend(ThreadState::Block))
+            }
+            ast::Instruction::BarrierCluster(ast::BarrierClusterOp::Arrive) => {}
+            // Lanes of a warp already execute in lockstep at every collective
+            ast::Instruction::BarWarp(..) => {}
+            ast::Instruction::Membar { .. }
+            | ast::Instruction::Fence { .. }
+            | ast::Instruction::FenceProxy(..)
+            | ast::Instruction::Brkpt
+            | ast::Instruction::Nanosleep(..) => {}
+            ast::Instruction::Trap => return Err(InterpreterError::Trap),
+            ast::Instruction::Tex(..)
+            | ast::Instruction::Tld4(..)
+            | ast::Instruction::Txq(..)
+            | ast::Instruction::Suld(..)
+            | ast::Instruction::Sust(..)
+            | ast::Instruction::Suq(..) => {
+                return Err(InterpreterError::Unsupported(
+                    "texture and surface instructions".to_string(),
+                ))
+            }
+            ast::Instruction::WmmaLoad(..)
+            | ast::Instruction::WmmaStore(..)
+            | ast::Instruction::WmmaMma(..)
+            | ast::Instruction::Mma(..) => {
+                return Err(InterpreterError::Unsupported(
+                    "matrix multiply-accumulate instructions".to_string(),
+                ))
+            }
+            // Waiting threads spin until other threads arrive, which never happens
+            // when threads run until the next collective
+            ast::Instruction::MbarrierInit(..)
+            | ast::Instruction::MbarrierArrive(..)
+            | ast::Instruction::MbarrierTestWait(..) => {
+                return Err(InterpreterError::Unsupported(
+                    "mbarrier instructions".to_string(),
+                ))
+            }
+            // replaced by Statement variants
+            ast::Instruction::Call(..)
+            | ast::Instruction::MadC { .. }
+            | ast::Instruction::MadCC { .. }
+            | ast::Instruction::AddC(..)
+            | ast::Instruction::AddCC(..)
+            | ast::Instruction::SubC(..)
+            | ast::Instruction::SubCC(..) => return Err(TranslateError::Unreachable.into()),
+        }
+        Ok(Flow::Next)
+    }
+
+    fn add_sub(
+        &self,
+        details: &ast::ArithDetails,
+        arg: &ast::Arg3<ExpandedArgParams>,
+        is_sub: bool,
+    ) -> Result<u64, InterpreterError> {
+        let (a, b) = (self.scalar(arg.src1)?, self.scalar(arg.src2)?);
+        Ok(match details {
+            ast::ArithDetails::Unsigned(type_) => {
+                let value = if is_sub {
+                    a.wrapping_sub(b)
+                } else {
+                    a.wrapping_add(b)
+                };
+                truncate(*type_, value)
+            }
+            ast::ArithDetails::Signed(desc) => {
+                let (a, b) = (
+                    sign_extend(desc.typ, a) as i128,
+                    sign_extend(desc.typ, b) as i128,
+                );
+                let mut value = if is_sub { a - b } else { a + b };
+                if desc.saturate {
+                    let max = (1i128 << (bit_width(desc.typ) - 1)) - 1;
+                    value = value.max(-max - 1).min(max);
+                }
+                truncate(desc.typ, value as u64)
+            }
+            ast::ArithDetails::Float(desc) => {
+                float_op(desc.typ, ftz(desc.flush_to_zero), &[a, b], |x| {
+                    saturate_if(
+                        desc.saturate,
+                        if is_sub { x[0] - x[1] } else { x[0] + x[1] },
+                    )
+                })?
+            }
+        })
+    }
+
+    fn float_unary(
+        &mut self,
+        type_: ast::ScalarType,
+        flush_to_zero: bool,
+        arg: &ast::Arg2<ExpandedArgParams>,
+        op: fn(f64) -> f64,
+    ) -> Result<(), InterpreterError> {
+        let a = self.scalar(arg.src)?;
+        let value = float_op(type_, flush_to_zero, &[a], |x| op(x[0]))?;
+        self.thread.set(arg.dst, Value::Scalar(value));
+        Ok(())
+    }
+}
+
+fn type_layout(type_: &ast::Type, align: Option<u32>) -> (usize, usize) {
+    let (size, natural_align) = match type_ {
+        ast::Type::Scalar(scalar_type) => {
+            let size = scalar_type.size_of() as usize;
+            (size, size)
+        }
+        ast::Type::Vector(scalar_type, len) => {
+            let size = scalar_type.size_of() as usize * *len as usize;
+            (size, size)
+        }
+        ast::Type::Array(scalar_type, dimensions) => {
+            let size = scalar_type.size_of() as usize;
+            (dimensions.iter().fold(size, |x, y| x * (*y as usize)), size)
+        }
+        ast::Type::Pointer(..) | ast::Type::Texref | ast::Type::Surfref => (8, 8),
+        ast::Type::Struct(_) => {
+            let layout = type_.layout();
+            (layout.size(), layout.align())
+        }
+    };
+    (size, natural_align.max(align.unwrap_or(0) as usize))
+}
+
+fn bit_width(type_: ast::ScalarType) -> u32 {
+    type_.size_of() as u32 * 8
+}
+
+fn truncate(type_: ast::ScalarType, value: u64) -> u64 {
+    match type_ {
+        ast::ScalarType::Pred => value & 1,
+        _ => {
+            let width = bit_width(type_);
+            if width >= 64 {
+                value
+            } else {
+                value & ((1u64 << width) - 1)
+            }
+        }
+    }
+}
+
+fn sign_extend(type_: ast::ScalarType, value: u64) -> i64 {
+    let shift = 64 - bit_width(type_);
+    ((value << shift) as i64) >> shift
+}
+
+fn immediate_to_bits(type_: ast::ScalarType, value: ast::ImmediateValue) -> u64 {
+    let bits = match (type_, value) {
+        (ast::ScalarType::F16, ast::ImmediateValue::F32(x)) => f16::from_f32(x).to_bits() as u64,
+        (ast::ScalarType::F16, ast::ImmediateValue::F64(x)) => f16::from_f64(x).to_bits() as u64,
+        (ast::ScalarType::F64, ast::ImmediateValue::F32(x)) => (x as f64).to_bits(),
+        (ast::ScalarType::F64, ast::ImmediateValue::F64(x)) => x.to_bits(),
+        (_, ast::ImmediateValue::F32(x)) => x.to_bits() as u64,
+        (_, ast::ImmediateValue::F64(x)) => (x as f32).to_bits() as u64,
+        (_, ast::ImmediateValue::U64(x)) => x,
+        (_, ast::ImmediateValue::S64(x)) => x as u64,
+    };
+    truncate(type_, bits)
+}
+
+fn convert(conv: &ImplicitConversion, value: Value) -> Value {
+    match (&conv.from_type, &conv.to_type, &conv.kind) {
+        (_, _, ConversionKind::BitToPtr)
+        | (_, _, ConversionKind::PtrToPtr)
+        | (_, _, ConversionKind::AddressOf) => Value::Scalar(value.as_u64()),
+        (ast::Type::Scalar(from), ast::Type::Scalar(to), ConversionKind::SignExtend) => {
+            Value::Scalar(truncate(*to, sign_extend(*from, value.as_u64()) as u64))
+        }
+        (ast::Type::Scalar(from), ast::Type::Scalar(to), ConversionKind::Default) => {
+            let value = truncate(*from, value.as_u64());
+            let value = if to.size_of() > from.size_of()
+                && from.kind() == ast::ScalarKind::Signed
+                && to.kind() == ast::ScalarKind::Signed
+            {
+                sign_extend(*from, value) as u64
+            } else {
+                value
+            };
+            Value::Scalar(truncate(*to, value))
+        }
+        (_, to_type, ConversionKind::Default) => {
+            let (size, _) = type_layout(to_type, None);
+            Value::from_bytes(to_type, &value.to_bytes(size))
+        }
+        (_, _, ConversionKind::SignExtend) => value,
+    }
+}
+
+fn mul_int(desc: ast::MulInt, a: u64, b: u64) -> (ast::ScalarType, u64) {
+    let type_ = desc.typ;
+    let product = if type_.kind() == ast::ScalarKind::Signed {
+        (sign_extend(type_, a) as i128 * sign_extend(type_, b) as i128) as u128
+    } else {
+        truncate(type_, a) as u128 * truncate(type_, b) as u128
+    };
+    let width = bit_width(type_);
+    match desc.control {
+        ast::MulIntControl::Low => (type_, truncate(type_, product as u64)),
+        ast::MulIntControl::High => (type_, truncate(type_, (product >> width) as u64)),
+        ast::MulIntControl::Wide => {
+            let wide_type = match (type_.kind(), width) {
+                (ast::ScalarKind::Signed, 16) => ast::ScalarType::S32,
+                (ast::ScalarKind::Signed, _) => ast::ScalarType::S64,
+                (_, 16) => ast::ScalarType::U32,
+                (_, _) => ast::ScalarType::U64,
+            };
+            (wide_type, truncate(wide_type, product as u64))
+        }
+    }
+}
+
+fn mul_int_part(type_: ast::ScalarType, is_hi: bool, a: u64, b: u64) -> u64 {
+    let control = if is_hi {
+        ast::MulIntControl::High
+    } else {
+        ast::MulIntControl::Low
+    };
+    mul_int(
+        ast::MulInt {
+            typ: type_,
+            control,
+        },
+        a,
+        b,
+    )
+    .1
+}
+
+fn add_with_carry(type_: ast::ScalarType, a: u64, b: u64, carry_in: u64) -> (u64, bool) {
+    let sum = truncate(type_, a) as u128 + truncate(type_, b) as u128 + (carry_in & 1) as u128;
+    (truncate(type_, sum as u64), sum >> bit_width(type_) != 0)
+}
+
+fn sub_with_borrow(type_: ast::ScalarType, a: u64, b: u64, borrow_in: u64) -> (u64, bool) {
+    let subtrahend = truncate(type_, b) as u128 + (borrow_in & 1) as u128;
+    let minuend = truncate(type_, a) as u128;
+    (
+        truncate(type_, minuend.wrapping_sub(subtrahend) as u64),
+        subtrahend > minuend,
+    )
+}
+
+fn ftz(flush_to_zero: Option<bool>) -> bool {
+    flush_to_zero == Some(true)
+}
+
+fn saturate_if(saturate: bool, x: f64) -> f64 {
+    if saturate {
+        saturate_float(x)
+    } else {
+        x
+    }
+}
+
+fn saturate_float(x: f64) -> f64 {
+    if x.is_nan() {
+        0.0
+    } else {
+        x.max(0.0).min(1.0)
+    }
+}
+
+fn fma(type_: ast::ScalarType, a: f64, b: f64, c: f64) -> f64 {
+    match type_ {
+        ast::ScalarType::F32 => (a as f32).mul_add(b as f32, c as f32) as f64,
+        _ => a.mul_add(b, c),
+    }
+}
+
+fn to_float(
+    type_: ast::ScalarType,
+    flush_to_zero: bool,
+    value: u64,
+) -> Result<f64, InterpreterError> {
+    Ok(match type_ {
+        ast::ScalarType::F16 => f16::from_bits(value as u16).to_f64(),
+        ast::ScalarType::BF16 => bf16::from_bits(value as u16).to_f64(),
+        ast::ScalarType::TF32 => f32::from_bits(value as u32) as f64,
+        ast::ScalarType::F32 => {
+            let x = f32::from_bits(value as u32);
+            if flush_to_zero && x.is_subnormal() {
+                0.0f32.copysign(x) as f64
+            } else {
+                x as f64
+            }
+        }
+        ast::ScalarType::F64 => f64::from_bits(value),
+        _ => return Err(TranslateError::MismatchedType.into()),
+    })
+}
+
+fn from_float(
+    type_: ast::ScalarType,
+    flush_to_zero: bool,
+    x: f64,
+) -> Result<u64, InterpreterError> {
+    Ok(match type_ {
+        ast::ScalarType::F16 => f16::from_f64(x).to_bits() as u64,
+        ast::ScalarType::BF16 => bf16::from_f64(x).to_bits() as u64,
+        ast::ScalarType::F32 => {
+            let x = x as f32;
+            let x = if flush_to_zero && x.is_subnormal() {
+                0.0f32.copysign(x)
+            } else {
+                x
+            };
+            x.to_bits() as u64
+        }
+        ast::ScalarType::F64 => x.to_bits(),
+        _ => return Err(TranslateError::MismatchedType.into()),
+    })
+}
+
+// Applies `op` to every lane, .f16x2 and .bf16x2 are treated as two
+// independent .f16 or .bf16 values
+fn float_op(
+    type_: ast::ScalarType,
+    flush_to_zero: bool,
+    args: &[u64],
+    op: impl Fn(&[f64]) -> f64,
+) -> Result<u64, InterpreterError> {
+    let lane_type = match type_ {
+        ast::ScalarType::F16x2 => ast::ScalarType::F16,
+        ast::ScalarType::BF16x2 => ast::ScalarType::BF16,
+        _ => return float_op_scalar(type_, flush_to_zero, args, &op),
+    };
+    let mut result = 0;
+    for lane in 0..2 {
+        let lane_args = args
+            .iter()
+            .map(|arg| (arg >> (lane * 16)) & 0xffff)
+            .collect::<Vec<_>>();
+        let lane_result = float_op_scalar(lane_type, flush_to_zero, &lane_args, &op)?;
+        result |= lane_result << (lane * 16);
+    }
+    Ok(result)
+}
+
+fn float_op_scalar(
+    type_: ast::ScalarType,
+    flush_to_zero: bool,
+    args: &[u64],
+    op: &dyn Fn(&[f64]) -> f64,
+) -> Result<u64, InterpreterError> {
+    let args = args
+        .iter()
+        .map(|arg| to_float(type_, flush_to_zero, *arg))
+        .collect::<Result<Vec<_>, _>>()?;
+    from_float(type_, flush_to_zero, op(&args))
+}
+
+fn compare(
+    op: ast::SetpCompareOp,
+    type_: ast::ScalarType,
+    a: u64,
+    b: u64,
+) -> Result<bool, InterpreterError> {
+    use std::cmp::Ordering;
+    let ordering = match type_.kind() {
+        ast::ScalarKind::Float => {
+            let (a, b) = (to_float(type_, false, a)?, to_float(type_, false, b)?);
+            let is_nan = a.is_nan() || b.is_nan();
+            return Ok(match op {
+                ast::SetpCompareOp::IsNotNan => !is_nan,
+                ast::SetpCompareOp::IsAnyNan => is_nan,
+                ast::SetpCompareOp::Eq => a == b,
+                ast::SetpCompareOp::NotEq => !is_nan && a != b,
+                ast::SetpCompareOp::Less => a < b,
+                ast::SetpCompareOp::LessOrEq => a <= b,
+                ast::SetpCompareOp::Greater => a > b,
+                ast::SetpCompareOp::GreaterOrEq => a >= b,
+                ast::SetpCompareOp::NanEq => is_nan || a == b,
+                ast::SetpCompareOp::NanNotEq => a != b,
+                ast::SetpCompareOp::NanLess => is_nan || a < b,
+                ast::SetpCompareOp::NanLessOrEq => is_nan || a <= b,
+                ast::SetpCompareOp::NanGreater => is_nan || a > b,
+                ast::SetpCompareOp::NanGreaterOrEq => is_nan || a >= b,
+            });
+        }
+        ast::ScalarKind::Signed => sign_extend(type_, a).cmp(&sign_extend(type_, b)),
+        _ => truncate(type_, a).cmp(&truncate(type_, b)),
+    };
+    Ok(match op {
+        ast::SetpCompareOp::Eq | ast::SetpCompareOp::NanEq => ordering == Ordering::Equal,
+        ast::SetpCompareOp::NotEq | ast::SetpCompareOp::NanNotEq => ordering != Ordering::Equal,
+        ast::SetpCompareOp::Less | ast::SetpCompareOp::NanLess => ordering == Ordering::Less,
+        ast::SetpCompareOp::LessOrEq | ast::SetpCompareOp::NanLessOrEq => {
+            ordering != Ordering::Greater
+        }
+        ast::SetpCompareOp::Greater | ast::SetpCompareOp::NanGreater => {
+            ordering == Ordering::Greater
+        }
+        ast::SetpCompareOp::GreaterOrEq | ast::SetpCompareOp::NanGreaterOrEq => {
+            ordering != Ordering::Less
+        }
+        ast::SetpCompareOp::IsNotNan | ast::SetpCompareOp::IsAnyNan => {
+            return Err(TranslateError::MismatchedType.into())
+        }
+    })
+}
+
+// Returns (p, q), for .f16x2 these are comparisons of the lower and upper
+// halves, otherwise q is the negation of p
+fn setp(details: &ast::SetpData, a: u64, b: u64) -> Result<(bool, bool), InterpreterError> {
+    let flush_to_zero = ftz(details.flush_to_zero);
+    let flush = |type_, x| -> Result<u64, InterpreterError> {
+        if flush_to_zero {
+            from_float(type_, true, to_float(type_, true, x)?)
+        } else {
+            Ok(x)
+        }
+    };
+    if details.typ == ast::ScalarType::F16x2 {
+        let lane = |x: u64, i: u32| (x >> (i * 16)) & 0xffff;
+        let p = compare(details.cmp_op, ast::ScalarType::F16, lane(a, 0), lane(b, 0))?;
+        let q = compare(details.cmp_op, ast::ScalarType::F16, lane(a, 1), lane(b, 1))?;
+        return Ok((p, q));
+    }
+    let (a, b) = if details.typ == ast::ScalarType::F32 {
+        (flush(details.typ, a)?, flush(details.typ, b)?)
+    } else {
+        (a, b)
+    };
+    let p = compare(details.cmp_op, details.typ, a, b)?;
+    Ok((p, !p))
+}
+
+fn set(details: &ast::SetData, a: u64, b: u64) -> Result<u64, InterpreterError> {
+    let true_value = |type_: ast::ScalarType| -> Result<u64, InterpreterError> {
+        match type_.kind() {
+            ast::ScalarKind::Float => from_float(type_, false, 1.0),
+            _ => Ok(truncate(type_, u64::MAX)),
+        }
+    };
+    if details.src_type == ast::ScalarType::F16x2 {
+        let lane_type = match details.dst_type {
+            ast::ScalarType::F16x2 => ast::ScalarType::F16,
+            _ => ast::ScalarType::U16,
+        };
+        let mut result = 0;
+        for lane in 0..2 {
+            let (a, b) = ((a >> (lane * 16)) & 0xffff, (b >> (lane * 16)) & 0xffff);
+            if compare(details.cmp_op, ast::ScalarType::F16, a, b)? {
+                result |= true_value(lane_type)? << (lane * 16);
+            }
+        }
+        return Ok(result);
+    }
+    Ok(if compare(details.cmp_op, details.src_type, a, b)? {
+        true_value(details.dst_type)?
+    } else {
+        0
+    })
+}
+
+fn min_max(
+    details: &ast::MinMaxDetails,
+    a: u64,
+    b: u64,
+    is_min: bool,
+) -> Result<u64, InterpreterError> {
+    Ok(match details {
+        ast::MinMaxDetails::Signed(type_) => {
+            let (a, b) = (sign_extend(*type_, a), sign_extend(*type_, b));
+            truncate(*type_, if is_min { a.min(b) } else { a.max(b) } as u64)
+        }
+        ast::MinMaxDetails::Unsigned(type_) => {
+            let (a, b) = (truncate(*type_, a), truncate(*type_, b));
+            if is_min {
+                a.min(b)
+            } else {
+                a.max(b)
+            }
+        }
+        ast::MinMaxDetails::Float(desc) => {
+            float_op(desc.typ, ftz(desc.flush_to_zero), &[a, b], |x| {
+                if desc.nan && (x[0].is_nan() || x[1].is_nan()) {
+                    f64::NAN
+                } else if is_min {
+                    x[0].min(x[1])
+                } else {
+                    x[0].max(x[1])
+                }
+            })?
+        }
+    })
+}
+
+fn atom(details: ast::AtomInnerDetails, old: u64, b: u64) -> Result<u64, InterpreterError> {
+    Ok(match details {
+        ast::AtomInnerDetails::Bit { op, typ } => truncate(
+            typ,
+            match op {
+                ast::AtomBitOp::And => old & b,
+                ast::AtomBitOp::Or => old | b,
+                ast::AtomBitOp::Xor => old ^ b,
+                ast::AtomBitOp::Exchange => b,
+            },
+        ),
+        ast::AtomInnerDetails::Unsigned { op, typ } => {
+            let b = truncate(typ, b);
+            truncate(
+                typ,
+                match op {
+                    ast::AtomUIntOp::Add => old.wrapping_add(b),
+                    ast::AtomUIntOp::Inc => {
+                        if old >= b {
+                            0
+                        } else {
+                            old + 1
+                        }
+                    }
+                    ast::AtomUIntOp::Dec => {
+                        if old == 0 || old > b {
+                            b
+                        } else {
+                            old - 1
+                        }
+                    }
+                    ast::AtomUIntOp::Min => old.min(b),
+                    ast::AtomUIntOp::Max => old.max(b),
+                },
+            )
+        }
+        ast::AtomInnerDetails::Signed { op, typ } => {
+            let (old, b) = (sign_extend(typ, old), sign_extend(typ, b));
+            truncate(
+                typ,
+                match op {
+                    ast::AtomSIntOp::Add => old.wrapping_add(b),
+                    ast::AtomSIntOp::Min => old.min(b),
+                    ast::AtomSIntOp::Max => old.max(b),
+                } as u64,
+            )
+        }
+        ast::AtomInnerDetails::Float {
+            op: ast::AtomFloatOp::Add,
+            typ,
+        } => float_op(typ, false, &[old, b], |x| x[0] + x[1])?,
+    })
+}
+
+fn bfe(type_: ast::ScalarType, a: u64, position: u32, length: u32) -> u64 {
+    let width = bit_width(type_);
+    let (position, length) = (position & 0xff, length & 0xff);
+    let msb = width - 1;
+    let bit = |i: u32| (a >> i) & 1;
+    let sign_bit = if type_.kind() != ast::ScalarKind::Signed || length == 0 {
+        0
+    } else {
+        bit((position + length - 1).min(msb))
+    };
+    let mut result = 0;
+    for i in 0..width {
+        let value = if i < length && position + i <= msb {
+            bit(position + i)
+        } else {
+            sign_bit
+        };
+        result |= value << i;
+    }
+    result
+}
+
+fn bfi(type_: ast::ScalarType, a: u64, b: u64, position: u32, length: u32) -> u64 {
+    let width = bit_width(type_);
+    let (position, length) = (position & 0xff, length & 0xff);
+    let mut result = b;
+    for i in 0..length {
+        if position + i >= width {
+            break;
+        }
+        let bit = 1u64 << (position + i);
+        result = (result & !bit) | (((a >> i) & 1) << (position + i));
+    }
+    result
+}
+
+fn redux(details: &ast::ReduxDetails, x: u32, y: u32) -> u32 {
+    let signed = details.type_.kind() == ast::ScalarKind::Signed;
+    match details.op {
+        ast::ReduxOp::Add => x.wrapping_add(y),
+        ast::ReduxOp::Min if signed => (x as i32).min(y as i32) as u32,
+        ast::ReduxOp::Min => x.min(y),
+        ast::ReduxOp::Max if signed => (x as i32).max(y as i32) as u32,
+        ast::ReduxOp::Max => x.max(y),
+        ast::ReduxOp::And => x & y,
+        ast::ReduxOp::Or => x | y,
+        ast::ReduxOp::Xor => x ^ y,
+    }
+}
+
+// Every set bit i of the lookup table selects the minterm of a, b and c
+// with the same truth table index
+fn lop3(lut: u8, a: u64, b: u64, c: u64) -> u64 {
+    let select = |x: u64, bit: usize| if bit != 0 { x } else { !x };
+    (0..8).filter(|i| lut & (1 << i) != 0).fold(0, |result, i| {
+        result | (select(a, i & 4) & select(b, i & 2) & select(c, i & 1))
+    })
+}
+
+// Position of the offset-th set bit of mask counting from base (inclusive),
+// towards msb for positive offsets and towards lsb for negative ones
+fn fns(mask: u32, base: u32, offset: i32) -> u32 {
+    let base = base & 31;
+    let is_set = |position: &u32| mask & (1 << position) != 0;
+    let position = if offset == 0 {
+        Some(base).filter(is_set)
+    } else if offset > 0 {
+        (base..32).filter(is_set).nth(offset as usize - 1)
+    } else {
+        (0..=base)
+            .rev()
+            .filter(is_set)
+            .nth(offset.unsigned_abs() as usize - 1)
+    };
+    position.unwrap_or(u32::MAX)
+}
+
+// .hi returns bits 16..47 of the 48 bit product
+fn mul24(details: &ast::Mul24Details, a: u64, b: u64) -> u64 {
+    let product = if details.type_.kind() == ast::ScalarKind::Signed {
+        let extend = |x: u64| ((x << 40) as i64) >> 40;
+        (extend(a) * extend(b)) as u64
+    } else {
+        (a & 0xFF_FFFF) * (b & 0xFF_FFFF)
+    };
+    if details.is_hi {
+        product >> 16
+    } else {
+        product
+    }
+}
+
+fn prmt(control: u16, a: u64, b: u64) -> u64 {
+    let bytes = ((b as u32 as u64) << 32) | a as u32 as u64;
+    let mut result = 0u64;
+    for i in 0..4 {
+        let selector = (control >> (i * 4)) & 0xf;
+        let mut byte = (bytes >> ((selector & 0x7) * 8)) & 0xff;
+        if selector & 0x8 != 0 {
+            byte = if byte & 0x80 != 0 { 0xff } else { 0 };
+        }
+        result |= byte << (i * 8);
+    }
+    result
+}
+
+fn round_float(rounding: ast::RoundingMode, x: f64) -> f64 {
+    match rounding {
+        ast::RoundingMode::NearestEven => {
+            let rounded = x.round();
+            if (x - x.trunc()).abs() == 0.5 {
+                2.0 * (x / 2.0).round()
+            } else {
+                rounded
+            }
+        }
+        ast::RoundingMode::Zero => x.trunc(),
+        ast::RoundingMode::NegativeInf => x.floor(),
+        ast::RoundingMode::PositiveInf => x.ceil(),
+        ast::RoundingMode::NearestAway => x.round(),
+    }
+}
+
+// .bf16 and .tf32 are f32 with a shorter mantissa, rounding drops the low
+// mantissa bits of the f32 value
+fn round_to_reduced_f32(
+    type_: ast::ScalarType,
+    rounding: Option<ast::RoundingMode>,
+    x: f64,
+) -> Result<u64, InterpreterError> {
+    let dropped_bits = match type_ {
+        ast::ScalarType::BF16 => 16,
+        ast::ScalarType::TF32 => 13,
+        _ => return Err(TranslateError::MismatchedType.into()),
+    };
+    let bits = (x as f32).to_bits();
+    let rounded = if x.is_nan() {
+        0x7fff_ffff
+    } else {
+        let half = 1u32 << (dropped_bits - 1);
+        let rounded = match rounding {
+            Some(ast::RoundingMode::Zero) => bits,
+            Some(ast::RoundingMode::NearestAway) => bits + half,
+            _ => bits + half - 1 + ((bits >> dropped_bits) & 1),
+        };
+        rounded & !((1 << dropped_bits) - 1)
+    };
+    Ok(match type_ {
+        ast::ScalarType::BF16 => (rounded >> 16) as u64,
+        _ => rounded as u64,
+    })
+}
+
+// Returns mantissa width, exponent bias and the largest finite encoding
+fn fp8_format(type_: ast::ScalarType) -> Result<(i32, i32, u64), InterpreterError> {
+    Ok(match type_ {
+        ast::ScalarType::E4M3x2 => (3, 7, 0x7e),
+        ast::ScalarType::E5M2x2 => (2, 15, 0x7b),
+        _ => return Err(TranslateError::MismatchedType.into()),
+    })
+}
+
+// Conversions to fp8 are always .rn.satfinite
+fn fp8_from_float(type_: ast::ScalarType, x: f64) -> Result<u64, InterpreterError> {
+    let (mantissa_bits, bias, max_finite) = fp8_format(type_)?;
+    if x.is_nan() {
+        return Ok(0x7f);
+    }
+    let sign = if x.is_sign_negative() { 0x80 } else { 0 };
+    let x = x.abs();
+    // Subnormals share the exponent of the smallest normal value, so the
+    // encoding below is continuous across the boundary and mantissa overflow
+    // carries into the exponent
+    let exponent = (((x.to_bits() >> 52) & 0x7ff) as i32 - 1023).max(1 - bias);
+    let mantissa = round_float(
+        ast::RoundingMode::NearestEven,
+        x / 2f64.powi(exponent - mantissa_bits),
+    ) as u64;
+    let encoded = (((exponent + bias - 1) as u64) << mantissa_bits).saturating_add(mantissa);
+    Ok(sign | encoded.min(max_finite))
+}
+
+fn fp8_to_float(type_: ast::ScalarType, bits: u64) -> Result<f64, InterpreterError> {
+    let (mantissa_bits, bias, _) = fp8_format(type_)?;
+    let sign = if bits & 0x80 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits & 0x7f) >> mantissa_bits) as i32;
+    let mantissa = (bits & ((1 << mantissa_bits) - 1)) as f64;
+    let max_exponent = 0x7f >> mantissa_bits;
+    Ok(match type_ {
+        ast::ScalarType::E4M3x2 if bits & 0x7f == 0x7f => f64::NAN,
+        ast::ScalarType::E5M2x2 if exponent == max_exponent && mantissa != 0.0 => f64::NAN,
+        ast::ScalarType::E5M2x2 if exponent == max_exponent => sign * f64::INFINITY,
+        _ if exponent == 0 => sign * mantissa * 2f64.powi(1 - bias - mantissa_bits),
+        _ => {
+            sign * (2f64.powi(mantissa_bits) + mantissa)
+                * 2f64.powi(exponent - bias - mantissa_bits)
+        }
+    })
+}
+
+// Lane 0 is in the low bits
+fn unpack_lanes(type_: ast::ScalarType, bits: u64) -> Result<[f64; 2], InterpreterError> {
+    Ok(match type_ {
+        ast::ScalarType::F16x2 => [
+            to_float(ast::ScalarType::F16, false, bits & 0xffff)?,
+            to_float(ast::ScalarType::F16, false, (bits >> 16) & 0xffff)?,
+        ],
+        ast::ScalarType::BF16x2 => [
+            to_float(ast::ScalarType::BF16, false, bits & 0xffff)?,
+            to_float(ast::ScalarType::BF16, false, (bits >> 16) & 0xffff)?,
+        ],
+        ast::ScalarType::E4M3x2 | ast::ScalarType::E5M2x2 => [
+            fp8_to_float(type_, bits & 0xff)?,
+            fp8_to_float(type_, (bits >> 8) & 0xff)?,
+        ],
+        _ => return Err(TranslateError::MismatchedType.into()),
+    })
+}
+
+fn pack_lanes(
+    type_: ast::ScalarType,
+    rounding: Option<ast::RoundingMode>,
+    x: [f64; 2],
+) -> Result<u64, InterpreterError> {
+    let (lane_width, lanes) = match type_ {
+        ast::ScalarType::F16x2 => (
+            16,
+            [
+                from_float(ast::ScalarType::F16, false, x[0])?,
+                from_float(ast::ScalarType::F16, false, x[1])?,
+            ],
+        ),
+        ast::ScalarType::BF16x2 => (
+            16,
+            [
+                round_to_reduced_f32(ast::ScalarType::BF16, rounding, x[0])?,
+                round_to_reduced_f32(ast::ScalarType::BF16, rounding, x[1])?,
+            ],
+        ),
+        ast::ScalarType::E4M3x2 | ast::ScalarType::E5M2x2 => (
+            8,
+            [fp8_from_float(type_, x[0])?, fp8_from_float(type_, x[1])?],
+        ),
+        _ => return Err(TranslateError::MismatchedType.into()),
+    };
+    Ok(lanes[0] | (lanes[1] << lane_width))
+}
+
+fn float_to_int(type_: ast::ScalarType, x: f64) -> u64 {
+    // Float to integer conversions in PTX always saturate, NaN becomes 0
+    let width = bit_width(type_);
+    if type_.kind() == ast::ScalarKind::Signed {
+        let max = (1i128 << (width - 1)) - 1;
+        let value = (x as i128).max(-max - 1).min(max);
+        truncate(type_, value as u64)
+    } else {
+        let max = (1u128 << width) - 1;
+        (x as u128).min(max) as u64
+    }
+}
+
+// Rounds exactly representable integer `value` to the floating point type
+fn int_to_float(
+    type_: ast::ScalarType,
+    rounding: Option<ast::RoundingMode>,
+    value: i128,
+) -> Result<u64, InterpreterError> {
+    // Adjusts a round-to-nearest result in the direction requested by the rounding mode
+    fn adjust(rounding: Option<ast::RoundingMode>, value: i128, nearest: i128) -> Option<bool> {
+        match rounding {
+            Some(ast::RoundingMode::Zero) if nearest.abs() > value.abs() => Some(nearest < 0),
+            Some(ast::RoundingMode::NegativeInf) if nearest > value => Some(false),
+            Some(ast::RoundingMode::PositiveInf) if nearest < value => Some(true),
+            _ => None,
+        }
+    }
+    Ok(match type_ {
+        ast::ScalarType::F32 => {
+            let mut x = value as f32;
+            if let Some(up) = adjust(rounding, value, x as i128) {
+                let bits = x.to_bits();
+                x = f32::from_bits(if up == (x >= 0.0) { bits + 1 } else { bits - 1 });
+            }
+            x.to_bits() as u64
+        }
+        ast::ScalarType::F64 => {
+            let mut x = value as f64;
+            if let Some(up) = adjust(rounding, value, x as i128) {
+                let bits = x.to_bits();
+                x = f64::from_bits(if up == (x >= 0.0) { bits + 1 } else { bits - 1 });
+            }
+            x.to_bits()
+        }
+        type_ => from_float(type_, false, value as f64)?,
+    })
+}
+
+fn cvt(details: &ast::CvtDetails, src: u64) -> Result<u64, InterpreterError> {
+    Ok(match details {
+        ast::CvtDetails::IntFromInt(desc) => {
+            let value = if desc.src.kind() == ast::ScalarKind::Signed {
+                sign_extend(desc.src, src) as i128
+            } else {
+                truncate(desc.src, src) as i128
+            };
+            let value = if desc.saturate {
+                let width = bit_width(desc.dst);
+                let (min, max) = if desc.dst.kind() == ast::ScalarKind::Signed {
+                    (-(1i128 << (width - 1)), (1i128 << (width - 1)) - 1)
+                } else {
+                    (0, (1i128 << width) - 1)
+                };
+                value.max(min).min(max)
+            } else {
+                value
+            };
+            truncate(desc.dst, value as u64)
+        }
+        ast::CvtDetails::FloatFromFloat(desc)
+            if desc.src.kind() == ast::ScalarKind::Float2
+                || desc.dst.kind() == ast::ScalarKind::Float2 =>
+        {
+            pack_lanes(desc.dst, desc.rounding, unpack_lanes(desc.src, src)?)?
+        }
+        ast::CvtDetails::FloatFromFloat(desc) => {
+            let flush_to_zero = ftz(desc.flush_to_zero);
+            let x = to_float(desc.src, flush_to_zero, src)?;
+            // Same type conversion with rounding is rounding to an integral value
+            let x = match (desc.src == desc.dst, desc.rounding) {
+                (true, Some(rounding)) => round_float(rounding, x),
+                _ => x,
+            };
+            match desc.dst {
+                ast::ScalarType::BF16 | ast::ScalarType::TF32 => {
+                    round_to_reduced_f32(desc.dst, desc.rounding, x)?
+                }
+                _ => from_float(desc.dst, flush_to_zero, saturate_if(desc.saturate, x))?,
+            }
+        }
+        ast::CvtDetails::IntFromFloat(desc) => {
+            let x = to_float(desc.src, ftz(desc.flush_to_zero), src)?;
+            let x = round_float(desc.rounding.unwrap_or(ast::RoundingMode::Zero), x);
+            float_to_int(desc.dst, x)
+        }
+        ast::CvtDetails::FloatFromInt(desc) => {
+            let value = if desc.src.kind() == ast::ScalarKind::Signed {
+                sign_extend(desc.src, src) as i128
+            } else {
+                truncate(desc.src, src) as i128
+            };
+            let bits = int_to_float(desc.dst, desc.rounding, value)?;
+            if desc.saturate {
+                let x = to_float(desc.dst, false, bits)?;
+                from_float(desc.dst, false, saturate_float(x))?
+            } else {
+                bits
+            }
+        }
+    })
+}
@@ -10,7 +10,9 @@ extern crate hip_runtime_sys as hip;
 lalrpop_mod!(ptx);
 
 pub mod ast;
+pub mod check;
 mod emit;
+pub mod interp;
 pub mod llvm;
 pub mod raytracing;
 #[cfg(test)]
@@ -24,7 +26,9 @@ pub use lalrpop_util::ParseError;
 use std::fmt;
 pub use translate::to_llvm_module;
 pub use translate::to_llvm_module_for_raytracing;
+pub use translate::to_llvm_module_with_line_info;
 pub use translate::Module;
+pub use translate::StatementSpan;
 pub use translate::TranslateError;
 
 pub trait ModuleParserExt {
@@ -71,8 +75,10 @@ impl ModuleParserExt for ModuleParser {
             Err(unrecoverable_err) => {
                 errors.push(unrecoverable_err);
                 ast::Module {
+                    version: (0, 0),
                     sm_version: 0,
                     directives: Vec::new(),
+                    files: Vec::new(),
                 }
             }
         };
@@ -80,53 +86,228 @@ impl ModuleParserExt for ModuleParser {
     }
 }
 
-pub struct DisplayParseError<'a, Loc, Tok, Err>(&'a str, &'a ParseError<Loc, Tok, Err>);
+// Renders a parse error the way rustc does:
+//   error: unexpected token `,`
+//    --> module_0001.ptx:12:25
+//      |
+//   12 |     add.s32 %r1, %r2, , %r3;
+//      |                       ^
+pub struct DisplayParseError<'a, Loc, Tok, Err> {
+    text: &'a str,
+    error: &'a ParseError<Loc, Tok, Err>,
+    file_name: Option<&'a str>,
+}
 
-impl<'a, Loc: fmt::Display + Into<usize> + Copy, Tok, Err> DisplayParseError<'a, Loc, Tok, Err> {
+impl<'a, Loc, Tok, Err> DisplayParseError<'a, Loc, Tok, Err> {
     // unsafe because there's no guarantee that the input str is the one that this error was created from
     pub unsafe fn new(error: &'a ParseError<Loc, Tok, Err>, text: &'a str) -> Self {
-        Self(text, error)
+        Self {
+            text,
+            error,
+            file_name: None,
+        }
+    }
+
+    pub fn with_file_name(self, file_name: &'a str) -> Self {
+        Self {
+            file_name: Some(file_name),
+            ..self
+        }
     }
 }
 
-impl<'a, Loc, Tok> fmt::Display for DisplayParseError<'a, Loc, Tok, PtxError>
-where
-    Loc: fmt::Display,
-    Tok: fmt::Display,
-{
+impl<'a, Tok: fmt::Display> DisplayParseError<'a, usize, Tok, PtxError> {
+    // Byte offsets into the module text
+    pub fn span(&self) -> Option<(usize, usize)> {
+        match self.error {
+            ParseError::InvalidToken { location } => Some((*location, *location + 1)),
+            ParseError::UnrecognizedEof { location, .. } => Some((*location, *location)),
+            ParseError::UnrecognizedToken {
+                token: (start, _, end),
+                ..
+            }
+            | ParseError::ExtraToken {
+                token: (start, _, end),
+            } => Some((*start, *end)),
+            ParseError::User { error } => error.span(),
+        }
+    }
+
+    // One-based line and column of the start of the error
+    pub fn line_column(&self) -> Option<(usize, usize)> {
+        self.span()
+            .map(|(start, _)| line_column(self.text, start.min(self.text.len())))
+    }
+
+    pub fn message(&self) -> String {
+        match self.error {
+            ParseError::InvalidToken { .. } => "invalid token".to_string(),
+            ParseError::UnrecognizedEof { .. } => "unexpected end of module".to_string(),
+            ParseError::UnrecognizedToken {
+                token: (_, token, _),
+                ..
+            } => format!("unexpected token `{}`", token),
+            ParseError::ExtraToken {
+                token: (_, token, _),
+            } => format!("extra token `{}`", token),
+            ParseError::User { error } => error.to_string(),
+        }
+    }
+
+    pub(crate) fn expected(&self) -> Option<String> {
+        const MAX_SHOWN: usize = 8;
+        let expected = match self.error {
+            ParseError::UnrecognizedEof { expected, .. }
+            | ParseError::UnrecognizedToken { expected, .. } => expected,
+            _ => return None,
+        };
+        if expected.is_empty() {
+            return None;
+        }
+        let mut result = format!(
+            "expected one of: {}",
+            expected
+                .iter()
+                .take(MAX_SHOWN)
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if expected.len() > MAX_SHOWN {
+            result.push_str(&format!(" and {} more", expected.len() - MAX_SHOWN));
+        }
+        Some(result)
+    }
+}
+
+impl<'a, Tok: fmt::Display> fmt::Display for DisplayParseError<'a, usize, Tok, PtxError> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.1 {
-            ParseError::User {
-                error: PtxError::UnrecognizedStatement { start, end },
-            } => self.fmt_unrecognized(f, *start, *end, "statement"),
-            ParseError::User {
-                error: PtxError::UnrecognizedDirective { start, end },
-            } => self.fmt_unrecognized(f, *start, *end, "directive"),
-            _ => self.1.fmt(f),
+        fmt_diagnostic(
+            f,
+            self.text,
+            self.file_name,
+            "error",
+            &self.message(),
+            self.span(),
+            self.expected().as_deref(),
+        )
+    }
+}
+
+// Renders a translation error the same way as DisplayParseError, pointing at
+// the statement that could not be translated if the error has a span
+pub struct DisplayTranslateError<'a> {
+    text: Option<&'a str>,
+    error: &'a TranslateError,
+    file_name: Option<&'a str>,
+}
+
+impl<'a> DisplayTranslateError<'a> {
+    // unsafe because there's no guarantee that the input strs are the ones
+    // that were translated, in the same order
+    pub unsafe fn new<T: AsRef<str>>(error: &'a TranslateError, modules: &'a [T]) -> Self {
+        let text = error
+            .span()
+            .and_then(|span| modules.get(span.module))
+            .map(AsRef::as_ref);
+        Self {
+            text,
+            error,
+            file_name: None,
         }
     }
+
+    pub fn with_file_name(self, file_name: &'a str) -> Self {
+        Self {
+            file_name: Some(file_name),
+            ..self
+        }
+    }
+
+    // One-based line and column of the start of the statement
+    pub fn line_column(&self) -> Option<(usize, usize)> {
+        let (text, span) = self.text.zip(self.error.span())?;
+        Some(line_column(text, span.start.min(text.len())))
+    }
 }
 
-impl<'a, Loc, Tok, Err> DisplayParseError<'a, Loc, Tok, Err> {
-    fn fmt_unrecognized(
-        &self,
-        f: &mut fmt::Formatter,
-        start: usize,
-        end: usize,
-        kind: &'static str,
-    ) -> fmt::Result {
-        let full_substring = unsafe { self.0.get_unchecked(start..end) };
-        write!(
+impl<'a> fmt::Display for DisplayTranslateError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let span = self
+            .text
+            .and(self.error.span())
+            .map(|span| (span.start, span.end));
+        fmt_diagnostic(
             f,
-            "Unrecognized {} `{}` found at {}:{}",
-            kind, full_substring, start, end
+            self.text.unwrap_or(""),
+            self.file_name,
+            "error",
+            &format!("PTX translation failed: {}", self.error),
+            span,
+            None,
         )
     }
 }
 
+pub(crate) fn fmt_diagnostic(
+    f: &mut fmt::Formatter,
+    text: &str,
+    file_name: Option<&str>,
+    severity: &str,
+    message: &str,
+    span: Option<(usize, usize)>,
+    note: Option<&str>,
+) -> fmt::Result {
+    write!(f, "{}: {}", severity, message)?;
+    let (start, end) = match span {
+        Some((start, end)) => (start.min(text.len()), end.min(text.len())),
+        None => {
+            if let Some(note) = note {
+                write!(f, "\n  = note: {}", note)?;
+            }
+            return Ok(());
+        }
+    };
+    let (line, column) = line_column(text, start);
+    let line_start = text[..start].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = text[start..]
+        .find(&['\n', '\r'][..])
+        .map_or(text.len(), |index| start + index);
+    let gutter = " ".repeat(line.to_string().len());
+    write!(f, "\n{}--> ", gutter)?;
+    if let Some(file_name) = file_name {
+        write!(f, "{}:", file_name)?;
+    }
+    write!(f, "{}:{}", line, column)?;
+    write!(f, "\n{} |", gutter)?;
+    write!(f, "\n{} | {}", line, &text[line_start..line_end])?;
+    // Keep tabs, so the markers line up with the source line
+    let padding = text[line_start..start]
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+    let markers = text[start..end.min(line_end).max(start)]
+        .chars()
+        .count()
+        .max(1);
+    write!(f, "\n{} | {}{}", gutter, padding, "^".repeat(markers))?;
+    if let Some(note) = note {
+        write!(f, "\n{} = note: {}", gutter, note)?;
+    }
+    Ok(())
+}
+
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
 pub(crate) mod lalrpop {
     use crate::ast;
     use lalrpop_util::{lexer::Token, ParseError};
+    use std::mem;
 
     enum ConstTypeMut<'a> {
         Type(&'a mut ast::Type),
@@ -333,6 +514,11 @@ pub(crate) mod lalrpop {
             | ast::ScalarType::S16
             | ast::ScalarType::S32
             | ast::ScalarType::S64
+            | ast::ScalarType::BF16
+            | ast::ScalarType::BF16x2
+            | ast::ScalarType::TF32
+            | ast::ScalarType::E4M3x2
+            | ast::ScalarType::E5M2x2
             | ast::ScalarType::Pred => ast::ImmediateValue::U64(0),
             ast::ScalarType::F16
             | ast::ScalarType::F32
@@ -448,8 +634,40 @@ pub(crate) mod lalrpop {
         }
     }
 
-    pub(crate) fn without_none<T>(x: Vec<Option<T>>) -> Vec<T> {
-        x.into_iter().filter_map(|x| x).collect()
+    // Statements and directives give their span to errors reported while
+    // parsing them, unless an error already has a span
+    pub(crate) fn set_error_span<'input>(
+        errors: &mut Vec<ParseError<usize, Token<'input>, ast::PtxError>>,
+        start: usize,
+        end: usize,
+    ) {
+        for error in errors.iter_mut() {
+            if let ParseError::User { error } = error {
+                if error.span().is_none() {
+                    let source = Box::new(mem::replace(error, ast::PtxError::SyntaxError));
+                    *error = ast::PtxError::Spanned { start, end, source };
+                }
+            }
+        }
+    }
+
+    // Instructions and declarations are preceded by their span, so errors
+    // reported during translation can point at the source
+    pub(crate) fn with_source_span<'input>(
+        statement: Option<ast::Statement<ast::ParsedArgParams<'input>>>,
+        start: usize,
+        end: usize,
+    ) -> Vec<ast::Statement<ast::ParsedArgParams<'input>>> {
+        match statement {
+            Some(statement @ ast::Statement::Instruction(..))
+            | Some(statement @ ast::Statement::Variable(..)) => {
+                vec![
+                    ast::Statement::Span(ast::SourceSpan { start, end }),
+                    statement,
+                ]
+            }
+            statement => statement.into_iter().collect(),
+        }
     }
 
     pub(crate) fn vector_index<'input>(
@@ -465,11 +683,33 @@ pub(crate) mod lalrpop {
             }),
         }
     }
+
+    // "If stride is not specified, then it defaults to the matrix dimension",
+    // all wmma shapes we support have M = N = 16
+    pub(crate) fn wmma_default_stride<'input>(
+        fragment: ast::MatrixFragment,
+        layout: ast::MatrixLayout,
+        shape: ast::MatrixShape,
+    ) -> ast::Operand<&'input str> {
+        let k = match shape {
+            ast::MatrixShape::M16N16K8 | ast::MatrixShape::M16N8K8 => 8,
+            ast::MatrixShape::M8N8K4 => 4,
+            ast::MatrixShape::M16N16K16 | ast::MatrixShape::M16N8K16 => 16,
+        };
+        let stride = match (fragment, layout) {
+            (ast::MatrixFragment::A, ast::MatrixLayout::Row)
+            | (ast::MatrixFragment::B, ast::MatrixLayout::Col) => k,
+            _ => 16,
+        };
+        ast::Operand::Imm(ast::ImmediateValue::U64(stride))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{DisplayParseError, ModuleParser, ModuleParserExt};
+    use crate::{
+        line_column, DisplayParseError, DisplayTranslateError, ModuleParser, ModuleParserExt,
+    };
 
     #[test]
     fn error_report_unknown_instructions() {
@@ -491,8 +731,147 @@ mod tests {
             Ok(_) => panic!(),
         };
         assert_eq!(errors.len(), 1);
-        let reporter = DisplayParseError(module, &errors[0]);
+        let reporter = unsafe { DisplayParseError::new(&errors[0], module) };
         let build_log_string = format!("{}", reporter);
         assert!(build_log_string.contains("does_not_exist"));
+        assert_eq!(reporter.line_column(), Some((11, 17)));
+    }
+
+    #[test]
+    fn error_report_has_span_of_statement() {
+        let module = ".version 6.5
+.target sm_30
+.address_size 64
+
+.visible .entry add()
+{
+    .reg .u64 x;
+    cvt.sat.s64.s32 x, x;
+    ret;
+}";
+        let errors = match ModuleParser::parse_checked(module) {
+            Err(e) => e,
+            Ok(_) => panic!(),
+        };
+        assert_eq!(errors.len(), 1);
+        let reporter =
+            unsafe { DisplayParseError::new(&errors[0], module) }.with_file_name("add.ptx");
+        assert_eq!(reporter.line_column(), Some((8, 5)));
+        assert_eq!(
+            reporter.to_string(),
+            "error: invalid combination of instruction modifiers
+ --> add.ptx:8:5
+  |
+8 |     cvt.sat.s64.s32 x, x;
+  |     ^^^^^^^^^^^^^^^^^^^^^"
+        );
+    }
+
+    #[test]
+    fn error_report_lists_expected_tokens() {
+        let module = ".version 6.5
+.target sm_30,
+.address_size 64";
+        let errors = match ModuleParser::parse_checked(module) {
+            Err(e) => e,
+            Ok(_) => panic!(),
+        };
+        assert_eq!(errors.len(), 1);
+        let reporter = unsafe { DisplayParseError::new(&errors[0], module) };
+        assert_eq!(reporter.line_column(), Some((3, 1)));
+        assert_eq!(
+            reporter.to_string(),
+            "error: unexpected token `.address_size`
+ --> 3:1
+  |
+3 | .address_size 64
+  | ^^^^^^^^^^^^^
+  = note: expected one of: \"debug\", \"map_f64_to_f32\", \"texmode_independent\", \"texmode_unified\", ShaderModel"
+        );
+    }
+
+    #[test]
+    fn error_report_markers_keep_tabs() {
+        let module = ".version 6.5
+.target sm_30
+.address_size 64
+
+.visible .entry add()
+{
+\tadd.u32 x, ,;
+\tret;
+}";
+        let errors = match ModuleParser::parse_checked(module) {
+            Err(e) => e,
+            Ok(_) => panic!(),
+        };
+        assert_eq!(errors.len(), 1);
+        let reporter = unsafe { DisplayParseError::new(&errors[0], module) };
+        assert_eq!(reporter.line_column(), Some((7, 2)));
+        assert_eq!(
+            reporter.to_string(),
+            "error: unrecognized statement
+ --> 7:2
+  |
+7 | \tadd.u32 x, ,;
+  | \t^^^^^^^^^^^^^"
+        );
+    }
+
+    #[test]
+    fn line_column_is_one_based() {
+        let text = "ab\n\tcd\r\nżółw x";
+        assert_eq!(line_column(text, 0), (1, 1));
+        assert_eq!(line_column(text, 2), (1, 3));
+        assert_eq!(line_column(text, 3), (2, 1));
+        assert_eq!(line_column(text, 4), (2, 2));
+        assert_eq!(line_column(text, 8), (3, 1));
+        // Columns count characters, not bytes
+        assert_eq!(line_column(text, "ab\n\tcd\r\nżółw".len()), (3, 5));
+    }
+
+    #[test]
+    fn translate_error_has_span_of_statement() {
+        let first = ".version 6.5
+.target sm_30
+.address_size 64
+
+.visible .entry first()
+{
+    ret;
+}";
+        let second = ".version 6.5
+.target sm_30
+.address_size 64
+
+.visible .entry second()
+{
+    .reg .u32 x;
+    .reg .u32 x;
+    ret;
+}";
+        let modules = [first, second];
+        let asts = modules
+            .iter()
+            .map(|module| ModuleParser::parse_checked(module).unwrap())
+            .collect::<Vec<_>>();
+        let error = match crate::to_llvm_module(hip_common::CompilationMode::Wave32, asts) {
+            Err(e) => e,
+            Ok(_) => panic!(),
+        };
+        let span = error.span().unwrap();
+        assert_eq!(span.module, 1);
+        assert_eq!(&second[span.start..span.end], ".reg .u32 x;");
+        let reporter =
+            unsafe { DisplayTranslateError::new(&error, &modules) }.with_file_name("second.ptx");
+        assert_eq!(reporter.line_column(), Some((8, 5)));
+        assert_eq!(
+            reporter.to_string(),
+            "error: PTX translation failed: identifier is declared more than once
+ --> second.ptx:8:5
+  |
+8 |     .reg .u32 x;
+  |     ^^^^^^^^^^^^"
+        );
     }
 }
@@ -385,6 +385,7 @@ pub(crate) fn create_module_with_builtins<'input>() -> TranslationModule<'input,
         id_defs,
         ptx_impl_imports,
         directives,
+        source_files: Vec::new(),
     }
 }
 
@@ -2727,6 +2728,8 @@ fn fixup_variable_with_empty_semantic_method<'a, 'input>(
                     | s @ Statement::StoreVar(..)
                     | s @ Statement::RetValue(..)
                     | s @ Statement::AsmVolatile { .. }
+                    | s @ Statement::Location(_)
+                    | s @ Statement::Span(_)
                     | s @ Statement::FunctionPointer(..) => s,
                 };
                 result.push(new_statement);
@@ -3002,6 +3005,7 @@ fn convert_optix_builtin_variable_and_attribute_access_single_function<'input>(
                     layer,
                     coordinates,
                     lod,
+                    gradient,
                 },
             )) => {
                 if let Some(StateSpaceRemapping::ToBlock(id, ast::StateSpace::Global, offset)) =
@@ -3021,6 +3025,7 @@ fn convert_optix_builtin_variable_and_attribute_access_single_function<'input>(
                             layer,
                             coordinates,
                             lod,
+                            gradient,
                         },
                     )));
                 } else {
@@ -3681,9 +3686,13 @@ fn initializer_to_binary_vector(
                         .to_le_bytes();
                     result.extend_from_slice(&bytes);
                 }
-                ast::ScalarType::F16x2 | ast::ScalarType::Pred => {
-                    return Err(TranslateError::unexpected_pattern())
-                }
+                ast::ScalarType::F16x2
+                | ast::ScalarType::BF16
+                | ast::ScalarType::BF16x2
+                | ast::ScalarType::TF32
+                | ast::ScalarType::E4M3x2
+                | ast::ScalarType::E5M2x2
+                | ast::ScalarType::Pred => return Err(TranslateError::unexpected_pattern()),
             },
             _ => return Err(TranslateError::unexpected_pattern()),
         }
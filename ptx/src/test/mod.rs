@@ -50,3 +50,63 @@ fn vectorAdd_11_ptx() -> Result<(), TranslateError> {
     let vector_add = include_str!("vectorAdd_11.ptx");
     compile_and_assert(CompilationMode::Wave32, vector_add)
 }
+
+#[test]
+fn envreg() -> Result<(), TranslateError> {
+    let ptx = include_str!("envreg.ptx");
+    let mut errors = Vec::new();
+    let ast = ptx::ModuleParser::new().parse(&mut errors, ptx).unwrap();
+    assert!(errors.len() == 0);
+    let module = crate::to_llvm_module(CompilationMode::Wave32, vec![ast])?;
+    let llvm_ir = module.get_llvm_text();
+    let llvm_ir = llvm_ir.as_cstr().to_string_lossy();
+    assert!(llvm_ir.contains("@__zluda_ptx_impl__sreg_envreg(i8 1)"));
+    assert!(llvm_ir.contains("@__zluda_ptx_impl__sreg_envreg(i8 2)"));
+    Ok(())
+}
+
+#[test]
+fn line_info() -> Result<(), TranslateError> {
+    let ptx = include_str!("line_info.ptx");
+    let mut errors = Vec::new();
+    let ast = ptx::ModuleParser::new().parse(&mut errors, ptx).unwrap();
+    assert!(errors.len() == 0);
+    assert_eq!(
+        ast.files,
+        vec![(1, "/tmp/line_info.cu"), (2, "/tmp/add_one.cuh")]
+    );
+    let module = crate::to_llvm_module_with_line_info(CompilationMode::Wave32, vec![ast])?;
+    let llvm_ir = module.get_llvm_text();
+    let llvm_ir = llvm_ir.as_cstr().to_string_lossy();
+    assert!(llvm_ir.contains("!DISubprogram(name: \"line_info\""));
+    assert!(llvm_ir.contains("!DILocation(line: 14, column: 5"));
+    assert!(llvm_ir.contains("!DILocation(line: 12, column: 9"));
+    Ok(())
+}
+
+#[test]
+fn line_info_disabled() -> Result<(), TranslateError> {
+    let ptx = include_str!("line_info.ptx");
+    let mut errors = Vec::new();
+    let ast = ptx::ModuleParser::new().parse(&mut errors, ptx).unwrap();
+    let module = crate::to_llvm_module(CompilationMode::Wave32, vec![ast])?;
+    let llvm_ir = module.get_llvm_text();
+    assert!(!llvm_ir.as_cstr().to_string_lossy().contains("!DILocation"));
+    Ok(())
+}
+
+#[test]
+fn texture_query() -> Result<(), TranslateError> {
+    let ptx = include_str!("texture_query.ptx");
+    let mut errors = Vec::new();
+    let ast = ptx::ModuleParser::new().parse(&mut errors, ptx).unwrap();
+    assert!(errors.len() == 0);
+    let module = crate::to_llvm_module(CompilationMode::Wave32, vec![ast])?;
+    let llvm_ir = module.get_llvm_text();
+    let llvm_ir = llvm_ir.as_cstr().to_string_lossy();
+    assert!(llvm_ir.contains("@__zluda_ptx_impl__txq_indirect_width("));
+    assert!(llvm_ir.contains("@__zluda_ptx_impl__suq_indirect_array_size("));
+    assert!(llvm_ir.contains("@__zluda_ptx_impl__tld4_g_indirect_2d_v4_f32_f32("));
+    assert!(llvm_ir.contains("@__zluda_ptx_impl__tex_grad_indirect_2d_v4_f32_f32("));
+    Ok(())
+}
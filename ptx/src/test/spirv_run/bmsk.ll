@@ -0,0 +1,81 @@
+target datalayout = "e-p:64:64-p1:64:64-p2:32:32-p3:32:32-p4:64:64-p5:32:32-p6:32:32-i64:64-v16:16-v24:32-v32:32-v48:64-v96:128-v192:256-v256:256-v512:512-v1024:1024-v2048:2048-n32:64-S32-A5-G1-ni:7"
+target triple = "amdgcn-amd-amdhsa"
+
+declare i32 @__zluda_ptx_impl__bmsk_clamp_b32(i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__bmsk_wrap_b32(i32, i32) #0
+
+define protected amdgpu_kernel void @bmsk(ptr addrspace(4) byref(i64) %"54", ptr addrspace(4) byref(i64) %"55") #1 {
+  %"11" = alloca i1, align 1, addrspace(5)
+  %"4" = alloca i64, align 8, addrspace(5)
+  %"5" = alloca i64, align 8, addrspace(5)
+  %"6" = alloca i32, align 4, addrspace(5)
+  %"7" = alloca i32, align 4, addrspace(5)
+  %"8" = alloca i32, align 4, addrspace(5)
+  %"9" = alloca i32, align 4, addrspace(5)
+  %"10" = alloca i32, align 4, addrspace(5)
+  br label %1
+
+1:                                                ; preds = %0
+  store i1 false, ptr addrspace(5) %"11", align 1
+  %"12" = load i64, ptr addrspace(4) %"54", align 8
+  store i64 %"12", ptr addrspace(5) %"4", align 8
+  %"13" = load i64, ptr addrspace(4) %"55", align 8
+  store i64 %"13", ptr addrspace(5) %"5", align 8
+  %"15" = load i64, ptr addrspace(5) %"4", align 8
+  %"56" = inttoptr i64 %"15" to ptr
+  %"14" = load i32, ptr %"56", align 4
+  store i32 %"14", ptr addrspace(5) %"6", align 4
+  %"17" = load i64, ptr addrspace(5) %"4", align 8
+  %"57" = inttoptr i64 %"17" to ptr
+  %"73" = getelementptr inbounds i8, ptr %"57", i64 4
+  %"16" = load i32, ptr %"73", align 4
+  store i32 %"16", ptr addrspace(5) %"7", align 4
+  %"19" = load i64, ptr addrspace(5) %"4", align 8
+  %"58" = inttoptr i64 %"19" to ptr
+  %"75" = getelementptr inbounds i8, ptr %"58", i64 8
+  %"18" = load i32, ptr %"75", align 4
+  store i32 %"18", ptr addrspace(5) %"8", align 4
+  %"21" = load i64, ptr addrspace(5) %"4", align 8
+  %"59" = inttoptr i64 %"21" to ptr
+  %"77" = getelementptr inbounds i8, ptr %"59", i64 12
+  %"20" = load i32, ptr %"77", align 4
+  store i32 %"20", ptr addrspace(5) %"9", align 4
+  %"23" = load i32, ptr addrspace(5) %"6", align 4
+  %"24" = load i32, ptr addrspace(5) %"7", align 4
+  %"22" = call i32 @__zluda_ptx_impl__bmsk_clamp_b32(i32 %"23", i32 %"24")
+  store i32 %"22", ptr addrspace(5) %"10", align 4
+  %"25" = load i64, ptr addrspace(5) %"5", align 8
+  %"26" = load i32, ptr addrspace(5) %"10", align 4
+  %"60" = inttoptr i64 %"25" to ptr
+  store i32 %"26", ptr %"60", align 4
+  %"28" = load i32, ptr addrspace(5) %"8", align 4
+  %"27" = call i32 @__zluda_ptx_impl__bmsk_clamp_b32(i32 %"28", i32 8)
+  store i32 %"27", ptr addrspace(5) %"10", align 4
+  %"29" = load i64, ptr addrspace(5) %"5", align 8
+  %"30" = load i32, ptr addrspace(5) %"10", align 4
+  %"61" = inttoptr i64 %"29" to ptr
+  %"79" = getelementptr inbounds i8, ptr %"61", i64 4
+  store i32 %"30", ptr %"79", align 4
+  %"32" = load i32, ptr addrspace(5) %"9", align 4
+  %"31" = call i32 @__zluda_ptx_impl__bmsk_clamp_b32(i32 %"32", i32 8)
+  store i32 %"31", ptr addrspace(5) %"10", align 4
+  %"33" = load i64, ptr addrspace(5) %"5", align 8
+  %"34" = load i32, ptr addrspace(5) %"10", align 4
+  %"62" = inttoptr i64 %"33" to ptr
+  %"81" = getelementptr inbounds i8, ptr %"62", i64 8
+  store i32 %"34", ptr %"81", align 4
+  %"36" = load i32, ptr addrspace(5) %"8", align 4
+  %"37" = load i32, ptr addrspace(5) %"9", align 4
+  %"35" = call i32 @__zluda_ptx_impl__bmsk_wrap_b32(i32 %"36", i32 %"37")
+  store i32 %"35", ptr addrspace(5) %"10", align 4
+  %"38" = load i64, ptr addrspace(5) %"5", align 8
+  %"39" = load i32, ptr addrspace(5) %"10", align 4
+  %"63" = inttoptr i64 %"38" to ptr
+  %"83" = getelementptr inbounds i8, ptr %"63", i64 12
+  store i32 %"39", ptr %"83", align 4
+  ret void
+}
+
+attributes #0 = { "amdgpu-unsafe-fp-atomics"="true" "no-trapping-math"="true" "uniform-work-group-size"="true" }
+attributes #1 = { "amdgpu-unsafe-fp-atomics"="true" "denormal-fp-math"="ieee,ieee" "denormal-fp-math-f32"="ieee,ieee" "no-trapping-math"="true" "uniform-work-group-size"="true" }
@@ -0,0 +1,103 @@
+target datalayout = "e-p:64:64-p1:64:64-p2:32:32-p3:32:32-p4:64:64-p5:32:32-p6:32:32-i64:64-v16:16-v24:32-v32:32-v48:64-v96:128-v192:256-v256:256-v512:512-v1024:1024-v2048:2048-n32:64-S32-A5-G1-ni:7"
+target triple = "amdgcn-amd-amdhsa"
+
+define protected amdgpu_kernel void @copysign_nan(ptr addrspace(4) byref(i64) %"68", ptr addrspace(4) byref(i64) %"69") #0 {
+  %"14" = alloca i1, align 1, addrspace(5)
+  %"4" = alloca i64, align 8, addrspace(5)
+  %"5" = alloca i64, align 8, addrspace(5)
+  %"6" = alloca float, align 4, addrspace(5)
+  %"7" = alloca float, align 4, addrspace(5)
+  %"8" = alloca float, align 4, addrspace(5)
+  %"9" = alloca float, align 4, addrspace(5)
+  %"10" = alloca double, align 8, addrspace(5)
+  %"11" = alloca double, align 8, addrspace(5)
+  %"12" = alloca float, align 4, addrspace(5)
+  %"13" = alloca double, align 8, addrspace(5)
+  br label %1
+
+1:                                                ; preds = %0
+  store i1 false, ptr addrspace(5) %"14", align 1
+  %"15" = load i64, ptr addrspace(4) %"68", align 8
+  store i64 %"15", ptr addrspace(5) %"4", align 8
+  %"16" = load i64, ptr addrspace(4) %"69", align 8
+  store i64 %"16", ptr addrspace(5) %"5", align 8
+  %"18" = load i64, ptr addrspace(5) %"4", align 8
+  %"70" = inttoptr i64 %"18" to ptr
+  %"17" = load float, ptr %"70", align 4
+  store float %"17", ptr addrspace(5) %"6", align 4
+  %"20" = load i64, ptr addrspace(5) %"4", align 8
+  %"71" = inttoptr i64 %"20" to ptr
+  %"80" = getelementptr inbounds i8, ptr %"71", i64 4
+  %"19" = load float, ptr %"80", align 4
+  store float %"19", ptr addrspace(5) %"7", align 4
+  %"22" = load i64, ptr addrspace(5) %"4", align 8
+  %"72" = inttoptr i64 %"22" to ptr
+  %"82" = getelementptr inbounds i8, ptr %"72", i64 8
+  %"21" = load float, ptr %"82", align 4
+  store float %"21", ptr addrspace(5) %"8", align 4
+  %"24" = load i64, ptr addrspace(5) %"4", align 8
+  %"73" = inttoptr i64 %"24" to ptr
+  %"84" = getelementptr inbounds i8, ptr %"73", i64 12
+  %"23" = load float, ptr %"84", align 4
+  store float %"23", ptr addrspace(5) %"9", align 4
+  %"26" = load float, ptr addrspace(5) %"6", align 4
+  %"25" = fpext float %"26" to double
+  store double %"25", ptr addrspace(5) %"10", align 8
+  %"28" = load float, ptr addrspace(5) %"7", align 4
+  %"27" = fpext float %"28" to double
+  store double %"27", ptr addrspace(5) %"11", align 8
+  %"30" = load float, ptr addrspace(5) %"7", align 4
+  %"31" = load float, ptr addrspace(5) %"6", align 4
+  %"29" = call float @llvm.copysign.f32(float %"31", float %"30")
+  store float %"29", ptr addrspace(5) %"12", align 4
+  %"32" = load i64, ptr addrspace(5) %"5", align 8
+  %"33" = load float, ptr addrspace(5) %"12", align 4
+  %"74" = inttoptr i64 %"32" to ptr
+  store float %"33", ptr %"74", align 4
+  %"35" = load float, ptr addrspace(5) %"6", align 4
+  %"36" = load float, ptr addrspace(5) %"8", align 4
+  %"34" = call float @llvm.copysign.f32(float %"36", float %"35")
+  store float %"34", ptr addrspace(5) %"12", align 4
+  %"37" = load i64, ptr addrspace(5) %"5", align 8
+  %"38" = load float, ptr addrspace(5) %"12", align 4
+  %"75" = inttoptr i64 %"37" to ptr
+  %"86" = getelementptr inbounds i8, ptr %"75", i64 4
+  store float %"38", ptr %"86", align 4
+  %"40" = load float, ptr addrspace(5) %"9", align 4
+  %"41" = load float, ptr addrspace(5) %"6", align 4
+  %"39" = call float @llvm.copysign.f32(float %"41", float %"40")
+  store float %"39", ptr addrspace(5) %"12", align 4
+  %"42" = load i64, ptr addrspace(5) %"5", align 8
+  %"43" = load float, ptr addrspace(5) %"12", align 4
+  %"76" = inttoptr i64 %"42" to ptr
+  %"88" = getelementptr inbounds i8, ptr %"76", i64 8
+  store float %"43", ptr %"88", align 4
+  %"45" = load float, ptr addrspace(5) %"6", align 4
+  %"46" = load float, ptr addrspace(5) %"9", align 4
+  %"44" = call float @llvm.copysign.f32(float %"46", float %"45")
+  store float %"44", ptr addrspace(5) %"12", align 4
+  %"47" = load i64, ptr addrspace(5) %"5", align 8
+  %"48" = load float, ptr addrspace(5) %"12", align 4
+  %"77" = inttoptr i64 %"47" to ptr
+  %"90" = getelementptr inbounds i8, ptr %"77", i64 12
+  store float %"48", ptr %"90", align 4
+  %"50" = load double, ptr addrspace(5) %"11", align 8
+  %"51" = load double, ptr addrspace(5) %"10", align 8
+  %"49" = call double @llvm.copysign.f64(double %"51", double %"50")
+  store double %"49", ptr addrspace(5) %"13", align 8
+  %"52" = load i64, ptr addrspace(5) %"5", align 8
+  %"53" = load double, ptr addrspace(5) %"13", align 8
+  %"78" = inttoptr i64 %"52" to ptr
+  %"92" = getelementptr inbounds i8, ptr %"78", i64 16
+  store double %"53", ptr %"92", align 8
+  ret void
+}
+
+; Function Attrs: nofree nosync nounwind readnone speculatable willreturn
+declare float @llvm.copysign.f32(float, float) #1
+
+; Function Attrs: nofree nosync nounwind readnone speculatable willreturn
+declare double @llvm.copysign.f64(double, double) #1
+
+attributes #0 = { "amdgpu-unsafe-fp-atomics"="true" "denormal-fp-math"="ieee,ieee" "denormal-fp-math-f32"="ieee,ieee" "no-trapping-math"="true" "uniform-work-group-size"="true" }
+attributes #1 = { nofree nosync nounwind readnone speculatable willreturn }
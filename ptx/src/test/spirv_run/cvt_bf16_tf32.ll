@@ -0,0 +1,106 @@
+target datalayout = "e-p:64:64-p1:64:64-p2:32:32-p3:32:32-p4:64:64-p5:32:32-p6:32:32-i64:64-v16:16-v24:32-v32:32-v48:64-v96:128-v192:256-v256:256-v512:512-v1024:1024-v2048:2048-n32:64-S32-A5-G1-ni:7"
+target triple = "amdgcn-amd-amdhsa"
+
+declare i16 @__zluda_ptx_impl__cvt_rn_bf16_f32(float) #0
+
+declare i32 @__zluda_ptx_impl__cvt_rn_bf16x2_f32(float, float) #0
+
+declare float @__zluda_ptx_impl__cvt_rn_f32_bf16(i16) #0
+
+declare i32 @__zluda_ptx_impl__cvt_rna_tf32_f32(float) #0
+
+declare i16 @__zluda_ptx_impl__cvt_rz_bf16_f32(float) #0
+
+declare i32 @__zluda_ptx_impl__cvt_rz_tf32_f32(float) #0
+
+define protected amdgpu_kernel void @cvt_bf16_tf32(ptr addrspace(4) byref(i64) %"65", ptr addrspace(4) byref(i64) %"66") #1 {
+  %"15" = alloca i1, align 1, addrspace(5)
+  %"4" = alloca i64, align 8, addrspace(5)
+  %"5" = alloca i64, align 8, addrspace(5)
+  %"6" = alloca float, align 4, addrspace(5)
+  %"7" = alloca float, align 4, addrspace(5)
+  %"8" = alloca i16, align 2, addrspace(5)
+  %"9" = alloca i16, align 2, addrspace(5)
+  %"10" = alloca i32, align 4, addrspace(5)
+  %"11" = alloca float, align 4, addrspace(5)
+  %"12" = alloca i32, align 4, addrspace(5)
+  %"13" = alloca i32, align 4, addrspace(5)
+  %"14" = alloca i32, align 4, addrspace(5)
+  br label %1
+
+1:                                                ; preds = %0
+  store i1 false, ptr addrspace(5) %"15", align 1
+  %"16" = load i64, ptr addrspace(4) %"65", align 8
+  store i64 %"16", ptr addrspace(5) %"4", align 8
+  %"17" = load i64, ptr addrspace(4) %"66", align 8
+  store i64 %"17", ptr addrspace(5) %"5", align 8
+  %"19" = load i64, ptr addrspace(5) %"4", align 8
+  %"67" = inttoptr i64 %"19" to ptr
+  %"18" = load float, ptr %"67", align 4
+  store float %"18", ptr addrspace(5) %"6", align 4
+  %"21" = load i64, ptr addrspace(5) %"4", align 8
+  %"68" = inttoptr i64 %"21" to ptr
+  %"103" = getelementptr inbounds i8, ptr %"68", i64 4
+  %"20" = load float, ptr %"103", align 4
+  store float %"20", ptr addrspace(5) %"7", align 4
+  %"23" = load float, ptr addrspace(5) %"6", align 4
+  %"69" = call i16 @__zluda_ptx_impl__cvt_rn_bf16_f32(float %"23")
+  store i16 %"69", ptr addrspace(5) %"8", align 2
+  %"25" = load float, ptr addrspace(5) %"6", align 4
+  %"70" = call i16 @__zluda_ptx_impl__cvt_rz_bf16_f32(float %"25")
+  store i16 %"70", ptr addrspace(5) %"9", align 2
+  %"27" = load float, ptr addrspace(5) %"6", align 4
+  %"28" = load float, ptr addrspace(5) %"7", align 4
+  %"71" = call i32 @__zluda_ptx_impl__cvt_rn_bf16x2_f32(float %"27", float %"28")
+  store i32 %"71", ptr addrspace(5) %"10", align 4
+  %"30" = load i16, ptr addrspace(5) %"8", align 2
+  %"29" = call float @__zluda_ptx_impl__cvt_rn_f32_bf16(i16 %"30")
+  store float %"29", ptr addrspace(5) %"11", align 4
+  %"32" = load float, ptr addrspace(5) %"6", align 4
+  %"73" = call i32 @__zluda_ptx_impl__cvt_rna_tf32_f32(float %"32")
+  store i32 %"73", ptr addrspace(5) %"12", align 4
+  %"34" = load float, ptr addrspace(5) %"7", align 4
+  %"74" = call i32 @__zluda_ptx_impl__cvt_rna_tf32_f32(float %"34")
+  store i32 %"74", ptr addrspace(5) %"13", align 4
+  %"36" = load float, ptr addrspace(5) %"7", align 4
+  %"75" = call i32 @__zluda_ptx_impl__cvt_rz_tf32_f32(float %"36")
+  store i32 %"75", ptr addrspace(5) %"14", align 4
+  %"37" = load i64, ptr addrspace(5) %"5", align 8
+  %"38" = load i16, ptr addrspace(5) %"8", align 2
+  %"76" = inttoptr i64 %"37" to ptr
+  store i16 %"38", ptr %"76", align 2
+  %"39" = load i64, ptr addrspace(5) %"5", align 8
+  %"40" = load i16, ptr addrspace(5) %"9", align 2
+  %"77" = inttoptr i64 %"39" to ptr
+  %"105" = getelementptr inbounds i8, ptr %"77", i64 2
+  store i16 %"40", ptr %"105", align 2
+  %"41" = load i64, ptr addrspace(5) %"5", align 8
+  %"42" = load i32, ptr addrspace(5) %"10", align 4
+  %"78" = inttoptr i64 %"41" to ptr
+  %"107" = getelementptr inbounds i8, ptr %"78", i64 4
+  store i32 %"42", ptr %"107", align 4
+  %"43" = load i64, ptr addrspace(5) %"5", align 8
+  %"44" = load float, ptr addrspace(5) %"11", align 4
+  %"79" = inttoptr i64 %"43" to ptr
+  %"109" = getelementptr inbounds i8, ptr %"79", i64 8
+  store float %"44", ptr %"109", align 4
+  %"45" = load i64, ptr addrspace(5) %"5", align 8
+  %"46" = load i32, ptr addrspace(5) %"12", align 4
+  %"80" = inttoptr i64 %"45" to ptr
+  %"111" = getelementptr inbounds i8, ptr %"80", i64 12
+  store i32 %"46", ptr %"111", align 4
+  %"47" = load i64, ptr addrspace(5) %"5", align 8
+  %"48" = load i32, ptr addrspace(5) %"13", align 4
+  %"81" = inttoptr i64 %"47" to ptr
+  %"113" = getelementptr inbounds i8, ptr %"81", i64 16
+  store i32 %"48", ptr %"113", align 4
+  %"49" = load i64, ptr addrspace(5) %"5", align 8
+  %"50" = load i32, ptr addrspace(5) %"14", align 4
+  %"82" = inttoptr i64 %"49" to ptr
+  %"115" = getelementptr inbounds i8, ptr %"82", i64 20
+  store i32 %"50", ptr %"115", align 4
+  ret void
+}
+
+attributes #0 = { "amdgpu-unsafe-fp-atomics"="true" "no-trapping-math"="true" "uniform-work-group-size"="true" }
+attributes #1 = { "amdgpu-unsafe-fp-atomics"="true" "denormal-fp-math"="ieee,ieee" "denormal-fp-math-f32"="ieee,ieee" "no-trapping-math"="true" "uniform-work-group-size"="true" }
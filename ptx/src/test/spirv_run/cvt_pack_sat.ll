@@ -0,0 +1,107 @@
+target datalayout = "e-p:64:64-p1:64:64-p2:32:32-p3:32:32-p4:64:64-p5:32:32-p6:32:32-i64:64-v16:16-v24:32-v32:32-v48:64-v96:128-v192:256-v256:256-v512:512-v1024:1024-v2048:2048-n32:64-S32-A5-G1-ni:7"
+target triple = "amdgcn-amd-amdhsa"
+
+declare i32 @__zluda_ptx_impl__cvt_pack_sat_s16_s32_b32(i32, i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__cvt_pack_sat_s4_s32_b32(i32, i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__cvt_pack_sat_s8_s32_b32(i32, i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__cvt_pack_sat_u16_s32_b32(i32, i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__cvt_pack_sat_u2_s32_b32(i32, i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__cvt_pack_sat_u8_s32_b32(i32, i32, i32) #0
+
+define protected amdgpu_kernel void @cvt_pack_sat(ptr addrspace(4) byref(i64) %"69", ptr addrspace(4) byref(i64) %"70") #1 {
+  %"10" = alloca i1, align 1, addrspace(5)
+  %"4" = alloca i64, align 8, addrspace(5)
+  %"5" = alloca i64, align 8, addrspace(5)
+  %"6" = alloca i32, align 4, addrspace(5)
+  %"7" = alloca i32, align 4, addrspace(5)
+  %"8" = alloca i32, align 4, addrspace(5)
+  %"9" = alloca i32, align 4, addrspace(5)
+  br label %1
+
+1:                                                ; preds = %0
+  store i1 false, ptr addrspace(5) %"10", align 1
+  %"11" = load i64, ptr addrspace(4) %"69", align 8
+  store i64 %"11", ptr addrspace(5) %"4", align 8
+  %"12" = load i64, ptr addrspace(4) %"70", align 8
+  store i64 %"12", ptr addrspace(5) %"5", align 8
+  %"14" = load i64, ptr addrspace(5) %"4", align 8
+  %"71" = inttoptr i64 %"14" to ptr
+  %"13" = load i32, ptr %"71", align 4
+  store i32 %"13", ptr addrspace(5) %"6", align 4
+  %"16" = load i64, ptr addrspace(5) %"4", align 8
+  %"72" = inttoptr i64 %"16" to ptr
+  %"123" = getelementptr inbounds i8, ptr %"72", i64 4
+  %"15" = load i32, ptr %"123", align 4
+  store i32 %"15", ptr addrspace(5) %"7", align 4
+  %"18" = load i64, ptr addrspace(5) %"4", align 8
+  %"73" = inttoptr i64 %"18" to ptr
+  %"125" = getelementptr inbounds i8, ptr %"73", i64 8
+  %"17" = load i32, ptr %"125", align 4
+  store i32 %"17", ptr addrspace(5) %"8", align 4
+  %"20" = load i32, ptr addrspace(5) %"6", align 4
+  %"21" = load i32, ptr addrspace(5) %"7", align 4
+  %"19" = call i32 @__zluda_ptx_impl__cvt_pack_sat_u16_s32_b32(i32 %"20", i32 %"21", i32 0)
+  store i32 %"19", ptr addrspace(5) %"9", align 4
+  %"22" = load i64, ptr addrspace(5) %"5", align 8
+  %"23" = load i32, ptr addrspace(5) %"9", align 4
+  %"76" = inttoptr i64 %"22" to ptr
+  store i32 %"23", ptr %"76", align 4
+  %"25" = load i32, ptr addrspace(5) %"6", align 4
+  %"26" = load i32, ptr addrspace(5) %"7", align 4
+  %"24" = call i32 @__zluda_ptx_impl__cvt_pack_sat_s16_s32_b32(i32 %"25", i32 %"26", i32 0)
+  store i32 %"24", ptr addrspace(5) %"9", align 4
+  %"27" = load i64, ptr addrspace(5) %"5", align 8
+  %"28" = load i32, ptr addrspace(5) %"9", align 4
+  %"79" = inttoptr i64 %"27" to ptr
+  %"127" = getelementptr inbounds i8, ptr %"79", i64 4
+  store i32 %"28", ptr %"127", align 4
+  %"30" = load i32, ptr addrspace(5) %"6", align 4
+  %"31" = load i32, ptr addrspace(5) %"7", align 4
+  %"32" = load i32, ptr addrspace(5) %"8", align 4
+  %"29" = call i32 @__zluda_ptx_impl__cvt_pack_sat_u8_s32_b32(i32 %"30", i32 %"31", i32 %"32")
+  store i32 %"29", ptr addrspace(5) %"9", align 4
+  %"33" = load i64, ptr addrspace(5) %"5", align 8
+  %"34" = load i32, ptr addrspace(5) %"9", align 4
+  %"82" = inttoptr i64 %"33" to ptr
+  %"129" = getelementptr inbounds i8, ptr %"82", i64 8
+  store i32 %"34", ptr %"129", align 4
+  %"36" = load i32, ptr addrspace(5) %"6", align 4
+  %"37" = load i32, ptr addrspace(5) %"7", align 4
+  %"38" = load i32, ptr addrspace(5) %"8", align 4
+  %"35" = call i32 @__zluda_ptx_impl__cvt_pack_sat_s8_s32_b32(i32 %"36", i32 %"37", i32 %"38")
+  store i32 %"35", ptr addrspace(5) %"9", align 4
+  %"39" = load i64, ptr addrspace(5) %"5", align 8
+  %"40" = load i32, ptr addrspace(5) %"9", align 4
+  %"85" = inttoptr i64 %"39" to ptr
+  %"131" = getelementptr inbounds i8, ptr %"85", i64 12
+  store i32 %"40", ptr %"131", align 4
+  %"42" = load i32, ptr addrspace(5) %"7", align 4
+  %"43" = load i32, ptr addrspace(5) %"6", align 4
+  %"44" = load i32, ptr addrspace(5) %"8", align 4
+  %"41" = call i32 @__zluda_ptx_impl__cvt_pack_sat_s4_s32_b32(i32 %"42", i32 %"43", i32 %"44")
+  store i32 %"41", ptr addrspace(5) %"9", align 4
+  %"45" = load i64, ptr addrspace(5) %"5", align 8
+  %"46" = load i32, ptr addrspace(5) %"9", align 4
+  %"88" = inttoptr i64 %"45" to ptr
+  %"133" = getelementptr inbounds i8, ptr %"88", i64 16
+  store i32 %"46", ptr %"133", align 4
+  %"48" = load i32, ptr addrspace(5) %"7", align 4
+  %"49" = load i32, ptr addrspace(5) %"6", align 4
+  %"50" = load i32, ptr addrspace(5) %"8", align 4
+  %"47" = call i32 @__zluda_ptx_impl__cvt_pack_sat_u2_s32_b32(i32 %"48", i32 %"49", i32 %"50")
+  store i32 %"47", ptr addrspace(5) %"9", align 4
+  %"51" = load i64, ptr addrspace(5) %"5", align 8
+  %"52" = load i32, ptr addrspace(5) %"9", align 4
+  %"91" = inttoptr i64 %"51" to ptr
+  %"135" = getelementptr inbounds i8, ptr %"91", i64 20
+  store i32 %"52", ptr %"135", align 4
+  ret void
+}
+
+attributes #0 = { "amdgpu-unsafe-fp-atomics"="true" "no-trapping-math"="true" "uniform-work-group-size"="true" }
+attributes #1 = { "amdgpu-unsafe-fp-atomics"="true" "denormal-fp-math"="ieee,ieee" "denormal-fp-math-f32"="ieee,ieee" "no-trapping-math"="true" "uniform-work-group-size"="true" }
@@ -0,0 +1,89 @@
+target datalayout = "e-p:64:64-p1:64:64-p2:32:32-p3:32:32-p4:64:64-p5:32:32-p6:32:32-i64:64-v16:16-v24:32-v32:32-v48:64-v96:128-v192:256-v256:256-v512:512-v1024:1024-v2048:2048-n32:64-S32-A5-G1-ni:7"
+target triple = "amdgcn-amd-amdhsa"
+
+declare i32 @__zluda_ptx_impl__fns_b32(i32, i32, i32) #0
+
+define protected amdgpu_kernel void @fns(ptr addrspace(4) byref(i64) %"69", ptr addrspace(4) byref(i64) %"70") #1 {
+  %"10" = alloca i1, align 1, addrspace(5)
+  %"4" = alloca i64, align 8, addrspace(5)
+  %"5" = alloca i64, align 8, addrspace(5)
+  %"6" = alloca i32, align 4, addrspace(5)
+  %"7" = alloca i32, align 4, addrspace(5)
+  %"8" = alloca i32, align 4, addrspace(5)
+  %"9" = alloca i32, align 4, addrspace(5)
+  br label %1
+
+1:                                                ; preds = %0
+  store i1 false, ptr addrspace(5) %"10", align 1
+  %"11" = load i64, ptr addrspace(4) %"69", align 8
+  store i64 %"11", ptr addrspace(5) %"4", align 8
+  %"12" = load i64, ptr addrspace(4) %"70", align 8
+  store i64 %"12", ptr addrspace(5) %"5", align 8
+  %"14" = load i64, ptr addrspace(5) %"4", align 8
+  %"71" = inttoptr i64 %"14" to ptr
+  %"13" = load i32, ptr %"71", align 4
+  store i32 %"13", ptr addrspace(5) %"6", align 4
+  %"16" = load i64, ptr addrspace(5) %"4", align 8
+  %"72" = inttoptr i64 %"16" to ptr
+  %"88" = getelementptr inbounds i8, ptr %"72", i64 4
+  %"15" = load i32, ptr %"88", align 4
+  store i32 %"15", ptr addrspace(5) %"7", align 4
+  %"18" = load i64, ptr addrspace(5) %"4", align 8
+  %"73" = inttoptr i64 %"18" to ptr
+  %"90" = getelementptr inbounds i8, ptr %"73", i64 8
+  %"17" = load i32, ptr %"90", align 4
+  store i32 %"17", ptr addrspace(5) %"8", align 4
+  %"20" = load i32, ptr addrspace(5) %"6", align 4
+  %"19" = call i32 @__zluda_ptx_impl__fns_b32(i32 %"20", i32 0, i32 1)
+  store i32 %"19", ptr addrspace(5) %"9", align 4
+  %"21" = load i64, ptr addrspace(5) %"5", align 8
+  %"22" = load i32, ptr addrspace(5) %"9", align 4
+  %"74" = inttoptr i64 %"21" to ptr
+  store i32 %"22", ptr %"74", align 4
+  %"24" = load i32, ptr addrspace(5) %"6", align 4
+  %"25" = load i32, ptr addrspace(5) %"7", align 4
+  %"23" = call i32 @__zluda_ptx_impl__fns_b32(i32 %"24", i32 %"25", i32 3)
+  store i32 %"23", ptr addrspace(5) %"9", align 4
+  %"26" = load i64, ptr addrspace(5) %"5", align 8
+  %"27" = load i32, ptr addrspace(5) %"9", align 4
+  %"76" = inttoptr i64 %"26" to ptr
+  %"92" = getelementptr inbounds i8, ptr %"76", i64 4
+  store i32 %"27", ptr %"92", align 4
+  %"29" = load i32, ptr addrspace(5) %"6", align 4
+  %"30" = load i32, ptr addrspace(5) %"8", align 4
+  %"28" = call i32 @__zluda_ptx_impl__fns_b32(i32 %"29", i32 31, i32 %"30")
+  store i32 %"28", ptr addrspace(5) %"9", align 4
+  %"31" = load i64, ptr addrspace(5) %"5", align 8
+  %"32" = load i32, ptr addrspace(5) %"9", align 4
+  %"78" = inttoptr i64 %"31" to ptr
+  %"94" = getelementptr inbounds i8, ptr %"78", i64 8
+  store i32 %"32", ptr %"94", align 4
+  %"34" = load i32, ptr addrspace(5) %"6", align 4
+  %"33" = call i32 @__zluda_ptx_impl__fns_b32(i32 %"34", i32 0, i32 0)
+  store i32 %"33", ptr addrspace(5) %"9", align 4
+  %"35" = load i64, ptr addrspace(5) %"5", align 8
+  %"36" = load i32, ptr addrspace(5) %"9", align 4
+  %"79" = inttoptr i64 %"35" to ptr
+  %"96" = getelementptr inbounds i8, ptr %"79", i64 12
+  store i32 %"36", ptr %"96", align 4
+  %"38" = load i32, ptr addrspace(5) %"6", align 4
+  %"37" = call i32 @__zluda_ptx_impl__fns_b32(i32 %"38", i32 4, i32 0)
+  store i32 %"37", ptr addrspace(5) %"9", align 4
+  %"39" = load i64, ptr addrspace(5) %"5", align 8
+  %"40" = load i32, ptr addrspace(5) %"9", align 4
+  %"80" = inttoptr i64 %"39" to ptr
+  %"98" = getelementptr inbounds i8, ptr %"80", i64 16
+  store i32 %"40", ptr %"98", align 4
+  %"42" = load i32, ptr addrspace(5) %"6", align 4
+  %"41" = call i32 @__zluda_ptx_impl__fns_b32(i32 %"42", i32 8, i32 100)
+  store i32 %"41", ptr addrspace(5) %"9", align 4
+  %"43" = load i64, ptr addrspace(5) %"5", align 8
+  %"44" = load i32, ptr addrspace(5) %"9", align 4
+  %"81" = inttoptr i64 %"43" to ptr
+  %"100" = getelementptr inbounds i8, ptr %"81", i64 20
+  store i32 %"44", ptr %"100", align 4
+  ret void
+}
+
+attributes #0 = { "amdgpu-unsafe-fp-atomics"="true" "no-trapping-math"="true" "uniform-work-group-size"="true" }
+attributes #1 = { "amdgpu-unsafe-fp-atomics"="true" "denormal-fp-math"="ieee,ieee" "denormal-fp-math-f32"="ieee,ieee" "no-trapping-math"="true" "uniform-work-group-size"="true" }
@@ -0,0 +1,87 @@
+target datalayout = "e-p:64:64-p1:64:64-p2:32:32-p3:32:32-p4:64:64-p5:32:32-p6:32:32-i64:64-v16:16-v24:32-v32:32-v48:64-v96:128-v192:256-v256:256-v512:512-v1024:1024-v2048:2048-n32:64-S32-A5-G1-ni:7"
+target triple = "amdgcn-amd-amdhsa"
+
+define protected amdgpu_kernel void @lop3_lut(ptr addrspace(4) byref(i64) %"56", ptr addrspace(4) byref(i64) %"57") #0 {
+  %"13" = alloca i1, align 1, addrspace(5)
+  %"4" = alloca i64, align 8, addrspace(5)
+  %"5" = alloca i64, align 8, addrspace(5)
+  %"6" = alloca i32, align 4, addrspace(5)
+  %"7" = alloca i32, align 4, addrspace(5)
+  %"8" = alloca i32, align 4, addrspace(5)
+  %"9" = alloca i32, align 4, addrspace(5)
+  %"10" = alloca i32, align 4, addrspace(5)
+  %"11" = alloca i32, align 4, addrspace(5)
+  %"12" = alloca i32, align 4, addrspace(5)
+  br label %1
+
+1:                                                ; preds = %0
+  store i1 false, ptr addrspace(5) %"13", align 1
+  %"14" = load i64, ptr addrspace(4) %"56", align 8
+  store i64 %"14", ptr addrspace(5) %"4", align 8
+  %"15" = load i64, ptr addrspace(4) %"57", align 8
+  store i64 %"15", ptr addrspace(5) %"5", align 8
+  %"17" = load i64, ptr addrspace(5) %"4", align 8
+  %"58" = inttoptr i64 %"17" to ptr
+  %"16" = load i32, ptr %"58", align 4
+  store i32 %"16", ptr addrspace(5) %"6", align 4
+  %"19" = load i64, ptr addrspace(5) %"4", align 8
+  %"59" = inttoptr i64 %"19" to ptr
+  %"66" = getelementptr inbounds i8, ptr %"59", i64 4
+  %"18" = load i32, ptr %"66", align 4
+  store i32 %"18", ptr addrspace(5) %"7", align 4
+  %"21" = load i64, ptr addrspace(5) %"4", align 8
+  %"60" = inttoptr i64 %"21" to ptr
+  %"68" = getelementptr inbounds i8, ptr %"60", i64 8
+  %"20" = load i32, ptr %"68", align 4
+  store i32 %"20", ptr addrspace(5) %"8", align 4
+  %"23" = load i32, ptr addrspace(5) %"6", align 4
+  %"24" = load i32, ptr addrspace(5) %"7", align 4
+  %"25" = load i32, ptr addrspace(5) %"8", align 4
+  %2 = and i32 %"25", %"24"
+  %3 = or i32 %"25", %"24"
+  %4 = and i32 %3, %"23"
+  %"22" = or i32 %2, %4
+  store i32 %"22", ptr addrspace(5) %"9", align 4
+  %"27" = load i32, ptr addrspace(5) %"6", align 4
+  %"28" = load i32, ptr addrspace(5) %"7", align 4
+  %"29" = load i32, ptr addrspace(5) %"8", align 4
+  %5 = xor i32 %"28", %"27"
+  %"26" = xor i32 %5, %"29"
+  store i32 %"26", ptr addrspace(5) %"10", align 4
+  %"31" = load i32, ptr addrspace(5) %"6", align 4
+  %"32" = load i32, ptr addrspace(5) %"7", align 4
+  %"33" = load i32, ptr addrspace(5) %"8", align 4
+  %6 = xor i32 %"33", %"32"
+  %7 = and i32 %6, %"31"
+  %"30" = xor i32 %7, %"33"
+  store i32 %"30", ptr addrspace(5) %"11", align 4
+  %"35" = load i32, ptr addrspace(5) %"6", align 4
+  %"36" = load i32, ptr addrspace(5) %"7", align 4
+  %"37" = load i32, ptr addrspace(5) %"8", align 4
+  %8 = or i32 %"37", %"35"
+  %9 = or i32 %"36", %8
+  %"34" = xor i32 %9, -1
+  store i32 %"34", ptr addrspace(5) %"12", align 4
+  %"38" = load i64, ptr addrspace(5) %"5", align 8
+  %"39" = load i32, ptr addrspace(5) %"9", align 4
+  %"61" = inttoptr i64 %"38" to ptr
+  store i32 %"39", ptr %"61", align 4
+  %"40" = load i64, ptr addrspace(5) %"5", align 8
+  %"41" = load i32, ptr addrspace(5) %"10", align 4
+  %"62" = inttoptr i64 %"40" to ptr
+  %"70" = getelementptr inbounds i8, ptr %"62", i64 4
+  store i32 %"41", ptr %"70", align 4
+  %"42" = load i64, ptr addrspace(5) %"5", align 8
+  %"43" = load i32, ptr addrspace(5) %"11", align 4
+  %"63" = inttoptr i64 %"42" to ptr
+  %"72" = getelementptr inbounds i8, ptr %"63", i64 8
+  store i32 %"43", ptr %"72", align 4
+  %"44" = load i64, ptr addrspace(5) %"5", align 8
+  %"45" = load i32, ptr addrspace(5) %"12", align 4
+  %"64" = inttoptr i64 %"44" to ptr
+  %"74" = getelementptr inbounds i8, ptr %"64", i64 12
+  store i32 %"45", ptr %"74", align 4
+  ret void
+}
+
+attributes #0 = { "amdgpu-unsafe-fp-atomics"="true" "denormal-fp-math"="ieee,ieee" "denormal-fp-math-f32"="ieee,ieee" "no-trapping-math"="true" "uniform-work-group-size"="true" }
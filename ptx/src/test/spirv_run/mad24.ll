@@ -0,0 +1,132 @@
+target datalayout = "e-p:64:64-p1:64:64-p2:32:32-p3:32:32-p4:64:64-p5:32:32-p6:32:32-i64:64-v16:16-v24:32-v32:32-v48:64-v96:128-v192:256-v256:256-v512:512-v1024:1024-v2048:2048-n32:64-S32-A5-G1-ni:7"
+target triple = "amdgcn-amd-amdhsa"
+
+declare i32 @__zluda_ptx_impl__mad24_hi_s32(i32, i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__mad24_hi_u32(i32, i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__mad24_lo_s32(i32, i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__mad24_lo_u32(i32, i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__mul24_hi_s32(i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__mul24_hi_u32(i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__mul24_lo_s32(i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__mul24_lo_u32(i32, i32) #0
+
+define protected amdgpu_kernel void @mad24(ptr addrspace(4) byref(i64) %"84", ptr addrspace(4) byref(i64) %"85") #1 {
+  %"13" = alloca i1, align 1, addrspace(5)
+  %"4" = alloca i64, align 8, addrspace(5)
+  %"5" = alloca i64, align 8, addrspace(5)
+  %"6" = alloca i32, align 4, addrspace(5)
+  %"7" = alloca i32, align 4, addrspace(5)
+  %"8" = alloca i32, align 4, addrspace(5)
+  %"9" = alloca i32, align 4, addrspace(5)
+  %"10" = alloca i32, align 4, addrspace(5)
+  %"11" = alloca i32, align 4, addrspace(5)
+  %"12" = alloca i32, align 4, addrspace(5)
+  br label %1
+
+1:                                                ; preds = %0
+  store i1 false, ptr addrspace(5) %"13", align 1
+  %"14" = load i64, ptr addrspace(4) %"84", align 8
+  store i64 %"14", ptr addrspace(5) %"4", align 8
+  %"15" = load i64, ptr addrspace(4) %"85", align 8
+  store i64 %"15", ptr addrspace(5) %"5", align 8
+  %"17" = load i64, ptr addrspace(5) %"4", align 8
+  %"86" = inttoptr i64 %"17" to ptr
+  %"16" = load i32, ptr %"86", align 4
+  store i32 %"16", ptr addrspace(5) %"6", align 4
+  %"19" = load i64, ptr addrspace(5) %"4", align 8
+  %"87" = inttoptr i64 %"19" to ptr
+  %"154" = getelementptr inbounds i8, ptr %"87", i64 4
+  %"18" = load i32, ptr %"154", align 4
+  store i32 %"18", ptr addrspace(5) %"7", align 4
+  %"21" = load i64, ptr addrspace(5) %"4", align 8
+  %"88" = inttoptr i64 %"21" to ptr
+  %"156" = getelementptr inbounds i8, ptr %"88", i64 8
+  %"20" = load i32, ptr %"156", align 4
+  store i32 %"20", ptr addrspace(5) %"8", align 4
+  %"23" = load i32, ptr addrspace(5) %"6", align 4
+  %"24" = load i32, ptr addrspace(5) %"7", align 4
+  %"22" = call i32 @__zluda_ptx_impl__mul24_lo_u32(i32 %"23", i32 %"24")
+  store i32 %"22", ptr addrspace(5) %"9", align 4
+  %"26" = load i32, ptr addrspace(5) %"6", align 4
+  %"27" = load i32, ptr addrspace(5) %"7", align 4
+  %"25" = call i32 @__zluda_ptx_impl__mul24_hi_u32(i32 %"26", i32 %"27")
+  store i32 %"25", ptr addrspace(5) %"10", align 4
+  %"29" = load i32, ptr addrspace(5) %"6", align 4
+  %"30" = load i32, ptr addrspace(5) %"7", align 4
+  %"28" = call i32 @__zluda_ptx_impl__mul24_lo_s32(i32 %"29", i32 %"30")
+  store i32 %"28", ptr addrspace(5) %"11", align 4
+  %"32" = load i32, ptr addrspace(5) %"6", align 4
+  %"33" = load i32, ptr addrspace(5) %"7", align 4
+  %"31" = call i32 @__zluda_ptx_impl__mul24_hi_s32(i32 %"32", i32 %"33")
+  store i32 %"31", ptr addrspace(5) %"12", align 4
+  %"34" = load i64, ptr addrspace(5) %"5", align 8
+  %"35" = load i32, ptr addrspace(5) %"9", align 4
+  %"97" = inttoptr i64 %"34" to ptr
+  store i32 %"35", ptr %"97", align 4
+  %"36" = load i64, ptr addrspace(5) %"5", align 8
+  %"37" = load i32, ptr addrspace(5) %"10", align 4
+  %"98" = inttoptr i64 %"36" to ptr
+  %"158" = getelementptr inbounds i8, ptr %"98", i64 4
+  store i32 %"37", ptr %"158", align 4
+  %"38" = load i64, ptr addrspace(5) %"5", align 8
+  %"39" = load i32, ptr addrspace(5) %"11", align 4
+  %"99" = inttoptr i64 %"38" to ptr
+  %"160" = getelementptr inbounds i8, ptr %"99", i64 8
+  store i32 %"39", ptr %"160", align 4
+  %"40" = load i64, ptr addrspace(5) %"5", align 8
+  %"41" = load i32, ptr addrspace(5) %"12", align 4
+  %"100" = inttoptr i64 %"40" to ptr
+  %"162" = getelementptr inbounds i8, ptr %"100", i64 12
+  store i32 %"41", ptr %"162", align 4
+  %"43" = load i32, ptr addrspace(5) %"6", align 4
+  %"44" = load i32, ptr addrspace(5) %"7", align 4
+  %"45" = load i32, ptr addrspace(5) %"8", align 4
+  %"42" = call i32 @__zluda_ptx_impl__mad24_lo_u32(i32 %"43", i32 %"44", i32 %"45")
+  store i32 %"42", ptr addrspace(5) %"9", align 4
+  %"47" = load i32, ptr addrspace(5) %"6", align 4
+  %"48" = load i32, ptr addrspace(5) %"7", align 4
+  %"49" = load i32, ptr addrspace(5) %"8", align 4
+  %"46" = call i32 @__zluda_ptx_impl__mad24_hi_u32(i32 %"47", i32 %"48", i32 %"49")
+  store i32 %"46", ptr addrspace(5) %"10", align 4
+  %"51" = load i32, ptr addrspace(5) %"6", align 4
+  %"52" = load i32, ptr addrspace(5) %"7", align 4
+  %"53" = load i32, ptr addrspace(5) %"8", align 4
+  %"50" = call i32 @__zluda_ptx_impl__mad24_lo_s32(i32 %"51", i32 %"52", i32 %"53")
+  store i32 %"50", ptr addrspace(5) %"11", align 4
+  %"55" = load i32, ptr addrspace(5) %"6", align 4
+  %"56" = load i32, ptr addrspace(5) %"7", align 4
+  %"57" = load i32, ptr addrspace(5) %"8", align 4
+  %"54" = call i32 @__zluda_ptx_impl__mad24_hi_s32(i32 %"55", i32 %"56", i32 %"57")
+  store i32 %"54", ptr addrspace(5) %"12", align 4
+  %"58" = load i64, ptr addrspace(5) %"5", align 8
+  %"59" = load i32, ptr addrspace(5) %"9", align 4
+  %"113" = inttoptr i64 %"58" to ptr
+  %"164" = getelementptr inbounds i8, ptr %"113", i64 16
+  store i32 %"59", ptr %"164", align 4
+  %"60" = load i64, ptr addrspace(5) %"5", align 8
+  %"61" = load i32, ptr addrspace(5) %"10", align 4
+  %"114" = inttoptr i64 %"60" to ptr
+  %"166" = getelementptr inbounds i8, ptr %"114", i64 20
+  store i32 %"61", ptr %"166", align 4
+  %"62" = load i64, ptr addrspace(5) %"5", align 8
+  %"63" = load i32, ptr addrspace(5) %"11", align 4
+  %"115" = inttoptr i64 %"62" to ptr
+  %"168" = getelementptr inbounds i8, ptr %"115", i64 24
+  store i32 %"63", ptr %"168", align 4
+  %"64" = load i64, ptr addrspace(5) %"5", align 8
+  %"65" = load i32, ptr addrspace(5) %"12", align 4
+  %"116" = inttoptr i64 %"64" to ptr
+  %"170" = getelementptr inbounds i8, ptr %"116", i64 28
+  store i32 %"65", ptr %"170", align 4
+  ret void
+}
+
+attributes #0 = { "amdgpu-unsafe-fp-atomics"="true" "no-trapping-math"="true" "uniform-work-group-size"="true" }
+attributes #1 = { "amdgpu-unsafe-fp-atomics"="true" "denormal-fp-math"="ieee,ieee" "denormal-fp-math-f32"="ieee,ieee" "no-trapping-math"="true" "uniform-work-group-size"="true" }
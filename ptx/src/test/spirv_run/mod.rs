@@ -1,3 +1,4 @@
+use crate::interp::Interpreter;
 use crate::llvm;
 use crate::ptx;
 use crate::translate;
@@ -17,13 +18,17 @@ use zluda_llvm::bit_writer::*;
 
 macro_rules! test_ptx {
     ($fn_name:ident, $input:expr, $output:expr) => {
+        test_ptx!($fn_name, $input, $output, [1, 1, 1], [1, 1, 1]);
+    };
+
+    ($fn_name:ident, $input:expr, $output:expr, $grid:expr, $block:expr) => {
         paste! {
             #[test]
             fn [<$fn_name _hip>]() -> Result<(), Box<dyn std::error::Error>> {
                 let ptx = include_str!(concat!(stringify!($fn_name), ".ptx"));
                 let input = $input;
                 let mut output = $output;
-                test_hip_assert(stringify!($fn_name), ptx, &input, &mut output)
+                test_hip_assert(stringify!($fn_name), ptx, &input, &mut output, $grid, $block)
             }
         }
 
@@ -33,7 +38,17 @@ macro_rules! test_ptx {
                 let ptx = include_str!(concat!(stringify!($fn_name), ".ptx"));
                 let input = $input;
                 let mut output = $output;
-                test_cuda_assert(stringify!($fn_name), ptx, Some(&input), &mut output, 1)
+                test_cuda_assert(stringify!($fn_name), ptx, Some(&input), &mut output, $grid, $block)
+            }
+        }
+
+        paste! {
+            #[test]
+            fn [<$fn_name _interp>]() -> Result<(), Box<dyn std::error::Error>> {
+                let ptx = include_str!(concat!(stringify!($fn_name), ".ptx"));
+                let input = $input;
+                let mut output = $output;
+                test_interp_assert(stringify!($fn_name), ptx, Some(&input), &mut output, $grid, $block)
             }
         }
 
@@ -70,13 +85,14 @@ macro_rules! test_ptx {
 }
 
 macro_rules! test_ptx_warp {
-    ($fn_name:ident, $expected:expr) => {
+    // For instructions the interpreter does not implement
+    (gpu_only $fn_name:ident, $expected:expr) => {
         paste! {
             #[test]
             fn [<$fn_name _cuda>]() -> Result<(), Box<dyn std::error::Error>> {
                 let ptx = include_str!(concat!(stringify!($fn_name), ".ptx"));
                 let mut expected = $expected;
-                test_cuda_assert::<u8, _>(stringify!($fn_name), ptx, None, &mut expected, 64)
+                test_cuda_assert::<u8, _>(stringify!($fn_name), ptx, None, &mut expected, [1, 1, 1], [64, 1, 1])
             }
 
             #[test]
@@ -100,7 +116,20 @@ macro_rules! test_ptx_warp {
                 test_hip_assert_output(CompilationMode::DoubleWave32OnWave64, stringify!($fn_name), ptx, &mut expected)
             }
         }
-    }
+    };
+
+    ($fn_name:ident, $expected:expr) => {
+        test_ptx_warp!(gpu_only $fn_name, $expected);
+
+        paste! {
+            #[test]
+            fn [<$fn_name _interp>]() -> Result<(), Box<dyn std::error::Error>> {
+                let ptx = include_str!(concat!(stringify!($fn_name), ".ptx"));
+                let mut expected = $expected;
+                test_interp_assert::<u8, _>(stringify!($fn_name), ptx, None, &mut expected, [1, 1, 1], [64, 1, 1])
+            }
+        }
+    };
 }
 
 test_ptx!(ld_st, [1u64], [1u64]);
@@ -367,6 +396,74 @@ test_ptx!(
 );
 test_ptx!(isspacep, [0xDEADu32], [1u32, 0]);
 test_ptx!(sad, [2147483648u32, 2, 13], [2147483659u32, 2147483663]);
+test_ptx!(
+    cvt_bf16_tf32,
+    [0x3FAAAAABu32, 0xC02DF854],
+    [
+        0x3FAA3FABu32,
+        0x3FABC02E,
+        0x3FAB0000,
+        0x3FAAA000,
+        0xC02E0000,
+        0xC02DE000
+    ]
+);
+test_ptx!(
+    lop3_lut,
+    [0x12345678u32, 0x9ABCDEF0, 0x0F0F0F0F],
+    [0x1A3C5E78u32, 0x87878787, 0x1F3F5F77, 0x60402000]
+);
+test_ptx!(
+    bmsk,
+    [8u32, 12, 28, 40],
+    [0xFFF00u32, 0xF0000000, 0, 0xF0000000]
+);
+test_ptx!(
+    szext,
+    [0xF0u32, 0x12345678, 0x87654321],
+    [0xFFFFFFF0u32, 0x8, 0x12345678, 0, 0x4321]
+);
+test_ptx!(
+    fns,
+    [0xF0F0F0F0u32, 0, -2i32 as u32],
+    [4u32, 6, 30, u32::MAX, 4, u32::MAX]
+);
+test_ptx!(
+    mad24,
+    [0xFF923456u32, 0x00789ABC, 0x11111111],
+    [
+        0xE72A2B28u32,
+        0x44E0E72A,
+        0x2B2A2B28,
+        0xCC462B2A,
+        0xF83B3C39,
+        0x55F1F83B,
+        0x3C3B3C39,
+        0xDD573C3B
+    ]
+);
+test_ptx!(
+    copysign_nan,
+    [0x3FC00000u32, 0x80000000, 0x7FC00000, 0xFFC00000],
+    [
+        0xBFC00000u32,
+        0x7FC00000,
+        0xBFC00000,
+        0x7FC00000,
+        0,
+        0xBFF80000
+    ]
+);
+test_ptx!(
+    testp_classes,
+    [0x1u32, 0x7F800000, 0x7FC00000, 0x3F800000, 0x1, 0x0],
+    [1u32, 0, 1, 0, 1, 0, 1, 1]
+);
+test_ptx!(
+    cvt_pack_sat,
+    [-70000i32, 300, 0x1234ABCD],
+    [0x12Cu32, 0x8000012C, 0xABCD00FF, 0xABCD807F, 0x34ABCD78, 0x234ABCDC]
+);
 
 test_ptx_warp!(
     shfl,
@@ -461,6 +558,233 @@ test_ptx_warp!(
         47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64
     ]
 );
+test_ptx_warp!(
+    gpu_only mma_m16n8k16,
+    [
+        16u32, 80, 144, 208, 16, 80, 144, 208, 16, 80, 144, 208, 16, 80, 144, 208, 16, 80, 144,
+        208, 16, 80, 144, 208, 16, 80, 144, 208, 16, 80, 144, 208, 16, 80, 144, 208, 16, 80, 144,
+        208, 16, 80, 144, 208, 16, 80, 144, 208, 16, 80, 144, 208, 16, 80, 144, 208, 16, 80, 144,
+        208, 16, 80, 144, 208
+    ]
+);
+test_ptx_warp!(
+    gpu_only wmma_m16n16k16,
+    [
+        0u32, 0, 0, 0, 0, 64, 128, 192, 0, 128, 256, 384, 0, 192, 384, 576, 0, 256, 512, 768, 0,
+        320, 640, 960, 0, 384, 768, 1152, 0, 448, 896, 1344, 0, 512, 1024, 1536, 0, 576, 1152,
+        1728, 0, 640, 1280, 1920, 0, 704, 1408, 2112, 0, 768, 1536, 2304, 0, 832, 1664, 2496, 0,
+        896, 1792, 2688, 0, 960, 1920, 2880
+    ]
+);
+test_ptx_warp!(
+    cp_async,
+    [
+        2u32, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+        26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48,
+        49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 1
+    ]
+);
+test_ptx_warp!(
+    cp_async_zfill,
+    [
+        0u32, 0, 0, 0, 5, 0, 0, 0, 9, 10, 0, 0, 13, 14, 15, 0, 0, 0, 0, 0, 21, 0, 0, 0, 25, 26, 0,
+        0, 29, 30, 31, 0, 0, 0, 0, 0, 37, 0, 0, 0, 41, 42, 0, 0, 45, 46, 47, 0, 0, 0, 0, 0, 53, 0,
+        0, 0, 57, 58, 0, 0, 61, 62, 63, 0
+    ]
+);
+test_ptx_warp!(
+    gpu_only mbarrier,
+    [
+        2u32, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+        26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48,
+        49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 1
+    ]
+);
+test_ptx_warp!(
+    gpu_only mbarrier_try_wait_parity,
+    [
+        3u32, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26,
+        27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49,
+        50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 1, 2
+    ]
+);
+test_ptx_warp!(
+    cvt_bf16,
+    [
+        0x0u32, 0x3eaa3eab, 0x3f2a3f2b, 0x3f803f80, 0x3faa3fab, 0x3fd53fd5, 0x40004000, 0x40154015,
+        0x402a402b, 0x40404040, 0x40554055, 0x406a406b, 0x40804080, 0x408a408b, 0x40954095,
+        0x40a040a0, 0x40aa40ab, 0x40b540b5, 0x40c040c0, 0x40ca40cb, 0x40d540d5, 0x40e040e0,
+        0x40ea40eb, 0x40f540f5, 0x41004100, 0x41054105, 0x410a410b, 0x41104110, 0x41154115,
+        0x411a411b, 0x41204120, 0x41254125, 0x412a412b, 0x41304130, 0x41354135, 0x413a413b,
+        0x41404140, 0x41454145, 0x414a414b, 0x41504150, 0x41554155, 0x415a415b, 0x41604160,
+        0x41654165, 0x416a416b, 0x41704170, 0x41754175, 0x417a417b, 0x41804180, 0x41824183,
+        0x41854185, 0x41884188, 0x418a418b, 0x418d418d, 0x41904190, 0x41924193, 0x41954195,
+        0x41984198, 0x419a419b, 0x419d419d, 0x41a041a0, 0x41a241a3, 0x41a541a5, 0x41a841a8
+    ]
+);
+test_ptx_warp!(
+    cvt_rna_tf32,
+    [
+        0x0u32, 0x3eaaa000, 0x3f2aa000, 0x3f800000, 0x3faaa000, 0x3fd56000, 0x40000000, 0x40156000,
+        0x402aa000, 0x40400000, 0x40556000, 0x406aa000, 0x40800000, 0x408aa000, 0x40956000,
+        0x40a00000, 0x40aaa000, 0x40b56000, 0x40c00000, 0x40caa000, 0x40d56000, 0x40e00000,
+        0x40eaa000, 0x40f56000, 0x41000000, 0x41056000, 0x410aa000, 0x41100000, 0x41156000,
+        0x411aa000, 0x41200000, 0x41256000, 0x412aa000, 0x41300000, 0x41356000, 0x413aa000,
+        0x41400000, 0x41456000, 0x414aa000, 0x41500000, 0x41556000, 0x415aa000, 0x41600000,
+        0x41656000, 0x416aa000, 0x41700000, 0x41756000, 0x417aa000, 0x41800000, 0x4182a000,
+        0x41856000, 0x41880000, 0x418aa000, 0x418d6000, 0x41900000, 0x4192a000, 0x41956000,
+        0x41980000, 0x419aa000, 0x419d6000, 0x41a00000, 0x41a2a000, 0x41a56000, 0x41a80000
+    ]
+);
+test_ptx_warp!(
+    fma_bf16x2,
+    [
+        0x3f40u32, 0x40003f40, 0x40c03f40, 0x41403f40, 0x41a03f40, 0x41f03f40, 0x42283f40,
+        0x42603f40, 0x42903f40, 0x42b43f40, 0x42dc3f40, 0x43043f40, 0x431c3f40, 0x43363f40,
+        0x43523f40, 0x43703f40, 0x43883f40, 0x43993f40, 0x43ab3f40, 0x43be3f40, 0x43d23f40,
+        0x43e73f40, 0x43fd3f40, 0x440a3f40, 0x44163f40, 0x44223f40, 0x44303f40, 0x443d3f40,
+        0x444b3f40, 0x445a3f40, 0x44683f40, 0x44783f40, 0x44843f40, 0x448c3f40, 0x44953f40,
+        0x449e3f40, 0x44a63f40, 0x44b03f40, 0x44b93f40, 0x44c33f40, 0x44cd3f40, 0x44d73f40,
+        0x44e23f40, 0x44ec3f40, 0x44f83f40, 0x45013f40, 0x45073f40, 0x450d3f40, 0x45133f40,
+        0x45193f40, 0x451f3f40, 0x45263f40, 0x452c3f40, 0x45333f40, 0x453a3f40, 0x45403f40,
+        0x45483f40, 0x454f3f40, 0x45563f40, 0x455d3f40, 0x45653f40, 0x456c3f40, 0x45743f40,
+        0x457c3f40
+    ]
+);
+test_ptx_warp!(
+    cvt_e4m3x2,
+    [
+        0x0u32, 0x4c003580, 0x50003980, 0x52003c00, 0x54003d80, 0x55003e80, 0x56004000, 0x57004080,
+        0x58004180, 0x58804200, 0x59004280, 0x59804380, 0x5a004400, 0x5a804480, 0x5b004480,
+        0x5b804500, 0x5c004580, 0x5c004580, 0x5c804600, 0x5d004680, 0x5d004680, 0x5d004700,
+        0x5d804780, 0x5e004780, 0x5e004800, 0x5e004800, 0x5e804880, 0x5f004880, 0x5f004880,
+        0x5f004900, 0x5f004900, 0x5f004900, 0x5f004980, 0x5f004980, 0x5f004980, 0x5f004a00,
+        0x5f004a00, 0x5f004a00, 0x5f004a80, 0x5f004a80, 0x5f004a80, 0x5f004b00, 0x5f004b00,
+        0x5f004b00, 0x5f004b80, 0x5f004b80, 0x5f004b80, 0x5f004c00, 0x5f004c00, 0x5f004c00,
+        0x5f004c00, 0x5f004c00, 0x5f004c80, 0x5f004c80, 0x5f004c80, 0x5f004c80, 0x5f004c80,
+        0x5f004d00, 0x5f004d00, 0x5f004d00, 0x5f004d00, 0x5f004d00, 0x5f004d00, 0x5f004d00
+    ]
+);
+test_ptx_warp!(
+    redux_sync,
+    [
+        2032112u32, 2032112, 2032112, 2032112, 2032112, 2032112, 2032112, 2032112, 2032112,
+        2032112, 2032112, 2032112, 2032112, 2032112, 2032112, 2032112, 2032112, 2032112, 2032112,
+        2032112, 2032112, 2032112, 2032112, 2032112, 2032112, 2032112, 2032112, 2032112, 2032112,
+        2032112, 2032112, 2032112, 4130288, 4130288, 4130288, 4130288, 4130288, 4130288, 4130288,
+        4130288, 4130288, 4130288, 4130288, 4130288, 4130288, 4130288, 4130288, 4130288, 4130288,
+        4130288, 4130288, 4130288, 4130288, 4130288, 4130288, 4130288, 4130288, 4130288, 4130288,
+        4130288, 4130288, 4130288, 4130288, 4130288
+    ]
+);
+test_ptx_warp!(
+    lop3,
+    [
+        0x0u32, 0x5b5b5b5b, 0xb6b6b6b6, 0xf0f0f1f, 0x6d6d6d6c, 0xc7c7c7ef, 0x1e1e1e3e, 0x7f7f7f7f,
+        0xdadadad8, 0x2d2d2d73, 0x8f8f8fde, 0xebebebe7, 0x3c3c3c7c, 0x9f9f9fff, 0xfefefefe,
+        0x4f4f4f7f, 0xb5b5b5b0, 0xfffffffb, 0x5a5a5ae6, 0xb7b7b7bf, 0x1f1f1fbc, 0x7d7d7dff,
+        0xd7d7d7ce, 0x1f1f1fcf, 0x787878f8, 0xdbdbdbe3, 0x3f3f3ffe, 0x9f9fa017, 0xfdfdfdfc,
+        0x3d3d3dff, 0x9e9e9efe, 0xffffffff, 0x6b6b6b60, 0xa5a5a6bb, 0xfffffff6, 0x7b7b7b7f,
+        0xb4b4b5cc, 0x2f2f302f, 0x6f6f6f7e, 0xe7e7e8df, 0x3e3e3f78, 0x79797973, 0xfafafbfe,
+        0x2f2f3087, 0xafafaf9c, 0xededeeff, 0x3e3e3f9e, 0xbfbfbfff, 0xf0f0f1f0, 0x7b7b7cbb,
+        0xb7b7b7c6, 0xff, 0x7e7e7ffc, 0xb5b5b5df, 0x3f3f402e, 0x7f7f810f, 0xfbfbfbf8, 0x3f3f4003,
+        0x7a7a7bfe, 0xfbfbfc17, 0x3d3d3dfc, 0xbfbfc11f, 0xfffffffe, 0x3f3f401f
+    ]
+);
+test_ptx_warp!(
+    mul24,
+    [
+        0x0u32, 0xc37a88, 0x186f757, 0x24a766c, 0x30df7c9, 0x3d17b6b, 0x4950154, 0x5588984,
+        0x61c13fa, 0x6dfa0b7, 0x7a32fba, 0x866c104, 0x92a5494, 0x9edea6b, 0xab18289, 0xb751ced,
+        0xc38b997, 0xcfc5888, 0xdbff9c0, 0xe839d3e, 0xf474303, 0x100aeb0e, 0x10ce9560, 0x119241f8,
+        0x1255f0d7, 0x1319a1fc, 0x13dd5568, 0x14a10b1b, 0x1564c314, 0x16287d53, 0x16ec39da,
+        0x17aff8a6, 0x1873b9b9, 0x19377d13, 0x19fb42b3, 0x1abf0a9a, 0x1b82d4c7, 0x1c46a13b,
+        0x1d0a6ff6, 0x1dce40f7, 0x1e92143e, 0x1f55e9cc, 0x2019c1a1, 0x20dd9bbc, 0x21a1781d,
+        0x226556c5, 0x232937b4, 0x23ed1ae9, 0x24b10065, 0x2574e828, 0x2638d230, 0x26fcbe80,
+        0x27c0ad16, 0x28849df2, 0x29489115, 0x2a0c867f, 0x2ad07e2f, 0x2b947826, 0x2c587463,
+        0x2d1c72e6, 0x2de073b1, 0x2ea476c1, 0x2f687c19, 0x302c83b7
+    ]
+);
+test_ptx_warp!(atom_add_packed, [0x40004500u32; 64]);
+test_ptx_warp!(red_add_packed, [0x3FC04040u32; 64]);
+test_ptx_warp!(red_add_v2_f32, [0x3FC00000u32; 128]);
+test_ptx_warp!(
+    cluster,
+    [
+        0x20301u32, 0x20000, 0x20003, 0x20002, 0x20005, 0x20004, 0x20007, 0x20006, 0x20009,
+        0x20008, 0x2000b, 0x2000a, 0x2000d, 0x2000c, 0x2000f, 0x2000e, 0x20011, 0x20010, 0x20013,
+        0x20012, 0x20015, 0x20014, 0x20017, 0x20016, 0x20019, 0x20018, 0x2001b, 0x2001a, 0x2001d,
+        0x2001c, 0x2001f, 0x2001e, 0x20321, 0x20020, 0x20023, 0x20022, 0x20025, 0x20024, 0x20027,
+        0x20026, 0x20029, 0x20028, 0x2002b, 0x2002a, 0x2002d, 0x2002c, 0x2002f, 0x2002e, 0x20031,
+        0x20030, 0x20033, 0x20032, 0x20035, 0x20034, 0x20037, 0x20036, 0x20039, 0x20038, 0x2003b,
+        0x2003a, 0x2003d, 0x2003c, 0x2003f, 0x2003e
+    ]
+);
+test_ptx_warp!(tanh_approx, [1u32; 64]);
+test_ptx_warp!(tanh_approx_f16, [1u32; 64]);
+test_ptx_warp!(ex2_approx_f16, [1u32; 64]);
+test_ptx_warp!(
+    copysign,
+    [
+        7u32, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7,
+        7, 7, 7, 0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9,
+        0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9,
+        0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9,
+        0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9,
+        0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9, 0xfffffff9
+    ]
+);
+test_ptx_warp!(
+    testp,
+    [
+        0x505u32, 0x2525, 0x1515, 0x606, 0x606, 0x808, 0x808, 0x1515, 0x505, 0x2525, 0x1515, 0x606,
+        0x606, 0x808, 0x808, 0x1515, 0x505, 0x2525, 0x1515, 0x606, 0x606, 0x808, 0x808, 0x1515,
+        0x505, 0x2525, 0x1515, 0x606, 0x606, 0x808, 0x808, 0x1515, 0x505, 0x2525, 0x1515, 0x606,
+        0x606, 0x808, 0x808, 0x1515, 0x505, 0x2525, 0x1515, 0x606, 0x606, 0x808, 0x808, 0x1515,
+        0x505, 0x2525, 0x1515, 0x606, 0x606, 0x808, 0x808, 0x1515, 0x505, 0x2525, 0x1515, 0x606,
+        0x606, 0x808, 0x808, 0x1515
+    ]
+);
+test_ptx_warp!(
+    cvt_pack,
+    [
+        0xffffu32, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xf870, 0xeb8c, 0xdea8,
+        0xd1c4, 0xc4e0, 0xb7fc, 0xab18, 0x9e34, 0x9150, 0x846c, 0x7788, 0x6aa4, 0x5dc0, 0x50dc,
+        0x43f8, 0x3714, 0x2a30, 0x1d4c, 0x1068, 0x384, 0x0, 0x0, 0x0, 0x0, 0x0, 0x8340000,
+        0x12c00000, 0x1d4c0000, 0x27d80000, 0x32640000, 0x3cf00000, 0x477c0000, 0x52080000,
+        0x5c940000, 0x67200000, 0x71ac0000, 0x7c380000, 0x86c40000, 0x91500000, 0x9bdc0000,
+        0xa6680000, 0xb0f40000, 0xbb800000, 0xc60c0000, 0xd0980000, 0xdb240000, 0xe5b00000,
+        0xf03c0000, 0xfac80000, 0xffff0000, 0xffff0000, 0xffff0000, 0xffff0000, 0xffff0000,
+        0xffff0000, 0xffff0000, 0x80007fff, 0x80007fff, 0x80007fff, 0x80007fff, 0x80007fff,
+        0x80007fff, 0x80007fff, 0x80007fff, 0x80007fff, 0x80007fff, 0x80007fff, 0x80007fff,
+        0x80007fff, 0x80007fff, 0x80007fff, 0x80007fff, 0x80007fff, 0x80007fff, 0x80007788,
+        0x80006aa4, 0x80005dc0, 0x89a450dc, 0x943043f8, 0x9ebc3714, 0xa9482a30, 0xb3d41d4c,
+        0xbe601068, 0xc8ec0384, 0xd378f6a0, 0xde04e9bc, 0xe890dcd8, 0xf31ccff4, 0xfda8c310,
+        0x834b62c, 0x12c0a948, 0x1d4c9c64, 0x27d88f80, 0x3264829c, 0x3cf08000, 0x477c8000,
+        0x52088000, 0x5c948000, 0x67208000, 0x71ac8000, 0x7c388000, 0x7fff8000, 0x7fff8000,
+        0x7fff8000, 0x7fff8000, 0x7fff8000, 0x7fff8000, 0x7fff8000, 0x7fff8000, 0x7fff8000,
+        0x7fff8000, 0x7fff8000, 0x7fff8000, 0x7fff8000, 0x7fff8000, 0x7fff8000, 0x7fff8000,
+        0x7fff8000, 0x7fff8000, 0x7fff8000, 0xd30f807f, 0xd30f807f, 0xd30f807f, 0xd30f807f,
+        0xd30f807f, 0xd30f807f, 0xd30e807f, 0xd30d807f, 0xd30c807f, 0xd30b807f, 0xd30a807f,
+        0xd309807f, 0xd308807f, 0xd307807f, 0xd306807f, 0xd305807f, 0xd304807c, 0xd3038071,
+        0xd2028066, 0xd101895b, 0xd0009250, 0xd0009b45, 0xd000a43a, 0xd000ad2f, 0xd000b624,
+        0xd000bf19, 0xd000c80e, 0xd000d103, 0xd000daf8, 0xd000e3ed, 0xd000ece2, 0xd000f5d7,
+        0xd000fecc, 0xd41007c1, 0xd82010b6, 0xdc3019ab, 0xdc4022a0, 0xdc502b95, 0xdc60348a,
+        0xdc703d80, 0xdc804680, 0xdc904f80, 0xdca05880, 0xdcb06180, 0xdcc06a80, 0xdcd07380,
+        0xdce07c80, 0xdcf07f80, 0xdcf07f80, 0xdcf07f80, 0xdcf07f80, 0xdcf07f80, 0xdcf07f80,
+        0xdcf07f80, 0xdcf07f80, 0xdcf07f80, 0xdcf07f80, 0xdcf07f80, 0xdcf07f80, 0xdcf07f80,
+        0xdcf07f80, 0xdcf07f80, 0xdcf07f80, 0xdcf07f80, 0xd98700ff, 0xd98700ff, 0xd98700ff,
+        0xd98700ff, 0xd98700ff, 0xd98700f5, 0xd98700ea, 0xd98700df, 0xd98700d4, 0xd98700c9,
+        0xd98700be, 0xd98700b3, 0xd98700a8, 0xd987009d, 0xd9860092, 0xd9850087, 0xd984007c,
+        0xd9830071, 0xd9820066, 0xd981005b, 0xd8800050, 0xdb8f0045, 0xda8e003a, 0xda8d002f,
+        0xda8c0024, 0xda9b0019, 0xdaaa000e, 0xdab90003, 0xdac80000, 0xdad80000, 0xdae80000,
+        0xdef80000, 0xd2080000, 0xd6180700, 0xd6281000, 0xd6381900, 0xd6482200, 0xd6582b00,
+        0xd6683400, 0xd6783d00, 0xd6784600, 0xd6784f00, 0xd6785800, 0xd6786100, 0xd6786a00,
+        0xd6787300, 0xd6787c00, 0xd6788500, 0xd6788e00, 0xd6789700, 0xd678a000, 0xd678a900,
+        0xd678b200, 0xd678bb00, 0xd678c400, 0xd678cd00, 0xd678d600, 0xd678df00, 0xd678e800,
+        0xd678f100, 0xd678fa00, 0xd678ff00, 0xd678ff00, 0xd678ff00
+    ]
+);
 
 test_ptx!(barrier);
 test_ptx!(assertfail);
@@ -495,6 +819,8 @@ fn test_hip_assert<
     ptx_text: &'a str,
     input: &[Input],
     output: &mut [Output],
+    grid: [u32; 3],
+    block: [u32; 3],
 ) -> Result<(), Box<dyn error::Error + 'a>> {
     let mut errors = Vec::new();
     let ast = ptx::ModuleParser::new().parse(&mut errors, ptx_text)?;
@@ -507,13 +833,60 @@ fn test_hip_assert<
         zluda_module,
         Some(input),
         output,
-        [1, 1, 1],
+        grid,
+        block,
     )
     .map_err(|err| DisplayError { err })?;
     assert_eq!(result.as_slice(), output);
     Ok(())
 }
 
+fn test_interp_assert<
+    'a,
+    Input: From<u8> + Debug + Copy + PartialEq,
+    Output: From<u8> + Debug + Copy + PartialEq + Default,
+>(
+    name: &str,
+    ptx_text: &'a str,
+    input: Option<&[Input]>,
+    output: &mut [Output],
+    grid: [u32; 3],
+    block: [u32; 3],
+) -> Result<(), Box<dyn error::Error + 'a>> {
+    let mut errors = Vec::new();
+    let ast = ptx::ModuleParser::new().parse(&mut errors, ptx_text)?;
+    assert!(errors.len() == 0);
+    let mut interpreter = Interpreter::new(vec![ast])?;
+    let output_size = mem::size_of_val(output);
+    let out_b = interpreter.alloc(output_size);
+    let out_arg = out_b.to_ne_bytes();
+    let inp_arg;
+    // Kernels without an input take the output buffer as their only argument
+    let args: &[&[u8]] = if let Some(input) = input {
+        let input_bytes = unsafe {
+            std::slice::from_raw_parts(input.as_ptr() as *const u8, mem::size_of_val(input))
+        };
+        let inp_b = interpreter.alloc(input_bytes.len());
+        interpreter.write(inp_b, input_bytes)?;
+        inp_arg = inp_b.to_ne_bytes();
+        &[&inp_arg, &out_arg]
+    } else {
+        &[&out_arg]
+    };
+    interpreter.launch(name, grid, block, 1024, args)?;
+    let output_bytes = interpreter.read(out_b, output_size)?;
+    let mut result = vec![Output::default(); output.len()];
+    unsafe {
+        ptr::copy_nonoverlapping(
+            output_bytes.as_ptr(),
+            result.as_mut_ptr() as *mut u8,
+            output_size,
+        )
+    };
+    assert_eq!(result.as_slice(), output);
+    Ok(())
+}
+
 fn test_hip_assert_output<'a>(
     compilation_mode: CompilationMode,
     name: &str,
@@ -536,6 +909,7 @@ fn test_hip_assert_output<'a>(
         zluda_module,
         None,
         expected,
+        [1, 1, 1],
         [64, 1, z_dimension],
     )
     .map_err(|err| DisplayError { err })?;
@@ -552,10 +926,11 @@ fn test_cuda_assert<
     ptx_text: &'a str,
     input: Option<&[Input]>,
     output: &mut [Output],
-    block_size_x: u32,
+    grid: [u32; 3],
+    block: [u32; 3],
 ) -> Result<(), Box<dyn error::Error + 'a>> {
     let name = CString::new(name)?;
-    let result = unsafe { run_cuda(name.as_c_str(), ptx_text, input, output, block_size_x) };
+    let result = unsafe { run_cuda(name.as_c_str(), ptx_text, input, output, grid, block) };
     assert_eq!(result.as_slice(), output);
     Ok(())
 }
@@ -577,7 +952,8 @@ unsafe fn run_cuda<Input: From<u8> + Copy + Debug, Output: From<u8> + Copy + Deb
     ptx_module: &str,
     input: Option<&[Input]>,
     output: &mut [Output],
-    block_size_x: u32,
+    grid: [u32; 3],
+    block: [u32; 3],
 ) -> Vec<Output> {
     use cuda_types::*;
     let cuda = CudaTestLibrary::new();
@@ -608,12 +984,12 @@ unsafe fn run_cuda<Input: From<u8> + Copy + Debug, Output: From<u8> + Copy + Deb
         cuda.cuMemsetD8_v2(out_b, 0, output.len() * mem::size_of::<Output>());
         cuda.cuLaunchKernel(
             kernel,
-            1,
-            1,
-            1,
-            block_size_x,
-            1,
-            1,
+            grid[0],
+            grid[1],
+            grid[2],
+            block[0],
+            block[1],
+            block[2],
             1024,
             0 as _,
             args.as_mut_ptr() as _,
@@ -651,6 +1027,7 @@ fn run_hip<Input: From<u8> + Copy + Debug, Output: From<u8> + Copy + Debug + Def
     module: translate::Module,
     input: Option<&[Input]>,
     output: &mut [Output],
+    grid: [u32; 3],
     block_size: [u32; 3],
 ) -> Result<Vec<Output>, hipError_t> {
     use hip_runtime_sys::*;
@@ -687,7 +1064,7 @@ fn run_hip<Input: From<u8> + Copy + Debug, Output: From<u8> + Copy + Debug + Def
             [&out_b, &out_b]
         };
         hip_call! { hipMemsetAsync(out_b, 0, output.len() * mem::size_of::<Output>(), stream) };
-        hip_call! { hipModuleLaunchKernel(kernel, 1,1,1, block_size[0],block_size[1],block_size[2], 1024, stream, args.as_mut_ptr().cast(), ptr::null_mut()) };
+        hip_call! { hipModuleLaunchKernel(kernel, grid[0],grid[1],grid[2], block_size[0],block_size[1],block_size[2], 1024, stream, args.as_mut_ptr().cast(), ptr::null_mut()) };
         hip_call! { hipMemcpyAsync(result.as_mut_ptr() as _, out_b, output.len() * mem::size_of::<Output>(), hipMemcpyKind::hipMemcpyDeviceToHost, stream) };
         hip_call! { hipStreamSynchronize(stream) };
         hip_call! { hipFree(inp_b) };
@@ -0,0 +1,85 @@
+target datalayout = "e-p:64:64-p1:64:64-p2:32:32-p3:32:32-p4:64:64-p5:32:32-p6:32:32-i64:64-v16:16-v24:32-v32:32-v48:64-v96:128-v192:256-v256:256-v512:512-v1024:1024-v2048:2048-n32:64-S32-A5-G1-ni:7"
+target triple = "amdgcn-amd-amdhsa"
+
+declare i32 @__zluda_ptx_impl__szext_clamp_s32(i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__szext_clamp_u32(i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__szext_wrap_s32(i32, i32) #0
+
+declare i32 @__zluda_ptx_impl__szext_wrap_u32(i32, i32) #0
+
+define protected amdgpu_kernel void @szext(ptr addrspace(4) byref(i64) %"56", ptr addrspace(4) byref(i64) %"57") #1 {
+  %"10" = alloca i1, align 1, addrspace(5)
+  %"4" = alloca i64, align 8, addrspace(5)
+  %"5" = alloca i64, align 8, addrspace(5)
+  %"6" = alloca i32, align 4, addrspace(5)
+  %"7" = alloca i32, align 4, addrspace(5)
+  %"8" = alloca i32, align 4, addrspace(5)
+  %"9" = alloca i32, align 4, addrspace(5)
+  br label %1
+
+1:                                                ; preds = %0
+  store i1 false, ptr addrspace(5) %"10", align 1
+  %"11" = load i64, ptr addrspace(4) %"56", align 8
+  store i64 %"11", ptr addrspace(5) %"4", align 8
+  %"12" = load i64, ptr addrspace(4) %"57", align 8
+  store i64 %"12", ptr addrspace(5) %"5", align 8
+  %"14" = load i64, ptr addrspace(5) %"4", align 8
+  %"58" = inttoptr i64 %"14" to ptr
+  %"13" = load i32, ptr %"58", align 4
+  store i32 %"13", ptr addrspace(5) %"6", align 4
+  %"16" = load i64, ptr addrspace(5) %"4", align 8
+  %"59" = inttoptr i64 %"16" to ptr
+  %"91" = getelementptr inbounds i8, ptr %"59", i64 4
+  %"15" = load i32, ptr %"91", align 4
+  store i32 %"15", ptr addrspace(5) %"7", align 4
+  %"18" = load i64, ptr addrspace(5) %"4", align 8
+  %"60" = inttoptr i64 %"18" to ptr
+  %"93" = getelementptr inbounds i8, ptr %"60", i64 8
+  %"17" = load i32, ptr %"93", align 4
+  store i32 %"17", ptr addrspace(5) %"8", align 4
+  %"20" = load i32, ptr addrspace(5) %"6", align 4
+  %"61" = call i32 @__zluda_ptx_impl__szext_clamp_s32(i32 %"20", i32 8)
+  store i32 %"61", ptr addrspace(5) %"9", align 4
+  %"21" = load i64, ptr addrspace(5) %"5", align 8
+  %"22" = load i32, ptr addrspace(5) %"9", align 4
+  %"63" = inttoptr i64 %"21" to ptr
+  store i32 %"22", ptr %"63", align 4
+  %"24" = load i32, ptr addrspace(5) %"7", align 4
+  %"23" = call i32 @__zluda_ptx_impl__szext_wrap_u32(i32 %"24", i32 36)
+  store i32 %"23", ptr addrspace(5) %"9", align 4
+  %"25" = load i64, ptr addrspace(5) %"5", align 8
+  %"26" = load i32, ptr addrspace(5) %"9", align 4
+  %"65" = inttoptr i64 %"25" to ptr
+  %"95" = getelementptr inbounds i8, ptr %"65", i64 4
+  store i32 %"26", ptr %"95", align 4
+  %"28" = load i32, ptr addrspace(5) %"7", align 4
+  %"66" = call i32 @__zluda_ptx_impl__szext_clamp_s32(i32 %"28", i32 40)
+  store i32 %"66", ptr addrspace(5) %"9", align 4
+  %"29" = load i64, ptr addrspace(5) %"5", align 8
+  %"30" = load i32, ptr addrspace(5) %"9", align 4
+  %"68" = inttoptr i64 %"29" to ptr
+  %"97" = getelementptr inbounds i8, ptr %"68", i64 8
+  store i32 %"30", ptr %"97", align 4
+  %"32" = load i32, ptr addrspace(5) %"7", align 4
+  %"69" = call i32 @__zluda_ptx_impl__szext_wrap_s32(i32 %"32", i32 32)
+  store i32 %"69", ptr addrspace(5) %"9", align 4
+  %"33" = load i64, ptr addrspace(5) %"5", align 8
+  %"34" = load i32, ptr addrspace(5) %"9", align 4
+  %"71" = inttoptr i64 %"33" to ptr
+  %"99" = getelementptr inbounds i8, ptr %"71", i64 12
+  store i32 %"34", ptr %"99", align 4
+  %"36" = load i32, ptr addrspace(5) %"8", align 4
+  %"35" = call i32 @__zluda_ptx_impl__szext_clamp_u32(i32 %"36", i32 16)
+  store i32 %"35", ptr addrspace(5) %"9", align 4
+  %"37" = load i64, ptr addrspace(5) %"5", align 8
+  %"38" = load i32, ptr addrspace(5) %"9", align 4
+  %"73" = inttoptr i64 %"37" to ptr
+  %"101" = getelementptr inbounds i8, ptr %"73", i64 16
+  store i32 %"38", ptr %"101", align 4
+  ret void
+}
+
+attributes #0 = { "amdgpu-unsafe-fp-atomics"="true" "no-trapping-math"="true" "uniform-work-group-size"="true" }
+attributes #1 = { "amdgpu-unsafe-fp-atomics"="true" "denormal-fp-math"="ieee,ieee" "denormal-fp-math-f32"="ieee,ieee" "no-trapping-math"="true" "uniform-work-group-size"="true" }
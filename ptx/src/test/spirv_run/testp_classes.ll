@@ -0,0 +1,144 @@
+target datalayout = "e-p:64:64-p1:64:64-p2:32:32-p3:32:32-p4:64:64-p5:32:32-p6:32:32-i64:64-v16:16-v24:32-v32:32-v48:64-v96:128-v192:256-v256:256-v512:512-v1024:1024-v2048:2048-n32:64-S32-A5-G1-ni:7"
+target triple = "amdgcn-amd-amdhsa"
+
+define protected amdgpu_kernel void @testp_classes(ptr addrspace(4) byref(i64) %"112", ptr addrspace(4) byref(i64) %"113") #0 {
+  %"13" = alloca i1, align 1, addrspace(5)
+  %"4" = alloca i64, align 8, addrspace(5)
+  %"5" = alloca i64, align 8, addrspace(5)
+  %"6" = alloca float, align 4, addrspace(5)
+  %"7" = alloca float, align 4, addrspace(5)
+  %"8" = alloca float, align 4, addrspace(5)
+  %"9" = alloca float, align 4, addrspace(5)
+  %"10" = alloca double, align 8, addrspace(5)
+  %"11" = alloca i1, align 1, addrspace(5)
+  %"12" = alloca i32, align 4, addrspace(5)
+  br label %1
+
+1:                                                ; preds = %0
+  store i1 false, ptr addrspace(5) %"13", align 1
+  %"14" = load i64, ptr addrspace(4) %"112", align 8
+  store i64 %"14", ptr addrspace(5) %"4", align 8
+  %"15" = load i64, ptr addrspace(4) %"113", align 8
+  store i64 %"15", ptr addrspace(5) %"5", align 8
+  %"17" = load i64, ptr addrspace(5) %"4", align 8
+  %"114" = inttoptr i64 %"17" to ptr
+  %"16" = load float, ptr %"114", align 4
+  store float %"16", ptr addrspace(5) %"6", align 4
+  %"19" = load i64, ptr addrspace(5) %"4", align 8
+  %"115" = inttoptr i64 %"19" to ptr
+  %"128" = getelementptr inbounds i8, ptr %"115", i64 4
+  %"18" = load float, ptr %"128", align 4
+  store float %"18", ptr addrspace(5) %"7", align 4
+  %"21" = load i64, ptr addrspace(5) %"4", align 8
+  %"116" = inttoptr i64 %"21" to ptr
+  %"130" = getelementptr inbounds i8, ptr %"116", i64 8
+  %"20" = load float, ptr %"130", align 4
+  store float %"20", ptr addrspace(5) %"8", align 4
+  %"23" = load i64, ptr addrspace(5) %"4", align 8
+  %"117" = inttoptr i64 %"23" to ptr
+  %"132" = getelementptr inbounds i8, ptr %"117", i64 12
+  %"22" = load float, ptr %"132", align 4
+  store float %"22", ptr addrspace(5) %"9", align 4
+  %"25" = load i64, ptr addrspace(5) %"4", align 8
+  %"118" = inttoptr i64 %"25" to ptr
+  %"134" = getelementptr inbounds i8, ptr %"118", i64 16
+  %"24" = load double, ptr %"134", align 8
+  store double %"24", ptr addrspace(5) %"10", align 8
+  %"27" = load float, ptr addrspace(5) %"6", align 4
+  %"26" = call i1 @llvm.amdgcn.class.f32(float %"27", i32 144)
+  store i1 %"26", ptr addrspace(5) %"11", align 1
+  %"29" = load i1, ptr addrspace(5) %"11", align 1
+  %"28" = select i1 %"29", i32 1, i32 0
+  store i32 %"28", ptr addrspace(5) %"12", align 4
+  %"30" = load i64, ptr addrspace(5) %"5", align 8
+  %"31" = load i32, ptr addrspace(5) %"12", align 4
+  %"119" = inttoptr i64 %"30" to ptr
+  store i32 %"31", ptr %"119", align 4
+  %"33" = load float, ptr addrspace(5) %"6", align 4
+  %"32" = call i1 @llvm.amdgcn.class.f32(float %"33", i32 264)
+  store i1 %"32", ptr addrspace(5) %"11", align 1
+  %"35" = load i1, ptr addrspace(5) %"11", align 1
+  %"34" = select i1 %"35", i32 1, i32 0
+  store i32 %"34", ptr addrspace(5) %"12", align 4
+  %"36" = load i64, ptr addrspace(5) %"5", align 8
+  %"37" = load i32, ptr addrspace(5) %"12", align 4
+  %"120" = inttoptr i64 %"36" to ptr
+  %"136" = getelementptr inbounds i8, ptr %"120", i64 4
+  store i32 %"37", ptr %"136", align 4
+  %"39" = load float, ptr addrspace(5) %"7", align 4
+  %"38" = call i1 @llvm.amdgcn.class.f32(float %"39", i32 516)
+  store i1 %"38", ptr addrspace(5) %"11", align 1
+  %"41" = load i1, ptr addrspace(5) %"11", align 1
+  %"40" = select i1 %"41", i32 1, i32 0
+  store i32 %"40", ptr addrspace(5) %"12", align 4
+  %"42" = load i64, ptr addrspace(5) %"5", align 8
+  %"43" = load i32, ptr addrspace(5) %"12", align 4
+  %"121" = inttoptr i64 %"42" to ptr
+  %"138" = getelementptr inbounds i8, ptr %"121", i64 8
+  store i32 %"43", ptr %"138", align 4
+  %"45" = load float, ptr addrspace(5) %"7", align 4
+  %"44" = call i1 @llvm.amdgcn.class.f32(float %"45", i32 504)
+  store i1 %"44", ptr addrspace(5) %"11", align 1
+  %"47" = load i1, ptr addrspace(5) %"11", align 1
+  %"46" = select i1 %"47", i32 1, i32 0
+  store i32 %"46", ptr addrspace(5) %"12", align 4
+  %"48" = load i64, ptr addrspace(5) %"5", align 8
+  %"49" = load i32, ptr addrspace(5) %"12", align 4
+  %"122" = inttoptr i64 %"48" to ptr
+  %"140" = getelementptr inbounds i8, ptr %"122", i64 12
+  store i32 %"49", ptr %"140", align 4
+  %"51" = load float, ptr addrspace(5) %"8", align 4
+  %"50" = call i1 @llvm.amdgcn.class.f32(float %"51", i32 3)
+  store i1 %"50", ptr addrspace(5) %"11", align 1
+  %"53" = load i1, ptr addrspace(5) %"11", align 1
+  %"52" = select i1 %"53", i32 1, i32 0
+  store i32 %"52", ptr addrspace(5) %"12", align 4
+  %"54" = load i64, ptr addrspace(5) %"5", align 8
+  %"55" = load i32, ptr addrspace(5) %"12", align 4
+  %"123" = inttoptr i64 %"54" to ptr
+  %"142" = getelementptr inbounds i8, ptr %"123", i64 16
+  store i32 %"55", ptr %"142", align 4
+  %"57" = load float, ptr addrspace(5) %"8", align 4
+  %"56" = call i1 @llvm.amdgcn.class.f32(float %"57", i32 1020)
+  store i1 %"56", ptr addrspace(5) %"11", align 1
+  %"59" = load i1, ptr addrspace(5) %"11", align 1
+  %"58" = select i1 %"59", i32 1, i32 0
+  store i32 %"58", ptr addrspace(5) %"12", align 4
+  %"60" = load i64, ptr addrspace(5) %"5", align 8
+  %"61" = load i32, ptr addrspace(5) %"12", align 4
+  %"124" = inttoptr i64 %"60" to ptr
+  %"144" = getelementptr inbounds i8, ptr %"124", i64 20
+  store i32 %"61", ptr %"144", align 4
+  %"63" = load float, ptr addrspace(5) %"9", align 4
+  %"62" = call i1 @llvm.amdgcn.class.f32(float %"63", i32 264)
+  store i1 %"62", ptr addrspace(5) %"11", align 1
+  %"65" = load i1, ptr addrspace(5) %"11", align 1
+  %"64" = select i1 %"65", i32 1, i32 0
+  store i32 %"64", ptr addrspace(5) %"12", align 4
+  %"66" = load i64, ptr addrspace(5) %"5", align 8
+  %"67" = load i32, ptr addrspace(5) %"12", align 4
+  %"125" = inttoptr i64 %"66" to ptr
+  %"146" = getelementptr inbounds i8, ptr %"125", i64 24
+  store i32 %"67", ptr %"146", align 4
+  %"69" = load double, ptr addrspace(5) %"10", align 8
+  %"68" = call i1 @llvm.amdgcn.class.f64(double %"69", i32 144)
+  store i1 %"68", ptr addrspace(5) %"11", align 1
+  %"71" = load i1, ptr addrspace(5) %"11", align 1
+  %"70" = select i1 %"71", i32 1, i32 0
+  store i32 %"70", ptr addrspace(5) %"12", align 4
+  %"72" = load i64, ptr addrspace(5) %"5", align 8
+  %"73" = load i32, ptr addrspace(5) %"12", align 4
+  %"126" = inttoptr i64 %"72" to ptr
+  %"148" = getelementptr inbounds i8, ptr %"126", i64 28
+  store i32 %"73", ptr %"148", align 4
+  ret void
+}
+
+; Function Attrs: nounwind readnone speculatable willreturn
+declare i1 @llvm.amdgcn.class.f32(float, i32) #1
+
+; Function Attrs: nounwind readnone speculatable willreturn
+declare i1 @llvm.amdgcn.class.f64(double, i32) #1
+
+attributes #0 = { "amdgpu-unsafe-fp-atomics"="true" "denormal-fp-math"="ieee,ieee" "denormal-fp-math-f32"="ieee,ieee" "no-trapping-math"="true" "uniform-work-group-size"="true" }
+attributes #1 = { nounwind readnone speculatable willreturn }
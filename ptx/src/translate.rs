@@ -16,7 +16,7 @@ use zluda_llvm::bit_writer::*;
 use zluda_llvm::core::LLVMPrintModuleToString;
 
 static ZLUDA_PTX_IMPL_AMD: &'static [u8] = include_bytes!("../lib/zluda_ptx_impl.bc");
-const ZLUDA_PTX_PREFIX: &'static str = "__zluda_ptx_impl__";
+pub(crate) const ZLUDA_PTX_PREFIX: &'static str = "__zluda_ptx_impl__";
 
 macro_rules! derive_error {
     (enum $type_:ident {
@@ -44,12 +44,6 @@ macro_rules! derive_error {
                 }
             )+
         }
-
-        impl std::fmt::Display for $type_ {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-                write!(f, "{:?}", self)
-            }
-        }
     }
 }
 
@@ -62,12 +56,61 @@ derive_error! {
         Unreachable,
         Todo,
         UnexpectedPattern,
-        SymbolRedefinition
+        SymbolRedefinition,
+        Spanned(Box<(StatementSpan, TranslateError)>)
+    }
+}
+
+// Location of the statement that could not be translated: index of its module
+// in the list passed to translation and byte offsets into the module text
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StatementSpan {
+    pub module: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl TranslateError {
+    // Errors keep the span of the innermost statement they were reported for
+    pub(crate) fn with_span(self, span: Option<StatementSpan>) -> Self {
+        match (self, span) {
+            (error @ TranslateError::Spanned(..), _) | (error, None) => error,
+            (error, Some(span)) => TranslateError::Spanned(Box::new((span, error))),
+        }
+    }
+
+    pub fn span(&self) -> Option<StatementSpan> {
+        match self {
+            TranslateError::Spanned(spanned) => Some(spanned.0),
+            _ => None,
+        }
     }
 }
 
 impl std::error::Error for TranslateError {}
 
+impl std::fmt::Display for TranslateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            TranslateError::UnknownSymbol => write!(f, "use of an undeclared identifier"),
+            TranslateError::UntypedSymbol => {
+                write!(f, "identifier is used before its type is known")
+            }
+            TranslateError::MismatchedType => {
+                write!(f, "operand type does not match the instruction type")
+            }
+            TranslateError::LLVM(message) => write!(f, "LLVM error: {}", message),
+            TranslateError::Unreachable => write!(f, "internal compiler error"),
+            TranslateError::Todo => write!(f, "unsupported PTX feature"),
+            TranslateError::UnexpectedPattern => write!(f, "unsupported instruction pattern"),
+            TranslateError::SymbolRedefinition => {
+                write!(f, "identifier is declared more than once")
+            }
+            TranslateError::Spanned(spanned) => write!(f, "{}", spanned.1),
+        }
+    }
+}
+
 pub struct Module<'input> {
     pub(crate) llvm_module: llvm::Module,
     pub(crate) _llvm_context: llvm::Context,
@@ -183,6 +226,8 @@ pub(crate) struct TranslationModule<'input, P: ast::ArgParams> {
     pub(crate) id_defs: IdNameMapBuilder<'input>,
     pub(crate) ptx_impl_imports: BTreeMap<String, Rc<RefCell<ast::MethodDeclaration<'input, Id>>>>,
     pub(crate) directives: Vec<TranslationDirective<'input, P>>,
+    // Indexed by ast::SourceLocation::file after collect_source_files(...)
+    pub(crate) source_files: Vec<&'input str>,
 }
 
 impl<'input, P: ast::ArgParams> TranslationModule<'input, P> {
@@ -196,6 +241,7 @@ impl<'input, P: ast::ArgParams> TranslationModule<'input, P> {
             id_defs,
             ptx_impl_imports,
             directives,
+            source_files: Vec::new(),
         }
     }
 }
@@ -976,6 +1022,7 @@ fn link_and_normalize_modules<'input>(
             id_defs,
             ptx_impl_imports,
             directives,
+            source_files: module.source_files,
         },
         functions,
     ))
@@ -1054,7 +1101,7 @@ fn normalize_method<'a, 'b, 'input>(
         method
             .body
             .map(|body| {
-                let body = normalize_identifiers2(&mut fn_scope, body)?;
+                let body = normalize_identifiers2(&mut fn_scope, module - 1, body)?;
                 normalize_predicates2(&mut fn_scope, body)
             })
             .transpose()?
@@ -1096,55 +1143,80 @@ fn normalize_method_params<'a, 'b, 'input>(
 
 fn normalize_identifiers2<'a, 'b, 'input>(
     scope: &mut StringIdResolverScope<'a, 'b, 'input>,
+    module: usize,
     func: Vec<ast::Statement<ast::ParsedArgParams<'input>>>,
 ) -> Result<Vec<NormalizedStatement>, TranslateError> {
     gather_labels_in_scope(scope, &func)?;
     let mut result = Vec::with_capacity(func.len());
+    let mut span = None;
     for statement in func {
-        match statement {
-            ast::Statement::Block(block) => {
-                let mut scope = scope.start_scope();
-                result.extend(normalize_identifiers2(&mut scope, block)?);
-            }
-            ast::Statement::Label(name) => {
-                result.push(Statement::Label(scope.get_id_in_function_scopes(name)?))
-            }
-            ast::Statement::Instruction(p, i) => result.push(Statement::Instruction((
-                p.map(|p| p.map_variable(&mut |id| scope.get_id_in_module_scopes(id)))
-                    .transpose()?,
-                i.map_variable(&mut |id| scope.get_id_in_module_scopes(id))?,
-            ))),
-            ast::Statement::Variable(vars) => {
-                expand_multivariable2(
-                    scope,
-                    vars.into_iter(),
-                    |scope, align, type_, space, name, initializer| {
-                        result.push(Statement::Variable(scope.register_variable(
-                            name,
-                            type_.clone(),
-                            space,
-                            align,
-                            initializer,
-                        )?));
-                        Ok(())
-                    },
-                )?;
-            }
-            ast::Statement::Callprototype(proto) => {
-                let name = scope.add_untyped_checked(proto.name)?;
-                scope.0.module.globals.function_prototypes.insert(
-                    name,
-                    Callprototype {
-                        return_arguments: proto.return_arguments,
-                        input_arguments: proto.input_arguments,
-                    },
-                );
-            }
+        if let ast::Statement::Span(source_span) = statement {
+            let statement_span = StatementSpan {
+                module,
+                start: source_span.start,
+                end: source_span.end,
+            };
+            span = Some(statement_span);
+            result.push(Statement::Span(statement_span));
+            continue;
         }
+        normalize_statement2(scope, module, &mut result, statement)
+            .map_err(|err| err.with_span(span.take()))?;
     }
     Ok(result)
 }
 
+fn normalize_statement2<'a, 'b, 'input>(
+    scope: &mut StringIdResolverScope<'a, 'b, 'input>,
+    module: usize,
+    result: &mut Vec<NormalizedStatement>,
+    statement: ast::Statement<ast::ParsedArgParams<'input>>,
+) -> Result<(), TranslateError> {
+    match statement {
+        ast::Statement::Block(block) => {
+            let mut scope = scope.start_scope();
+            result.extend(normalize_identifiers2(&mut scope, module, block)?);
+        }
+        ast::Statement::Label(name) => {
+            result.push(Statement::Label(scope.get_id_in_function_scopes(name)?))
+        }
+        ast::Statement::Location(location) => result.push(Statement::Location(location)),
+        ast::Statement::Span(_) => return Err(TranslateError::unreachable()),
+        ast::Statement::Instruction(p, i) => result.push(Statement::Instruction((
+            p.map(|p| p.map_variable(&mut |id| scope.get_id_in_module_scopes(id)))
+                .transpose()?,
+            i.map_variable(&mut |id| scope.get_id_in_module_scopes(id))?,
+        ))),
+        ast::Statement::Variable(vars) => {
+            expand_multivariable2(
+                scope,
+                vars.into_iter(),
+                |scope, align, type_, space, name, initializer| {
+                    result.push(Statement::Variable(scope.register_variable(
+                        name,
+                        type_.clone(),
+                        space,
+                        align,
+                        initializer,
+                    )?));
+                    Ok(())
+                },
+            )?;
+        }
+        ast::Statement::Callprototype(proto) => {
+            let name = scope.add_untyped_checked(proto.name)?;
+            scope.0.module.globals.function_prototypes.insert(
+                name,
+                Callprototype {
+                    return_arguments: proto.return_arguments,
+                    input_arguments: proto.input_arguments,
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
 fn expand_multivariable2<'a, 'b, 'input>(
     scope: &mut StringIdResolverScope<'a, 'b, 'input>,
     vars: impl Iterator<Item = ast::MultiVariableDefinition<&'input str>>,
@@ -1252,6 +1324,8 @@ fn normalize_predicates2<'a, 'b, 'input>(
                 }
             }
             Statement::Variable(var) => result.push(Statement::Variable(var)),
+            Statement::Location(location) => result.push(Statement::Location(location)),
+            Statement::Span(span) => result.push(Statement::Span(span)),
             // Blocks are flattened when resolving ids
             _ => return Err(TranslateError::unreachable()),
         }
@@ -1296,11 +1370,13 @@ fn resolve_instruction_types<'input>(
                 }
                 TranslationDirective::Method(method) => {
                     let body = match method.body {
-                        Some(body) => Some(resolve_instruction_types_method(
-                            id_defs,
-                            &function_decls,
-                            body,
-                        )?),
+                        Some(body) => {
+                            id_defs.current_span = None;
+                            Some(
+                                resolve_instruction_types_method(id_defs, &function_decls, body)
+                                    .map_err(|err| err.with_span(id_defs.current_span))?,
+                            )
+                        }
                         None => None,
                     };
                     TranslationDirective::Method(TranslationMethod {
@@ -1323,6 +1399,7 @@ fn resolve_instruction_types<'input>(
         directives,
         id_defs: module.id_defs,
         ptx_impl_imports: module.ptx_impl_imports,
+        source_files: module.source_files,
     })
 }
 
@@ -1397,6 +1474,48 @@ fn resolve_instruction_types_method<'input>(
                     visitor.func.push(reresolved_call);
                     visitor.func.extend(visitor.post_stmts);
                 }
+                ast::Instruction::Tld4(mut details, args) => {
+                    if let ast::Operand::Reg(image) = args.image {
+                        let (image_type, _, _, _) = id_defs.get_typed(image)?;
+                        if matches!(image_type, ast::Type::Texref) {
+                            details.direct = true;
+                        }
+                    }
+                    let mut visitor =
+                        VectorRepackVisitor::new(&mut constants, &mut result, id_defs);
+                    let reresolved_call =
+                        ast::Instruction::Tld4(details, args).visit(&mut visitor)?;
+                    visitor.func.push(reresolved_call);
+                    visitor.func.extend(visitor.post_stmts);
+                }
+                ast::Instruction::Txq(mut details, args) => {
+                    if let ast::Operand::Reg(image) = args.image {
+                        let (image_type, _, _, _) = id_defs.get_typed(image)?;
+                        if matches!(image_type, ast::Type::Texref) {
+                            details.direct = true;
+                        }
+                    }
+                    let mut visitor =
+                        VectorRepackVisitor::new(&mut constants, &mut result, id_defs);
+                    let reresolved_call =
+                        ast::Instruction::Txq(details, args).visit(&mut visitor)?;
+                    visitor.func.push(reresolved_call);
+                    visitor.func.extend(visitor.post_stmts);
+                }
+                ast::Instruction::Suq(mut details, args) => {
+                    if let ast::Operand::Reg(image) = args.image {
+                        let (image_type, _, _, _) = id_defs.get_typed(image)?;
+                        if matches!(image_type, ast::Type::Surfref) {
+                            details.direct = true;
+                        }
+                    }
+                    let mut visitor =
+                        VectorRepackVisitor::new(&mut constants, &mut result, id_defs);
+                    let reresolved_call =
+                        ast::Instruction::Suq(details, args).visit(&mut visitor)?;
+                    visitor.func.push(reresolved_call);
+                    visitor.func.extend(visitor.post_stmts);
+                }
                 ast::Instruction::Mov(
                     mov,
                     ast::Arg2Mov {
@@ -1466,6 +1585,11 @@ fn resolve_instruction_types_method<'input>(
             Statement::Label(i) => result.push(Statement::Label(i)),
             Statement::Variable(v) => result.push(Statement::Variable(v)),
             Statement::Conditional(c) => result.push(Statement::Conditional(c)),
+            Statement::Location(l) => result.push(Statement::Location(l)),
+            Statement::Span(span) => {
+                id_defs.current_span = Some(span);
+                result.push(Statement::Span(span))
+            }
             _ => return Err(TranslateError::unreachable()),
         }
     }
@@ -1592,15 +1716,21 @@ fn convert_methods<'input, From: ast::ArgParams<Id = Id>, To: ast::ArgParams<Id
             Ok(match directive {
                 TranslationDirective::Method(mut method) => {
                     let body = match method.body {
-                        Some(body) => Some(mapper(
-                            compilation_mode,
-                            id_defs,
-                            &mut additional_declarations,
-                            &mut method.return_arguments,
-                            &mut method.input_arguments,
-                            method.is_kernel,
-                            body,
-                        )?),
+                        Some(body) => {
+                            id_defs.current_span = None;
+                            Some(
+                                mapper(
+                                    compilation_mode,
+                                    id_defs,
+                                    &mut additional_declarations,
+                                    &mut method.return_arguments,
+                                    &mut method.input_arguments,
+                                    method.is_kernel,
+                                    body,
+                                )
+                                .map_err(|err| err.with_span(id_defs.current_span))?,
+                            )
+                        }
                         None => None,
                     };
                     TranslationDirective::Method(TranslationMethod {
@@ -1629,6 +1759,7 @@ fn convert_methods<'input, From: ast::ArgParams<Id = Id>, To: ast::ArgParams<Id
         directives: directives,
         id_defs: module.id_defs,
         ptx_impl_imports: module.ptx_impl_imports,
+        source_files: module.source_files,
     })
 }
 
@@ -2124,7 +2255,12 @@ fn fix_special_registers_impl<'input>(
             s @ Statement::Variable(_)
             | s @ Statement::Constant(_)
             | s @ Statement::Label(_)
-            | s @ Statement::FunctionPointer(_) => sreg_sresolver.result.push(s),
+            | s @ Statement::FunctionPointer(_)
+            | s @ Statement::Location(_) => sreg_sresolver.result.push(s),
+            Statement::Span(span) => {
+                sreg_sresolver.id_defs.current_span = Some(span);
+                sreg_sresolver.result.push(Statement::Span(span))
+            }
             _ => return Err(TranslateError::unreachable()),
         }
     }
@@ -2266,8 +2402,13 @@ fn insert_mem_ssa_statements_impl<'input>(
             Statement::SubCC(type_, arg) => {
                 insert_mem_ssa_statement_default(id_def, &mut result, VisitSubCC(type_, arg))?
             }
-            s @ Statement::Variable(_) | s @ Statement::Label(_) | s @ Statement::Constant(..) => {
-                result.push(s)
+            s @ Statement::Variable(_)
+            | s @ Statement::Label(_)
+            | s @ Statement::Constant(..)
+            | s @ Statement::Location(_) => result.push(s),
+            Statement::Span(span) => {
+                id_def.current_span = Some(span);
+                result.push(Statement::Span(span))
             }
             _ => return Err(TranslateError::unreachable()),
         }
@@ -2416,6 +2557,11 @@ fn expand_arguments2_impl<'input>(
             Statement::AsmVolatile { asm, constraints } => {
                 result.push(Statement::AsmVolatile { asm, constraints })
             }
+            Statement::Location(location) => result.push(Statement::Location(location)),
+            Statement::Span(span) => {
+                id_defs.current_span = Some(span);
+                result.push(Statement::Span(span))
+            }
         }
     }
     Ok(result)
@@ -2488,7 +2634,12 @@ fn insert_implicit_conversions2_impl<'input>(
             | s @ Statement::StoreVar(..)
             | s @ Statement::RetValue(..)
             | s @ Statement::AsmVolatile { .. }
+            | s @ Statement::Location(_)
             | s @ Statement::FunctionPointer(..) => result.push(s),
+            Statement::Span(span) => {
+                id_def.current_span = Some(span);
+                result.push(Statement::Span(span))
+            }
         }
     }
     Ok(result)
@@ -2570,220 +2721,574 @@ fn replace_instructions_with_builtins_impl<'input>(
                     fn_name,
                 )?);
             }
-            Statement::Instruction(ast::Instruction::MatchAny(arg)) => {
-                let fn_name = [ZLUDA_PTX_PREFIX, "match_any_sync_b32"].concat();
+            Statement::Instruction(ast::Instruction::WmmaLoad(details, arg)) => {
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    "wmma_load_",
+                    details.fragment.to_ptx_name(),
+                    "_",
+                    details.layout.to_ptx_name(),
+                    "_",
+                    details.shape.to_ptx_name(),
+                    "_",
+                    details.type_.to_ptx_name(),
+                    "_",
+                    details.state_space.to_ptx_name(),
+                ]
+                .concat();
                 statements.push(instruction_to_fn_call(
                     id_def,
                     ptx_impl_imports,
-                    ast::Instruction::MatchAny(arg),
+                    ast::Instruction::WmmaLoad(details, arg),
                     fn_name,
                 )?);
             }
-            Statement::Instruction(ast::Instruction::Dp4a(type_, arg)) => {
+            Statement::Instruction(ast::Instruction::WmmaStore(details, arg)) => {
                 let fn_name = [
                     ZLUDA_PTX_PREFIX,
-                    "dp4a_",
-                    type_.to_ptx_name(),
+                    "wmma_store_d_",
+                    details.layout.to_ptx_name(),
                     "_",
-                    type_.to_ptx_name(),
+                    details.shape.to_ptx_name(),
+                    "_",
+                    details.type_.to_ptx_name(),
+                    "_",
+                    details.state_space.to_ptx_name(),
                 ]
                 .concat();
                 statements.push(instruction_to_fn_call(
                     id_def,
                     ptx_impl_imports,
-                    ast::Instruction::Dp4a(type_, arg),
+                    ast::Instruction::WmmaStore(details, arg),
                     fn_name,
                 )?);
             }
-            Statement::Instruction(ast::Instruction::BarRed(op, arg3)) => {
-                let op_name = match op {
-                    ast::ReductionOp::And => "and",
-                    ast::ReductionOp::Or => "or",
-                    ast::ReductionOp::Popc => "popc",
+            // Fragments produced by wmma.load.* do not depend on the memory layout,
+            // so layouts are not part of the function name
+            Statement::Instruction(ast::Instruction::WmmaMma(details, arg)) => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "wmma_mma_", &details.ptx_suffix()].concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::WmmaMma(details, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Mma(details, arg)) => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "mma_", &details.ptx_suffix()].concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Mma(details, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::CpAsync(details, arg)) => {
+                let cp_size = match details.cp_size {
+                    4 => "4",
+                    8 => "8",
+                    16 => "16",
+                    _ => return Err(TranslateError::mismatched_type()),
                 };
-                let dst_type = op.dst_type().to_ptx_name();
-                let fn_name = [ZLUDA_PTX_PREFIX, "bar_red_", op_name, "_", dst_type].concat();
+                let fn_name = [ZLUDA_PTX_PREFIX, "cp_async_", cp_size].concat();
                 statements.push(instruction_to_fn_call(
                     id_def,
                     ptx_impl_imports,
-                    ast::Instruction::BarRed(op, arg3),
+                    ast::Instruction::CpAsync(details, arg),
                     fn_name,
                 )?);
             }
-            // We dispatch vote_sync_... by compilation mode suffix
-            // because LLVM crashes if there are both calls to `llvm.amdgcn.ballot.i32` and
-            // `llvm.amdgcn.ballot.i64` inside same function (even if only one of them can be called)
-            Statement::Instruction(ast::Instruction::Vote(
-                ast::VoteDetails {
-                    mode: ast::VoteMode::Any,
-                    negate_pred,
-                },
-                arg,
-            )) => {
-                let instr_suffix = if negate_pred { "_negate" } else { "" };
-                let mode_suffix = compilation_mode_suffix(compilation_mode);
-                let fn_name = [
-                    ZLUDA_PTX_PREFIX,
-                    "vote_sync_any_pred",
-                    instr_suffix,
-                    mode_suffix,
-                ]
-                .concat();
+            // cp.async is lowered to a synchronous copy, there is nothing to wait for
+            Statement::Instruction(ast::Instruction::CpAsyncCommitGroup)
+            | Statement::Instruction(ast::Instruction::CpAsyncWaitGroup(..)) => {}
+            // AMDGPU has no separate async or alias proxies and cp.async is synchronous
+            Statement::Instruction(ast::Instruction::FenceProxy(..)) => {}
+            Statement::Instruction(ast::Instruction::MbarrierInit(space, arg)) => {
+                let fn_name =
+                    [ZLUDA_PTX_PREFIX, "mbarrier_init_b64_", space.to_ptx_name()].concat();
                 statements.push(instruction_to_fn_call(
                     id_def,
                     ptx_impl_imports,
-                    ast::Instruction::Vote(
-                        ast::VoteDetails {
-                            mode: ast::VoteMode::Any,
-                            negate_pred,
-                        },
-                        arg,
-                    ),
+                    ast::Instruction::MbarrierInit(space, arg),
                     fn_name,
                 )?);
             }
-            Statement::Instruction(ast::Instruction::Vote(
-                ast::VoteDetails {
-                    mode: ast::VoteMode::All,
-                    negate_pred,
-                },
-                arg,
-            )) => {
-                let instr_suffix = if negate_pred { "_negate" } else { "" };
-                let mode_suffix = compilation_mode_suffix(compilation_mode);
+            Statement::Instruction(ast::Instruction::MbarrierArrive(space, arg)) => {
                 let fn_name = [
                     ZLUDA_PTX_PREFIX,
-                    "vote_sync_all_pred",
-                    instr_suffix,
-                    mode_suffix,
+                    "mbarrier_arrive_b64_",
+                    space.to_ptx_name(),
                 ]
                 .concat();
                 statements.push(instruction_to_fn_call(
                     id_def,
                     ptx_impl_imports,
-                    ast::Instruction::Vote(
-                        ast::VoteDetails {
-                            mode: ast::VoteMode::All,
-                            negate_pred,
-                        },
-                        arg,
-                    ),
+                    ast::Instruction::MbarrierArrive(space, arg),
                     fn_name,
                 )?);
             }
-            Statement::Instruction(ast::Instruction::Vote(
-                ast::VoteDetails {
-                    mode: ast::VoteMode::Ballot,
-                    negate_pred,
-                },
-                arg,
-            )) => {
-                let instr_suffix = if negate_pred { "_negate" } else { "" };
-                let mode_suffix = compilation_mode_suffix(compilation_mode);
+            Statement::Instruction(ast::Instruction::MbarrierTestWait(details, arg)) => {
+                let parity = if details.parity { "parity_" } else { "" };
                 let fn_name = [
                     ZLUDA_PTX_PREFIX,
-                    "vote_sync_ballot_b32",
-                    instr_suffix,
-                    mode_suffix,
+                    "mbarrier_test_wait_",
+                    parity,
+                    "b64_",
+                    details.state_space.to_ptx_name(),
                 ]
                 .concat();
                 statements.push(instruction_to_fn_call(
                     id_def,
                     ptx_impl_imports,
-                    ast::Instruction::Vote(
-                        ast::VoteDetails {
-                            mode: ast::VoteMode::Ballot,
-                            negate_pred,
-                        },
-                        arg,
-                    ),
+                    ast::Instruction::MbarrierTestWait(details, arg),
                     fn_name,
                 )?);
             }
-            Statement::Instruction(ast::Instruction::Bar(details, arg)) => {
-                let fn_name = [ZLUDA_PTX_PREFIX, "barrier_sync"].concat();
+            Statement::Instruction(ast::Instruction::Mapa(type_, arg)) => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "mapa_", type_.to_ptx_name()].concat();
                 statements.push(instruction_to_fn_call(
                     id_def,
                     ptx_impl_imports,
-                    ast::Instruction::Bar(details, arg),
+                    ast::Instruction::Mapa(type_, arg),
                     fn_name,
                 )?);
             }
-            Statement::Instruction(ast::Instruction::Bfe { typ, arg }) => {
-                let fn_name = [ZLUDA_PTX_PREFIX, "bfe_", typ.to_ptx_name()].concat();
+            Statement::Instruction(ast::Instruction::Getctarank(type_, arg)) => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "getctarank_", type_.to_ptx_name()].concat();
                 statements.push(instruction_to_fn_call(
                     id_def,
                     ptx_impl_imports,
-                    ast::Instruction::Bfe { typ, arg },
+                    ast::Instruction::Getctarank(type_, arg),
                     fn_name,
                 )?);
             }
-            Statement::Instruction(ast::Instruction::Bfi { typ, arg }) => {
-                let fn_name = [ZLUDA_PTX_PREFIX, "bfi_", typ.to_ptx_name()].concat();
+            // A cluster is a single CTA, arriving is subsumed by the block barrier in wait
+            Statement::Instruction(ast::Instruction::BarrierCluster(
+                ast::BarrierClusterOp::Arrive,
+            )) => {}
+            Statement::Instruction(ast::Instruction::BarrierCluster(
+                ast::BarrierClusterOp::Wait,
+            )) => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "barrier_cluster_wait"].concat();
                 statements.push(instruction_to_fn_call(
                     id_def,
                     ptx_impl_imports,
-                    ast::Instruction::Bfi { typ, arg },
+                    ast::Instruction::BarrierCluster(ast::BarrierClusterOp::Wait),
                     fn_name,
                 )?);
             }
-            Statement::Instruction(ast::Instruction::Activemask { arg }) => {
-                let fn_name = [ZLUDA_PTX_PREFIX, "activemask"].concat();
+            Statement::Instruction(ast::Instruction::ElectSync(arg)) if arg.dst1.is_none() => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "elect_sync_pred"].concat();
                 statements.push(instruction_to_fn_call(
                     id_def,
                     ptx_impl_imports,
-                    ast::Instruction::Activemask { arg },
+                    ast::Instruction::ElectSync(arg),
                     fn_name,
                 )?);
             }
-            Statement::Instruction(ast::Instruction::Tex(tex, arg)) => {
-                let geometry = tex.geometry.as_ptx();
-                let op_name = if arg.lod.is_none() {
-                    "tex"
-                } else {
-                    "tex_level"
-                };
-                let fn_name = [
-                    ZLUDA_PTX_PREFIX,
-                    op_name,
-                    tex.suffix(),
-                    "_",
-                    geometry,
-                    "_v4",
-                    "_",
-                    tex.channel_type.to_ptx_name(),
-                    "_",
-                    tex.coordinate_type.to_ptx_name(),
-                ]
-                .concat();
+            Statement::Instruction(ast::Instruction::ElectSync(arg)) => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "elect_sync"].concat();
+                replace_with_pred_call(
+                    id_def,
+                    ptx_impl_imports,
+                    &mut statements,
+                    ast::Instruction::ElectSync(arg),
+                    fn_name,
+                )?;
+            }
+            Statement::Instruction(ast::Instruction::MatchAny(arg)) => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "match_any_sync_b32"].concat();
                 statements.push(instruction_to_fn_call(
                     id_def,
                     ptx_impl_imports,
-                    ast::Instruction::Tex(tex, arg),
+                    ast::Instruction::MatchAny(arg),
                     fn_name,
                 )?);
             }
-            Statement::Instruction(ast::Instruction::Shfl(shfl_mode, arg))
-                if arg.dst2.is_none() =>
-            {
+            Statement::Instruction(ast::Instruction::Redux(details, arg)) => {
                 let fn_name = [
                     ZLUDA_PTX_PREFIX,
-                    "shfl_",
-                    shfl_mode.to_ptx_name(),
-                    "_b32_slow",
+                    "redux_sync_",
+                    details.op.to_ptx_name(),
+                    "_",
+                    details.type_.to_ptx_name(),
                 ]
                 .concat();
                 statements.push(instruction_to_fn_call(
                     id_def,
                     ptx_impl_imports,
-                    ast::Instruction::Shfl(shfl_mode, arg),
+                    ast::Instruction::Redux(details, arg),
                     fn_name,
                 )?);
             }
-            Statement::Instruction(ast::Instruction::Shfl(shfl_mode, arg))
-                if arg.dst2.is_some() =>
-            {
-                replace_shfl_with_pred(id_def, ptx_impl_imports, &mut statements, shfl_mode, arg)?;
+            Statement::Instruction(ast::Instruction::Bmsk(mode, arg)) => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "bmsk_", mode.to_ptx_name(), "_b32"].concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Bmsk(mode, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Szext(mode, type_, arg)) => {
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    "szext_",
+                    mode.to_ptx_name(),
+                    "_",
+                    type_.to_ptx_name(),
+                ]
+                .concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Szext(mode, type_, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Fns(arg)) => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "fns_b32"].concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Fns(arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Mul24(details, arg)) => {
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    "mul24_",
+                    details.control_ptx_name(),
+                    "_",
+                    details.type_.to_ptx_name(),
+                ]
+                .concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Mul24(details, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Mad24(details, arg)) => {
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    "mad24_",
+                    details.control_ptx_name(),
+                    "_",
+                    details.type_.to_ptx_name(),
+                ]
+                .concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Mad24(details, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Tanh(typ, arg)) => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "tanh_approx_", typ.to_ptx_name()].concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Tanh(typ, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::CvtPack(details, arg)) => {
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    "cvt_pack_sat_",
+                    if details.signed { "s" } else { "u" },
+                    &details.bits.to_string(),
+                    "_s32_b32",
+                ]
+                .concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::CvtPack(details, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Dp4a(type_, arg)) => {
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    "dp4a_",
+                    type_.to_ptx_name(),
+                    "_",
+                    type_.to_ptx_name(),
+                ]
+                .concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Dp4a(type_, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::BarRed(op, arg3)) => {
+                let op_name = match op {
+                    ast::ReductionOp::And => "and",
+                    ast::ReductionOp::Or => "or",
+                    ast::ReductionOp::Popc => "popc",
+                };
+                let dst_type = op.dst_type().to_ptx_name();
+                let fn_name = [ZLUDA_PTX_PREFIX, "bar_red_", op_name, "_", dst_type].concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::BarRed(op, arg3),
+                    fn_name,
+                )?);
+            }
+            // We dispatch vote_sync_... by compilation mode suffix
+            // because LLVM crashes if there are both calls to `llvm.amdgcn.ballot.i32` and
+            // `llvm.amdgcn.ballot.i64` inside same function (even if only one of them can be called)
+            Statement::Instruction(ast::Instruction::Vote(
+                ast::VoteDetails {
+                    mode: ast::VoteMode::Any,
+                    negate_pred,
+                },
+                arg,
+            )) => {
+                let instr_suffix = if negate_pred { "_negate" } else { "" };
+                let mode_suffix = compilation_mode_suffix(compilation_mode);
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    "vote_sync_any_pred",
+                    instr_suffix,
+                    mode_suffix,
+                ]
+                .concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Vote(
+                        ast::VoteDetails {
+                            mode: ast::VoteMode::Any,
+                            negate_pred,
+                        },
+                        arg,
+                    ),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Vote(
+                ast::VoteDetails {
+                    mode: ast::VoteMode::All,
+                    negate_pred,
+                },
+                arg,
+            )) => {
+                let instr_suffix = if negate_pred { "_negate" } else { "" };
+                let mode_suffix = compilation_mode_suffix(compilation_mode);
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    "vote_sync_all_pred",
+                    instr_suffix,
+                    mode_suffix,
+                ]
+                .concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Vote(
+                        ast::VoteDetails {
+                            mode: ast::VoteMode::All,
+                            negate_pred,
+                        },
+                        arg,
+                    ),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Vote(
+                ast::VoteDetails {
+                    mode: ast::VoteMode::Ballot,
+                    negate_pred,
+                },
+                arg,
+            )) => {
+                let instr_suffix = if negate_pred { "_negate" } else { "" };
+                let mode_suffix = compilation_mode_suffix(compilation_mode);
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    "vote_sync_ballot_b32",
+                    instr_suffix,
+                    mode_suffix,
+                ]
+                .concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Vote(
+                        ast::VoteDetails {
+                            mode: ast::VoteMode::Ballot,
+                            negate_pred,
+                        },
+                        arg,
+                    ),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Bar(details, arg)) => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "barrier_sync"].concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Bar(details, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Bfe { typ, arg }) => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "bfe_", typ.to_ptx_name()].concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Bfe { typ, arg },
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Bfi { typ, arg }) => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "bfi_", typ.to_ptx_name()].concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Bfi { typ, arg },
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Activemask { arg }) => {
+                let fn_name = [ZLUDA_PTX_PREFIX, "activemask"].concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Activemask { arg },
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Tex(tex, arg)) => {
+                let geometry = tex.geometry.as_ptx();
+                let op_name = if arg.lod.is_some() {
+                    "tex_level"
+                } else if arg.gradient.is_some() {
+                    // HIP has no half-precision gradient sampling
+                    if tex.channel_type == ast::ScalarType::F16 {
+                        return Err(TranslateError::todo());
+                    }
+                    "tex_grad"
+                } else {
+                    "tex"
+                };
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    op_name,
+                    tex.suffix(),
+                    "_",
+                    geometry,
+                    "_v4",
+                    "_",
+                    tex.channel_type.to_ptx_name(),
+                    "_",
+                    tex.coordinate_type.to_ptx_name(),
+                ]
+                .concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Tex(tex, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Tld4(tld4, arg)) => {
+                if tld4.channel_type == ast::ScalarType::F16 {
+                    return Err(TranslateError::todo());
+                }
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    "tld4_",
+                    tld4.component.to_ptx_name(),
+                    tld4.suffix(),
+                    "_2d_v4_",
+                    tld4.channel_type.to_ptx_name(),
+                    "_f32",
+                ]
+                .concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Tld4(tld4, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Txq(details, arg)) => {
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    "txq",
+                    details.suffix(),
+                    "_",
+                    details.query.to_ptx_name(),
+                ]
+                .concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Txq(details, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Suq(details, arg)) => {
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    "suq",
+                    details.suffix(),
+                    "_",
+                    details.query.to_ptx_name(),
+                ]
+                .concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Suq(details, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Shfl(shfl_mode, arg))
+                if arg.dst2.is_none() =>
+            {
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    "shfl_",
+                    shfl_mode.to_ptx_name(),
+                    "_b32_slow",
+                ]
+                .concat();
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Shfl(shfl_mode, arg),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Shfl(shfl_mode, arg))
+                if arg.dst2.is_some() =>
+            {
+                let fn_name = [
+                    ZLUDA_PTX_PREFIX,
+                    "shfl_",
+                    shfl_mode.to_ptx_name(),
+                    "_b32_pred_slow",
+                ]
+                .concat();
+                replace_with_pred_call(
+                    id_def,
+                    ptx_impl_imports,
+                    &mut statements,
+                    ast::Instruction::Shfl(shfl_mode, arg),
+                    fn_name,
+                )?;
             }
             Statement::Instruction(ast::Instruction::Cvt(
                 ast::CvtDetails::FloatFromFloat(ast::CvtDesc {
@@ -2944,6 +3449,50 @@ fn replace_instructions_with_builtins_impl<'input>(
                     fn_name,
                 )?);
             }
+            Statement::Instruction(ast::Instruction::Atom(details, args))
+                if details.is_packed_float_add() =>
+            {
+                let fn_name = details.packed_float_add_fn_name("atom_");
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Atom(details, args),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Red(details, args))
+                if details.is_packed_float_add() =>
+            {
+                if details.vector.is_some() {
+                    return Err(TranslateError::todo());
+                }
+                let fn_name = details.packed_float_add_fn_name("red_");
+                statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    ast::Instruction::Red(details, args),
+                    fn_name,
+                )?);
+            }
+            Statement::Instruction(ast::Instruction::Cvt(
+                ast::CvtDetails::FloatFromFloat(desc),
+                args,
+            )) if desc.dst.is_storage_only() || desc.src.is_storage_only() => extract_global_cvt(
+                &mut statements,
+                ptx_impl_imports,
+                id_def,
+                desc.clone(),
+                ast::Instruction::Cvt(ast::CvtDetails::FloatFromFloat(desc), args),
+            )?,
+            Statement::Instruction(ast::Instruction::CvtPackedFloat(desc, args)) => {
+                extract_global_cvt(
+                    &mut statements,
+                    ptx_impl_imports,
+                    id_def,
+                    desc.clone(),
+                    ast::Instruction::CvtPackedFloat(desc, args),
+                )?
+            }
             Statement::Instruction(ast::Instruction::Cvt(
                 ast::CvtDetails::FloatFromInt(desc),
                 args,
@@ -3030,12 +3579,48 @@ fn replace_instructions_with_builtins_impl<'input>(
                     fn_name,
                 )?);
             }
+            Statement::Instruction(inst) => match bf16_arith_fn_name(&inst) {
+                Some(fn_name) => statements.push(instruction_to_fn_call(
+                    id_def,
+                    ptx_impl_imports,
+                    inst,
+                    fn_name,
+                )?),
+                None => statements.push(Statement::Instruction(inst)),
+            },
             s => statements.push(s),
         }
     }
     Ok(statements)
 }
 
+// There is no bf16 arithmetic in LLVM for AMDGPU, all of it goes through ZLUDA's PTX library
+fn bf16_arith_fn_name(inst: &ast::Instruction<ExpandedArgParams>) -> Option<String> {
+    let (op, typ) = match inst {
+        ast::Instruction::Add(ast::ArithDetails::Float(desc), _) => ("add_rn_", desc.typ),
+        ast::Instruction::Sub(ast::ArithDetails::Float(desc), _) => ("sub_rn_", desc.typ),
+        ast::Instruction::Mul(ast::MulDetails::Float(desc), _) => ("mul_rn_", desc.typ),
+        ast::Instruction::Mad(ast::MulDetails::Float(desc), _) | ast::Instruction::Fma(desc, _) => {
+            ("fma_rn_", desc.typ)
+        }
+        ast::Instruction::Min(ast::MinMaxDetails::Float(desc), _) => {
+            (if desc.nan { "min_nan_" } else { "min_" }, desc.typ)
+        }
+        ast::Instruction::Max(ast::MinMaxDetails::Float(desc), _) => {
+            (if desc.nan { "max_nan_" } else { "max_" }, desc.typ)
+        }
+        ast::Instruction::Neg(desc, _) => ("neg_", desc.typ),
+        ast::Instruction::Abs(desc, _) => ("abs_", desc.typ),
+        _ => return None,
+    };
+    match typ {
+        ast::ScalarType::BF16 | ast::ScalarType::BF16x2 => {
+            Some([ZLUDA_PTX_PREFIX, op, typ.to_ptx_name()].concat())
+        }
+        _ => None,
+    }
+}
+
 fn compilation_mode_suffix(compilation_mode: CompilationMode) -> &'static str {
     match compilation_mode {
         CompilationMode::Wave32 => "_32",
@@ -3044,21 +3629,15 @@ fn compilation_mode_suffix(compilation_mode: CompilationMode) -> &'static str {
     }
 }
 
-fn replace_shfl_with_pred<'input>(
+// Lowers an instruction with a 32-bit value and a predicate as its two
+// destinations (in that order) to a builtin returning both
+fn replace_with_pred_call<'input>(
     id_defs: &mut IdNameMapBuilder<'input>,
     ptx_impl_imports: &mut AdditionalFunctionDeclarations,
     statements: &mut Vec<ExpandedStatement>,
-    shfl_mode: ast::ShflMode,
-    arg: ast::Arg5Shfl<ExpandedArgParams>,
+    inst: ast::Instruction<ExpandedArgParams>,
+    fn_name: String,
 ) -> Result<(), TranslateError> {
-    let fn_name = [
-        ZLUDA_PTX_PREFIX,
-        "shfl_",
-        shfl_mode.to_ptx_name(),
-        "_b32_pred_slow",
-    ]
-    .concat();
-    let inst = ast::Instruction::Shfl(shfl_mode, arg);
     let mut arguments = Vec::new();
     inst.visit(
         &mut |desc: ArgumentDescriptor<Id>, typ: Option<(&ast::Type, ast::StateSpace)>| {
@@ -3192,6 +3771,8 @@ fn compute_denorm_statistics<'input, P: ast::ArgParams<Id = Id>>(
                         Statement::SubC(..) => {}
                         Statement::SubCC(..) => {}
                         Statement::AsmVolatile { .. } => {}
+                        Statement::Location(_) => {}
+                        Statement::Span(_) => {}
                     }
                 }
                 let summary = DenormSummary {
@@ -3223,7 +3804,15 @@ pub fn to_llvm_module<'input>(
     compilation_mode: CompilationMode,
     ast: Vec<ast::Module<'input>>,
 ) -> Result<Module, TranslateError> {
-    to_llvm_module_impl2(compilation_mode, ast, None)
+    to_llvm_module_impl2(compilation_mode, ast, false, None)
+}
+
+// Same as to_llvm_module(...), but .loc directives are turned into DWARF line tables
+pub fn to_llvm_module_with_line_info<'input>(
+    compilation_mode: CompilationMode,
+    ast: Vec<ast::Module<'input>>,
+) -> Result<Module, TranslateError> {
+    to_llvm_module_impl2(compilation_mode, ast, true, None)
 }
 
 pub fn to_llvm_module_for_raytracing<'input>(
@@ -3236,6 +3825,7 @@ pub fn to_llvm_module_for_raytracing<'input>(
     let compilation_module = to_llvm_module_impl2(
         CompilationMode::Wave32,
         vec![ast],
+        false,
         Some(&mut raytracing_state),
     )?;
     let entry_point_kind: RaytracingEntryPointKind = raytracing_state.entry_point_kind.unwrap();
@@ -3299,14 +3889,16 @@ pub(crate) enum RaytracingEntryPointKind {
 
 fn to_llvm_module_impl2<'a, 'input>(
     compilation_mode: CompilationMode,
-    asts: Vec<ast::Module<'input>>,
+    mut asts: Vec<ast::Module<'input>>,
+    line_info: bool,
     mut raytracing: Option<&mut RaytracingTranslationState<'a, 'input>>,
 ) -> Result<Module<'input>, TranslateError> {
-    let empty_module = if raytracing.is_some() {
+    let mut empty_module = if raytracing.is_some() {
         raytracing::create_module_with_builtins()
     } else {
         TranslationModule::new(compilation_mode)
     };
+    empty_module.source_files = collect_source_files(&mut asts, line_info);
     let linking = resolve_linking(&*asts, raytracing.is_some())?;
     let (mut translation_module, functions) =
         link_and_normalize_modules(asts, empty_module, linking)?;
@@ -3359,6 +3951,202 @@ fn to_llvm_module_impl2<'a, 'input>(
     })
 }
 
+// Runs the same passes as to_llvm_module_impl2(...) up to and including
+// implicit conversions. Warp and block collectives are kept as instructions
+// (no replace_instructions_with_builtins(...)) and there is no compilation
+// mode prologue, so the result is directly executable by crate::interp
+pub(crate) fn to_interpreter_module<'input>(
+    mut asts: Vec<ast::Module<'input>>,
+) -> Result<TranslationModule<'input, ExpandedArgParams>, TranslateError> {
+    let empty_module = TranslationModule::new(CompilationMode::Wave32);
+    collect_source_files(&mut asts, false);
+    let linking = resolve_linking(&*asts, false)?;
+    let (translation_module, functions) = link_and_normalize_modules(asts, empty_module, linking)?;
+    let translation_module = return_from_noreturn(translation_module);
+    let translation_module = extract_builtin_functions(translation_module);
+    let translation_module = resolve_instruction_types(translation_module, functions)?;
+    let translation_module = restructure_function_return_types(translation_module)?;
+    let translation_module = deparamize_function_declarations(translation_module)?;
+    let translation_module = insert_hardware_registers(translation_module)?;
+    let translation_module = fix_special_registers(translation_module)?;
+    let translation_module = insert_mem_ssa_statements(translation_module)?;
+    let translation_module = expand_arguments(translation_module)?;
+    let translation_module = deparamize_variable_declarations(translation_module)?;
+    let translation_module = insert_implicit_conversions(translation_module)?;
+    Ok(hoist_globals(translation_module))
+}
+
+// Used by ptx-check. Returns identifiers used by instructions that are
+// neither declared in the module nor special registers we support, in order
+// of the first use. Declarations are visible in the whole function, nested
+// blocks are not treated as separate scopes
+pub(crate) fn unresolved_symbols<'input>(ast: ast::Module<'input>) -> Vec<String> {
+    let mut module_names = FxHashSet::default();
+    for directive in ast.directives.iter() {
+        match directive {
+            ast::Directive::Variable(_, multivar) => {
+                add_declared_names(&mut module_names, iter::once(multivar))
+            }
+            ast::Directive::Method(_, method) => {
+                module_names.insert(Cow::Borrowed(method.func_directive.name()));
+            }
+        }
+    }
+    let mut result = Vec::new();
+    for directive in ast.directives {
+        let method = match directive {
+            ast::Directive::Method(_, method) => method,
+            ast::Directive::Variable(..) => continue,
+        };
+        let body = match method.body {
+            Some(body) => body,
+            None => continue,
+        };
+        let mut names = module_names.clone();
+        for argument in method
+            .func_directive
+            .return_arguments
+            .iter()
+            .chain(method.func_directive.input_arguments.iter())
+        {
+            names.insert(Cow::Borrowed(argument.name));
+        }
+        add_function_names(&mut names, &body);
+        resolve_operands(body, &mut |name: &str| {
+            if name != "_"
+                && !names.contains(name)
+                && PtxSpecialRegister::try_parse(name).is_none()
+                && !result.iter().any(|unresolved| unresolved == name)
+            {
+                result.push(name.to_string());
+            }
+            Ok::<_, TranslateError>(Id(NonZeroU32::new(1).unwrap()))
+        });
+    }
+    result
+}
+
+fn resolve_operands<'input, F>(
+    statements: Vec<ast::Statement<ast::ParsedArgParams<'input>>>,
+    resolve: &mut F,
+) where
+    F: for<'x> FnMut(&'x str) -> Result<Id, TranslateError>,
+{
+    for statement in statements {
+        match statement {
+            ast::Statement::Instruction(predicate, instruction) => {
+                if let Some(predicate) = predicate {
+                    predicate.map_variable(&mut |name| resolve(name)).ok();
+                }
+                instruction.map_variable(resolve).ok();
+            }
+            ast::Statement::Block(block) => resolve_operands(block, resolve),
+            ast::Statement::Label(_)
+            | ast::Statement::Callprototype(_)
+            | ast::Statement::Variable(_)
+            | ast::Statement::Location(_)
+            | ast::Statement::Span(_) => {}
+        }
+    }
+}
+
+fn add_function_names<'input>(
+    names: &mut FxHashSet<Cow<'input, str>>,
+    statements: &[ast::Statement<ast::ParsedArgParams<'input>>],
+) {
+    for statement in statements {
+        match statement {
+            ast::Statement::Label(name) => {
+                names.insert(Cow::Borrowed(*name));
+            }
+            ast::Statement::Callprototype(proto) => {
+                names.insert(Cow::Borrowed(proto.name));
+            }
+            ast::Statement::Variable(vars) => add_declared_names(names, vars.iter()),
+            ast::Statement::Block(block) => add_function_names(names, block),
+            ast::Statement::Instruction(..)
+            | ast::Statement::Location(_)
+            | ast::Statement::Span(_) => {}
+        }
+    }
+}
+
+fn add_declared_names<'a, 'input: 'a>(
+    names: &mut FxHashSet<Cow<'input, str>>,
+    vars: impl Iterator<Item = &'a ast::MultiVariableDefinition<&'input str>>,
+) {
+    for var in vars {
+        match var.suffix {
+            Some(ast::DeclarationSuffix::Count(count)) => {
+                for offset in 0..count {
+                    names.insert(Cow::Owned(format!("{}{}", var.variable.name, offset)));
+                }
+            }
+            _ => {
+                names.insert(Cow::Borrowed(var.variable.name));
+            }
+        }
+    }
+}
+
+// .loc directives refer to files by module-local .file indices. This pass
+// renumbers them into indices of the returned list of all source files.
+// Locations are dropped if line info is disabled or their file is unknown
+fn collect_source_files<'input>(
+    asts: &mut [ast::Module<'input>],
+    line_info: bool,
+) -> Vec<&'input str> {
+    let mut source_files = Vec::new();
+    for ast in asts.iter_mut() {
+        let mut file_indices = FxHashMap::default();
+        if line_info {
+            for (index, name) in ast.files.iter().copied() {
+                file_indices.insert(index, source_files.len() as u32);
+                source_files.push(name);
+            }
+        }
+        for directive in ast.directives.iter_mut() {
+            if let ast::Directive::Method(_, method) = directive {
+                method.body = method
+                    .body
+                    .take()
+                    .map(|body| remap_source_locations(body, &file_indices));
+            }
+        }
+    }
+    source_files
+}
+
+fn remap_source_locations<'input>(
+    statements: Vec<ast::Statement<ast::ParsedArgParams<'input>>>,
+    file_indices: &FxHashMap<u32, u32>,
+) -> Vec<ast::Statement<ast::ParsedArgParams<'input>>> {
+    statements
+        .into_iter()
+        .filter_map(|statement| match statement {
+            ast::Statement::Block(block) => Some(ast::Statement::Block(remap_source_locations(
+                block,
+                file_indices,
+            ))),
+            ast::Statement::Location(location) => {
+                let file = *file_indices.get(&location.file)?;
+                let inlined_at = location.inlined_at.and_then(|inlined_at| {
+                    Some(ast::InlinedAt {
+                        file: *file_indices.get(&inlined_at.file)?,
+                        ..inlined_at
+                    })
+                });
+                Some(ast::Statement::Location(ast::SourceLocation {
+                    file,
+                    inlined_at,
+                    ..location
+                }))
+            }
+            statement => Some(statement),
+        })
+        .collect()
+}
+
 // In PTX it's legal to have a function like this:
 //      .func noreturn(.param .b64 noreturn_0)
 //      .noreturn
@@ -4132,6 +4920,14 @@ impl<'a, 'input> SpecialRegisterResolver<'a, 'input> {
             if desc.is_dst {
                 return Err(TranslateError::mismatched_type());
             }
+            // %envreg<N> is a scalar, but it's backed by a single function taking N
+            let vector_index = match (sreg, vector_index) {
+                (PtxSpecialRegister::Envreg(_), Some(_)) => {
+                    return Err(TranslateError::mismatched_type())
+                }
+                (PtxSpecialRegister::Envreg(index), None) => Some(index),
+                (_, vector_index) => vector_index,
+            };
             let input_arguments = match (vector_index, sreg.get_function_input_type()) {
                 (Some(idx), Some(inp_type)) => {
                     if inp_type != ast::ScalarType::U8 {
@@ -4246,6 +5042,7 @@ fn rounding_to_ptx_name(this: Option<ast::RoundingMode>) -> &'static str {
         Some(ast::RoundingMode::Zero) => "rz",
         Some(ast::RoundingMode::NegativeInf) => "rm",
         Some(ast::RoundingMode::PositiveInf) => "rp",
+        Some(ast::RoundingMode::NearestAway) => "rna",
     }
 }
 
@@ -4285,6 +5082,38 @@ impl ast::StateSpace {
     }
 }
 
+impl ast::ReduxOp {
+    fn to_ptx_name(self) -> &'static str {
+        match self {
+            ast::ReduxOp::Add => "add",
+            ast::ReduxOp::Min => "min",
+            ast::ReduxOp::Max => "max",
+            ast::ReduxOp::And => "and",
+            ast::ReduxOp::Or => "or",
+            ast::ReduxOp::Xor => "xor",
+        }
+    }
+}
+
+impl ast::ShiftNormalization {
+    fn to_ptx_name(self) -> &'static str {
+        match self {
+            ast::ShiftNormalization::Wrap => "wrap",
+            ast::ShiftNormalization::Clamp => "clamp",
+        }
+    }
+}
+
+impl ast::Mul24Details {
+    fn control_ptx_name(self) -> &'static str {
+        if self.is_hi {
+            "hi"
+        } else {
+            "lo"
+        }
+    }
+}
+
 impl ast::ShflMode {
     fn to_ptx_name(self) -> &'static str {
         match self {
@@ -4295,6 +5124,247 @@ impl ast::ShflMode {
         }
     }
 }
+
+impl ast::MatrixShape {
+    fn to_ptx_name(self) -> &'static str {
+        match self {
+            ast::MatrixShape::M16N16K16 => "m16n16k16",
+            ast::MatrixShape::M16N16K8 => "m16n16k8",
+            ast::MatrixShape::M16N8K16 => "m16n8k16",
+            ast::MatrixShape::M16N8K8 => "m16n8k8",
+            ast::MatrixShape::M8N8K4 => "m8n8k4",
+        }
+    }
+}
+
+impl ast::MatrixLayout {
+    fn to_ptx_name(self) -> &'static str {
+        match self {
+            ast::MatrixLayout::Row => "row",
+            ast::MatrixLayout::Col => "col",
+        }
+    }
+}
+
+impl ast::MatrixFragment {
+    fn to_ptx_name(self) -> &'static str {
+        match self {
+            ast::MatrixFragment::A => "a",
+            ast::MatrixFragment::B => "b",
+            ast::MatrixFragment::C => "c",
+        }
+    }
+}
+
+impl ast::MatrixType {
+    fn to_ptx_name(self) -> &'static str {
+        match self {
+            ast::MatrixType::F16 => "f16",
+            ast::MatrixType::BF16 => "bf16",
+            ast::MatrixType::TF32 => "tf32",
+            ast::MatrixType::S8 => "s8",
+            ast::MatrixType::U8 => "u8",
+            ast::MatrixType::F32 => "f32",
+            ast::MatrixType::S32 => "s32",
+        }
+    }
+
+    // Type of a single matrix element in memory
+    fn memory_type(self) -> ast::ScalarType {
+        match self {
+            ast::MatrixType::F16 | ast::MatrixType::BF16 => ast::ScalarType::B16,
+            ast::MatrixType::TF32 | ast::MatrixType::F32 | ast::MatrixType::S32 => {
+                ast::ScalarType::B32
+            }
+            ast::MatrixType::S8 | ast::MatrixType::U8 => ast::ScalarType::B8,
+        }
+    }
+}
+
+// All fragments are passed around as vectors of .b32 registers, registers of
+// other types (.f16x2, .f32, .s32) get there through relaxed type conversions
+fn matrix_fragment_type(registers: u8) -> ast::Type {
+    ast::Type::Vector(ast::ScalarType::B32, registers)
+}
+
+fn wmma_fragment_registers(
+    shape: ast::MatrixShape,
+    fragment: ast::MatrixFragment,
+    type_: ast::MatrixType,
+) -> Result<u8, TranslateError> {
+    Ok(match (shape, fragment, type_) {
+        (
+            ast::MatrixShape::M16N16K16,
+            ast::MatrixFragment::A | ast::MatrixFragment::B,
+            ast::MatrixType::F16,
+        ) => 8,
+        (
+            ast::MatrixShape::M16N16K16,
+            ast::MatrixFragment::A | ast::MatrixFragment::B,
+            ast::MatrixType::BF16,
+        ) => 4,
+        (
+            ast::MatrixShape::M16N16K16,
+            ast::MatrixFragment::A | ast::MatrixFragment::B,
+            ast::MatrixType::S8 | ast::MatrixType::U8,
+        ) => 2,
+        (
+            ast::MatrixShape::M16N16K8,
+            ast::MatrixFragment::A | ast::MatrixFragment::B,
+            ast::MatrixType::TF32,
+        ) => 4,
+        (ast::MatrixShape::M16N16K16, ast::MatrixFragment::C, ast::MatrixType::F16) => 4,
+        (
+            ast::MatrixShape::M16N16K16 | ast::MatrixShape::M16N16K8,
+            ast::MatrixFragment::C,
+            ast::MatrixType::F32,
+        ) => 8,
+        (ast::MatrixShape::M16N16K16, ast::MatrixFragment::C, ast::MatrixType::S32) => 8,
+        _ => return Err(TranslateError::mismatched_type()),
+    })
+}
+
+fn mma_fragment_registers(
+    shape: ast::MatrixShape,
+    fragment: ast::MatrixFragment,
+    type_: ast::MatrixType,
+) -> Result<u8, TranslateError> {
+    Ok(match (shape, fragment, type_) {
+        (
+            ast::MatrixShape::M16N8K16,
+            ast::MatrixFragment::A,
+            ast::MatrixType::F16 | ast::MatrixType::BF16,
+        ) => 4,
+        (
+            ast::MatrixShape::M16N8K16,
+            ast::MatrixFragment::B,
+            ast::MatrixType::F16 | ast::MatrixType::BF16,
+        ) => 2,
+        (
+            ast::MatrixShape::M16N8K16,
+            ast::MatrixFragment::A,
+            ast::MatrixType::S8 | ast::MatrixType::U8,
+        ) => 2,
+        (
+            ast::MatrixShape::M16N8K16,
+            ast::MatrixFragment::B,
+            ast::MatrixType::S8 | ast::MatrixType::U8,
+        ) => 1,
+        (ast::MatrixShape::M16N8K8, ast::MatrixFragment::A, ast::MatrixType::TF32) => 4,
+        (ast::MatrixShape::M16N8K8, ast::MatrixFragment::B, ast::MatrixType::TF32) => 2,
+        (
+            ast::MatrixShape::M16N8K16 | ast::MatrixShape::M16N8K8,
+            ast::MatrixFragment::C,
+            ast::MatrixType::F32 | ast::MatrixType::S32,
+        ) => 4,
+        (ast::MatrixShape::M16N8K16, ast::MatrixFragment::C, ast::MatrixType::F16) => 2,
+        (
+            ast::MatrixShape::M8N8K4,
+            ast::MatrixFragment::A | ast::MatrixFragment::B,
+            ast::MatrixType::F16,
+        ) => 2,
+        (ast::MatrixShape::M8N8K4, ast::MatrixFragment::C, ast::MatrixType::F32) => 8,
+        (ast::MatrixShape::M8N8K4, ast::MatrixFragment::C, ast::MatrixType::F16) => 4,
+        _ => return Err(TranslateError::mismatched_type()),
+    })
+}
+
+impl ast::WmmaLoadDetails {
+    fn fragment_type(&self) -> Result<ast::Type, TranslateError> {
+        let registers = wmma_fragment_registers(self.shape, self.fragment, self.type_)?;
+        Ok(matrix_fragment_type(registers))
+    }
+}
+
+impl ast::WmmaStoreDetails {
+    fn fragment_type(&self) -> Result<ast::Type, TranslateError> {
+        let registers = wmma_fragment_registers(self.shape, ast::MatrixFragment::C, self.type_)?;
+        Ok(matrix_fragment_type(registers))
+    }
+}
+
+impl ast::MmaDetails {
+    // Returns types of d, a, b and c fragments
+    fn fragment_types(&self, is_wmma: bool) -> Result<[ast::Type; 4], TranslateError> {
+        let valid_types = match (self.a_type, self.b_type) {
+            (ast::MatrixType::F16, ast::MatrixType::F16) => {
+                !self.satfinite
+                    && matches!(self.d_type, ast::MatrixType::F16 | ast::MatrixType::F32)
+                    && matches!(self.c_type, ast::MatrixType::F16 | ast::MatrixType::F32)
+            }
+            (ast::MatrixType::BF16, ast::MatrixType::BF16)
+            | (ast::MatrixType::TF32, ast::MatrixType::TF32) => {
+                !self.satfinite
+                    && self.d_type == ast::MatrixType::F32
+                    && self.c_type == ast::MatrixType::F32
+            }
+            (
+                ast::MatrixType::S8 | ast::MatrixType::U8,
+                ast::MatrixType::S8 | ast::MatrixType::U8,
+            ) => {
+                (!is_wmma || self.a_type == self.b_type)
+                    && self.d_type == ast::MatrixType::S32
+                    && self.c_type == ast::MatrixType::S32
+            }
+            _ => false,
+        };
+        if !valid_types {
+            return Err(TranslateError::mismatched_type());
+        }
+        let fragment_registers = if is_wmma {
+            wmma_fragment_registers
+        } else {
+            // Only the layouts used by CUTLASS are supported. m8n8k4 accumulators
+            // have different layouts for .f16 and .f32, so no mixing those either
+            if self.a_layout != ast::MatrixLayout::Row
+                || self.b_layout != ast::MatrixLayout::Col
+                || (self.shape == ast::MatrixShape::M8N8K4 && self.d_type != self.c_type)
+            {
+                return Err(TranslateError::todo());
+            }
+            mma_fragment_registers
+        };
+        Ok([
+            matrix_fragment_type(fragment_registers(
+                self.shape,
+                ast::MatrixFragment::C,
+                self.d_type,
+            )?),
+            matrix_fragment_type(fragment_registers(
+                self.shape,
+                ast::MatrixFragment::A,
+                self.a_type,
+            )?),
+            matrix_fragment_type(fragment_registers(
+                self.shape,
+                ast::MatrixFragment::B,
+                self.b_type,
+            )?),
+            matrix_fragment_type(fragment_registers(
+                self.shape,
+                ast::MatrixFragment::C,
+                self.c_type,
+            )?),
+        ])
+    }
+
+    fn ptx_suffix(&self) -> String {
+        let satfinite = if self.satfinite { "_satfinite" } else { "" };
+        [
+            self.shape.to_ptx_name(),
+            "_",
+            self.d_type.to_ptx_name(),
+            "_",
+            self.a_type.to_ptx_name(),
+            "_",
+            self.b_type.to_ptx_name(),
+            "_",
+            self.c_type.to_ptx_name(),
+            satfinite,
+        ]
+        .concat()
+    }
+}
 struct VectorRepackVisitor<'a, 'input, V> {
     extra_vistor: &'a mut V,
     func: &'a mut Vec<TypedStatement>,
@@ -4898,6 +5968,14 @@ enum PtxSpecialRegister {
     LanemaskGe,
     Laneid,
     Clock64,
+    Envreg(u8),
+    Clusterid,
+    Nclusterid,
+    ClusterCtaid,
+    ClusterNctaid,
+    ClusterCtarank,
+    ClusterNctarank,
+    IsExplicitCluster,
 }
 
 impl PtxSpecialRegister {
@@ -4913,7 +5991,24 @@ impl PtxSpecialRegister {
             "%lanemask_ge" => Some(Self::LanemaskGe),
             "%laneid" => Some(Self::Laneid),
             "%clock64" => Some(Self::Clock64),
-            _ => None,
+            "%clusterid" => Some(Self::Clusterid),
+            "%nclusterid" => Some(Self::Nclusterid),
+            "%cluster_ctaid" => Some(Self::ClusterCtaid),
+            "%cluster_nctaid" => Some(Self::ClusterNctaid),
+            "%cluster_ctarank" => Some(Self::ClusterCtarank),
+            "%cluster_nctarank" => Some(Self::ClusterNctarank),
+            "%is_explicit_cluster" => Some(Self::IsExplicitCluster),
+            _ => {
+                let index = s.strip_prefix("%envreg")?;
+                if index.starts_with('0') && index != "0" {
+                    return None;
+                }
+                index
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|index| *index < 32)
+                    .map(Self::Envreg)
+            }
         }
     }
 
@@ -4922,7 +6017,13 @@ impl PtxSpecialRegister {
             PtxSpecialRegister::Tid
             | PtxSpecialRegister::Ntid
             | PtxSpecialRegister::Ctaid
-            | PtxSpecialRegister::Nctaid => ast::Type::Vector(self.get_function_return_type(), 4),
+            | PtxSpecialRegister::Nctaid
+            | PtxSpecialRegister::Clusterid
+            | PtxSpecialRegister::Nclusterid
+            | PtxSpecialRegister::ClusterCtaid
+            | PtxSpecialRegister::ClusterNctaid => {
+                ast::Type::Vector(self.get_function_return_type(), 4)
+            }
             _ => ast::Type::Scalar(self.get_function_return_type()),
         }
     }
@@ -4937,8 +6038,16 @@ impl PtxSpecialRegister {
             | PtxSpecialRegister::LanemaskLt
             | PtxSpecialRegister::LanemaskLe
             | PtxSpecialRegister::LanemaskGe
-            | PtxSpecialRegister::Laneid => ast::ScalarType::U32,
+            | PtxSpecialRegister::Laneid
+            | PtxSpecialRegister::Envreg(_)
+            | PtxSpecialRegister::Clusterid
+            | PtxSpecialRegister::Nclusterid
+            | PtxSpecialRegister::ClusterCtaid
+            | PtxSpecialRegister::ClusterNctaid
+            | PtxSpecialRegister::ClusterCtarank
+            | PtxSpecialRegister::ClusterNctarank => ast::ScalarType::U32,
             PtxSpecialRegister::Clock64 => ast::ScalarType::U64,
+            PtxSpecialRegister::IsExplicitCluster => ast::ScalarType::Pred,
         }
     }
 
@@ -4947,13 +6056,21 @@ impl PtxSpecialRegister {
             PtxSpecialRegister::Tid
             | PtxSpecialRegister::Ntid
             | PtxSpecialRegister::Ctaid
-            | PtxSpecialRegister::Nctaid => Some(ast::ScalarType::U8),
+            | PtxSpecialRegister::Nctaid
+            | PtxSpecialRegister::Envreg(_)
+            | PtxSpecialRegister::Clusterid
+            | PtxSpecialRegister::Nclusterid
+            | PtxSpecialRegister::ClusterCtaid
+            | PtxSpecialRegister::ClusterNctaid => Some(ast::ScalarType::U8),
             PtxSpecialRegister::Clock
             | PtxSpecialRegister::Clock64
             | PtxSpecialRegister::LanemaskLt
             | PtxSpecialRegister::LanemaskLe
             | PtxSpecialRegister::LanemaskGe
-            | PtxSpecialRegister::Laneid => None,
+            | PtxSpecialRegister::Laneid
+            | PtxSpecialRegister::ClusterCtarank
+            | PtxSpecialRegister::ClusterNctarank
+            | PtxSpecialRegister::IsExplicitCluster => None,
         }
     }
 
@@ -4969,6 +6086,14 @@ impl PtxSpecialRegister {
             PtxSpecialRegister::LanemaskLe => "sreg_lanemask_le",
             PtxSpecialRegister::LanemaskGe => "sreg_lanemask_ge",
             PtxSpecialRegister::Laneid => "sreg_laneid",
+            PtxSpecialRegister::Envreg(_) => "sreg_envreg",
+            PtxSpecialRegister::Clusterid => "sreg_clusterid",
+            PtxSpecialRegister::Nclusterid => "sreg_nclusterid",
+            PtxSpecialRegister::ClusterCtaid => "sreg_cluster_ctaid",
+            PtxSpecialRegister::ClusterNctaid => "sreg_cluster_nctaid",
+            PtxSpecialRegister::ClusterCtarank => "sreg_cluster_ctarank",
+            PtxSpecialRegister::ClusterNctarank => "sreg_cluster_nctarank",
+            PtxSpecialRegister::IsExplicitCluster => "sreg_is_explicit_cluster",
         }
     }
 }
@@ -5043,6 +6168,9 @@ pub(crate) struct IdNameMapBuilder<'input> {
     pub(crate) id_gen: IdGenerator,
     type_check: FxHashMap<Id, Option<(ast::Type, ast::StateSpace, Option<u32>, bool)>>,
     pub(crate) globals: GlobalsResolver<'input>,
+    // Span of the statement currently processed by a pass, attached to the
+    // errors it returns
+    pub(crate) current_span: Option<StatementSpan>,
 }
 
 impl<'input> IdNameMapBuilder<'input> {
@@ -5057,6 +6185,7 @@ impl<'input> IdNameMapBuilder<'input> {
             id_gen,
             globals,
             type_check: FxHashMap::default(),
+            current_span: None,
         }
     }
 
@@ -5562,6 +6691,8 @@ pub(crate) enum Statement<I, P: ast::ArgParams> {
         asm: &'static str,
         constraints: &'static str,
     },
+    Location(ast::SourceLocation),
+    Span(StatementSpan),
 }
 
 impl ExpandedStatement {
@@ -5702,6 +6833,8 @@ impl ExpandedStatement {
             Statement::AsmVolatile { asm, constraints } => {
                 Statement::AsmVolatile { asm, constraints }
             }
+            Statement::Location(location) => Statement::Location(location),
+            Statement::Span(span) => Statement::Span(span),
         }
     }
 }
@@ -6242,6 +7375,11 @@ impl<T: ArgParamsEx> ast::Instruction<T> {
                 };
                 ast::Instruction::Cvt(d, a.map_cvt(visitor, dst_t, src_t, int_to_int)?)
             }
+            ast::Instruction::CvtPackedFloat(d, a) => {
+                let dst_t = ast::Type::Scalar(d.dst);
+                let src_t = ast::Type::Scalar(d.src);
+                ast::Instruction::CvtPackedFloat(d, a.map_different_types(visitor, &dst_t, &src_t)?)
+            }
             ast::Instruction::Shl(t, a) => {
                 ast::Instruction::Shl(t, a.map_shift(visitor, &ast::Type::Scalar(t))?)
             }
@@ -6335,12 +7473,17 @@ impl<T: ArgParamsEx> ast::Instruction<T> {
                     arg: arg.map(visitor, &typ)?,
                 }
             }
-            ast::Instruction::Ex2 { flush_to_zero, arg } => {
-                let typ = ast::Type::Scalar(ast::ScalarType::F32);
-                ast::Instruction::Ex2 {
-                    flush_to_zero,
-                    arg: arg.map(visitor, &typ)?,
-                }
+            ast::Instruction::Ex2 {
+                typ,
+                flush_to_zero,
+                arg,
+            } => ast::Instruction::Ex2 {
+                typ,
+                flush_to_zero,
+                arg: arg.map(visitor, &ast::Type::Scalar(typ))?,
+            },
+            ast::Instruction::Tanh(typ, arg) => {
+                ast::Instruction::Tanh(typ, arg.map(visitor, &ast::Type::Scalar(typ))?)
             }
             ast::Instruction::Clz { typ, arg } => {
                 let dst_type = ast::Type::Scalar(ast::ScalarType::B32);
@@ -6429,6 +7572,10 @@ impl<T: ArgParamsEx> ast::Instruction<T> {
                 )?,
             },
             ast::Instruction::Membar { level } => ast::Instruction::Membar { level },
+            ast::Instruction::Fence { semantics, scope } => {
+                ast::Instruction::Fence { semantics, scope }
+            }
+            ast::Instruction::FenceProxy(kind) => ast::Instruction::FenceProxy(kind),
             ast::Instruction::MadC {
                 type_,
                 arg,
@@ -6463,6 +7610,28 @@ impl<T: ArgParamsEx> ast::Instruction<T> {
                 )?;
                 ast::Instruction::Tex(details, arg)
             }
+            ast::Instruction::Tld4(details, arg) => {
+                let image_type_space = if details.direct {
+                    (ast::Type::Texref, ast::StateSpace::Global)
+                } else {
+                    (
+                        ast::Type::Scalar(ast::ScalarType::B64),
+                        ast::StateSpace::Reg,
+                    )
+                };
+                let arg = arg.map(
+                    visitor,
+                    image_type_space,
+                    ast::TextureGeometry::TwoD,
+                    ast::Type::Vector(details.channel_type, 4),
+                    ast::ScalarType::F32,
+                )?;
+                ast::Instruction::Tld4(details, arg)
+            }
+            ast::Instruction::Txq(details, arg) => {
+                let arg = arg.map(visitor, details.direct)?;
+                ast::Instruction::Txq(details, arg)
+            }
             ast::Instruction::Suld(details, arg) => {
                 let image_type_space = if details.direct {
                     (ast::Type::Texref, ast::StateSpace::Global)
@@ -6485,6 +7654,10 @@ impl<T: ArgParamsEx> ast::Instruction<T> {
                 let arg = arg.map(visitor, &details)?;
                 ast::Instruction::Sust(details, arg)
             }
+            ast::Instruction::Suq(details, arg) => {
+                let arg = arg.map(visitor, details.direct)?;
+                ast::Instruction::Suq(details, arg)
+            }
             ast::Instruction::Shfl(mode, arg) => {
                 let arg = arg.map(visitor)?;
                 ast::Instruction::Shfl(mode, arg)
@@ -6536,17 +7709,59 @@ impl<T: ArgParamsEx> ast::Instruction<T> {
                 let arg = arg.map(visitor, &ast::Type::Scalar(type_), false)?;
                 ast::Instruction::Dp4a(type_, arg)
             }
+            ast::Instruction::Copysign(typ, arg) => {
+                let arg = arg.map_generic(visitor, &ast::Type::Scalar(typ), false)?;
+                ast::Instruction::Copysign(typ, arg)
+            }
+            ast::Instruction::Testp(op, typ, arg) => {
+                let dst_type = ast::Type::Scalar(ast::ScalarType::Pred);
+                let src_type = ast::Type::Scalar(typ);
+                ast::Instruction::Testp(
+                    op,
+                    typ,
+                    arg.map_different_types(visitor, &dst_type, &src_type)?,
+                )
+            }
+            ast::Instruction::CvtPack(details, arg) => {
+                let arg = arg.map(visitor, &ast::Type::Scalar(ast::ScalarType::B32), false)?;
+                ast::Instruction::CvtPack(details, arg)
+            }
             ast::Instruction::MatchAny(arg) => {
                 let arg =
                     arg.map_generic(visitor, &ast::Type::Scalar(ast::ScalarType::B32), false)?;
                 ast::Instruction::MatchAny(arg)
             }
+            ast::Instruction::Redux(details, arg) => {
+                let arg = arg.map_generic(visitor, &ast::Type::Scalar(details.type_), false)?;
+                ast::Instruction::Redux(details, arg)
+            }
+            ast::Instruction::Lop3 { lut, arg } => ast::Instruction::Lop3 {
+                lut,
+                arg: arg.map(visitor, &ast::Type::Scalar(ast::ScalarType::B32), false)?,
+            },
+            ast::Instruction::Bmsk(mode, arg) => {
+                let arg =
+                    arg.map_generic(visitor, &ast::Type::Scalar(ast::ScalarType::B32), false)?;
+                ast::Instruction::Bmsk(mode, arg)
+            }
+            ast::Instruction::Szext(mode, type_, arg) => {
+                let arg = arg.map_generic(visitor, &ast::Type::Scalar(type_), false)?;
+                ast::Instruction::Szext(mode, type_, arg)
+            }
+            ast::Instruction::Fns(arg) => {
+                let arg = arg.map(visitor, &ast::Type::Scalar(ast::ScalarType::B32), false)?;
+                ast::Instruction::Fns(arg)
+            }
+            ast::Instruction::Mul24(details, arg) => {
+                let arg = arg.map_generic(visitor, &ast::Type::Scalar(details.type_), false)?;
+                ast::Instruction::Mul24(details, arg)
+            }
+            ast::Instruction::Mad24(details, arg) => {
+                let arg = arg.map(visitor, &ast::Type::Scalar(details.type_), false)?;
+                ast::Instruction::Mad24(details, arg)
+            }
             ast::Instruction::Red(details, args) => {
-                let args = args.map(
-                    visitor,
-                    &ast::Type::Scalar(details.inner.get_type()),
-                    details.space,
-                )?;
+                let args = args.map(visitor, &details.value_type(), details.space)?;
                 ast::Instruction::Red(details, args)
             }
             ast::Instruction::Nanosleep(a) => ast::Instruction::Nanosleep(a.map(
@@ -6568,6 +7783,62 @@ impl<T: ArgParamsEx> ast::Instruction<T> {
                     &ast::Type::Scalar(ast::ScalarType::U64),
                 )?,
             ),
+            ast::Instruction::WmmaLoad(details, arg) => {
+                let arg = arg.map_wmma_load(visitor, &details)?;
+                ast::Instruction::WmmaLoad(details, arg)
+            }
+            ast::Instruction::WmmaStore(details, arg) => {
+                let arg = arg.map_wmma_store(visitor, &details)?;
+                ast::Instruction::WmmaStore(details, arg)
+            }
+            ast::Instruction::WmmaMma(details, arg) => {
+                let arg = arg.map_mma(visitor, &details.fragment_types(true)?)?;
+                ast::Instruction::WmmaMma(details, arg)
+            }
+            ast::Instruction::Mma(details, arg) => {
+                let arg = arg.map_mma(visitor, &details.fragment_types(false)?)?;
+                ast::Instruction::Mma(details, arg)
+            }
+            ast::Instruction::CpAsync(details, arg) => {
+                ast::Instruction::CpAsync(details, arg.map_cp_async(visitor)?)
+            }
+            ast::Instruction::CpAsyncCommitGroup => ast::Instruction::CpAsyncCommitGroup,
+            ast::Instruction::CpAsyncWaitGroup(n) => ast::Instruction::CpAsyncWaitGroup(n),
+            ast::Instruction::MbarrierInit(space, arg) => {
+                ast::Instruction::MbarrierInit(space, arg.map_mbarrier_init(visitor, space)?)
+            }
+            ast::Instruction::MbarrierArrive(space, arg) => {
+                let arg =
+                    arg.map_mbarrier(visitor, ast::ScalarType::B64, ast::ScalarType::U32, space)?;
+                ast::Instruction::MbarrierArrive(space, arg)
+            }
+            ast::Instruction::MbarrierTestWait(details, arg) => {
+                let src2_type = if details.parity {
+                    ast::ScalarType::U32
+                } else {
+                    ast::ScalarType::B64
+                };
+                let arg = arg.map_mbarrier(
+                    visitor,
+                    ast::ScalarType::Pred,
+                    src2_type,
+                    details.state_space,
+                )?;
+                ast::Instruction::MbarrierTestWait(details, arg)
+            }
+            ast::Instruction::Mapa(type_, arg) => {
+                ast::Instruction::Mapa(type_, arg.map_shift(visitor, &ast::Type::Scalar(type_))?)
+            }
+            ast::Instruction::Getctarank(type_, arg) => {
+                let dst_type = ast::Type::Scalar(ast::ScalarType::U32);
+                let src_type = ast::Type::Scalar(type_);
+                ast::Instruction::Getctarank(
+                    type_,
+                    arg.map_different_types(visitor, &dst_type, &src_type)?,
+                )
+            }
+            ast::Instruction::BarrierCluster(op) => ast::Instruction::BarrierCluster(op),
+            ast::Instruction::ElectSync(arg) => ast::Instruction::ElectSync(arg.map(visitor)?),
 
         })
     }
@@ -6845,6 +8116,11 @@ impl<T: ast::ArgParams> ast::Instruction<T> {
                 ast::ScalarType::B64 => 8,
                 ast::ScalarType::F64 => 8,
                 ast::ScalarType::F16x2 => 2,
+                ast::ScalarType::BF16 => 2,
+                ast::ScalarType::BF16x2 => 2,
+                ast::ScalarType::TF32 => 4,
+                ast::ScalarType::E4M3x2 => 1,
+                ast::ScalarType::E5M2x2 => 1,
                 ast::ScalarType::Pred => 1,
             }
         }
@@ -6880,7 +8156,18 @@ impl<T: ast::ArgParams> ast::Instruction<T> {
             ast::Instruction::SubCC { .. } => None,
             ast::Instruction::Vshr { .. } => None,
             ast::Instruction::Dp4a { .. } => None,
+            ast::Instruction::Tanh(..) => None,
+            ast::Instruction::Copysign(..) => None,
+            ast::Instruction::Testp(..) => None,
+            ast::Instruction::CvtPack(..) => None,
             ast::Instruction::MatchAny { .. } => None,
+            ast::Instruction::Redux(..) => None,
+            ast::Instruction::Lop3 { .. } => None,
+            ast::Instruction::Bmsk(..) => None,
+            ast::Instruction::Szext(..) => None,
+            ast::Instruction::Fns(..) => None,
+            ast::Instruction::Mul24(..) => None,
+            ast::Instruction::Mad24(..) => None,
             ast::Instruction::Isspacep { .. } => None,
             ast::Instruction::Sub(ast::ArithDetails::Signed(_), _) => None,
             ast::Instruction::Sub(ast::ArithDetails::Unsigned(_), _) => None,
@@ -6896,6 +8183,7 @@ impl<T: ast::ArgParams> ast::Instruction<T> {
             ast::Instruction::Max(ast::MinMaxDetails::Unsigned(_), _) => None,
             ast::Instruction::Cvt(ast::CvtDetails::IntFromInt(_), _) => None,
             ast::Instruction::Cvt(ast::CvtDetails::FloatFromInt(_), _) => None,
+            ast::Instruction::CvtPackedFloat(_, _) => None,
             ast::Instruction::Div(ast::DivDetails::Unsigned(_), _) => None,
             ast::Instruction::Div(ast::DivDetails::Signed(_), _) => None,
             ast::Instruction::Clz { .. } => None,
@@ -6910,14 +8198,33 @@ impl<T: ast::ArgParams> ast::Instruction<T> {
             ast::Instruction::PrmtSlow { .. } => None,
             ast::Instruction::Activemask { .. } => None,
             ast::Instruction::Membar { .. } => None,
+            ast::Instruction::Fence { .. } => None,
+            ast::Instruction::FenceProxy(..) => None,
             ast::Instruction::Tex(..) => None,
+            ast::Instruction::Tld4(..) => None,
+            ast::Instruction::Txq(..) => None,
             ast::Instruction::Suld(..) => None,
             ast::Instruction::Sust(..) => None,
+            ast::Instruction::Suq(..) => None,
             ast::Instruction::Shfl(..) => None,
             ast::Instruction::Shf(..) => None,
             ast::Instruction::Vote(..) => None,
             ast::Instruction::Nanosleep(..) => None,
             ast::Instruction::Sad(_, _) => None,
+            ast::Instruction::WmmaLoad(..) => None,
+            ast::Instruction::WmmaStore(..) => None,
+            ast::Instruction::WmmaMma(..) => None,
+            ast::Instruction::Mma(..) => None,
+            ast::Instruction::CpAsync(..) => None,
+            ast::Instruction::CpAsyncCommitGroup => None,
+            ast::Instruction::CpAsyncWaitGroup(..) => None,
+            ast::Instruction::MbarrierInit(..) => None,
+            ast::Instruction::MbarrierArrive(..) => None,
+            ast::Instruction::MbarrierTestWait(..) => None,
+            ast::Instruction::Mapa(..) => None,
+            ast::Instruction::Getctarank(..) => None,
+            ast::Instruction::BarrierCluster(..) => None,
+            ast::Instruction::ElectSync(..) => None,
             ast::Instruction::Sub(ast::ArithDetails::Float(float_control), _)
             | ast::Instruction::Add(ast::ArithDetails::Float(float_control), _)
             | ast::Instruction::Mul(ast::MulDetails::Float(float_control), _)
@@ -6977,10 +8284,12 @@ impl<T: ast::ArgParams> ast::Instruction<T> {
                 .map(|ftz| (ftz, scalar_size_of(details.typ))),
             ast::Instruction::Sin { flush_to_zero, .. }
             | ast::Instruction::Cos { flush_to_zero, .. }
-            | ast::Instruction::Lg2 { flush_to_zero, .. }
-            | ast::Instruction::Ex2 { flush_to_zero, .. } => {
+            | ast::Instruction::Lg2 { flush_to_zero, .. } => {
                 Some((*flush_to_zero, mem::size_of::<f32>() as u8))
             }
+            ast::Instruction::Ex2 {
+                typ, flush_to_zero, ..
+            } => Some((*flush_to_zero, scalar_size_of(*typ))),
             ast::Instruction::Set(
                 ast::SetData {
                     flush_to_zero,
@@ -7699,6 +9008,34 @@ impl<T: ArgParamsEx> ast::Arg2St<T> {
         )?;
         Ok(ast::Arg2St { src1, src2 })
     }
+
+    fn map_mbarrier_init<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
+        self,
+        visitor: &mut V,
+        state_space: ast::StateSpace,
+    ) -> Result<ast::Arg2St<U>, TranslateError> {
+        let src1 = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src1,
+                is_dst: false,
+                is_memory_access: true,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(ast::ScalarType::B64),
+            state_space,
+        )?;
+        let src2 = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src2,
+                is_dst: false,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(ast::ScalarType::U32),
+            ast::StateSpace::Reg,
+        )?;
+        Ok(ast::Arg2St { src1, src2 })
+    }
 }
 
 impl<T: ArgParamsEx> ast::Arg2Mov<T> {
@@ -7837,7 +9174,164 @@ impl<T: ArgParamsEx> ast::Arg3<T> {
                 is_memory_access: false,
                 non_default_implicit_conversion: None,
             },
-            t,
+            t,
+            ast::StateSpace::Reg,
+        )?;
+        let src2 = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src2,
+                is_dst: false,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(ast::ScalarType::U32),
+            ast::StateSpace::Reg,
+        )?;
+        Ok(ast::Arg3 { dst, src1, src2 })
+    }
+
+    fn map_atom<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
+        self,
+        visitor: &mut V,
+        t: ast::ScalarType,
+        state_space: ast::StateSpace,
+    ) -> Result<ast::Arg3<U>, TranslateError> {
+        let scalar_type = ast::ScalarType::from(t);
+        let dst = visitor.operand(
+            ArgumentDescriptor {
+                op: self.dst,
+                is_dst: true,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(scalar_type),
+            ast::StateSpace::Reg,
+        )?;
+        let src1 = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src1,
+                is_dst: false,
+                is_memory_access: true,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(scalar_type),
+            state_space,
+        )?;
+        let src2 = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src2,
+                is_dst: false,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(scalar_type),
+            ast::StateSpace::Reg,
+        )?;
+        Ok(ast::Arg3 { dst, src1, src2 })
+    }
+
+    fn map_prmt<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
+        self,
+        visitor: &mut V,
+    ) -> Result<ast::Arg3<U>, TranslateError> {
+        let dst = visitor.operand(
+            ArgumentDescriptor {
+                op: self.dst,
+                is_dst: true,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(ast::ScalarType::B32),
+            ast::StateSpace::Reg,
+        )?;
+        let src1 = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src1,
+                is_dst: false,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(ast::ScalarType::B32),
+            ast::StateSpace::Reg,
+        )?;
+        let src2 = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src2,
+                is_dst: false,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(ast::ScalarType::B32),
+            ast::StateSpace::Reg,
+        )?;
+        Ok(ast::Arg3 { dst, src1, src2 })
+    }
+
+    fn map_vote<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
+        self,
+        visitor: &mut V,
+        mode: ast::VoteMode,
+    ) -> Result<ast::Arg3<U>, TranslateError> {
+        let dst_type = match mode {
+            ast::VoteMode::Ballot => ast::ScalarType::B32,
+            ast::VoteMode::All | ast::VoteMode::Any | ast::VoteMode::Uni => ast::ScalarType::Pred,
+        };
+        let dst = visitor.operand(
+            ArgumentDescriptor {
+                op: self.dst,
+                is_dst: true,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(dst_type),
+            ast::StateSpace::Reg,
+        )?;
+        let src1 = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src1,
+                is_dst: false,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(ast::ScalarType::Pred),
+            ast::StateSpace::Reg,
+        )?;
+        let src2 = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src2,
+                is_dst: false,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(ast::ScalarType::B32),
+            ast::StateSpace::Reg,
+        )?;
+        Ok(ast::Arg3 { dst, src1, src2 })
+    }
+
+    fn map_bar_red<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
+        self,
+        visitor: &mut V,
+        op: ast::ReductionOp,
+    ) -> Result<ast::Arg3<U>, TranslateError> {
+        let dst = visitor.operand(
+            ArgumentDescriptor {
+                op: self.dst,
+                is_dst: true,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(op.dst_type()),
+            ast::StateSpace::Reg,
+        )?;
+        let src1 = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src1,
+                is_dst: false,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(ast::ScalarType::B32),
             ast::StateSpace::Reg,
         )?;
         let src2 = visitor.operand(
@@ -7847,27 +9341,25 @@ impl<T: ArgParamsEx> ast::Arg3<T> {
                 is_memory_access: false,
                 non_default_implicit_conversion: None,
             },
-            &ast::Type::Scalar(ast::ScalarType::U32),
+            &ast::Type::Scalar(ast::ScalarType::Pred),
             ast::StateSpace::Reg,
         )?;
         Ok(ast::Arg3 { dst, src1, src2 })
     }
 
-    fn map_atom<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
+    fn map_wmma_load<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
         self,
         visitor: &mut V,
-        t: ast::ScalarType,
-        state_space: ast::StateSpace,
+        details: &ast::WmmaLoadDetails,
     ) -> Result<ast::Arg3<U>, TranslateError> {
-        let scalar_type = ast::ScalarType::from(t);
         let dst = visitor.operand(
             ArgumentDescriptor {
                 op: self.dst,
                 is_dst: true,
                 is_memory_access: false,
-                non_default_implicit_conversion: None,
+                non_default_implicit_conversion: Some(should_convert_relaxed_dst_wrapper),
             },
-            &ast::Type::Scalar(scalar_type),
+            &details.fragment_type()?,
             ast::StateSpace::Reg,
         )?;
         let src1 = visitor.operand(
@@ -7877,8 +9369,8 @@ impl<T: ArgParamsEx> ast::Arg3<T> {
                 is_memory_access: true,
                 non_default_implicit_conversion: None,
             },
-            &ast::Type::Scalar(scalar_type),
-            state_space,
+            &ast::Type::Scalar(details.type_.memory_type()),
+            details.state_space,
         )?;
         let src2 = visitor.operand(
             ArgumentDescriptor {
@@ -7887,15 +9379,18 @@ impl<T: ArgParamsEx> ast::Arg3<T> {
                 is_memory_access: false,
                 non_default_implicit_conversion: None,
             },
-            &ast::Type::Scalar(scalar_type),
+            &ast::Type::Scalar(ast::ScalarType::U32),
             ast::StateSpace::Reg,
         )?;
         Ok(ast::Arg3 { dst, src1, src2 })
     }
 
-    fn map_prmt<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
+    fn map_mbarrier<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
         self,
         visitor: &mut V,
+        dst_type: ast::ScalarType,
+        src2_type: ast::ScalarType,
+        state_space: ast::StateSpace,
     ) -> Result<ast::Arg3<U>, TranslateError> {
         let dst = visitor.operand(
             ArgumentDescriptor {
@@ -7904,18 +9399,18 @@ impl<T: ArgParamsEx> ast::Arg3<T> {
                 is_memory_access: false,
                 non_default_implicit_conversion: None,
             },
-            &ast::Type::Scalar(ast::ScalarType::B32),
+            &ast::Type::Scalar(dst_type),
             ast::StateSpace::Reg,
         )?;
         let src1 = visitor.operand(
             ArgumentDescriptor {
                 op: self.src1,
                 is_dst: false,
-                is_memory_access: false,
+                is_memory_access: true,
                 non_default_implicit_conversion: None,
             },
-            &ast::Type::Scalar(ast::ScalarType::B32),
-            ast::StateSpace::Reg,
+            &ast::Type::Scalar(ast::ScalarType::B64),
+            state_space,
         )?;
         let src2 = visitor.operand(
             ArgumentDescriptor {
@@ -7924,90 +9419,87 @@ impl<T: ArgParamsEx> ast::Arg3<T> {
                 is_memory_access: false,
                 non_default_implicit_conversion: None,
             },
-            &ast::Type::Scalar(ast::ScalarType::B32),
+            &ast::Type::Scalar(src2_type),
             ast::StateSpace::Reg,
         )?;
         Ok(ast::Arg3 { dst, src1, src2 })
     }
+}
 
-    fn map_vote<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
+impl<T: ArgParamsEx> ast::Arg3St<T> {
+    fn map_wmma_store<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
         self,
         visitor: &mut V,
-        mode: ast::VoteMode,
-    ) -> Result<ast::Arg3<U>, TranslateError> {
-        let dst_type = match mode {
-            ast::VoteMode::Ballot => ast::ScalarType::B32,
-            ast::VoteMode::All | ast::VoteMode::Any | ast::VoteMode::Uni => ast::ScalarType::Pred,
-        };
-        let dst = visitor.operand(
-            ArgumentDescriptor {
-                op: self.dst,
-                is_dst: true,
-                is_memory_access: false,
-                non_default_implicit_conversion: None,
-            },
-            &ast::Type::Scalar(dst_type),
-            ast::StateSpace::Reg,
-        )?;
+        details: &ast::WmmaStoreDetails,
+    ) -> Result<ast::Arg3St<U>, TranslateError> {
         let src1 = visitor.operand(
             ArgumentDescriptor {
                 op: self.src1,
                 is_dst: false,
-                is_memory_access: false,
+                is_memory_access: true,
                 non_default_implicit_conversion: None,
             },
-            &ast::Type::Scalar(ast::ScalarType::Pred),
-            ast::StateSpace::Reg,
+            &ast::Type::Scalar(details.type_.memory_type()),
+            details.state_space,
         )?;
         let src2 = visitor.operand(
             ArgumentDescriptor {
                 op: self.src2,
                 is_dst: false,
                 is_memory_access: false,
-                non_default_implicit_conversion: None,
+                non_default_implicit_conversion: Some(should_convert_relaxed_src_wrapper),
             },
-            &ast::Type::Scalar(ast::ScalarType::B32),
+            &details.fragment_type()?,
             ast::StateSpace::Reg,
         )?;
-        Ok(ast::Arg3 { dst, src1, src2 })
-    }
-
-    fn map_bar_red<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
-        self,
-        visitor: &mut V,
-        op: ast::ReductionOp,
-    ) -> Result<ast::Arg3<U>, TranslateError> {
-        let dst = visitor.operand(
+        let src3 = visitor.operand(
             ArgumentDescriptor {
-                op: self.dst,
-                is_dst: true,
+                op: self.src3,
+                is_dst: false,
                 is_memory_access: false,
                 non_default_implicit_conversion: None,
             },
-            &ast::Type::Scalar(op.dst_type()),
+            &ast::Type::Scalar(ast::ScalarType::U32),
             ast::StateSpace::Reg,
         )?;
+        Ok(ast::Arg3St { src1, src2, src3 })
+    }
+
+    fn map_cp_async<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
+        self,
+        visitor: &mut V,
+    ) -> Result<ast::Arg3St<U>, TranslateError> {
         let src1 = visitor.operand(
             ArgumentDescriptor {
                 op: self.src1,
                 is_dst: false,
-                is_memory_access: false,
+                is_memory_access: true,
                 non_default_implicit_conversion: None,
             },
-            &ast::Type::Scalar(ast::ScalarType::B32),
-            ast::StateSpace::Reg,
+            &ast::Type::Scalar(ast::ScalarType::B8),
+            ast::StateSpace::Shared,
         )?;
         let src2 = visitor.operand(
             ArgumentDescriptor {
                 op: self.src2,
                 is_dst: false,
+                is_memory_access: true,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(ast::ScalarType::B8),
+            ast::StateSpace::Global,
+        )?;
+        let src3 = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src3,
+                is_dst: false,
                 is_memory_access: false,
                 non_default_implicit_conversion: None,
             },
-            &ast::Type::Scalar(ast::ScalarType::Pred),
+            &ast::Type::Scalar(ast::ScalarType::U32),
             ast::StateSpace::Reg,
         )?;
-        Ok(ast::Arg3 { dst, src1, src2 })
+        Ok(ast::Arg3St { src1, src2, src3 })
     }
 }
 
@@ -8089,16 +9581,73 @@ impl<T: ArgParamsEx> ast::Arg5Tex<T> {
                 )
             })
             .transpose()?;
+        let gradient = self
+            .gradient
+            .map(|(dpdx, dpdy)| {
+                let mut map_gradient = |op| {
+                    visitor.operand(
+                        ArgumentDescriptor {
+                            op,
+                            is_dst: false,
+                            is_memory_access: false,
+                            non_default_implicit_conversion: None,
+                        },
+                        &ast::Type::Vector(ast::ScalarType::F32, coord_length),
+                        ast::StateSpace::Reg,
+                    )
+                };
+                Ok((map_gradient(dpdx)?, map_gradient(dpdy)?))
+            })
+            .transpose()?;
         Ok(ast::Arg5Tex {
             dst,
             image,
             layer,
             coordinates,
             lod,
+            gradient,
         })
     }
 }
 
+impl<T: ArgParamsEx> ast::Arg2Query<T> {
+    fn map<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
+        self,
+        visitor: &mut V,
+        direct: bool,
+    ) -> Result<ast::Arg2Query<U>, TranslateError> {
+        let (image_type, image_space) = if direct {
+            (ast::Type::Texref, ast::StateSpace::Global)
+        } else {
+            (
+                ast::Type::Scalar(ast::ScalarType::B64),
+                ast::StateSpace::Reg,
+            )
+        };
+        let dst = visitor.operand(
+            ArgumentDescriptor {
+                op: self.dst,
+                is_dst: true,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(ast::ScalarType::B32),
+            ast::StateSpace::Reg,
+        )?;
+        let image = visitor.operand(
+            ArgumentDescriptor {
+                op: self.image,
+                is_dst: false,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &image_type,
+            image_space,
+        )?;
+        Ok(ast::Arg2Query { dst, image })
+    }
+}
+
 impl<T: ArgParamsEx> ast::Arg4Sust<T> {
     pub(crate) fn map<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
         self,
@@ -8420,6 +9969,59 @@ impl<T: ArgParamsEx> ast::Arg4<T> {
             src3,
         })
     }
+
+    fn map_mma<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
+        self,
+        visitor: &mut V,
+        [d_type, a_type, b_type, c_type]: &[ast::Type; 4],
+    ) -> Result<ast::Arg4<U>, TranslateError> {
+        let dst = visitor.operand(
+            ArgumentDescriptor {
+                op: self.dst,
+                is_dst: true,
+                is_memory_access: false,
+                non_default_implicit_conversion: Some(should_convert_relaxed_dst_wrapper),
+            },
+            d_type,
+            ast::StateSpace::Reg,
+        )?;
+        let src1 = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src1,
+                is_dst: false,
+                is_memory_access: false,
+                non_default_implicit_conversion: Some(should_convert_relaxed_src_wrapper),
+            },
+            a_type,
+            ast::StateSpace::Reg,
+        )?;
+        let src2 = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src2,
+                is_dst: false,
+                is_memory_access: false,
+                non_default_implicit_conversion: Some(should_convert_relaxed_src_wrapper),
+            },
+            b_type,
+            ast::StateSpace::Reg,
+        )?;
+        let src3 = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src3,
+                is_dst: false,
+                is_memory_access: false,
+                non_default_implicit_conversion: Some(should_convert_relaxed_src_wrapper),
+            },
+            c_type,
+            ast::StateSpace::Reg,
+        )?;
+        Ok(ast::Arg4 {
+            dst,
+            src1,
+            src2,
+            src3,
+        })
+    }
 }
 
 impl<T: ArgParamsEx> ast::Arg4Setp<T> {
@@ -8627,6 +10229,54 @@ impl<T: ArgParamsEx> ast::Arg5Setp<T> {
     }
 }
 
+impl<T: ArgParamsEx> ast::Arg2Elect<T> {
+    fn map<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
+        self,
+        visitor: &mut V,
+    ) -> Result<ast::Arg2Elect<U>, TranslateError> {
+        let dst1 = self
+            .dst1
+            .map(|dst1| {
+                visitor.id(
+                    ArgumentDescriptor {
+                        op: dst1,
+                        is_dst: true,
+                        is_memory_access: false,
+                        non_default_implicit_conversion: None,
+                    },
+                    Some((
+                        &ast::Type::Scalar(ast::ScalarType::U32),
+                        ast::StateSpace::Reg,
+                    )),
+                )
+            })
+            .transpose()?;
+        let dst2 = visitor.id(
+            ArgumentDescriptor {
+                op: self.dst2,
+                is_dst: true,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            Some((
+                &ast::Type::Scalar(ast::ScalarType::Pred),
+                ast::StateSpace::Reg,
+            )),
+        )?;
+        let src = visitor.operand(
+            ArgumentDescriptor {
+                op: self.src,
+                is_dst: false,
+                is_memory_access: false,
+                non_default_implicit_conversion: None,
+            },
+            &ast::Type::Scalar(ast::ScalarType::B32),
+            ast::StateSpace::Reg,
+        )?;
+        Ok(ast::Arg2Elect { dst1, dst2, src })
+    }
+}
+
 impl<T: ArgParamsEx> ast::Arg5Shfl<T> {
     fn map<U: ArgParamsEx, V: ArgumentMapVisitor<T, U>>(
         self,
@@ -8817,8 +10467,44 @@ impl ast::DivDetails {
     }
 }
 
+impl ast::AtomDetails {
+    fn value_type(&self) -> ast::Type {
+        let scalar_type = self.inner.get_type();
+        match self.vector {
+            Some(len) => ast::Type::Vector(scalar_type, len),
+            None => ast::Type::Scalar(scalar_type),
+        }
+    }
+
+    // LLVM atomicrmw can't do fadd on bf16 or on packed halves
+    fn is_packed_float_add(&self) -> bool {
+        matches!(
+            self.inner,
+            ast::AtomInnerDetails::Float {
+                op: ast::AtomFloatOp::Add,
+                typ: ast::ScalarType::F16x2 | ast::ScalarType::BF16 | ast::ScalarType::BF16x2,
+            }
+        )
+    }
+
+    fn packed_float_add_fn_name(&self, prefix: &str) -> String {
+        [
+            ZLUDA_PTX_PREFIX,
+            prefix,
+            self.semantics.to_ptx_name(),
+            "_",
+            self.scope.to_ptx_name(),
+            "_",
+            self.space.to_ptx_name(),
+            "_add_",
+            self.inner.get_type().to_ptx_name(),
+        ]
+        .concat()
+    }
+}
+
 impl ast::AtomInnerDetails {
-    fn get_type(&self) -> ast::ScalarType {
+    pub(crate) fn get_type(&self) -> ast::ScalarType {
         match self {
             ast::AtomInnerDetails::Bit { typ, .. } => (*typ).into(),
             ast::AtomInnerDetails::Unsigned { typ, .. } => (*typ).into(),
@@ -8889,6 +10575,49 @@ impl ast::TexDetails {
     }
 }
 
+impl ast::Tld4Details {
+    fn suffix(&self) -> &'static str {
+        match self.direct {
+            true => "",
+            false => "_indirect",
+        }
+    }
+}
+
+impl ast::ImageQueryDetails {
+    fn suffix(&self) -> &'static str {
+        match self.direct {
+            true => "",
+            false => "_indirect",
+        }
+    }
+}
+
+impl ast::TextureComponent {
+    fn to_ptx_name(self) -> &'static str {
+        match self {
+            ast::TextureComponent::R => "r",
+            ast::TextureComponent::G => "g",
+            ast::TextureComponent::B => "b",
+            ast::TextureComponent::A => "a",
+        }
+    }
+}
+
+impl ast::ImageQuery {
+    fn to_ptx_name(self) -> &'static str {
+        match self {
+            ast::ImageQuery::Width => "width",
+            ast::ImageQuery::Height => "height",
+            ast::ImageQuery::Depth => "depth",
+            ast::ImageQuery::ChannelDataType => "channel_data_type",
+            ast::ImageQuery::ChannelOrder => "channel_order",
+            ast::ImageQuery::ArraySize => "array_size",
+            ast::ImageQuery::NumMipmapLevels => "num_mipmap_levels",
+        }
+    }
+}
+
 fn default_implicit_conversion(
     (operand_space, operand_type): (ast::StateSpace, &ast::Type),
     (instruction_space, instruction_type): (ast::StateSpace, &ast::Type),
@@ -9103,7 +10832,7 @@ fn should_convert_relaxed_src(
                     None
                 }
             }
-            ast::ScalarKind::Float => {
+            ast::ScalarKind::Float | ast::ScalarKind::Float2 => {
                 if instr_type.size_of() <= src_type.size_of()
                     && src_type.kind() == ast::ScalarKind::Bit
                 {
@@ -9112,7 +10841,6 @@ fn should_convert_relaxed_src(
                     None
                 }
             }
-            ast::ScalarKind::Float2 => todo!(),
             ast::ScalarKind::Pred => None,
         },
         (ast::Type::Vector(dst_type, _), ast::Type::Vector(instr_type, _))
@@ -9181,7 +10909,7 @@ fn should_convert_relaxed_dst(
                     None
                 }
             }
-            ast::ScalarKind::Float => {
+            ast::ScalarKind::Float | ast::ScalarKind::Float2 => {
                 if instr_type.size_of() <= dst_type.size_of()
                     && dst_type.kind() == ast::ScalarKind::Bit
                 {
@@ -9190,7 +10918,6 @@ fn should_convert_relaxed_dst(
                     None
                 }
             }
-            ast::ScalarKind::Float2 => todo!(),
             ast::ScalarKind::Pred => None,
         },
         (ast::Type::Vector(dst_type, _), ast::Type::Vector(instr_type, _))
@@ -9226,6 +10953,18 @@ impl ast::ScalarType {
             ast::ScalarKind::Float | ast::ScalarKind::Float2 | ast::ScalarKind::Pred => false,
         }
     }
+
+    // Types without LLVM arithmetic, they are stored as integers of the same width
+    pub(crate) fn is_storage_only(self) -> bool {
+        match self {
+            ast::ScalarType::BF16
+            | ast::ScalarType::BF16x2
+            | ast::ScalarType::TF32
+            | ast::ScalarType::E4M3x2
+            | ast::ScalarType::E5M2x2 => true,
+            _ => false,
+        }
+    }
 }
 
 impl ast::ReductionOp {
@@ -0,0 +1,72 @@
+use argh::FromArgs;
+use ptx::check::{self, Severity};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+type DynError = Box<dyn Error>;
+
+#[derive(FromArgs)]
+/// Check if PTX modules can be compiled by ZLUDA, without a GPU
+struct Arguments {
+    /// paths to PTX files or directories searched recursively for .ptx files
+    #[argh(positional)]
+    inputs: Vec<PathBuf>,
+    /// exit with an error also if there are only warnings
+    #[argh(switch)]
+    deny_warnings: bool,
+}
+
+fn main() -> Result<(), DynError> {
+    let args: Arguments = argh::from_env();
+    if args.inputs.is_empty() {
+        return Err("No input files".into());
+    }
+    let mut files = Vec::new();
+    for input in args.inputs.iter() {
+        collect_ptx_files(input, &mut files)?;
+    }
+    let (mut errors, mut warnings) = (0usize, 0usize);
+    for file in files.iter() {
+        let text = fs::read_to_string(file)
+            .map_err(|err| format!("Could not read {}: {}", file.display(), err))?;
+        let file_name = file.to_string_lossy();
+        for issue in check::check(&text) {
+            match issue.severity {
+                Severity::Error => errors += 1,
+                Severity::Warning => warnings += 1,
+            }
+            eprintln!("{}\n", issue.display(&text, Some(&file_name)));
+        }
+    }
+    println!(
+        "Checked {} files: {} errors, {} warnings",
+        files.len(),
+        errors,
+        warnings
+    );
+    if errors > 0 || (args.deny_warnings && warnings > 0) {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+fn collect_ptx_files(path: &Path, result: &mut Vec<PathBuf>) -> Result<(), DynError> {
+    if !path.is_dir() {
+        result.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut entries = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            collect_ptx_files(&entry, result)?;
+        } else if entry.extension().map_or(false, |ext| ext == "ptx") {
+            result.push(entry);
+        }
+    }
+    Ok(())
+}
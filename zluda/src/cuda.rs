@@ -38,7 +38,9 @@ cuda_function_declarations!(
         cuDeviceCanAccessPeer,
         cuDeviceGet,
         cuDeviceGetCount,
+        cuDeviceGetDefaultMemPool,
         cuDeviceGetMemPool,
+        cuDeviceSetMemPool,
         cuDeviceGetName,
         cuDeviceGetUuid,
         cuDeviceGetUuid_v2,
@@ -93,14 +95,38 @@ cuda_function_declarations!(
         cuMemAllocPitch_v2,
         cuMemFree_v2,
         cuMemFreeAsync,
+        cuMemFreeAsync_ptsz,
         cuMemFreeHost,
         cuMemHostAlloc,
         cuMemHostRegister,
         cuMemHostRegister_v2,
         cuMemHostUnregister,
         cuMemGetAddressRange_v2,
+        cuMemAllocAsync,
+        cuMemAllocAsync_ptsz,
+        cuMemAllocFromPoolAsync,
+        cuMemAllocFromPoolAsync_ptsz,
+        cuMemPoolCreate,
+        cuMemPoolDestroy,
+        cuMemPoolGetAccess,
+        cuMemPoolGetAttribute,
+        cuMemPoolSetAccess,
         cuMemPoolSetAttribute,
+        cuMemPoolTrimTo,
         cuMemPrefetchAsync,
+        cuMemAddressFree,
+        cuMemAddressReserve,
+        cuMemCreate,
+        cuMemExportToShareableHandle,
+        cuMemGetAccess,
+        cuMemGetAllocationGranularity,
+        cuMemGetAllocationPropertiesFromHandle,
+        cuMemImportFromShareableHandle,
+        cuMemMap,
+        cuMemRelease,
+        cuMemRetainAllocationHandle,
+        cuMemSetAccess,
+        cuMemUnmap,
         cuDeviceGetPCIBusId,
         cuMemcpy,
         cuMemcpy_ptds,
@@ -128,6 +154,7 @@ cuda_function_declarations!(
         cuMemsetD8Async_ptsz,
         cuMemsetD16_v2,
         cuMemsetD32Async,
+        cuMemsetD32Async_ptsz,
         cuMemsetD32_v2,
         cuMemsetD32_v2_ptds,
         cuMemsetD2D8_v2,
@@ -140,11 +167,24 @@ cuda_function_declarations!(
         cuPointerGetAttributes,
         cuStreamCreate,
         cuStreamCreateWithPriority,
+        cuStreamBeginCapture,
+        cuStreamBeginCapture_ptsz,
+        cuStreamBeginCapture_v2,
+        cuStreamBeginCapture_v2_ptsz,
+        cuStreamEndCapture,
+        cuStreamEndCapture_ptsz,
         cuStreamGetCaptureInfo,
+        cuStreamGetCaptureInfo_ptsz,
+        cuStreamGetCaptureInfo_v2,
+        cuStreamGetCaptureInfo_v2_ptsz,
+        cuStreamUpdateCaptureDependencies,
+        cuStreamUpdateCaptureDependencies_ptsz,
+        cuThreadExchangeStreamCaptureMode,
         cuStreamGetCtx,
         cuStreamGetCtx_ptsz,
         cuStreamGetFlags,
         cuStreamIsCapturing,
+        cuStreamIsCapturing_ptsz,
         cuStreamQuery,
         cuStreamSynchronize,
         cuStreamSynchronize_ptsz,
@@ -155,8 +195,14 @@ cuda_function_declarations!(
         cuFuncGetAttribute,
         cuFuncSetAttribute,
         cuLaunchHostFunc,
+        cuLaunchHostFunc_ptsz,
         cuLaunchKernel,
         cuLaunchKernel_ptsz,
+        cuLaunchCooperativeKernel,
+        cuLaunchCooperativeKernel_ptsz,
+        cuLaunchCooperativeKernelMultiDevice,
+        cuLaunchKernelEx,
+        cuLaunchKernelEx_ptsz,
         cuMemHostGetDevicePointer_v2,
         cuOccupancyMaxActiveBlocksPerMultiprocessorWithFlags,
         cuSurfObjectCreate,
@@ -194,15 +240,47 @@ cuda_function_declarations!(
         cuEventRecord,
         cuEventRecord_ptsz,
         cuEventSynchronize,
+        cuGraphAddChildGraphNode,
         cuGraphAddDependencies,
         cuGraphAddEmptyNode,
+        cuGraphAddEventRecordNode,
+        cuGraphAddEventWaitNode,
+        cuGraphAddHostNode,
         cuGraphAddKernelNode,
+        cuGraphAddKernelNode_v2,
+        cuGraphAddMemAllocNode,
+        cuGraphAddMemFreeNode,
+        cuGraphAddMemcpyNode,
+        cuGraphAddMemsetNode,
+        cuGraphChildGraphNodeGetGraph,
+        cuGraphClone,
         cuGraphCreate,
+        cuGraphDebugDotPrint,
         cuGraphDestroy,
+        cuGraphDestroyNode,
+        cuGraphExecChildGraphNodeSetParams,
         cuGraphExecDestroy,
+        cuGraphExecEventRecordNodeSetEvent,
+        cuGraphExecEventWaitNodeSetEvent,
+        cuGraphExecHostNodeSetParams,
+        cuGraphExecKernelNodeSetParams,
+        cuGraphExecKernelNodeSetParams_v2,
+        cuGraphExecMemcpyNodeSetParams,
+        cuGraphExecMemsetNodeSetParams,
+        cuGraphExecUpdate,
+        cuGraphExecUpdate_v2,
+        cuGraphGetEdges,
+        cuGraphGetNodes,
+        cuGraphGetRootNodes,
         cuGraphInstantiate,
         cuGraphInstantiate_v2,
+        cuGraphInstantiateWithFlags,
+        cuGraphKernelNodeSetParams,
+        cuGraphKernelNodeSetParams_v2,
         cuGraphLaunch,
+        cuGraphNodeGetDependencies,
+        cuGraphNodeGetDependentNodes,
+        cuGraphNodeGetType,
         cuGraphicsSubResourceGetMappedArray,
         cuGraphicsGLRegisterBuffer,
         cuGraphicsGLRegisterImage,
@@ -240,6 +318,7 @@ mod definitions {
     use crate::r#impl::link;
     use crate::r#impl::memcpy2d_from_cuda;
     use crate::r#impl::memory;
+    use crate::r#impl::memory_pool;
     use crate::r#impl::module;
     use crate::r#impl::pointer;
     use crate::r#impl::stream;
@@ -247,6 +326,7 @@ mod definitions {
     use crate::r#impl::surfref;
     use crate::r#impl::texobj;
     use crate::r#impl::texref;
+    use crate::r#impl::vmm;
 
     pub(crate) unsafe fn cuGetErrorString(
         error: hipError_t,
@@ -310,6 +390,13 @@ mod definitions {
         hipGetDeviceCount(count)
     }
 
+    pub(crate) unsafe fn cuDeviceGetDefaultMemPool(
+        pool_out: *mut hipMemPool_t,
+        dev: hipDevice_t,
+    ) -> hipError_t {
+        hipDeviceGetDefaultMemPool(pool_out, dev)
+    }
+
     pub(crate) unsafe fn cuDeviceGetMemPool(
         pool: *mut hipMemPool_t,
         dev: hipDevice_t,
@@ -317,6 +404,10 @@ mod definitions {
         hipDeviceGetMemPool(pool, dev)
     }
 
+    pub(crate) unsafe fn cuDeviceSetMemPool(dev: hipDevice_t, pool: hipMemPool_t) -> hipError_t {
+        hipDeviceSetMemPool(dev, pool)
+    }
+
     pub(crate) unsafe fn cuDeviceGetName(
         name: *mut ::std::os::raw::c_char,
         len: ::std::os::raw::c_int,
@@ -570,7 +661,6 @@ mod definitions {
         module::load_data(module, image)
     }
 
-    // TODO: parse jit options
     pub(crate) unsafe fn cuModuleLoadDataEx(
         module: *mut *mut module::Module,
         image: *const ::std::os::raw::c_void,
@@ -578,7 +668,7 @@ mod definitions {
         options: *mut CUjit_option,
         optionValues: *mut *mut ::std::os::raw::c_void,
     ) -> Result<(), CUresult> {
-        module::load_data(module, image)
+        module::load_data_ex(module, image, numOptions, options, optionValues)
     }
 
     pub(crate) unsafe fn cuModuleUnload(hmod: *mut module::Module) -> Result<(), CUresult> {
@@ -659,7 +749,14 @@ mod definitions {
         dptr: hipDeviceptr_t,
         hStream: *mut stream::Stream,
     ) -> Result<(), CUresult> {
-        memory::free_async(dptr, hStream)
+        memory::free_async(dptr, hStream, false)
+    }
+
+    pub(crate) unsafe fn cuMemFreeAsync_ptsz(
+        dptr: hipDeviceptr_t,
+        hStream: *mut stream::Stream,
+    ) -> Result<(), CUresult> {
+        memory::free_async(dptr, hStream, true)
     }
 
     pub(crate) unsafe fn cuMemFreeHost(p: *mut ::std::os::raw::c_void) -> hipError_t {
@@ -702,12 +799,85 @@ mod definitions {
         memory::get_address_range(pbase, psize, dptr)
     }
 
-    pub(crate) unsafe fn cuMemPoolSetAttribute(
+    pub(crate) unsafe fn cuMemAllocAsync(
+        dptr: *mut hipDeviceptr_t,
+        bytesize: usize,
+        hStream: *mut stream::Stream,
+    ) -> Result<(), CUresult> {
+        memory_pool::alloc_async(dptr, bytesize, hStream, false)
+    }
+
+    pub(crate) unsafe fn cuMemAllocAsync_ptsz(
+        dptr: *mut hipDeviceptr_t,
+        bytesize: usize,
+        hStream: *mut stream::Stream,
+    ) -> Result<(), CUresult> {
+        memory_pool::alloc_async(dptr, bytesize, hStream, true)
+    }
+
+    pub(crate) unsafe fn cuMemAllocFromPoolAsync(
+        dptr: *mut hipDeviceptr_t,
+        bytesize: usize,
         pool: hipMemPool_t,
-        attr: hipMemPoolAttr,
+        hStream: *mut stream::Stream,
+    ) -> Result<(), CUresult> {
+        memory_pool::alloc_from_pool_async(dptr, bytesize, pool, hStream, false)
+    }
+
+    pub(crate) unsafe fn cuMemAllocFromPoolAsync_ptsz(
+        dptr: *mut hipDeviceptr_t,
+        bytesize: usize,
+        pool: hipMemPool_t,
+        hStream: *mut stream::Stream,
+    ) -> Result<(), CUresult> {
+        memory_pool::alloc_from_pool_async(dptr, bytesize, pool, hStream, true)
+    }
+
+    pub(crate) unsafe fn cuMemPoolCreate(
+        pool: *mut hipMemPool_t,
+        poolProps: *const CUmemPoolProps,
+    ) -> Result<(), CUresult> {
+        memory_pool::create(pool, poolProps)
+    }
+
+    pub(crate) unsafe fn cuMemPoolDestroy(pool: hipMemPool_t) -> hipError_t {
+        hipMemPoolDestroy(pool)
+    }
+
+    pub(crate) unsafe fn cuMemPoolGetAccess(
+        flags: *mut hipMemAccessFlags,
+        memPool: hipMemPool_t,
+        location: *mut hipMemLocation,
+    ) -> hipError_t {
+        hipMemPoolGetAccess(flags, memPool, location)
+    }
+
+    pub(crate) unsafe fn cuMemPoolGetAttribute(
+        pool: hipMemPool_t,
+        attr: CUmemPool_attribute,
         value: *mut ::std::os::raw::c_void,
+    ) -> Result<(), CUresult> {
+        memory_pool::get_attribute(pool, attr, value)
+    }
+
+    pub(crate) unsafe fn cuMemPoolSetAccess(
+        pool: hipMemPool_t,
+        map: *const hipMemAccessDesc,
+        count: usize,
     ) -> hipError_t {
-        hipMemPoolGetAttribute(pool, attr, value)
+        hipMemPoolSetAccess(pool, map, count)
+    }
+
+    pub(crate) unsafe fn cuMemPoolSetAttribute(
+        pool: hipMemPool_t,
+        attr: CUmemPool_attribute,
+        value: *mut ::std::os::raw::c_void,
+    ) -> Result<(), CUresult> {
+        memory_pool::set_attribute(pool, attr, value)
+    }
+
+    pub(crate) unsafe fn cuMemPoolTrimTo(pool: hipMemPool_t, minBytesToKeep: usize) -> hipError_t {
+        hipMemPoolTrimTo(pool, minBytesToKeep)
     }
 
     pub(crate) unsafe fn cuMemPrefetchAsync(
@@ -719,6 +889,103 @@ mod definitions {
         memory::prefetch_async(devPtr, count, dev, hStream)
     }
 
+    pub(crate) unsafe fn cuMemAddressFree(ptr: hipDeviceptr_t, size: usize) -> hipError_t {
+        hipMemAddressFree(ptr.0, size)
+    }
+
+    pub(crate) unsafe fn cuMemAddressReserve(
+        ptr: *mut hipDeviceptr_t,
+        size: usize,
+        alignment: usize,
+        addr: hipDeviceptr_t,
+        flags: ::std::os::raw::c_ulonglong,
+    ) -> Result<(), CUresult> {
+        vmm::address_reserve(ptr, size, alignment, addr, flags)
+    }
+
+    pub(crate) unsafe fn cuMemCreate(
+        handle: *mut hipMemGenericAllocationHandle_t,
+        size: usize,
+        prop: *const CUmemAllocationProp,
+        flags: ::std::os::raw::c_ulonglong,
+    ) -> Result<(), CUresult> {
+        vmm::create(handle, size, prop, flags)
+    }
+
+    pub(crate) unsafe fn cuMemExportToShareableHandle(
+        shareableHandle: *mut ::std::os::raw::c_void,
+        handle: hipMemGenericAllocationHandle_t,
+        handleType: hipMemAllocationHandleType,
+        flags: ::std::os::raw::c_ulonglong,
+    ) -> Result<(), CUresult> {
+        vmm::export_to_shareable_handle(shareableHandle, handle, handleType, flags)
+    }
+
+    pub(crate) unsafe fn cuMemGetAccess(
+        flags: *mut ::std::os::raw::c_ulonglong,
+        location: *const hipMemLocation,
+        ptr: hipDeviceptr_t,
+    ) -> hipError_t {
+        hipMemGetAccess(flags, location, ptr.0)
+    }
+
+    pub(crate) unsafe fn cuMemGetAllocationGranularity(
+        granularity: *mut usize,
+        prop: *const CUmemAllocationProp,
+        option: CUmemAllocationGranularity_flags,
+    ) -> Result<(), CUresult> {
+        vmm::get_allocation_granularity(granularity, prop, option)
+    }
+
+    pub(crate) unsafe fn cuMemGetAllocationPropertiesFromHandle(
+        prop: *mut CUmemAllocationProp,
+        handle: hipMemGenericAllocationHandle_t,
+    ) -> Result<(), CUresult> {
+        vmm::get_allocation_properties_from_handle(prop, handle)
+    }
+
+    pub(crate) unsafe fn cuMemImportFromShareableHandle(
+        handle: *mut hipMemGenericAllocationHandle_t,
+        osHandle: *mut ::std::os::raw::c_void,
+        shHandleType: hipMemAllocationHandleType,
+    ) -> Result<(), CUresult> {
+        vmm::import_from_shareable_handle(handle, osHandle, shHandleType)
+    }
+
+    pub(crate) unsafe fn cuMemMap(
+        ptr: hipDeviceptr_t,
+        size: usize,
+        offset: usize,
+        handle: hipMemGenericAllocationHandle_t,
+        flags: ::std::os::raw::c_ulonglong,
+    ) -> hipError_t {
+        hipMemMap(ptr.0, size, offset, handle, flags)
+    }
+
+    pub(crate) unsafe fn cuMemRelease(handle: hipMemGenericAllocationHandle_t) -> hipError_t {
+        hipMemRelease(handle)
+    }
+
+    pub(crate) unsafe fn cuMemRetainAllocationHandle(
+        handle: *mut hipMemGenericAllocationHandle_t,
+        addr: *mut ::std::os::raw::c_void,
+    ) -> hipError_t {
+        hipMemRetainAllocationHandle(handle, addr)
+    }
+
+    pub(crate) unsafe fn cuMemSetAccess(
+        ptr: hipDeviceptr_t,
+        size: usize,
+        desc: *const hipMemAccessDesc,
+        count: usize,
+    ) -> hipError_t {
+        hipMemSetAccess(ptr.0, size, desc, count)
+    }
+
+    pub(crate) unsafe fn cuMemUnmap(ptr: hipDeviceptr_t, size: usize) -> hipError_t {
+        hipMemUnmap(ptr.0, size)
+    }
+
     pub(crate) unsafe fn cuDeviceGetPCIBusId(
         pciBusId: *mut ::std::os::raw::c_char,
         len: ::std::os::raw::c_int,
@@ -939,7 +1206,16 @@ mod definitions {
         N: usize,
         hStream: *mut stream::Stream,
     ) -> Result<(), CUresult> {
-        memory::set_d32_async(dstDevice, ui, N, hStream)
+        memory::set_d32_async(dstDevice, ui, N, hStream, false)
+    }
+
+    pub(crate) unsafe fn cuMemsetD32Async_ptsz(
+        dstDevice: hipDeviceptr_t,
+        ui: ::std::os::raw::c_uint,
+        N: usize,
+        hStream: *mut stream::Stream,
+    ) -> Result<(), CUresult> {
+        memory::set_d32_async(dstDevice, ui, N, hStream, true)
     }
 
     pub(crate) unsafe fn cuMemsetD16_v2_ptds(
@@ -1058,12 +1334,130 @@ mod definitions {
         stream::create_with_priority(phStream, flags, priority)
     }
 
+    pub(crate) unsafe fn cuStreamBeginCapture(
+        hStream: *mut stream::Stream,
+    ) -> Result<(), CUresult> {
+        stream::begin_capture(
+            hStream,
+            hipStreamCaptureMode::hipStreamCaptureModeGlobal,
+            false,
+        )
+    }
+
+    pub(crate) unsafe fn cuStreamBeginCapture_ptsz(
+        hStream: *mut stream::Stream,
+    ) -> Result<(), CUresult> {
+        stream::begin_capture(
+            hStream,
+            hipStreamCaptureMode::hipStreamCaptureModeGlobal,
+            true,
+        )
+    }
+
+    pub(crate) unsafe fn cuStreamBeginCapture_v2(
+        hStream: *mut stream::Stream,
+        mode: hipStreamCaptureMode,
+    ) -> Result<(), CUresult> {
+        stream::begin_capture(hStream, mode, false)
+    }
+
+    pub(crate) unsafe fn cuStreamBeginCapture_v2_ptsz(
+        hStream: *mut stream::Stream,
+        mode: hipStreamCaptureMode,
+    ) -> Result<(), CUresult> {
+        stream::begin_capture(hStream, mode, true)
+    }
+
+    pub(crate) unsafe fn cuStreamEndCapture(
+        hStream: *mut stream::Stream,
+        phGraph: *mut hipGraph_t,
+    ) -> Result<(), CUresult> {
+        stream::end_capture(hStream, phGraph, false)
+    }
+
+    pub(crate) unsafe fn cuStreamEndCapture_ptsz(
+        hStream: *mut stream::Stream,
+        phGraph: *mut hipGraph_t,
+    ) -> Result<(), CUresult> {
+        stream::end_capture(hStream, phGraph, true)
+    }
+
     pub(crate) unsafe fn cuStreamGetCaptureInfo(
         stream: *mut stream::Stream,
         captureStatus_out: *mut hipStreamCaptureStatus,
         id_out: *mut cuuint64_t,
     ) -> Result<(), CUresult> {
-        stream::get_capture_info(stream, captureStatus_out, id_out)
+        stream::get_capture_info(stream, captureStatus_out, id_out, false)
+    }
+
+    pub(crate) unsafe fn cuStreamGetCaptureInfo_ptsz(
+        stream: *mut stream::Stream,
+        captureStatus_out: *mut hipStreamCaptureStatus,
+        id_out: *mut cuuint64_t,
+    ) -> Result<(), CUresult> {
+        stream::get_capture_info(stream, captureStatus_out, id_out, true)
+    }
+
+    pub(crate) unsafe fn cuStreamGetCaptureInfo_v2(
+        stream: *mut stream::Stream,
+        captureStatus_out: *mut hipStreamCaptureStatus,
+        id_out: *mut cuuint64_t,
+        graph_out: *mut hipGraph_t,
+        dependencies_out: *mut *const hipGraphNode_t,
+        numDependencies_out: *mut usize,
+    ) -> Result<(), CUresult> {
+        stream::get_capture_info_v2(
+            stream,
+            captureStatus_out,
+            id_out,
+            graph_out,
+            dependencies_out,
+            numDependencies_out,
+            false,
+        )
+    }
+
+    pub(crate) unsafe fn cuStreamGetCaptureInfo_v2_ptsz(
+        stream: *mut stream::Stream,
+        captureStatus_out: *mut hipStreamCaptureStatus,
+        id_out: *mut cuuint64_t,
+        graph_out: *mut hipGraph_t,
+        dependencies_out: *mut *const hipGraphNode_t,
+        numDependencies_out: *mut usize,
+    ) -> Result<(), CUresult> {
+        stream::get_capture_info_v2(
+            stream,
+            captureStatus_out,
+            id_out,
+            graph_out,
+            dependencies_out,
+            numDependencies_out,
+            true,
+        )
+    }
+
+    pub(crate) unsafe fn cuStreamUpdateCaptureDependencies(
+        hStream: *mut stream::Stream,
+        dependencies: *mut hipGraphNode_t,
+        numDependencies: usize,
+        flags: ::std::os::raw::c_uint,
+    ) -> Result<(), CUresult> {
+        stream::update_capture_dependencies(hStream, dependencies, numDependencies, flags, false)
+    }
+
+    pub(crate) unsafe fn cuStreamUpdateCaptureDependencies_ptsz(
+        hStream: *mut stream::Stream,
+        dependencies: *mut hipGraphNode_t,
+        numDependencies: usize,
+        flags: ::std::os::raw::c_uint,
+    ) -> Result<(), CUresult> {
+        stream::update_capture_dependencies(hStream, dependencies, numDependencies, flags, true)
+    }
+
+    pub(crate) unsafe fn cuThreadExchangeStreamCaptureMode(
+        mode: *mut hipStreamCaptureMode,
+    ) -> hipError_t {
+        hipThreadExchangeStreamCaptureMode(mode)
     }
 
     pub(crate) unsafe fn cuStreamGetCtx(
@@ -1091,7 +1485,14 @@ mod definitions {
         hStream: *mut stream::Stream,
         captureStatus: *mut hipStreamCaptureStatus,
     ) -> Result<(), CUresult> {
-        stream::is_capturing(hStream, captureStatus)
+        stream::is_capturing(hStream, captureStatus, false)
+    }
+
+    pub(crate) unsafe fn cuStreamIsCapturing_ptsz(
+        hStream: *mut stream::Stream,
+        captureStatus: *mut hipStreamCaptureStatus,
+    ) -> Result<(), CUresult> {
+        stream::is_capturing(hStream, captureStatus, true)
     }
 
     pub(crate) unsafe fn cuStreamQuery(hStream: *mut stream::Stream) -> Result<(), CUresult> {
@@ -1153,7 +1554,15 @@ mod definitions {
         fn_: CUhostFn,
         userData: *mut ::std::os::raw::c_void,
     ) -> Result<(), CUresult> {
-        stream::launch_host_func(stream, fn_, userData)
+        stream::launch_host_func(stream, fn_, userData, false)
+    }
+
+    pub(crate) unsafe fn cuLaunchHostFunc_ptsz(
+        stream: *mut stream::Stream,
+        fn_: CUhostFn,
+        userData: *mut ::std::os::raw::c_void,
+    ) -> Result<(), CUresult> {
+        stream::launch_host_func(stream, fn_, userData, true)
     }
 
     pub(crate) unsafe fn cuLaunchKernel(
@@ -1214,6 +1623,86 @@ mod definitions {
         )
     }
 
+    pub(crate) unsafe fn cuLaunchCooperativeKernel(
+        f: *mut function::Function,
+        gridDimX: ::std::os::raw::c_uint,
+        gridDimY: ::std::os::raw::c_uint,
+        gridDimZ: ::std::os::raw::c_uint,
+        blockDimX: ::std::os::raw::c_uint,
+        blockDimY: ::std::os::raw::c_uint,
+        blockDimZ: ::std::os::raw::c_uint,
+        sharedMemBytes: ::std::os::raw::c_uint,
+        hStream: *mut stream::Stream,
+        kernelParams: *mut *mut ::std::os::raw::c_void,
+    ) -> Result<(), CUresult> {
+        function::launch_cooperative_kernel(
+            f,
+            gridDimX,
+            gridDimY,
+            gridDimZ,
+            blockDimX,
+            blockDimY,
+            blockDimZ,
+            sharedMemBytes,
+            hStream,
+            kernelParams,
+            false,
+        )
+    }
+
+    pub(crate) unsafe fn cuLaunchCooperativeKernel_ptsz(
+        f: *mut function::Function,
+        gridDimX: ::std::os::raw::c_uint,
+        gridDimY: ::std::os::raw::c_uint,
+        gridDimZ: ::std::os::raw::c_uint,
+        blockDimX: ::std::os::raw::c_uint,
+        blockDimY: ::std::os::raw::c_uint,
+        blockDimZ: ::std::os::raw::c_uint,
+        sharedMemBytes: ::std::os::raw::c_uint,
+        hStream: *mut stream::Stream,
+        kernelParams: *mut *mut ::std::os::raw::c_void,
+    ) -> Result<(), CUresult> {
+        function::launch_cooperative_kernel(
+            f,
+            gridDimX,
+            gridDimY,
+            gridDimZ,
+            blockDimX,
+            blockDimY,
+            blockDimZ,
+            sharedMemBytes,
+            hStream,
+            kernelParams,
+            true,
+        )
+    }
+
+    pub(crate) unsafe fn cuLaunchCooperativeKernelMultiDevice(
+        launchParamsList: *mut CUDA_LAUNCH_PARAMS,
+        numDevices: ::std::os::raw::c_uint,
+        flags: ::std::os::raw::c_uint,
+    ) -> Result<(), CUresult> {
+        function::launch_cooperative_kernel_multi_device(launchParamsList, numDevices, flags)
+    }
+
+    pub(crate) unsafe fn cuLaunchKernelEx(
+        config: *const CUlaunchConfig,
+        f: *mut function::Function,
+        kernelParams: *mut *mut ::std::os::raw::c_void,
+        extra: *mut *mut ::std::os::raw::c_void,
+    ) -> Result<(), CUresult> {
+        function::launch_kernel_ex(config, f, kernelParams, extra, false)
+    }
+
+    pub(crate) unsafe fn cuLaunchKernelEx_ptsz(
+        config: *const CUlaunchConfig,
+        f: *mut function::Function,
+        kernelParams: *mut *mut ::std::os::raw::c_void,
+        extra: *mut *mut ::std::os::raw::c_void,
+    ) -> Result<(), CUresult> {
+        function::launch_kernel_ex(config, f, kernelParams, extra, true)
+    }
+
     pub(crate) unsafe fn cuMemHostGetDevicePointer_v2(
         pdptr: *mut hipDeviceptr_t,
         p: *mut ::std::os::raw::c_void,
@@ -1479,6 +1968,22 @@ mod definitions {
         hipEventSynchronize(event)
     }
 
+    pub(crate) unsafe fn cuGraphAddChildGraphNode(
+        phGraphNode: *mut hipGraphNode_t,
+        hGraph: hipGraph_t,
+        dependencies: *const hipGraphNode_t,
+        numDependencies: usize,
+        childGraph: hipGraph_t,
+    ) -> hipError_t {
+        hipGraphAddChildGraphNode(
+            phGraphNode,
+            hGraph,
+            dependencies,
+            numDependencies,
+            childGraph,
+        )
+    }
+
     pub(crate) unsafe fn cuGraphAddDependencies(
         graph: hipGraph_t,
         from: *const hipGraphNode_t,
@@ -1497,6 +2002,42 @@ mod definitions {
         hipGraphAddEmptyNode(pGraphNode, graph, pDependencies, numDependencies)
     }
 
+    pub(crate) unsafe fn cuGraphAddEventRecordNode(
+        phGraphNode: *mut hipGraphNode_t,
+        hGraph: hipGraph_t,
+        dependencies: *const hipGraphNode_t,
+        numDependencies: usize,
+        event: hipEvent_t,
+    ) -> hipError_t {
+        hipGraphAddEventRecordNode(phGraphNode, hGraph, dependencies, numDependencies, event)
+    }
+
+    pub(crate) unsafe fn cuGraphAddEventWaitNode(
+        phGraphNode: *mut hipGraphNode_t,
+        hGraph: hipGraph_t,
+        dependencies: *const hipGraphNode_t,
+        numDependencies: usize,
+        event: hipEvent_t,
+    ) -> hipError_t {
+        hipGraphAddEventWaitNode(phGraphNode, hGraph, dependencies, numDependencies, event)
+    }
+
+    pub(crate) unsafe fn cuGraphAddHostNode(
+        phGraphNode: *mut hipGraphNode_t,
+        hGraph: hipGraph_t,
+        dependencies: *const hipGraphNode_t,
+        numDependencies: usize,
+        nodeParams: *const CUDA_HOST_NODE_PARAMS,
+    ) -> Result<(), CUresult> {
+        graph::add_host_node(
+            phGraphNode,
+            hGraph,
+            dependencies,
+            numDependencies,
+            nodeParams,
+        )
+    }
+
     pub(crate) unsafe fn cuGraphAddKernelNode(
         phGraphNode: *mut hipGraphNode_t,
         hGraph: hipGraph_t,
@@ -1513,6 +2054,96 @@ mod definitions {
         )
     }
 
+    pub(crate) unsafe fn cuGraphAddKernelNode_v2(
+        phGraphNode: *mut hipGraphNode_t,
+        hGraph: hipGraph_t,
+        dependencies: *const hipGraphNode_t,
+        numDependencies: usize,
+        nodeParams: *const CUDA_KERNEL_NODE_PARAMS,
+    ) -> Result<(), CUresult> {
+        graph::add_kernel_node(
+            phGraphNode,
+            hGraph,
+            dependencies,
+            numDependencies,
+            nodeParams,
+        )
+    }
+
+    pub(crate) unsafe fn cuGraphAddMemAllocNode(
+        phGraphNode: *mut hipGraphNode_t,
+        hGraph: hipGraph_t,
+        dependencies: *const hipGraphNode_t,
+        numDependencies: usize,
+        nodeParams: *mut CUDA_MEM_ALLOC_NODE_PARAMS,
+    ) -> Result<(), CUresult> {
+        graph::add_mem_alloc_node(
+            phGraphNode,
+            hGraph,
+            dependencies,
+            numDependencies,
+            nodeParams,
+        )
+    }
+
+    pub(crate) unsafe fn cuGraphAddMemFreeNode(
+        phGraphNode: *mut hipGraphNode_t,
+        hGraph: hipGraph_t,
+        dependencies: *const hipGraphNode_t,
+        numDependencies: usize,
+        dptr: hipDeviceptr_t,
+    ) -> hipError_t {
+        hipGraphAddMemFreeNode(phGraphNode, hGraph, dependencies, numDependencies, dptr.0)
+    }
+
+    pub(crate) unsafe fn cuGraphAddMemcpyNode(
+        phGraphNode: *mut hipGraphNode_t,
+        hGraph: hipGraph_t,
+        dependencies: *const hipGraphNode_t,
+        numDependencies: usize,
+        copyParams: *const CUDA_MEMCPY3D,
+        _ctx: *mut context::Context,
+    ) -> Result<(), CUresult> {
+        graph::add_memcpy_node(
+            phGraphNode,
+            hGraph,
+            dependencies,
+            numDependencies,
+            copyParams,
+        )
+    }
+
+    pub(crate) unsafe fn cuGraphAddMemsetNode(
+        phGraphNode: *mut hipGraphNode_t,
+        hGraph: hipGraph_t,
+        dependencies: *const hipGraphNode_t,
+        numDependencies: usize,
+        memsetParams: *const CUDA_MEMSET_NODE_PARAMS,
+        _ctx: *mut context::Context,
+    ) -> Result<(), CUresult> {
+        graph::add_memset_node(
+            phGraphNode,
+            hGraph,
+            dependencies,
+            numDependencies,
+            memsetParams,
+        )
+    }
+
+    pub(crate) unsafe fn cuGraphChildGraphNodeGetGraph(
+        hNode: hipGraphNode_t,
+        phGraph: *mut hipGraph_t,
+    ) -> hipError_t {
+        hipGraphChildGraphNodeGetGraph(hNode, phGraph)
+    }
+
+    pub(crate) unsafe fn cuGraphClone(
+        phGraphClone: *mut hipGraph_t,
+        originalGraph: hipGraph_t,
+    ) -> hipError_t {
+        hipGraphClone(phGraphClone, originalGraph)
+    }
+
     pub(crate) unsafe fn cuGraphCreate(
         phGraph: *mut hipGraph_t,
         flags: ::std::os::raw::c_uint,
@@ -1520,14 +2151,134 @@ mod definitions {
         hipGraphCreate(phGraph, flags)
     }
 
+    pub(crate) unsafe fn cuGraphDebugDotPrint(
+        hGraph: hipGraph_t,
+        path: *const ::std::os::raw::c_char,
+        flags: ::std::os::raw::c_uint,
+    ) -> hipError_t {
+        hipGraphDebugDotPrint(hGraph, path, flags)
+    }
+
     pub(crate) unsafe fn cuGraphDestroy(graph: hipGraph_t) -> hipError_t {
         hipGraphDestroy(graph)
     }
 
+    pub(crate) unsafe fn cuGraphDestroyNode(hNode: hipGraphNode_t) -> hipError_t {
+        hipGraphDestroyNode(hNode)
+    }
+
+    pub(crate) unsafe fn cuGraphExecChildGraphNodeSetParams(
+        hGraphExec: hipGraphExec_t,
+        hNode: hipGraphNode_t,
+        childGraph: hipGraph_t,
+    ) -> hipError_t {
+        hipGraphExecChildGraphNodeSetParams(hGraphExec, hNode, childGraph)
+    }
+
     pub(crate) unsafe fn cuGraphExecDestroy(graphExec: hipGraphExec_t) -> hipError_t {
         hipGraphExecDestroy(graphExec)
     }
 
+    pub(crate) unsafe fn cuGraphExecEventRecordNodeSetEvent(
+        hGraphExec: hipGraphExec_t,
+        hNode: hipGraphNode_t,
+        event: hipEvent_t,
+    ) -> hipError_t {
+        hipGraphExecEventRecordNodeSetEvent(hGraphExec, hNode, event)
+    }
+
+    pub(crate) unsafe fn cuGraphExecEventWaitNodeSetEvent(
+        hGraphExec: hipGraphExec_t,
+        hNode: hipGraphNode_t,
+        event: hipEvent_t,
+    ) -> hipError_t {
+        hipGraphExecEventWaitNodeSetEvent(hGraphExec, hNode, event)
+    }
+
+    pub(crate) unsafe fn cuGraphExecHostNodeSetParams(
+        hGraphExec: hipGraphExec_t,
+        hNode: hipGraphNode_t,
+        nodeParams: *const CUDA_HOST_NODE_PARAMS,
+    ) -> Result<(), CUresult> {
+        graph::exec_host_node_set_params(hGraphExec, hNode, nodeParams)
+    }
+
+    pub(crate) unsafe fn cuGraphExecKernelNodeSetParams(
+        hGraphExec: hipGraphExec_t,
+        hNode: hipGraphNode_t,
+        nodeParams: *const CUDA_KERNEL_NODE_PARAMS_v1,
+    ) -> Result<(), CUresult> {
+        graph::exec_kernel_node_set_params(hGraphExec, hNode, nodeParams)
+    }
+
+    pub(crate) unsafe fn cuGraphExecKernelNodeSetParams_v2(
+        hGraphExec: hipGraphExec_t,
+        hNode: hipGraphNode_t,
+        nodeParams: *const CUDA_KERNEL_NODE_PARAMS,
+    ) -> Result<(), CUresult> {
+        graph::exec_kernel_node_set_params(hGraphExec, hNode, nodeParams)
+    }
+
+    pub(crate) unsafe fn cuGraphExecMemcpyNodeSetParams(
+        hGraphExec: hipGraphExec_t,
+        hNode: hipGraphNode_t,
+        copyParams: *const CUDA_MEMCPY3D,
+        _ctx: *mut context::Context,
+    ) -> Result<(), CUresult> {
+        graph::exec_memcpy_node_set_params(hGraphExec, hNode, copyParams)
+    }
+
+    pub(crate) unsafe fn cuGraphExecMemsetNodeSetParams(
+        hGraphExec: hipGraphExec_t,
+        hNode: hipGraphNode_t,
+        memsetParams: *const CUDA_MEMSET_NODE_PARAMS,
+        _ctx: *mut context::Context,
+    ) -> Result<(), CUresult> {
+        graph::exec_memset_node_set_params(hGraphExec, hNode, memsetParams)
+    }
+
+    pub(crate) unsafe fn cuGraphExecUpdate(
+        hGraphExec: hipGraphExec_t,
+        hGraph: hipGraph_t,
+        hErrorNode_out: *mut hipGraphNode_t,
+        updateResult_out: *mut hipGraphExecUpdateResult,
+    ) -> hipError_t {
+        hipGraphExecUpdate(hGraphExec, hGraph, hErrorNode_out, updateResult_out)
+    }
+
+    pub(crate) unsafe fn cuGraphExecUpdate_v2(
+        hGraphExec: hipGraphExec_t,
+        hGraph: hipGraph_t,
+        resultInfo: *mut CUgraphExecUpdateResultInfo,
+    ) -> Result<(), CUresult> {
+        graph::exec_update(hGraphExec, hGraph, resultInfo)
+    }
+
+    pub(crate) unsafe fn cuGraphGetEdges(
+        hGraph: hipGraph_t,
+        from: *mut hipGraphNode_t,
+        to: *mut hipGraphNode_t,
+        numEdges: *mut usize,
+    ) -> hipError_t {
+        hipGraphGetEdges(hGraph, from, to, numEdges)
+    }
+
+    pub(crate) unsafe fn cuGraphGetNodes(
+        hGraph: hipGraph_t,
+        nodes: *mut hipGraphNode_t,
+        numNodes: *mut usize,
+    ) -> hipError_t {
+        hipGraphGetNodes(hGraph, nodes, numNodes)
+    }
+
+    pub(crate) unsafe fn cuGraphGetRootNodes(
+        hGraph: hipGraph_t,
+        rootNodes: *mut hipGraphNode_t,
+        numRootNodes: *mut usize,
+    ) -> hipError_t {
+        hipGraphGetRootNodes(hGraph, rootNodes, numRootNodes)
+    }
+
     pub(crate) unsafe fn cuGraphInstantiate(
         phGraphExec: *mut hipGraphExec_t,
         hGraph: hipGraph_t,
@@ -1548,6 +2299,28 @@ mod definitions {
         cuGraphInstantiate(phGraphExec, hGraph, phErrorNode, logBuffer, bufferSize)
     }
 
+    pub(crate) unsafe fn cuGraphInstantiateWithFlags(
+        phGraphExec: *mut hipGraphExec_t,
+        hGraph: hipGraph_t,
+        flags: ::std::os::raw::c_ulonglong,
+    ) -> hipError_t {
+        hipGraphInstantiateWithFlags(phGraphExec, hGraph, flags)
+    }
+
+    pub(crate) unsafe fn cuGraphKernelNodeSetParams(
+        hNode: hipGraphNode_t,
+        nodeParams: *const CUDA_KERNEL_NODE_PARAMS_v1,
+    ) -> Result<(), CUresult> {
+        graph::kernel_node_set_params(hNode, nodeParams)
+    }
+
+    pub(crate) unsafe fn cuGraphKernelNodeSetParams_v2(
+        hNode: hipGraphNode_t,
+        nodeParams: *const CUDA_KERNEL_NODE_PARAMS,
+    ) -> Result<(), CUresult> {
+        graph::kernel_node_set_params(hNode, nodeParams)
+    }
+
     pub(crate) unsafe fn cuGraphLaunch(
         hGraph: hipGraphExec_t,
         hStream: *mut stream::Stream,
@@ -1555,6 +2328,29 @@ mod definitions {
         graph::launch(hGraph, hStream)
     }
 
+    pub(crate) unsafe fn cuGraphNodeGetDependencies(
+        hNode: hipGraphNode_t,
+        dependencies: *mut hipGraphNode_t,
+        numDependencies: *mut usize,
+    ) -> hipError_t {
+        hipGraphNodeGetDependencies(hNode, dependencies, numDependencies)
+    }
+
+    pub(crate) unsafe fn cuGraphNodeGetDependentNodes(
+        hNode: hipGraphNode_t,
+        dependentNodes: *mut hipGraphNode_t,
+        numDependentNodes: *mut usize,
+    ) -> hipError_t {
+        hipGraphNodeGetDependentNodes(hNode, dependentNodes, numDependentNodes)
+    }
+
+    pub(crate) unsafe fn cuGraphNodeGetType(
+        hNode: hipGraphNode_t,
+        type_: *mut hipGraphNodeType,
+    ) -> Result<(), CUresult> {
+        graph::node_get_type(hNode, type_)
+    }
+
     pub(crate) unsafe fn cuGraphicsSubResourceGetMappedArray(
         pArray: *mut CUarray,
         resource: hipGraphicsResource_t,
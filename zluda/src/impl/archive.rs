@@ -0,0 +1,127 @@
+use super::module;
+use cuda_types::CUresult;
+use goblin::archive::Archive;
+use ptx::{ast, ModuleParserExt};
+use rustc_hash::FxHashSet;
+use std::{borrow::Cow, mem, slice};
+
+// Device libraries (nvcc -lib -rdc=true) are ar archives of host objects,
+// each one carrying a fatbin with PTX of a single translation unit.
+// Members that have no PTX (SASS-only, host-only) are skipped
+pub(crate) fn ptx_members(archive: &[u8]) -> Result<Vec<Cow<'static, str>>, CUresult> {
+    let parsed = Archive::parse(archive).map_err(|_| CUresult::CUDA_ERROR_INVALID_IMAGE)?;
+    let mut result = Vec::new();
+    for index in 0..parsed.len() {
+        let member = match parsed.get_at(index) {
+            Some(member) => member,
+            None => continue,
+        };
+        let start = member.offset as usize;
+        let data = match archive.get(start..start + member.size()) {
+            Some(data) => data,
+            None => return Err(CUresult::CUDA_ERROR_INVALID_IMAGE),
+        };
+        if member.extended_name().ends_with(".ptx") {
+            if let Ok(text) = std::str::from_utf8(data) {
+                result.push(Cow::Owned(text.to_string()));
+            }
+            continue;
+        }
+        // Members are only 2-byte aligned inside the archive, fatbin headers need 8
+        let mut buffer =
+            vec![0u64; (data.len() + mem::size_of::<u64>() - 1) / mem::size_of::<u64>()];
+        let aligned =
+            unsafe { slice::from_raw_parts_mut(buffer.as_mut_ptr().cast::<u8>(), data.len()) };
+        aligned.copy_from_slice(data);
+        if let Ok(modules) = unsafe { module::object_link_inputs(aligned) } {
+            result.extend(
+                modules
+                    .into_iter()
+                    .map(|module| Cow::Owned(module.into_owned())),
+            );
+        }
+    }
+    Ok(result)
+}
+
+// Works like a traditional static linker: a member is pulled in only if it
+// defines a symbol that is still unresolved, which in turn might make other
+// members necessary
+pub(crate) fn select_members<'a>(
+    modules: &[Cow<'_, str>],
+    members: &'a [Cow<'static, str>],
+) -> Vec<&'a str> {
+    let mut defined = FxHashSet::default();
+    let mut undefined = FxHashSet::default();
+    for module in modules {
+        if let Ok(ast) = ptx::ModuleParser::parse_checked(module) {
+            add_symbols(&ast, &mut defined, &mut undefined);
+        }
+    }
+    let mut candidates = members
+        .iter()
+        .filter_map(|member| Some((&**member, ptx::ModuleParser::parse_checked(member).ok()?)))
+        .collect::<Vec<_>>();
+    let mut result = Vec::new();
+    loop {
+        undefined.retain(|name| !defined.contains(name));
+        let needed = candidates
+            .iter()
+            .position(|(_, ast)| defines_any(ast, &undefined));
+        match needed {
+            Some(index) => {
+                let (text, ast) = candidates.swap_remove(index);
+                add_symbols(&ast, &mut defined, &mut undefined);
+                result.push(text);
+            }
+            None => break,
+        }
+    }
+    result
+}
+
+fn add_symbols(
+    ast: &ast::Module,
+    defined: &mut FxHashSet<String>,
+    undefined: &mut FxHashSet<String>,
+) {
+    for (name, is_definition) in linking_symbols(ast) {
+        if is_definition {
+            defined.insert(name.to_string());
+        } else if !defined.contains(name) {
+            undefined.insert(name.to_string());
+        }
+    }
+}
+
+fn defines_any(ast: &ast::Module, undefined: &FxHashSet<String>) -> bool {
+    linking_symbols(ast).any(|(name, is_definition)| is_definition && undefined.contains(name))
+}
+
+// Yields globally visible symbols, with a flag telling if it's a definition
+fn linking_symbols<'a, 'input>(
+    ast: &'a ast::Module<'input>,
+) -> impl Iterator<Item = (&'input str, bool)> + 'a {
+    ast.directives
+        .iter()
+        .filter_map(|directive| match directive {
+            ast::Directive::Variable(linking, multivar) => match linking {
+                ast::LinkingDirective::None => None,
+                ast::LinkingDirective::Extern => Some((multivar.variable.name, false)),
+                ast::LinkingDirective::Visible
+                | ast::LinkingDirective::Weak
+                | ast::LinkingDirective::Common => Some((multivar.variable.name, true)),
+            },
+            ast::Directive::Method(linking, method) => {
+                let name = match method.func_directive.name {
+                    ast::MethodName::Func(name) => name,
+                    ast::MethodName::Kernel(_) => return None,
+                };
+                match linking {
+                    ast::LinkingDirective::None => None,
+                    ast::LinkingDirective::Extern => Some((name, false)),
+                    _ => Some((name, method.body.is_some())),
+                }
+            }
+        })
+}
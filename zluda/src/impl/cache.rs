@@ -1,15 +1,15 @@
 use hip_common::{
-    cache::{KernelExtendedData, KernelRepository},
+    cache::{self, ComputeExtendedData, KernelRepository},
     unwrap_or_return, CompilationMode,
 };
 use static_assertions::assert_impl_one;
 use std::{borrow::Cow, ffi::CStr, path::Path};
 
-pub(crate) struct KernelCache(KernelRepository<NoExtendedData>);
+pub(crate) struct KernelCache(KernelRepository<ComputeExtendedData>);
 assert_impl_one!(KernelCache: Sync);
 
 impl KernelCache {
-    pub(crate) const CACHE_FILE: &'static str = "zluda.db";
+    pub(crate) const CACHE_FILE: &'static str = cache::COMPUTE_CACHE_FILE;
 
     pub(crate) fn new(cache_dir: &Path) -> Option<Self> {
         let mut file = cache_dir.to_path_buf();
@@ -25,12 +25,8 @@ impl KernelCache {
         compilation_mode: CompilationMode,
         binary: &[u8],
     ) {
-        let now = unwrap_or_return!(KernelRepository::<NoExtendedData>::now());
-        let mut hasher = blake3::Hasher::new();
-        for module in ptx_modules {
-            hasher.update(module.as_bytes());
-        }
-        let hash = hasher.finalize().to_hex();
+        let now = unwrap_or_return!(KernelRepository::<ComputeExtendedData>::now());
+        let hash = cache::compute_hash(ptx_modules);
         let git_hash = env!("VERGEN_GIT_SHA");
         self.0
             .save_program(
@@ -52,12 +48,8 @@ impl KernelCache {
         ptx_modules: &[Cow<'_, str>],
         compilation_mode: CompilationMode,
     ) -> Option<Vec<u8>> {
-        let now = KernelRepository::<NoExtendedData>::now().ok()?;
-        let mut hasher = blake3::Hasher::new();
-        for module in ptx_modules {
-            hasher.update(module.as_bytes());
-        }
-        let hash = hasher.finalize().to_hex();
+        let now = KernelRepository::<ComputeExtendedData>::now().ok()?;
+        let hash = cache::compute_hash(ptx_modules);
         let git_hash = env!("VERGEN_GIT_SHA");
         Some(
             self.0
@@ -74,9 +66,3 @@ impl KernelCache {
         )
     }
 }
-
-struct NoExtendedData;
-
-impl KernelExtendedData for NoExtendedData {
-    const INPUT_COLUMNS: &'static [[&'static str; 2]] = &[["compilation_mode", "INTEGER NOT NULL"]];
-}
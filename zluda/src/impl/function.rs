@@ -3,7 +3,7 @@ use crate::{hip_call_cuda, r#impl::hipfix};
 use cuda_types::*;
 use hip_common::CompilationMode;
 use hip_runtime_sys::*;
-use std::{ffi::c_void, ptr};
+use std::{ffi::c_void, mem, ptr};
 
 const CU_LAUNCH_PARAM_BUFFER_POINTER: *mut c_void = 1 as *mut _;
 const CU_LAUNCH_PARAM_BUFFER_SIZE: *mut c_void = 2 as *mut _;
@@ -30,6 +30,7 @@ pub(crate) struct FunctionData {
     pub(crate) binary_version: u32,
     pub(crate) group_size: Option<(u32, u32)>,
     pub(crate) compilation_mode: CompilationMode,
+    pub(crate) grid_workspace: Option<hipDeviceptr_t>,
 }
 
 pub(crate) unsafe fn launch_kernel(
@@ -97,6 +98,218 @@ pub(crate) unsafe fn launch_kernel(
     Ok(())
 }
 
+pub(crate) unsafe fn launch_cooperative_kernel(
+    f: *mut Function,
+    grid_dim_x: ::std::os::raw::c_uint,
+    grid_dim_y: ::std::os::raw::c_uint,
+    grid_dim_z: ::std::os::raw::c_uint,
+    block_dim_x: ::std::os::raw::c_uint,
+    block_dim_y: ::std::os::raw::c_uint,
+    mut block_dim_z: ::std::os::raw::c_uint,
+    shared_mem_bytes: ::std::os::raw::c_uint,
+    stream: *mut stream::Stream,
+    kernel_params: *mut *mut ::std::os::raw::c_void,
+    default_stream_per_thread: bool,
+) -> Result<(), CUresult> {
+    let hip_stream = hipfix::as_hip_stream_per_thread(stream, default_stream_per_thread)?;
+    let function = LiveCheck::as_result(f)?;
+    hipfix::validate_block_size(function, block_dim_x, block_dim_y, block_dim_z)?;
+    if function.compilation_mode == CompilationMode::Wave32OnWave64 {
+        block_dim_z *= 2;
+    }
+    reset_grid_workspace(function, hip_stream)?;
+    hip_call_cuda!(hipModuleLaunchCooperativeKernel(
+        function.base,
+        grid_dim_x,
+        grid_dim_y,
+        grid_dim_z,
+        block_dim_x,
+        block_dim_y,
+        block_dim_z,
+        shared_mem_bytes,
+        hip_stream,
+        kernel_params,
+    ));
+    Ok(())
+}
+
+// Workspace left over by an earlier launch that did not finish its grid sync
+// (e.g. aborted) would deadlock the next one, so it's reset before every
+// cooperative launch, in order with other work on the stream
+unsafe fn reset_grid_workspace(
+    function: &FunctionData,
+    hip_stream: hipStream_t,
+) -> Result<(), CUresult> {
+    if let Some(workspace) = function.grid_workspace {
+        // struct grid_workspace { unsigned int wsSize; unsigned int barrier; }
+        hip_call_cuda!(hipMemsetD32Async(
+            workspace,
+            mem::size_of::<[u32; 2]>() as i32,
+            1,
+            hip_stream
+        ));
+        hip_call_cuda!(hipMemsetD32Async(
+            hipDeviceptr_t(workspace.0.cast::<u32>().add(1).cast()),
+            0,
+            1,
+            hip_stream
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) unsafe fn launch_cooperative_kernel_multi_device(
+    launch_params_list: *mut CUDA_LAUNCH_PARAMS,
+    num_devices: ::std::os::raw::c_uint,
+    flags: ::std::os::raw::c_uint,
+) -> Result<(), CUresult> {
+    if launch_params_list == ptr::null_mut() || num_devices == 0 {
+        return Err(CUresult::CUDA_ERROR_INVALID_VALUE);
+    }
+    let launch_params_list = std::slice::from_raw_parts(launch_params_list, num_devices as usize);
+    let mut hip_params_list = launch_params_list
+        .iter()
+        .map(|params| {
+            let hip_stream = hipfix::as_hip_stream_per_thread(params.hStream.cast(), false)?;
+            let function = LiveCheck::as_result(params.function.cast::<Function>())?;
+            hipfix::validate_block_size(
+                function,
+                params.blockDimX,
+                params.blockDimY,
+                params.blockDimZ,
+            )?;
+            let mut block_dim_z = params.blockDimZ;
+            if function.compilation_mode == CompilationMode::Wave32OnWave64 {
+                block_dim_z *= 2;
+            }
+            reset_grid_workspace(function, hip_stream)?;
+            Ok(hipFunctionLaunchParams {
+                function: function.base,
+                gridDimX: params.gridDimX,
+                gridDimY: params.gridDimY,
+                gridDimZ: params.gridDimZ,
+                blockDimX: params.blockDimX,
+                blockDimY: params.blockDimY,
+                blockDimZ: block_dim_z,
+                sharedMemBytes: params.sharedMemBytes,
+                hStream: hip_stream,
+                kernelParams: params.kernelParams,
+            })
+        })
+        .collect::<Result<Vec<_>, CUresult>>()?;
+    // CUDA_COOPERATIVE_LAUNCH_MULTI_DEVICE_NO_* flags have the same values as in HIP
+    hip_call_cuda!(hipModuleLaunchCooperativeKernelMultiDevice(
+        hip_params_list.as_mut_ptr(),
+        num_devices,
+        flags,
+    ));
+    Ok(())
+}
+
+// Launch attributes are handled as follows:
+// * COOPERATIVE is honoured with a cooperative launch
+// * CLUSTER_DIMENSION is accepted only for 1x1x1 clusters, see below
+// * PROGRAMMATIC_EVENT is honoured by recording the event after the kernel.
+//   CUDA allows the event to fire earlier (once every block triggered), never
+//   later, so this is a valid, if conservative, implementation
+// * PROGRAMMATIC_STREAM_SERIALIZATION, ACCESS_POLICY_WINDOW,
+//   SYNCHRONIZATION_POLICY, CLUSTER_SCHEDULING_POLICY_PREFERENCE,
+//   MEM_SYNC_DOMAIN and MEM_SYNC_DOMAIN_MAP are ignored. They either relax
+//   ordering guarantees that we keep anyway (full stream serialization) or
+//   are performance hints without an AMD equivalent
+// * PRIORITY: HIP only has per-stream priorities; ignored
+// * Anything else is rejected with CUDA_ERROR_NOT_SUPPORTED
+// The attributes passed, and so which of them were ignored, are visible in
+// zluda_dump traces
+pub(crate) unsafe fn launch_kernel_ex(
+    config: *const CUlaunchConfig,
+    f: *mut Function,
+    kernel_params: *mut *mut ::std::os::raw::c_void,
+    extra: *mut *mut ::std::os::raw::c_void,
+    default_stream_per_thread: bool,
+) -> Result<(), CUresult> {
+    let config = config.as_ref().ok_or(CUresult::CUDA_ERROR_INVALID_VALUE)?;
+    let attributes = if config.numAttrs == 0 {
+        &[][..]
+    } else if config.attrs == ptr::null_mut() {
+        return Err(CUresult::CUDA_ERROR_INVALID_VALUE);
+    } else {
+        std::slice::from_raw_parts(config.attrs, config.numAttrs as usize)
+    };
+    let mut cooperative = false;
+    let mut programmatic_event = None;
+    for attribute in attributes {
+        match attribute.id {
+            CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_IGNORE => {}
+            CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_COOPERATIVE => {
+                cooperative = attribute.value.cooperative != 0;
+            }
+            // There are no thread block clusters on AMD GPUs, every block is
+            // its own cluster and kernels see it through %cluster_* registers
+            CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_CLUSTER_DIMENSION => {
+                let cluster_dim = attribute.value.clusterDim;
+                if (cluster_dim.x, cluster_dim.y, cluster_dim.z) != (1, 1, 1) {
+                    return Err(CUresult::CUDA_ERROR_INVALID_CLUSTER_SIZE);
+                }
+            }
+            CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_PROGRAMMATIC_EVENT => {
+                let event = attribute.value.programmaticEvent.event;
+                if event == ptr::null_mut() {
+                    return Err(CUresult::CUDA_ERROR_INVALID_VALUE);
+                }
+                programmatic_event = Some(event);
+            }
+            CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_ACCESS_POLICY_WINDOW
+            | CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_SYNCHRONIZATION_POLICY
+            | CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_CLUSTER_SCHEDULING_POLICY_PREFERENCE
+            | CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_PROGRAMMATIC_STREAM_SERIALIZATION
+            | CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_PRIORITY
+            | CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_MEM_SYNC_DOMAIN_MAP
+            | CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_MEM_SYNC_DOMAIN => {}
+            _ => return Err(CUresult::CUDA_ERROR_NOT_SUPPORTED),
+        }
+    }
+    if cooperative {
+        if extra != ptr::null_mut() {
+            return Err(CUresult::CUDA_ERROR_INVALID_VALUE);
+        }
+        launch_cooperative_kernel(
+            f,
+            config.gridDimX,
+            config.gridDimY,
+            config.gridDimZ,
+            config.blockDimX,
+            config.blockDimY,
+            config.blockDimZ,
+            config.sharedMemBytes,
+            config.hStream.cast(),
+            kernel_params,
+            default_stream_per_thread,
+        )?;
+    } else {
+        launch_kernel(
+            f,
+            config.gridDimX,
+            config.gridDimY,
+            config.gridDimZ,
+            config.blockDimX,
+            config.blockDimY,
+            config.blockDimZ,
+            config.sharedMemBytes,
+            config.hStream.cast(),
+            kernel_params,
+            extra,
+            default_stream_per_thread,
+        )?;
+    }
+    if let Some(event) = programmatic_event {
+        let hip_stream =
+            hipfix::as_hip_stream_per_thread(config.hStream.cast(), default_stream_per_thread)?;
+        hip_call_cuda!(hipEventRecord(event.cast(), hip_stream));
+    }
+    Ok(())
+}
+
 pub(crate) unsafe fn occupancy_max_potential_block_size(
     min_grid_size: *mut i32,
     block_size: *mut i32,
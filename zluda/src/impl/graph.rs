@@ -1,18 +1,239 @@
-use super::{function, stream, LiveCheck};
+use super::{
+    function, hipfix, mem_pool_props_from_cuda, stream, surface, FromCuda, IntoCuda, LiveCheck,
+};
 use crate::hip_call_cuda;
 use cuda_types::*;
+use hip_common::CompilationMode;
 use hip_runtime_sys::*;
+use std::{mem, ptr};
 
-pub(crate) unsafe fn add_kernel_node(
+// Translation of CUDA node parameters to their HIP counterparts, shared by
+// node creation and the (exec) node parameter setters
+pub(crate) trait NodeParams {
+    type Hip;
+    unsafe fn to_hip(&self) -> Result<Self::Hip, CUresult>;
+}
+
+unsafe fn hip_node_params<T: NodeParams>(cuda: *const T) -> Result<T::Hip, CUresult> {
+    cuda.as_ref()
+        .ok_or(CUresult::CUDA_ERROR_INVALID_VALUE)?
+        .to_hip()
+}
+
+impl NodeParams for CUDA_KERNEL_NODE_PARAMS_v1 {
+    type Hip = hipKernelNodeParams;
+
+    unsafe fn to_hip(&self) -> Result<hipKernelNodeParams, CUresult> {
+        let zluda_func = self.func.cast::<function::Function>();
+        let zluda_func = LiveCheck::as_result(zluda_func)?;
+        hipfix::validate_block_size(zluda_func, self.blockDimX, self.blockDimY, self.blockDimZ)?;
+        let mut block_dim_z = self.blockDimZ;
+        if zluda_func.compilation_mode == CompilationMode::Wave32OnWave64 {
+            block_dim_z *= 2;
+        }
+        Ok(hipKernelNodeParams {
+            blockDim: dim3 {
+                x: self.blockDimX,
+                y: self.blockDimY,
+                z: block_dim_z,
+            },
+            extra: self.extra,
+            func: zluda_func.base.cast(),
+            gridDim: dim3 {
+                x: self.gridDimX,
+                y: self.gridDimY,
+                z: self.gridDimZ,
+            },
+            kernelParams: self.kernelParams,
+            sharedMemBytes: self.sharedMemBytes,
+        })
+    }
+}
+
+impl NodeParams for CUDA_KERNEL_NODE_PARAMS {
+    type Hip = hipKernelNodeParams;
+
+    unsafe fn to_hip(&self) -> Result<hipKernelNodeParams, CUresult> {
+        // We don't support CUkernel handles, so the node must use a function
+        if self.kern != ptr::null_mut() && self.func == ptr::null_mut() {
+            return Err(CUresult::CUDA_ERROR_NOT_SUPPORTED);
+        }
+        CUDA_KERNEL_NODE_PARAMS_v1 {
+            func: self.func,
+            gridDimX: self.gridDimX,
+            gridDimY: self.gridDimY,
+            gridDimZ: self.gridDimZ,
+            blockDimX: self.blockDimX,
+            blockDimY: self.blockDimY,
+            blockDimZ: self.blockDimZ,
+            sharedMemBytes: self.sharedMemBytes,
+            kernelParams: self.kernelParams,
+            extra: self.extra,
+        }
+        .to_hip()
+    }
+}
+
+impl NodeParams for CUDA_MEMCPY3D {
+    type Hip = hipMemcpy3DParms;
+
+    unsafe fn to_hip(&self) -> Result<hipMemcpy3DParms, CUresult> {
+        let (src_array, src_element_size) = array_element_size(self.srcMemoryType, self.srcArray)?;
+        let (dst_array, dst_element_size) = array_element_size(self.dstMemoryType, self.dstArray)?;
+        // Runtime-style copy parameters measure array extents in elements,
+        // so the array side (or both sides, which must agree) sets the unit
+        let element_size = match (src_element_size, dst_element_size) {
+            (Some(src), Some(dst)) if src != dst => return Err(CUresult::CUDA_ERROR_INVALID_VALUE),
+            (Some(size), _) | (_, Some(size)) => size,
+            (None, None) => 1,
+        };
+        let to_elements = |bytes: usize, is_array: bool| {
+            if !is_array {
+                Ok(bytes)
+            } else if bytes % element_size != 0 {
+                Err(CUresult::CUDA_ERROR_INVALID_VALUE)
+            } else {
+                Ok(bytes / element_size)
+            }
+        };
+        Ok(hipMemcpy3DParms {
+            srcArray: src_array,
+            srcPos: hipPos {
+                x: to_elements(self.srcXInBytes, src_element_size.is_some())?,
+                y: self.srcY,
+                z: self.srcZ,
+            },
+            srcPtr: pitched_ptr(
+                self.srcMemoryType,
+                self.srcHost as _,
+                self.srcDevice,
+                self.srcPitch,
+                self.srcHeight,
+                self.WidthInBytes,
+            ),
+            dstArray: dst_array,
+            dstPos: hipPos {
+                x: to_elements(self.dstXInBytes, dst_element_size.is_some())?,
+                y: self.dstY,
+                z: self.dstZ,
+            },
+            dstPtr: pitched_ptr(
+                self.dstMemoryType,
+                self.dstHost,
+                self.dstDevice,
+                self.dstPitch,
+                self.dstHeight,
+                self.WidthInBytes,
+            ),
+            extent: hipExtent {
+                width: to_elements(
+                    self.WidthInBytes,
+                    src_element_size.is_some() || dst_element_size.is_some(),
+                )?,
+                height: self.Height,
+                depth: self.Depth,
+            },
+            kind: hipMemcpyKind::hipMemcpyDefault,
+        })
+    }
+}
+
+unsafe fn array_element_size(
+    memory_type: CUmemorytype,
+    array: CUarray,
+) -> Result<(hipArray_t, Option<usize>), CUresult> {
+    if memory_type != CUmemorytype::CU_MEMORYTYPE_ARRAY {
+        return Ok((ptr::null_mut(), None));
+    }
+    // Layered 1D arrays keep their layers in the height, not in the depth,
+    // see hipfix::array::copy3d
+    if hipfix::array::get_layered_dimensions(array) == 1 {
+        return Err(CUresult::CUDA_ERROR_NOT_SUPPORTED);
+    }
+    let hip_array = hipfix::array::get(array);
+    let hip_array_ref = hip_array
+        .as_ref()
+        .ok_or(CUresult::CUDA_ERROR_INVALID_VALUE)?;
+    let element_size =
+        surface::format_size(hip_array_ref.Format)? * hip_array_ref.NumChannels as usize;
+    Ok((hip_array, Some(element_size)))
+}
+
+fn pitched_ptr(
+    memory_type: CUmemorytype,
+    host: *mut std::ffi::c_void,
+    device: CUdeviceptr,
+    pitch: usize,
+    height: usize,
+    width_in_bytes: usize,
+) -> hipPitchedPtr {
+    let ptr = if memory_type == CUmemorytype::CU_MEMORYTYPE_HOST {
+        host
+    } else if memory_type == CUmemorytype::CU_MEMORYTYPE_ARRAY {
+        ptr::null_mut()
+    } else {
+        hipDeviceptr_t::from_cuda(device).0
+    };
+    // CUDA ignores pitch of single row copies, HIP validates it
+    let pitch = pitch.max(width_in_bytes);
+    hipPitchedPtr {
+        ptr,
+        pitch,
+        xsize: width_in_bytes,
+        ysize: height,
+    }
+}
+
+impl NodeParams for CUDA_MEMSET_NODE_PARAMS {
+    type Hip = hipMemsetParams;
+
+    unsafe fn to_hip(&self) -> Result<hipMemsetParams, CUresult> {
+        Ok(hipMemsetParams {
+            dst: hipDeviceptr_t::from_cuda(self.dst).0,
+            elementSize: self.elementSize,
+            height: self.height,
+            pitch: self.pitch,
+            value: self.value,
+            width: self.width,
+        })
+    }
+}
+
+impl NodeParams for CUDA_HOST_NODE_PARAMS {
+    type Hip = hipHostNodeParams;
+
+    unsafe fn to_hip(&self) -> Result<hipHostNodeParams, CUresult> {
+        if self.fn_.is_none() {
+            return Err(CUresult::CUDA_ERROR_INVALID_VALUE);
+        }
+        Ok(hipHostNodeParams {
+            fn_: mem::transmute(self.fn_),
+            userData: self.userData,
+        })
+    }
+}
+
+impl NodeParams for CUDA_MEM_ALLOC_NODE_PARAMS {
+    type Hip = hipMemAllocNodeParams;
+
+    unsafe fn to_hip(&self) -> Result<hipMemAllocNodeParams, CUresult> {
+        Ok(hipMemAllocNodeParams {
+            poolProps: mem_pool_props_from_cuda(&self.poolProps)?,
+            accessDescs: FromCuda::from_cuda(self.accessDescs),
+            accessDescCount: self.accessDescCount,
+            bytesize: self.bytesize,
+            dptr: ptr::null_mut(),
+        })
+    }
+}
+
+pub(crate) unsafe fn add_kernel_node<T: NodeParams<Hip = hipKernelNodeParams>>(
     ph_graph_node: *mut hipGraphNode_t,
     h_graph: hipGraph_t,
     dependencies: *const hipGraphNode_t,
     num_dependencies: usize,
-    node_params: *const CUDA_KERNEL_NODE_PARAMS_v1,
+    node_params: *const T,
 ) -> Result<(), CUresult> {
-    let node_params = node_params
-        .as_ref()
-        .ok_or(CUresult::CUDA_ERROR_INVALID_VALUE)?;
     let node_params = hip_node_params(node_params)?;
     hip_call_cuda!(hipGraphAddKernelNode(
         ph_graph_node,
@@ -24,27 +245,179 @@ pub(crate) unsafe fn add_kernel_node(
     Ok(())
 }
 
-unsafe fn hip_node_params(
-    cuda: &CUDA_KERNEL_NODE_PARAMS_v1,
-) -> Result<hipKernelNodeParams, CUresult> {
-    let zluda_func = cuda.func.cast::<function::Function>();
-    let zluda_func = LiveCheck::as_result(zluda_func)?;
-    Ok(hipKernelNodeParams {
-        blockDim: dim3 {
-            x: cuda.blockDimX,
-            y: cuda.blockDimY,
-            z: cuda.blockDimZ,
-        },
-        extra: cuda.extra,
-        func: zluda_func.base.cast(),
-        gridDim: dim3 {
-            x: cuda.gridDimX,
-            y: cuda.gridDimY,
-            z: cuda.gridDimZ,
-        },
-        kernelParams: cuda.kernelParams,
-        sharedMemBytes: cuda.sharedMemBytes,
-    })
+pub(crate) unsafe fn kernel_node_set_params<T: NodeParams<Hip = hipKernelNodeParams>>(
+    node: hipGraphNode_t,
+    node_params: *const T,
+) -> Result<(), CUresult> {
+    let node_params = hip_node_params(node_params)?;
+    hip_call_cuda!(hipGraphKernelNodeSetParams(node, &node_params));
+    Ok(())
+}
+
+pub(crate) unsafe fn exec_kernel_node_set_params<T: NodeParams<Hip = hipKernelNodeParams>>(
+    graph_exec: hipGraphExec_t,
+    node: hipGraphNode_t,
+    node_params: *const T,
+) -> Result<(), CUresult> {
+    let node_params = hip_node_params(node_params)?;
+    hip_call_cuda!(hipGraphExecKernelNodeSetParams(
+        graph_exec,
+        node,
+        &node_params
+    ));
+    Ok(())
+}
+
+pub(crate) unsafe fn add_memcpy_node(
+    ph_graph_node: *mut hipGraphNode_t,
+    h_graph: hipGraph_t,
+    dependencies: *const hipGraphNode_t,
+    num_dependencies: usize,
+    copy_params: *const CUDA_MEMCPY3D,
+) -> Result<(), CUresult> {
+    let copy_params = hip_node_params(copy_params)?;
+    hip_call_cuda!(hipGraphAddMemcpyNode(
+        ph_graph_node,
+        h_graph,
+        dependencies,
+        num_dependencies,
+        &copy_params,
+    ));
+    Ok(())
+}
+
+pub(crate) unsafe fn exec_memcpy_node_set_params(
+    graph_exec: hipGraphExec_t,
+    node: hipGraphNode_t,
+    copy_params: *const CUDA_MEMCPY3D,
+) -> Result<(), CUresult> {
+    let mut copy_params = hip_node_params(copy_params)?;
+    hip_call_cuda!(hipGraphExecMemcpyNodeSetParams(
+        graph_exec,
+        node,
+        &mut copy_params
+    ));
+    Ok(())
+}
+
+pub(crate) unsafe fn add_memset_node(
+    ph_graph_node: *mut hipGraphNode_t,
+    h_graph: hipGraph_t,
+    dependencies: *const hipGraphNode_t,
+    num_dependencies: usize,
+    memset_params: *const CUDA_MEMSET_NODE_PARAMS,
+) -> Result<(), CUresult> {
+    let memset_params = hip_node_params(memset_params)?;
+    hip_call_cuda!(hipGraphAddMemsetNode(
+        ph_graph_node,
+        h_graph,
+        dependencies,
+        num_dependencies,
+        &memset_params,
+    ));
+    Ok(())
+}
+
+pub(crate) unsafe fn exec_memset_node_set_params(
+    graph_exec: hipGraphExec_t,
+    node: hipGraphNode_t,
+    memset_params: *const CUDA_MEMSET_NODE_PARAMS,
+) -> Result<(), CUresult> {
+    let memset_params = hip_node_params(memset_params)?;
+    hip_call_cuda!(hipGraphExecMemsetNodeSetParams(
+        graph_exec,
+        node,
+        &memset_params
+    ));
+    Ok(())
+}
+
+pub(crate) unsafe fn add_host_node(
+    ph_graph_node: *mut hipGraphNode_t,
+    h_graph: hipGraph_t,
+    dependencies: *const hipGraphNode_t,
+    num_dependencies: usize,
+    node_params: *const CUDA_HOST_NODE_PARAMS,
+) -> Result<(), CUresult> {
+    let node_params = hip_node_params(node_params)?;
+    hip_call_cuda!(hipGraphAddHostNode(
+        ph_graph_node,
+        h_graph,
+        dependencies,
+        num_dependencies,
+        &node_params,
+    ));
+    Ok(())
+}
+
+pub(crate) unsafe fn exec_host_node_set_params(
+    graph_exec: hipGraphExec_t,
+    node: hipGraphNode_t,
+    node_params: *const CUDA_HOST_NODE_PARAMS,
+) -> Result<(), CUresult> {
+    let node_params = hip_node_params(node_params)?;
+    hip_call_cuda!(hipGraphExecHostNodeSetParams(
+        graph_exec,
+        node,
+        &node_params
+    ));
+    Ok(())
+}
+
+pub(crate) unsafe fn add_mem_alloc_node(
+    ph_graph_node: *mut hipGraphNode_t,
+    h_graph: hipGraph_t,
+    dependencies: *const hipGraphNode_t,
+    num_dependencies: usize,
+    node_params: *mut CUDA_MEM_ALLOC_NODE_PARAMS,
+) -> Result<(), CUresult> {
+    let mut hip_params = hip_node_params(node_params)?;
+    hip_call_cuda!(hipGraphAddMemAllocNode(
+        ph_graph_node,
+        h_graph,
+        dependencies,
+        num_dependencies,
+        &mut hip_params,
+    ));
+    (*node_params).dptr = CUdeviceptr_v2(hip_params.dptr as _);
+    Ok(())
+}
+
+pub(crate) unsafe fn node_get_type(
+    node: hipGraphNode_t,
+    type_: *mut hipGraphNodeType,
+) -> Result<(), CUresult> {
+    hip_call_cuda!(hipGraphNodeGetType(node, type_));
+    // CUDA has no separate node types for copies from and to symbols
+    if *type_ == hipGraphNodeType::hipGraphNodeTypeMemcpyFromSymbol
+        || *type_ == hipGraphNodeType::hipGraphNodeTypeMemcpyToSymbol
+    {
+        *type_ = hipGraphNodeType::hipGraphNodeTypeMemcpy;
+    }
+    Ok(())
+}
+
+pub(crate) unsafe fn exec_update(
+    graph_exec: hipGraphExec_t,
+    graph: hipGraph_t,
+    result_info: *mut CUgraphExecUpdateResultInfo,
+) -> Result<(), CUresult> {
+    let result_info = result_info
+        .as_mut()
+        .ok_or(CUresult::CUDA_ERROR_INVALID_VALUE)?;
+    let mut error_node = ptr::null_mut();
+    let mut update_result = hipGraphExecUpdateResult::hipGraphExecUpdateSuccess;
+    let hip_result = hipGraphExecUpdate(graph_exec, graph, &mut error_node, &mut update_result);
+    // values are compatible
+    result_info.result = CUgraphExecUpdateResult(update_result.0);
+    // HIP reports only the offending node of the new graph, the matching node
+    // of the instantiated graph is unknown
+    result_info.errorNode = error_node.cast();
+    result_info.errorFromNode = ptr::null_mut();
+    if hip_result != hipError_t::hipSuccess {
+        return Err(hip_result.into_cuda());
+    }
+    Ok(())
 }
 
 pub(crate) unsafe fn launch(
@@ -59,6 +59,8 @@ pub(crate) unsafe fn load_data(
                 &device.comgr_isa,
                 zluda_dark_api::CUmoduleContent::from_ptr(code.cast())
                     .map_err(|_| CUresult::CUDA_ERROR_INVALID_VALUE)?,
+                &mut String::new(),
+                false,
             )?;
             Ok(ModuleData::alloc(module_data))
         })
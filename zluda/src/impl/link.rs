@@ -1,6 +1,16 @@
-use super::{context, module, LiveCheck, ZludaObject, GLOBAL_STATE};
+use super::{archive, context, module, LiveCheck, ZludaObject, GLOBAL_STATE};
 use cuda_types::*;
-use std::{borrow::Cow, ptr, sync::Mutex};
+use std::{
+    borrow::Cow,
+    cmp,
+    ffi::c_void,
+    fmt::Write,
+    os::raw::{c_char, c_uint},
+    ptr,
+    sync::Mutex,
+    time::Instant,
+};
+use zluda_dark_api::CUmoduleContent;
 
 pub(crate) type LinkState = LiveCheck<LinkStateData>;
 
@@ -18,20 +28,189 @@ impl ZludaObject for LinkStateData {
 
 pub(crate) struct LinkStateData {
     ptx_modules: Mutex<Vec<Cow<'static, str>>>,
+    library_members: Mutex<Vec<Cow<'static, str>>>,
+    // CUDA requires option arrays passed to cuLinkCreate to stay valid until
+    // cuLinkDestroy, so log buffers are filled directly in cuLinkComplete
+    options: JitOptions,
+}
+
+// Subset of CUjit_option values that we understand. Values that we can't act
+// on (optimization level, target, debug info) are only reported in the info log
+// when CU_JIT_LOG_VERBOSE is set
+pub(crate) struct JitOptions {
+    info_log: Option<JitLogBuffer>,
+    error_log: Option<JitLogBuffer>,
+    wall_time: Option<*mut *mut c_void>,
+    optimization_level: Option<u32>,
+    target: Option<u32>,
+    generate_debug_info: bool,
+    pub(crate) generate_line_info: bool,
+    log_verbose: bool,
+}
+
+struct JitLogBuffer {
+    buffer: *mut c_char,
+    capacity: usize,
+    size_out: Option<*mut *mut c_void>,
+}
+
+impl JitOptions {
+    pub(crate) unsafe fn parse(
+        num_options: c_uint,
+        options: *mut CUjit_option,
+        option_values: *mut *mut c_void,
+    ) -> Result<Self, CUresult> {
+        let mut result = JitOptions {
+            info_log: None,
+            error_log: None,
+            wall_time: None,
+            optimization_level: None,
+            target: None,
+            generate_debug_info: false,
+            generate_line_info: false,
+            log_verbose: false,
+        };
+        if num_options == 0 {
+            return Ok(result);
+        }
+        if options == ptr::null_mut() || option_values == ptr::null_mut() {
+            return Err(CUresult::CUDA_ERROR_INVALID_VALUE);
+        }
+        let mut info_log = (ptr::null_mut(), None);
+        let mut error_log = (ptr::null_mut(), None);
+        for i in 0..num_options as usize {
+            let value_slot = option_values.add(i);
+            let value = *value_slot as usize;
+            match *options.add(i) {
+                CUjit_option::CU_JIT_INFO_LOG_BUFFER => info_log.0 = value as *mut c_char,
+                CUjit_option::CU_JIT_INFO_LOG_BUFFER_SIZE_BYTES => info_log.1 = Some(value_slot),
+                CUjit_option::CU_JIT_ERROR_LOG_BUFFER => error_log.0 = value as *mut c_char,
+                CUjit_option::CU_JIT_ERROR_LOG_BUFFER_SIZE_BYTES => error_log.1 = Some(value_slot),
+                CUjit_option::CU_JIT_WALL_TIME => result.wall_time = Some(value_slot),
+                CUjit_option::CU_JIT_OPTIMIZATION_LEVEL => {
+                    let level = value as u32;
+                    if level > 4 {
+                        return Err(CUresult::CUDA_ERROR_INVALID_VALUE);
+                    }
+                    result.optimization_level = Some(level);
+                }
+                CUjit_option::CU_JIT_TARGET => result.target = Some(value as u32),
+                CUjit_option::CU_JIT_GENERATE_DEBUG_INFO => {
+                    result.generate_debug_info = value as u32 != 0
+                }
+                CUjit_option::CU_JIT_GENERATE_LINE_INFO => {
+                    result.generate_line_info = value as u32 != 0
+                }
+                CUjit_option::CU_JIT_LOG_VERBOSE => result.log_verbose = value as u32 != 0,
+                _ => {}
+            }
+        }
+        result.info_log = JitLogBuffer::new(info_log.0, info_log.1);
+        result.error_log = JitLogBuffer::new(error_log.0, error_log.1);
+        Ok(result)
+    }
+
+    pub(crate) fn info_messages(&self) -> String {
+        let mut log = String::new();
+        if !self.log_verbose {
+            return log;
+        }
+        if let Some(level) = self.optimization_level {
+            writeln!(
+                log,
+                "info    : CU_JIT_OPTIMIZATION_LEVEL {} ignored, compiling with -O3",
+                level
+            )
+            .unwrap();
+        }
+        if let Some(target) = self.target {
+            writeln!(
+                log,
+                "info    : CU_JIT_TARGET {} ignored, compiling for the current device",
+                target
+            )
+            .unwrap();
+        }
+        if self.generate_debug_info {
+            writeln!(log, "info    : CU_JIT_GENERATE_DEBUG_INFO ignored").unwrap();
+        }
+        log
+    }
+
+    pub(crate) unsafe fn write_logs(&self, info_log: &str, error_log: &str) {
+        if let Some(ref buffer) = self.info_log {
+            buffer.write(info_log);
+        }
+        if let Some(ref buffer) = self.error_log {
+            buffer.write(error_log);
+        }
+    }
+
+    // CU_JIT_WALL_TIME is reported as a float (milliseconds) stored in place of the option value
+    pub(crate) unsafe fn write_wall_time(&self, start: Instant) {
+        if let Some(slot) = self.wall_time {
+            *slot = ptr::null_mut();
+            *slot.cast::<f32>() = start.elapsed().as_secs_f32() * 1000.0;
+        }
+    }
+}
+
+impl JitLogBuffer {
+    fn new(buffer: *mut c_char, size_slot: Option<*mut *mut c_void>) -> Option<Self> {
+        if buffer == ptr::null_mut() {
+            return None;
+        }
+        let capacity = size_slot.map_or(0, |slot| unsafe { *slot } as usize as u32 as usize);
+        Some(Self {
+            buffer,
+            capacity,
+            size_out: size_slot,
+        })
+    }
+
+    // Same as CUDA: the log is truncated to fit and always NUL-terminated,
+    // the size option is overwritten with the number of bytes written
+    unsafe fn write(&self, text: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+        let len = cmp::min(text.len(), self.capacity - 1);
+        ptr::copy_nonoverlapping(text.as_ptr(), self.buffer.cast::<u8>(), len);
+        *self.buffer.add(len) = 0;
+        if let Some(size_out) = self.size_out {
+            *size_out = len as *mut c_void;
+        }
+    }
 }
 
 pub(crate) unsafe fn add_data(
     state: *mut LinkState,
     type_: CUjitInputType,
     data: *mut ::std::os::raw::c_void,
-    mut size: usize,
+    size: usize,
     _name: *const ::std::os::raw::c_char,
-    _num_options: ::std::os::raw::c_uint,
-    _options: *mut CUjit_option,
-    _option_values: *mut *mut ::std::os::raw::c_void,
+    num_options: ::std::os::raw::c_uint,
+    options: *mut CUjit_option,
+    option_values: *mut *mut ::std::os::raw::c_void,
 ) -> Result<(), CUresult> {
     let state = LiveCheck::as_result(state)?;
-    match type_ {
+    let options = JitOptions::parse(num_options, options, option_values)?;
+    let start = Instant::now();
+    let mut error_log = String::new();
+    let result = add_data_impl(state, type_, data, size, &mut error_log);
+    options.write_wall_time(start);
+    options.write_logs(&options.info_messages(), &error_log);
+    result
+}
+
+unsafe fn add_data_impl(
+    state: &LinkStateData,
+    type_: CUjitInputType,
+    data: *mut ::std::os::raw::c_void,
+    mut size: usize,
+    error_log: &mut String,
+) -> Result<(), CUresult> {
+    let new_modules = match type_ {
         CUjitInputType::CU_JIT_INPUT_PTX => {
             let data = data.cast::<u8>();
             loop {
@@ -42,22 +221,54 @@ pub(crate) unsafe fn add_data(
                 }
             }
             let buffer = std::slice::from_raw_parts(data.cast::<u8>(), size);
-            let buffer =
-                std::str::from_utf8(buffer).map_err(|_| CUresult::CUDA_ERROR_INVALID_VALUE)?;
-            let ptx = buffer.to_string();
-            let mut modules = state
-                .ptx_modules
+            let buffer = std::str::from_utf8(buffer).map_err(|err| {
+                writeln!(error_log, "error   : PTX input is not valid UTF-8: {}", err).unwrap();
+                CUresult::CUDA_ERROR_INVALID_VALUE
+            })?;
+            vec![Cow::Owned(buffer.to_string())]
+        }
+        CUjitInputType::CU_JIT_INPUT_FATBINARY => match CUmoduleContent::from_ptr(data.cast()) {
+            Ok(CUmoduleContent::Fatbin(fatbin)) => module::fatbin_link_inputs(fatbin)?,
+            _ => return Err(CUresult::CUDA_ERROR_INVALID_IMAGE),
+        },
+        // We can't do anything with SASS, but host objects (and cubins
+        // mislabeled as such) might carry fatbins with PTX in them
+        CUjitInputType::CU_JIT_INPUT_OBJECT | CUjitInputType::CU_JIT_INPUT_CUBIN => {
+            let elf = std::slice::from_raw_parts(data.cast::<u8>(), size);
+            let ptx_modules = module::object_link_inputs(elf)?;
+            if ptx_modules.is_empty() {
+                writeln!(error_log, "error   : Input ELF does not contain PTX code").unwrap();
+                return Err(CUresult::CUDA_ERROR_NOT_SUPPORTED);
+            }
+            ptx_modules
+        }
+        // Archive members are linked in cuLinkComplete, and only if they are
+        // needed to resolve symbols of other inputs. Libraries we can't use
+        // contribute nothing instead of failing the link: V-Ray, for one,
+        // passes CUDA Runtime archive that is not used anyway
+        CUjitInputType::CU_JIT_INPUT_LIBRARY => {
+            let archive = std::slice::from_raw_parts(data.cast::<u8>(), size);
+            let members = archive::ptx_members(archive).unwrap_or_default();
+            let mut libraries = state
+                .library_members
                 .lock()
                 .map_err(|_| CUresult::CUDA_ERROR_UNKNOWN)?;
-            modules.push(Cow::Owned(ptx));
-            Ok(())
-        }
-        // Right now only user of this data type is
-        // V-Ray, which passes CUDA Runtime archive
-        // that is not used anyway
-        CUjitInputType::CU_JIT_INPUT_LIBRARY => Ok(()),
-        _ => Err(CUresult::CUDA_ERROR_NOT_SUPPORTED),
-    }
+            libraries.extend(members);
+            return Ok(());
+        }
+        _ => return Err(CUresult::CUDA_ERROR_NOT_SUPPORTED),
+    };
+    let mut modules = state
+        .ptx_modules
+        .lock()
+        .map_err(|_| CUresult::CUDA_ERROR_UNKNOWN)?;
+    // Input buffers belong to the caller and are not guaranteed to outlive the linker
+    modules.extend(
+        new_modules
+            .into_iter()
+            .map(|module| Cow::Owned(module.into_owned())),
+    );
+    Ok(())
 }
 
 pub(crate) unsafe fn complete(
@@ -73,15 +284,39 @@ pub(crate) unsafe fn complete(
         .ptx_modules
         .lock()
         .map_err(|_| CUresult::CUDA_ERROR_UNKNOWN)?;
+    let library_members = state
+        .library_members
+        .lock()
+        .map_err(|_| CUresult::CUDA_ERROR_UNKNOWN)?;
+    let mut inputs = modules
+        .iter()
+        .map(|module| Cow::Borrowed(&**module))
+        .collect::<Vec<_>>();
+    if !library_members.is_empty() {
+        inputs.extend(
+            archive::select_members(&modules, &library_members)
+                .into_iter()
+                .map(Cow::Borrowed),
+        );
+    }
     let device = context::with_current(|ctx| ctx.device)?;
     let global_state = GLOBAL_STATE.get()?;
     let device_object = global_state.device(device)?;
+    let start = Instant::now();
+    let mut error_log = String::new();
     let module = module::link_build_zluda_module(
         global_state,
         device_object.compilation_mode,
         &device_object.comgr_isa,
-        &modules,
-    )?;
+        &inputs,
+        &mut error_log,
+        state.options.generate_line_info,
+    );
+    state.options.write_wall_time(start);
+    state
+        .options
+        .write_logs(&state.options.info_messages(), &error_log);
+    let module = module?;
     let module = module.into_boxed_slice();
     let size = module.len();
     let ptr = Box::into_raw(module);
@@ -91,13 +326,19 @@ pub(crate) unsafe fn complete(
 }
 
 pub(crate) unsafe fn create(
-    _num_options: ::std::os::raw::c_uint,
-    _options: *mut CUjit_option,
-    _option_values: *mut *mut ::std::os::raw::c_void,
+    num_options: ::std::os::raw::c_uint,
+    options: *mut CUjit_option,
+    option_values: *mut *mut ::std::os::raw::c_void,
     state_out: *mut *mut LinkState,
 ) -> Result<(), CUresult> {
+    if state_out == ptr::null_mut() {
+        return Err(CUresult::CUDA_ERROR_INVALID_VALUE);
+    }
+    let options = JitOptions::parse(num_options, options, option_values)?;
     let link_state = LinkState::new(LinkStateData {
         ptx_modules: Mutex::new(Vec::new()),
+        library_members: Mutex::new(Vec::new()),
+        options,
     });
     let link_state = Box::into_raw(Box::new(link_state));
     *state_out = link_state;
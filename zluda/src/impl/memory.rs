@@ -140,8 +140,9 @@ pub(crate) unsafe fn set_d32_async(
     uc: ::std::os::raw::c_uint,
     n: usize,
     stream: *mut stream::Stream,
+    default_stream_per_thread: bool,
 ) -> Result<(), CUresult> {
-    let hip_stream = stream::as_hip_stream(stream)?;
+    let hip_stream = hipfix::as_hip_stream_per_thread(stream, default_stream_per_thread)?;
     hip_call_cuda!(hipMemsetD32Async(dst_device, uc as i32, n, hip_stream));
     Ok(())
 }
@@ -189,8 +190,9 @@ pub(crate) unsafe fn copy_async(
 pub(crate) unsafe fn free_async(
     dptr: hipDeviceptr_t,
     stream: *mut stream::Stream,
+    default_stream_per_thread: bool,
 ) -> Result<(), CUresult> {
-    let hip_stream = stream::as_hip_stream(stream)?;
+    let hip_stream = hipfix::as_hip_stream_per_thread(stream, default_stream_per_thread)?;
     hip_call_cuda! { hipFreeAsync(dptr.0, hip_stream) };
     Ok(())
 }
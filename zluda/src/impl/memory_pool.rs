@@ -0,0 +1,128 @@
+use super::{hipfix, mem_pool_props_from_cuda, stream, GLOBAL_STATE};
+use crate::hip_call_cuda;
+use cuda_types::*;
+use hip_runtime_sys::*;
+use std::{ffi::c_void, ptr};
+
+// Same rounding and zeroing as synchronous allocations in memory::alloc.
+// Allocations without an explicit pool come from the current pool of the
+// stream's device, which is the device's default pool until changed with
+// cuDeviceSetMemPool. HIP follows the same rules
+pub(crate) unsafe fn alloc_async(
+    dptr: *mut hipDeviceptr_t,
+    bytesize: usize,
+    stream: *mut stream::Stream,
+    default_stream_per_thread: bool,
+) -> Result<(), CUresult> {
+    alloc_async_impl(dptr, bytesize, None, stream, default_stream_per_thread)
+}
+
+pub(crate) unsafe fn alloc_from_pool_async(
+    dptr: *mut hipDeviceptr_t,
+    bytesize: usize,
+    pool: hipMemPool_t,
+    stream: *mut stream::Stream,
+    default_stream_per_thread: bool,
+) -> Result<(), CUresult> {
+    alloc_async_impl(
+        dptr,
+        bytesize,
+        Some(pool),
+        stream,
+        default_stream_per_thread,
+    )
+}
+
+unsafe fn alloc_async_impl(
+    dptr: *mut hipDeviceptr_t,
+    mut bytesize: usize,
+    pool: Option<hipMemPool_t>,
+    stream: *mut stream::Stream,
+    default_stream_per_thread: bool,
+) -> Result<(), CUresult> {
+    if dptr == ptr::null_mut() {
+        return Err(CUresult::CUDA_ERROR_INVALID_VALUE);
+    }
+    let hip_stream = hipfix::as_hip_stream_per_thread(stream, default_stream_per_thread)?;
+    let zero_buffers = GLOBAL_STATE.get()?.zero_buffers;
+    bytesize = hipfix::alloc_round_up(bytesize);
+    let mut ptr = ptr::null_mut();
+    if let Some(pool) = pool {
+        hip_call_cuda!(hipMallocFromPoolAsync(&mut ptr, bytesize, pool, hip_stream));
+    } else {
+        hip_call_cuda!(hipMallocAsync(&mut ptr, bytesize, hip_stream));
+    }
+    if zero_buffers {
+        hip_call_cuda!(hipMemsetD32Async(
+            hipDeviceptr_t(ptr),
+            0,
+            bytesize / 4,
+            hip_stream
+        ));
+    }
+    *dptr = hipDeviceptr_t(ptr);
+    Ok(())
+}
+
+pub(crate) unsafe fn create(
+    pool: *mut hipMemPool_t,
+    pool_props: *const CUmemPoolProps,
+) -> Result<(), CUresult> {
+    let pool_props = pool_props
+        .as_ref()
+        .ok_or(CUresult::CUDA_ERROR_INVALID_VALUE)?;
+    let pool_props = mem_pool_props_from_cuda(pool_props)?;
+    hip_call_cuda!(hipMemPoolCreate(pool, &pool_props));
+    Ok(())
+}
+
+// Attribute values have the same types (int or cuuint64_t) in both APIs
+pub(crate) unsafe fn get_attribute(
+    pool: hipMemPool_t,
+    attr: CUmemPool_attribute,
+    value: *mut c_void,
+) -> Result<(), CUresult> {
+    let attr = attribute_from_cuda(attr)?;
+    hip_call_cuda!(hipMemPoolGetAttribute(pool, attr, value));
+    Ok(())
+}
+
+pub(crate) unsafe fn set_attribute(
+    pool: hipMemPool_t,
+    attr: CUmemPool_attribute,
+    value: *mut c_void,
+) -> Result<(), CUresult> {
+    let attr = attribute_from_cuda(attr)?;
+    hip_call_cuda!(hipMemPoolSetAttribute(pool, attr, value));
+    Ok(())
+}
+
+fn attribute_from_cuda(attr: CUmemPool_attribute) -> Result<hipMemPoolAttr, CUresult> {
+    Ok(match attr {
+        CUmemPool_attribute::CU_MEMPOOL_ATTR_REUSE_FOLLOW_EVENT_DEPENDENCIES => {
+            hipMemPoolAttr::hipMemPoolReuseFollowEventDependencies
+        }
+        CUmemPool_attribute::CU_MEMPOOL_ATTR_REUSE_ALLOW_OPPORTUNISTIC => {
+            hipMemPoolAttr::hipMemPoolReuseAllowOpportunistic
+        }
+        CUmemPool_attribute::CU_MEMPOOL_ATTR_REUSE_ALLOW_INTERNAL_DEPENDENCIES => {
+            hipMemPoolAttr::hipMemPoolReuseAllowInternalDependencies
+        }
+        CUmemPool_attribute::CU_MEMPOOL_ATTR_RELEASE_THRESHOLD => {
+            hipMemPoolAttr::hipMemPoolAttrReleaseThreshold
+        }
+        CUmemPool_attribute::CU_MEMPOOL_ATTR_RESERVED_MEM_CURRENT => {
+            hipMemPoolAttr::hipMemPoolAttrReservedMemCurrent
+        }
+        CUmemPool_attribute::CU_MEMPOOL_ATTR_RESERVED_MEM_HIGH => {
+            hipMemPoolAttr::hipMemPoolAttrReservedMemHigh
+        }
+        CUmemPool_attribute::CU_MEMPOOL_ATTR_USED_MEM_CURRENT => {
+            hipMemPoolAttr::hipMemPoolAttrUsedMemCurrent
+        }
+        CUmemPool_attribute::CU_MEMPOOL_ATTR_USED_MEM_HIGH => {
+            hipMemPoolAttr::hipMemPoolAttrUsedMemHigh
+        }
+        _ => return Err(CUresult::CUDA_ERROR_INVALID_VALUE),
+    })
+}
@@ -14,6 +14,7 @@ use std::{
 
 use self::cache::KernelCache;
 
+pub(crate) mod archive;
 pub(crate) mod array;
 pub(crate) mod cache;
 pub(crate) mod context;
@@ -26,6 +27,7 @@ pub(crate) mod hipfix;
 pub(crate) mod library;
 pub(crate) mod link;
 pub(crate) mod memory;
+pub(crate) mod memory_pool;
 pub(crate) mod module;
 #[cfg_attr(windows, path = "os_win.rs")]
 #[cfg_attr(not(windows), path = "os_unix.rs")]
@@ -36,6 +38,7 @@ pub(crate) mod surface;
 pub(crate) mod surfref;
 pub(crate) mod texobj;
 pub(crate) mod texref;
+pub(crate) mod vmm;
 
 #[cfg(debug_assertions)]
 pub(crate) fn unimplemented() -> cuda_types::CUresult {
@@ -83,6 +86,7 @@ pub(crate) struct GlobalState {
     pub(crate) comgr: Comgr,
     pub(crate) comgr_version: String,
     pub(crate) zero_buffers: bool,
+    pub(crate) line_info: bool,
 }
 assert_impl_one!(GlobalState: Sync);
 
@@ -218,6 +222,22 @@ impl FromCuda<CUdriverProcAddressQueryResult_enum> for CUdriverProcAddressQueryR
 impl FromCuda<CUmoduleLoadingMode> for CUmoduleLoadingMode {}
 impl FromCuda<CUlibraryOption> for CUlibraryOption {}
 impl FromCuda<CUDA_KERNEL_NODE_PARAMS_v1> for CUDA_KERNEL_NODE_PARAMS_v1 {}
+impl FromCuda<CUDA_KERNEL_NODE_PARAMS> for CUDA_KERNEL_NODE_PARAMS {}
+// Node parameters are translated in graph::NodeParams
+impl FromCuda<CUDA_MEMSET_NODE_PARAMS> for CUDA_MEMSET_NODE_PARAMS {}
+impl FromCuda<CUDA_HOST_NODE_PARAMS> for CUDA_HOST_NODE_PARAMS {}
+impl FromCuda<CUDA_MEM_ALLOC_NODE_PARAMS> for CUDA_MEM_ALLOC_NODE_PARAMS {}
+impl FromCuda<CUgraphExecUpdateResultInfo> for CUgraphExecUpdateResultInfo {}
+// Allocation properties are translated in vmm
+impl FromCuda<CUmemAllocationProp> for CUmemAllocationProp {}
+impl FromCuda<CUmemAllocationGranularity_flags> for CUmemAllocationGranularity_flags {}
+// Translated in memory_pool
+impl FromCuda<CUmemPool_attribute> for CUmemPool_attribute {}
+impl FromCuda<CUmemPoolProps> for CUmemPoolProps {}
+// Function and stream handles are unpacked in function::launch_cooperative_kernel_multi_device
+impl FromCuda<CUDA_LAUNCH_PARAMS> for CUDA_LAUNCH_PARAMS {}
+// Stream handle and attributes are unpacked in function::launch_kernel_ex
+impl FromCuda<CUlaunchConfig> for CUlaunchConfig {}
 impl FromCuda<CUjitInputType> for CUjitInputType {}
 impl FromCuda<CUDA_RESOURCE_DESC> for CUDA_RESOURCE_DESC {}
 impl FromCuda<CUmipmappedArray> for CUmipmappedArray {}
@@ -235,10 +255,23 @@ impl FromCuda<CUsurfref> for *mut textureReference {}
 impl FromCuda<CUevent> for hipEvent_t {}
 impl FromCuda<CUtexObject> for hipTextureObject_t {}
 impl FromCuda<CUmemoryPool> for hipMemPool_t {}
+// HIP allocation handles are pointers, CUDA handles are 64 bit integers
+impl FromCuda<CUmemGenericAllocationHandle> for hipMemGenericAllocationHandle_t {}
 // values are compatible
 impl FromCuda<CUstreamCaptureStatus> for hipStreamCaptureStatus {}
 // values are compatible
-impl FromCuda<CUmemPool_attribute> for hipMemPoolAttr {}
+impl FromCuda<CUstreamCaptureMode> for hipStreamCaptureMode {}
+// values are compatible
+impl FromCuda<CUgraphNodeType> for hipGraphNodeType {}
+// values are compatible
+impl FromCuda<CUgraphExecUpdateResult> for hipGraphExecUpdateResult {}
+// values are compatible
+impl FromCuda<CUmemAllocationType> for hipMemAllocationType {}
+impl FromCuda<CUmemAllocationHandleType> for hipMemAllocationHandleType {}
+impl FromCuda<CUmemLocationType> for hipMemLocationType {}
+impl FromCuda<CUmemAccess_flags> for hipMemAccessFlags {}
+impl FromCuda<CUmemLocation> for hipMemLocation {}
+impl FromCuda<CUmemAccessDesc> for hipMemAccessDesc {}
 // values are compatible
 impl FromCuda<CUpointer_attribute> for hipPointer_attribute {}
 impl FromCuda<CUfunction_attribute> for hipFunction_attribute {}
@@ -283,6 +316,20 @@ pub(crate) fn memcpy2d_from_cuda(this: &CUDA_MEMCPY2D) -> hip_Memcpy2D {
     }
 }
 
+// HIP pool properties have no maxSize field
+pub(crate) fn mem_pool_props_from_cuda(this: &CUmemPoolProps) -> Result<hipMemPoolProps, CUresult> {
+    if this.maxSize != 0 {
+        return Err(CUresult::CUDA_ERROR_NOT_SUPPORTED);
+    }
+    Ok(hipMemPoolProps {
+        allocType: FromCuda::from_cuda(this.allocType),
+        handleTypes: FromCuda::from_cuda(this.handleTypes),
+        location: FromCuda::from_cuda(this.location),
+        win32SecurityAttributes: this.win32SecurityAttributes,
+        reserved: [0; 64],
+    })
+}
+
 #[macro_export]
 macro_rules! try_downcast {
     ($expr:expr, $type_from:ty => $type_to:ty) => {{
@@ -460,6 +507,7 @@ pub(crate) fn init(flags: u32) -> Result<(), CUresult> {
     }
     let kernel_cache = create_default_cache();
     let zero_buffers = hipfix::should_zero_buffers().unwrap_or(false);
+    let line_info = get_line_info_override();
     GLOBAL_STATE.init(|| GlobalState {
         devices,
         kernel_cache,
@@ -467,10 +515,20 @@ pub(crate) fn init(flags: u32) -> Result<(), CUresult> {
         comgr,
         comgr_version,
         zero_buffers,
+        line_info,
     });
     Ok(())
 }
 
+// Line info is normally requested per module with CU_JIT_GENERATE_LINE_INFO,
+// ZLUDA_LINE_INFO=1 enables it for every module, e.g. for profilers
+fn get_line_info_override() -> bool {
+    match std::env::var("ZLUDA_LINE_INFO") {
+        Ok(value) => matches!(str::parse::<u32>(&value), Ok(value) if value != 0),
+        Err(_) => false,
+    }
+}
+
 fn create_default_cache() -> Option<KernelCache> {
     let mut disk_cache_location = dirs::cache_dir()?;
     disk_cache_location.push("ZLUDA");
@@ -1,20 +1,23 @@
 use super::context::Context;
-use super::{context, function, LiveCheck, ZludaObject};
+use super::link::JitOptions;
+use super::{archive, context, function, LiveCheck, ZludaObject};
 use crate::hip_call_cuda;
 use crate::r#impl::function::FunctionData;
-use crate::r#impl::{comgr_error_to_cuda, device, hipfix, GLOBAL_STATE};
+use crate::r#impl::{comgr_error_to_cuda, device, hipfix, IntoCuda, GLOBAL_STATE};
 use cuda_types::{CUmoduleLoadingMode, CUresult};
 use hip_common::CompilationMode;
 use hip_runtime_sys::*;
 use ptx::ModuleParserExt;
 use rustc_hash::FxHashMap;
 use std::borrow::Cow;
-use std::cmp;
 use std::collections::hash_map;
 use std::ffi::{CStr, CString};
+use std::fmt::Write;
+use std::mem;
 use std::ptr::{self, NonNull};
 use std::sync::Mutex;
-use zluda_dark_api::{CUmoduleContent, FatbinFileKind};
+use std::time::Instant;
+use zluda_dark_api::{CUmoduleContent, FatbinModuleHandle};
 
 const EMPTY_MODULE: &'static str = include_str!("empty_module.ptx");
 
@@ -41,9 +44,16 @@ impl ZludaObject for ModuleData {
         } else {
             Ok(())
         };
+        let workspace_err = match self.grid_workspace {
+            Some(workspace) => match unsafe { hipFree(workspace.0) } {
+                hipError_t::hipSuccess => Ok(()),
+                err => Err(err.into_cuda()),
+            },
+            None => Ok(()),
+        };
         // Crashes HIP in 5.6 and 5.7.1
         //deregistration_err.and(unsafe { hipModuleUnload(self.base) }.into_cuda().into())
-        deregistration_err
+        deregistration_err.and(workspace_err)
     }
 }
 
@@ -56,6 +66,7 @@ pub(crate) struct ModuleData {
     device_version: u32,
     hipfix_max_group_sizes: FxHashMap<CString, (u32, u32)>,
     compilation_mode: CompilationMode,
+    grid_workspace: Option<hipDeviceptr_t>,
 }
 
 impl ModuleData {
@@ -84,9 +95,39 @@ pub(crate) unsafe fn load_data(
     )
 }
 
+pub(crate) unsafe fn load_data_ex(
+    module: *mut *mut Module,
+    image: *const ::std::os::raw::c_void,
+    num_options: ::std::os::raw::c_uint,
+    options: *mut cuda_types::CUjit_option,
+    option_values: *mut *mut ::std::os::raw::c_void,
+) -> Result<(), CUresult> {
+    if image == ptr::null_mut() {
+        return Err(CUresult::CUDA_ERROR_INVALID_VALUE);
+    }
+    let options = JitOptions::parse(num_options, options, option_values)?;
+    let input =
+        CUmoduleContent::from_ptr(image.cast()).map_err(|_| CUresult::CUDA_ERROR_INVALID_VALUE)?;
+    let start = Instant::now();
+    let mut error_log = String::new();
+    let result = load_impl_with_log(module, input, &mut error_log, options.generate_line_info);
+    options.write_wall_time(start);
+    options.write_logs(&options.info_messages(), &error_log);
+    result
+}
+
 pub(crate) unsafe fn load_impl(
     output: *mut *mut Module,
     input: CUmoduleContent,
+) -> Result<(), CUresult> {
+    load_impl_with_log(output, input, &mut String::new(), false)
+}
+
+unsafe fn load_impl_with_log(
+    output: *mut *mut Module,
+    input: CUmoduleContent,
+    error_log: &mut String,
+    line_info: bool,
 ) -> Result<(), CUresult> {
     if output == ptr::null_mut() {
         return Err(CUresult::CUDA_ERROR_INVALID_VALUE);
@@ -101,6 +142,8 @@ pub(crate) unsafe fn load_impl(
             device.compilation_mode,
             isa,
             input,
+            error_log,
+            line_info,
         )?);
         ctx.with_inner_mut(|ctx_mutable| {
             ctx_mutable.modules.insert(module);
@@ -115,83 +158,231 @@ unsafe fn link_build_or_load_cuda_module(
     compilation_mode: CompilationMode,
     isa: &CStr,
     input: CUmoduleContent,
+    error_log: &mut String,
+    line_info: bool,
 ) -> Result<Cow<'static, [u8]>, CUresult> {
     match input {
         CUmoduleContent::Elf(ptr) => Ok(Cow::Borrowed(hip_common::elf::as_slice(ptr))),
-        CUmoduleContent::Archive(..) => return Err(CUresult::CUDA_ERROR_NOT_SUPPORTED),
+        CUmoduleContent::Archive(archive) => link_build_archive(
+            global_state,
+            compilation_mode,
+            isa,
+            archive,
+            error_log,
+            line_info,
+        )
+        .map(Cow::Owned),
         CUmoduleContent::RawText(ptr) => {
             let ptx = CStr::from_ptr(ptr.cast())
                 .to_str()
                 .map_err(|_| CUresult::CUDA_ERROR_INVALID_VALUE)?;
-            link_build_zluda_module(global_state, compilation_mode, isa, &[Cow::Borrowed(ptx)])
-                .map(Cow::Owned)
+            link_build_zluda_module(
+                global_state,
+                compilation_mode,
+                isa,
+                &[Cow::Borrowed(ptx)],
+                error_log,
+                line_info,
+            )
+            .map(Cow::Owned)
         }
         CUmoduleContent::File(file) => {
             let name = CStr::from_ptr(file)
                 .to_str()
                 .map_err(|_| CUresult::CUDA_ERROR_INVALID_VALUE)?;
-            let ptx =
-                std::fs::read_to_string(name).map_err(|_| CUresult::CUDA_ERROR_INVALID_VALUE)?;
-            link_build_zluda_module(global_state, compilation_mode, isa, &[Cow::Owned(ptx)])
-                .map(Cow::Owned)
+            let file = std::fs::read(name).map_err(|_| CUresult::CUDA_ERROR_INVALID_VALUE)?;
+            if file.starts_with(goblin::archive::MAGIC) {
+                return link_build_archive(
+                    global_state,
+                    compilation_mode,
+                    isa,
+                    &file,
+                    error_log,
+                    line_info,
+                )
+                .map(Cow::Owned);
+            }
+            let ptx = String::from_utf8(file).map_err(|_| CUresult::CUDA_ERROR_INVALID_VALUE)?;
+            link_build_zluda_module(
+                global_state,
+                compilation_mode,
+                isa,
+                &[Cow::Owned(ptx)],
+                error_log,
+                line_info,
+            )
+            .map(Cow::Owned)
         }
         CUmoduleContent::Fatbin(files) => match files {
-            zluda_dark_api::CudaFatbin::Version1(module) => {
-                link_build_or_load_fatbin_module(global_state, compilation_mode, isa, module)
-                    .map(Cow::Owned)
-            }
+            zluda_dark_api::CudaFatbin::Version1(module) => link_build_or_load_fatbin_module(
+                global_state,
+                compilation_mode,
+                isa,
+                module,
+                error_log,
+                line_info,
+            )
+            .map(Cow::Owned),
             zluda_dark_api::CudaFatbin::Version2 {
                 post_link,
                 pre_link,
             } => {
-                if let Ok(binary) =
-                    link_build_or_load_fatbin_module(global_state, compilation_mode, isa, post_link)
-                {
+                if let Ok(binary) = link_build_or_load_fatbin_module(
+                    global_state,
+                    compilation_mode,
+                    isa,
+                    post_link,
+                    error_log,
+                    line_info,
+                ) {
                     return Ok(Cow::Owned(binary));
                 }
-                let ptx_files = pre_link
-                    .iter()
-                    .map(|module| {
-                        let module = unsafe { module.get() }
-                            .map_err(|_| CUresult::CUDA_ERROR_NOT_SUPPORTED)?;
-                        match module {
-                            zluda_dark_api::FatbinModule::Elf(_) => {
-                                return Err(CUresult::CUDA_ERROR_NOT_SUPPORTED);
-                            }
-                            zluda_dark_api::FatbinModule::Files(files) => {
-                                let ptx_files = extract_ptx(files);
-                                if ptx_files.is_empty() {
-                                    return Err(CUresult::CUDA_ERROR_NOT_SUPPORTED);
-                                }
-                                Ok(ptx_files.into_iter().next().unwrap().0)
-                            }
-                        }
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
-                link_build_zluda_module(global_state, compilation_mode, isa, &*ptx_files)
-                    .map(Cow::Owned)
+                // Modules without PTX might still carry device libraries
+                let mut ptx_files = Vec::new();
+                let mut archive_members = Vec::new();
+                for module in pre_link {
+                    ptx_files.extend(fatbin_module_ptx(module)?);
+                    archive_members.extend(fatbin_module_archive_members(module)?);
+                }
+                let modules = if ptx_files.is_empty() {
+                    archive_members
+                        .iter()
+                        .map(|m| Cow::Borrowed(&**m))
+                        .collect()
+                } else {
+                    with_archive_members(ptx_files, &archive_members)
+                };
+                if modules.is_empty() {
+                    return Err(CUresult::CUDA_ERROR_NOT_SUPPORTED);
+                }
+                link_build_zluda_module(
+                    global_state,
+                    compilation_mode,
+                    isa,
+                    &modules,
+                    error_log,
+                    line_info,
+                )
+                .map(Cow::Owned)
             }
         },
     }
 }
 
+// Archive loaded on its own has no undefined symbols to drive member
+// selection, so all of its members are linked
+fn link_build_archive(
+    global_state: &super::GlobalState,
+    compilation_mode: CompilationMode,
+    isa: &CStr,
+    archive: &[u8],
+    error_log: &mut String,
+    line_info: bool,
+) -> Result<Vec<u8>, CUresult> {
+    let members = archive::ptx_members(archive)?;
+    if members.is_empty() {
+        return Err(CUresult::CUDA_ERROR_NOT_SUPPORTED);
+    }
+    link_build_zluda_module(
+        global_state,
+        compilation_mode,
+        isa,
+        &members,
+        error_log,
+        line_info,
+    )
+}
+
+// Host objects carry fatbins in .nv_fatbin (whole program compilation)
+// or __nv_relfatbin (separate compilation) section
+pub(crate) unsafe fn object_link_inputs(elf: &[u8]) -> Result<Vec<Cow<'static, str>>, CUresult> {
+    let mut result = Vec::new();
+    for section_name in &[".nv_fatbin", "__nv_relfatbin"] {
+        if let Some(section) = hip_common::kernel_metadata::get_section(section_name, elf) {
+            for fatbin in FatbinModuleHandle::split_section(section) {
+                result.extend(fatbin_module_ptx(&fatbin)?);
+            }
+        }
+    }
+    Ok(result)
+}
+
+// Returns PTX of every module in the fatbin, ready to be linked together.
+// Modules without PTX are skipped
+pub(crate) fn fatbin_link_inputs(
+    fatbin: zluda_dark_api::CudaFatbin,
+) -> Result<Vec<Cow<'static, str>>, CUresult> {
+    match fatbin {
+        zluda_dark_api::CudaFatbin::Version1(module) => {
+            Ok(fatbin_module_ptx(&module)?.into_iter().collect())
+        }
+        zluda_dark_api::CudaFatbin::Version2 { pre_link, .. } => {
+            let mut result = Vec::new();
+            for module in pre_link {
+                result.extend(fatbin_module_ptx(module)?);
+            }
+            Ok(result)
+        }
+    }
+}
+
+// Same preference as in link_build_or_load_fatbin_module: highest sm version
+// first, falling back to older PTX if the newer one can't be used. We can't
+// build a single input on its own, so here "can't be used" means it does not
+// parse
+pub(crate) fn fatbin_module_ptx(
+    module: &zluda_dark_api::FatbinModuleHandle,
+) -> Result<Option<Cow<'static, str>>, CUresult> {
+    let module = unsafe { module.get() }.map_err(|_| CUresult::CUDA_ERROR_NOT_SUPPORTED)?;
+    match module {
+        zluda_dark_api::FatbinModule::Elf(_) => Ok(None),
+        zluda_dark_api::FatbinModule::Files(files) => {
+            let mut ptx_files = files.ptx_files().into_iter().peekable();
+            while let Some((ptx, _)) = ptx_files.next() {
+                if ptx_files.peek().is_none() || ptx::ModuleParser::parse_checked(&ptx).is_ok() {
+                    return Ok(Some(ptx));
+                }
+            }
+            Ok(None)
+        }
+    }
+}
+
 fn link_build_or_load_fatbin_module(
     global_state: &super::GlobalState,
     compilation_mode: CompilationMode,
     isa: &CStr,
     module: zluda_dark_api::FatbinModuleHandle,
+    error_log: &mut String,
+    line_info: bool,
 ) -> Result<Vec<u8>, CUresult> {
+    let archive_members = fatbin_module_archive_members(&module)?;
     let module = unsafe { module.get() }.map_err(|_| CUresult::CUDA_ERROR_NOT_SUPPORTED)?;
     match module {
         zluda_dark_api::FatbinModule::Elf(_) => {
             return Err(CUresult::CUDA_ERROR_NOT_SUPPORTED);
         }
         zluda_dark_api::FatbinModule::Files(files) => {
-            let ptx_files = extract_ptx(files);
+            let ptx_files = files.ptx_files();
+            if ptx_files.is_empty() && !archive_members.is_empty() {
+                return link_build_zluda_module(
+                    global_state,
+                    compilation_mode,
+                    isa,
+                    &archive_members,
+                    error_log,
+                    line_info,
+                );
+            }
             for (ptx, _) in ptx_files {
-                if let Ok(binary) =
-                    link_build_zluda_module(global_state, compilation_mode, isa, &[ptx])
-                {
+                if let Ok(binary) = link_build_zluda_module(
+                    global_state,
+                    compilation_mode,
+                    isa,
+                    &with_archive_members(vec![ptx], &archive_members),
+                    error_log,
+                    line_info,
+                ) {
                     return Ok(binary);
                 }
             }
@@ -200,40 +391,32 @@ fn link_build_or_load_fatbin_module(
     }
 }
 
-fn extract_ptx(files: zluda_dark_api::FatbinModuleFiles) -> Vec<(Cow<'static, str>, u32)> {
-    let mut ptx_files = files
-        .filter_map(|file| {
-            file.ok()
-                .map(|file| {
-                    if file.kind == FatbinFileKind::Ptx {
-                        unsafe { file.get_or_decompress() }
-                            .ok()
-                            .map(|f| {
-                                // TODO: implement support for envreg
-                                // %envreg is currently used by global grid sync in PETSc on never CUDA architectures:
-                                //  auto g = cooperative_groups::this_grid();
-                                //  g.sync();
-                                if memchr::memmem::find(&*f, b"%envreg").is_some() {
-                                    return None;
-                                }
-                                let text = match f {
-                                    Cow::Borrowed(slice) => {
-                                        Cow::Borrowed(std::str::from_utf8(slice).ok()?)
-                                    }
-                                    Cow::Owned(vec) => Cow::Owned(String::from_utf8(vec).ok()?),
-                                };
-                                Some((text, file.sm_version))
-                            })
-                            .flatten()
-                    } else {
-                        None
-                    }
-                })
-                .flatten()
-        })
-        .collect::<Vec<_>>();
-    ptx_files.sort_unstable_by_key(|(_, sm_version)| cmp::Reverse(*sm_version));
-    ptx_files
+// PTX members of device library archives embedded in the fatbin module
+fn fatbin_module_archive_members(
+    module: &FatbinModuleHandle,
+) -> Result<Vec<Cow<'static, str>>, CUresult> {
+    let files = match unsafe { module.get() } {
+        Ok(zluda_dark_api::FatbinModule::Files(files)) => files,
+        _ => return Ok(Vec::new()),
+    };
+    let mut result = Vec::new();
+    for archive in files.archive_files() {
+        result.extend(archive::ptx_members(&archive)?);
+    }
+    Ok(result)
+}
+
+// Same as in cuLinkComplete, archive members are linked only if they resolve
+// symbols left undefined by the modules
+fn with_archive_members<'a>(
+    mut modules: Vec<Cow<'a, str>>,
+    archive_members: &'a [Cow<'static, str>],
+) -> Vec<Cow<'a, str>> {
+    if !archive_members.is_empty() {
+        let selected = archive::select_members(&modules, archive_members);
+        modules.extend(selected.into_iter().map(Cow::Borrowed));
+    }
+    modules
 }
 
 pub(crate) unsafe fn load_data_any(
@@ -241,12 +424,28 @@ pub(crate) unsafe fn load_data_any(
     compilation_mode: CompilationMode,
     isa: &CStr,
     input: CUmoduleContent,
+    error_log: &mut String,
+    line_info: bool,
 ) -> Result<ModuleData, CUresult> {
     let global_state = GLOBAL_STATE.get()?;
-    let gpu_module = link_build_or_load_cuda_module(global_state, compilation_mode, isa, input)?;
+    let gpu_module = link_build_or_load_cuda_module(
+        global_state,
+        compilation_mode,
+        isa,
+        input,
+        error_log,
+        line_info,
+    )?;
     let (hipfix_max_group_sizes, sm_version) = load_kernel_metadata(&*gpu_module)?;
     let mut hip_module = ptr::null_mut();
     hip_call_cuda! { hipModuleLoadData(&mut hip_module, gpu_module.as_ptr() as _) };
+    let grid_workspace = match setup_grid_workspace(hip_module) {
+        Ok(grid_workspace) => grid_workspace,
+        Err(err) => {
+            let _ = hipModuleUnload(hip_module);
+            return Err(err);
+        }
+    };
     let device_version = device::COMPUTE_CAPABILITY_MAJOR * 10 + device::COMPUTE_CAPABILITY_MINOR;
     Ok(ModuleData {
         compilation_mode,
@@ -256,9 +455,60 @@ pub(crate) unsafe fn load_data_any(
         sm_version,
         hipfix_max_group_sizes,
         functions: Mutex::new(FxHashMap::default()),
+        grid_workspace,
     })
 }
 
+// cooperative_groups::this_grid().sync() finds its barrier through %envreg1
+// and %envreg2. Those are read from a module global, so there is one
+// workspace per module. Every cooperative launch resets it in stream order
+// (see function::reset_grid_workspace), which makes back-to-back launches
+// safe. Cooperative launches of the same module running concurrently on
+// different streams would share the barrier and are not supported
+unsafe fn setup_grid_workspace(
+    hip_module: hipModule_t,
+) -> Result<Option<hipDeviceptr_t>, CUresult> {
+    let mut envreg = hipDeviceptr_t(ptr::null_mut());
+    let mut envreg_size = 0usize;
+    // Unused globals of ptx_impl do not survive linking
+    if hipfix::module_get_global(
+        &mut envreg,
+        &mut envreg_size,
+        hip_module,
+        b"__zluda_ptx_impl__ENVREG\0".as_ptr().cast(),
+    ) != hipError_t::hipSuccess
+    {
+        return Ok(None);
+    }
+    // %envreg0, %envreg1 and %envreg2
+    if envreg_size < mem::size_of::<[u32; 3]>() {
+        return Err(CUresult::CUDA_ERROR_UNKNOWN);
+    }
+    // struct grid_workspace { unsigned int wsSize; unsigned int barrier; }
+    let mut workspace = [mem::size_of::<[u32; 2]>() as u32, 0u32];
+    let mut workspace_ptr = ptr::null_mut();
+    hip_call_cuda!(hipMalloc(&mut workspace_ptr, mem::size_of_val(&workspace)));
+    let address = workspace_ptr as usize as u64;
+    let mut envreg_values = [0u32, address as u32, (address >> 32) as u32];
+    let mut error = hipMemcpyHtoD(
+        hipDeviceptr_t(workspace_ptr),
+        workspace.as_mut_ptr().cast(),
+        mem::size_of_val(&workspace),
+    );
+    if error == hipError_t::hipSuccess {
+        error = hipMemcpyHtoD(
+            envreg,
+            envreg_values.as_mut_ptr().cast(),
+            mem::size_of_val(&envreg_values),
+        );
+    }
+    if error != hipError_t::hipSuccess {
+        let _ = hipFree(workspace_ptr);
+        return Err(error.into_cuda());
+    }
+    Ok(Some(hipDeviceptr_t(workspace_ptr)))
+}
+
 fn load_kernel_metadata(
     gpu_module: &[u8],
 ) -> Result<(FxHashMap<CString, (u32, u32)>, u32), CUresult> {
@@ -292,11 +542,20 @@ pub(crate) fn link_build_zluda_module(
     compilation_mode: CompilationMode,
     isa: &CStr,
     ptx_text: &[Cow<'_, str>],
+    error_log: &mut String,
+    line_info: bool,
 ) -> Result<Vec<u8>, CUresult> {
     if ptx_text.is_empty() {
         return Err(CUresult::CUDA_ERROR_UNKNOWN);
     }
-    if let Some(ref cache) = global_state.kernel_cache {
+    let line_info = line_info || global_state.line_info;
+    // Cached binaries don't record whether they were built with line info
+    let kernel_cache = if line_info {
+        None
+    } else {
+        global_state.kernel_cache.as_ref()
+    };
+    if let Some(cache) = kernel_cache {
         if let Some(binary) =
             cache.try_load_program(&global_state.comgr_version, isa, ptx_text, compilation_mode)
         {
@@ -311,6 +570,12 @@ pub(crate) fn link_build_zluda_module(
         .iter()
         .map(|ptx_mod| {
             let mut module = ptx::ModuleParser::parse_checked(&*ptx_mod);
+            if let Err(ref errors) = module {
+                for error in errors {
+                    let error = unsafe { ptx::DisplayParseError::new(error, &*ptx_mod) };
+                    writeln!(error_log, "{}", error).unwrap();
+                }
+            }
             if !cfg!(debug_assertions) {
                 module = module.or_else(|_| ptx::ModuleParser::parse_checked(EMPTY_MODULE))
             }
@@ -318,7 +583,15 @@ pub(crate) fn link_build_zluda_module(
         })
         .collect::<Result<Vec<_>, _>>()
         .map_err(|_| CUresult::CUDA_ERROR_INVALID_PTX)?;
-    let mut llvm_module = ptx::to_llvm_module(compilation_mode, asts);
+    let mut llvm_module = if line_info {
+        ptx::to_llvm_module_with_line_info(compilation_mode, asts)
+    } else {
+        ptx::to_llvm_module(compilation_mode, asts)
+    };
+    if let Err(ref error) = llvm_module {
+        let error = unsafe { ptx::DisplayTranslateError::new(error, ptx_text) };
+        writeln!(error_log, "{}", error).unwrap();
+    }
     if !cfg!(debug_assertions) {
         llvm_module = llvm_module.or_else(|_| {
             ptx::to_llvm_module(
@@ -338,7 +611,7 @@ pub(crate) fn link_build_zluda_module(
             &llvm_module.metadata.to_elf_section(),
         )
         .map_err(comgr_error_to_cuda)?;
-    if let Some(ref cache) = global_state.kernel_cache {
+    if let Some(cache) = kernel_cache {
         cache.save_program(
             &global_state.comgr_version,
             isa,
@@ -394,6 +667,7 @@ pub(crate) unsafe fn get_function(
                     ptx_version: module.sm_version,
                     group_size: module.hipfix_max_group_sizes.get(&name).copied(),
                     compilation_mode: module.compilation_mode,
+                    grid_workspace: module.grid_workspace,
                 })));
             function as *const function::Function as *mut _
         }
@@ -2,7 +2,7 @@ use super::{context, LiveCheck, ZludaObject};
 use crate::{hip_call_cuda, r#impl::hipfix};
 use cuda_types::{CUhostFn, CUresult};
 use hip_runtime_sys::*;
-use std::{ffi::c_void, ptr};
+use std::{ffi::c_void, mem, ptr};
 
 pub(crate) const CU_STREAM_NULL: *mut Stream = 0 as *mut _;
 pub(crate) const CU_STREAM_LEGACY: *mut Stream = 1 as *mut _;
@@ -116,9 +116,15 @@ pub(crate) unsafe fn launch_host_func(
     stream: *mut Stream,
     fn_: CUhostFn,
     user_data: *mut ::std::os::raw::c_void,
+    default_stream_per_thread: bool,
 ) -> Result<(), CUresult> {
     let fn_ = *fn_.as_ref().ok_or(CUresult::CUDA_ERROR_INVALID_VALUE)?;
-    let hip_stream = as_hip_stream(stream)?;
+    let hip_stream = hipfix::as_hip_stream_per_thread(stream, default_stream_per_thread)?;
+    // Legacy default stream is never captured and querying its capture status
+    // while another stream is capturing fails with an implicit capture error
+    if hip_stream != hipStreamNull && add_captured_host_node(hip_stream, fn_, user_data)? {
+        return Ok(());
+    }
     // TODO: use hipLaunchHostFunc when it comes to Windows
     //hip_call_cuda!(hipLaunchHostFunc(hip_stream, fn_, user_data));
     let callback = Box::new(HostCallback { fn_, user_data });
@@ -131,6 +137,50 @@ pub(crate) unsafe fn launch_host_func(
     Ok(())
 }
 
+// Stream callbacks can't be captured, so during capture we append a host
+// node to the graph ourselves and make it the new capture dependency.
+// Returns false if the stream is not capturing
+unsafe fn add_captured_host_node(
+    hip_stream: hipStream_t,
+    fn_: unsafe extern "system" fn(userData: *mut ::std::os::raw::c_void),
+    user_data: *mut ::std::os::raw::c_void,
+) -> Result<bool, CUresult> {
+    let mut capture_status = hipStreamCaptureStatus::hipStreamCaptureStatusNone;
+    let mut graph = ptr::null_mut();
+    let mut dependencies = ptr::null();
+    let mut dependencies_count = 0;
+    hip_call_cuda!(hipStreamGetCaptureInfo_v2(
+        hip_stream,
+        &mut capture_status,
+        ptr::null_mut(),
+        &mut graph,
+        &mut dependencies,
+        &mut dependencies_count
+    ));
+    if capture_status == hipStreamCaptureStatus::hipStreamCaptureStatusActive {
+        let params = hipHostNodeParams {
+            fn_: Some(mem::transmute(fn_)),
+            userData: user_data,
+        };
+        let mut node = ptr::null_mut();
+        hip_call_cuda!(hipGraphAddHostNode(
+            &mut node,
+            graph,
+            dependencies,
+            dependencies_count,
+            &params
+        ));
+        hip_call_cuda!(hipStreamUpdateCaptureDependencies(
+            hip_stream,
+            &mut node,
+            1,
+            hipStreamUpdateCaptureDependenciesFlags::hipStreamSetCaptureDependencies.0 as u32
+        ));
+        return Ok(true);
+    }
+    Ok(false)
+}
+
 pub(crate) unsafe fn wait_event(
     stream: *mut Stream,
     h_event: hipEvent_t,
@@ -169,12 +219,71 @@ pub(crate) unsafe fn get_capture_info(
     stream: *mut Stream,
     capture_status_out: *mut hipStreamCaptureStatus,
     id_out: *mut u64,
+    default_stream_per_thread: bool,
 ) -> Result<(), CUresult> {
-    let hip_stream = as_hip_stream(stream)?;
+    let hip_stream = hipfix::as_hip_stream_per_thread(stream, default_stream_per_thread)?;
     hip_call_cuda! { hipStreamGetCaptureInfo(hip_stream, capture_status_out, id_out) };
     Ok(())
 }
 
+pub(crate) unsafe fn get_capture_info_v2(
+    stream: *mut Stream,
+    capture_status_out: *mut hipStreamCaptureStatus,
+    id_out: *mut u64,
+    graph_out: *mut hipGraph_t,
+    dependencies_out: *mut *const hipGraphNode_t,
+    dependencies_count_out: *mut usize,
+    default_stream_per_thread: bool,
+) -> Result<(), CUresult> {
+    let hip_stream = hipfix::as_hip_stream_per_thread(stream, default_stream_per_thread)?;
+    hip_call_cuda!(hipStreamGetCaptureInfo_v2(
+        hip_stream,
+        capture_status_out,
+        id_out,
+        graph_out,
+        dependencies_out,
+        dependencies_count_out
+    ));
+    Ok(())
+}
+
+pub(crate) unsafe fn begin_capture(
+    stream: *mut Stream,
+    mode: hipStreamCaptureMode,
+    default_stream_per_thread: bool,
+) -> Result<(), CUresult> {
+    let hip_stream = hipfix::as_hip_stream_per_thread(stream, default_stream_per_thread)?;
+    hip_call_cuda! { hipStreamBeginCapture(hip_stream, mode) };
+    Ok(())
+}
+
+pub(crate) unsafe fn end_capture(
+    stream: *mut Stream,
+    graph: *mut hipGraph_t,
+    default_stream_per_thread: bool,
+) -> Result<(), CUresult> {
+    let hip_stream = hipfix::as_hip_stream_per_thread(stream, default_stream_per_thread)?;
+    hip_call_cuda! { hipStreamEndCapture(hip_stream, graph) };
+    Ok(())
+}
+
+pub(crate) unsafe fn update_capture_dependencies(
+    stream: *mut Stream,
+    dependencies: *mut hipGraphNode_t,
+    dependencies_count: usize,
+    flags: ::std::os::raw::c_uint,
+    default_stream_per_thread: bool,
+) -> Result<(), CUresult> {
+    let hip_stream = hipfix::as_hip_stream_per_thread(stream, default_stream_per_thread)?;
+    hip_call_cuda!(hipStreamUpdateCaptureDependencies(
+        hip_stream,
+        dependencies,
+        dependencies_count,
+        flags
+    ));
+    Ok(())
+}
+
 pub(crate) unsafe fn get_flags(stream: *mut Stream, flags: *mut u32) -> Result<(), CUresult> {
     let hip_stream = as_hip_stream(stream)?;
     hip_call_cuda! { hipStreamGetFlags(hip_stream, flags) };
@@ -184,8 +293,9 @@ pub(crate) unsafe fn get_flags(stream: *mut Stream, flags: *mut u32) -> Result<(
 pub(crate) unsafe fn is_capturing(
     stream: *mut Stream,
     capture_status: *mut hipStreamCaptureStatus,
+    default_stream_per_thread: bool,
 ) -> Result<(), CUresult> {
-    let hip_stream = as_hip_stream(stream)?;
+    let hip_stream = hipfix::as_hip_stream_per_thread(stream, default_stream_per_thread)?;
     hip_call_cuda! { hipStreamIsCapturing(hip_stream, capture_status) };
     Ok(())
 }
@@ -0,0 +1,139 @@
+use super::FromCuda;
+use crate::hip_call_cuda;
+use cuda_types::*;
+use hip_runtime_sys::*;
+use std::{ffi::c_void, mem, ptr};
+
+pub(crate) unsafe fn create(
+    handle: *mut hipMemGenericAllocationHandle_t,
+    size: usize,
+    prop: *const CUmemAllocationProp,
+    flags: ::std::os::raw::c_ulonglong,
+) -> Result<(), CUresult> {
+    let prop = allocation_prop_from_cuda(prop)?;
+    hip_call_cuda!(hipMemCreate(handle, size, &prop, flags));
+    Ok(())
+}
+
+pub(crate) unsafe fn get_allocation_granularity(
+    granularity: *mut usize,
+    prop: *const CUmemAllocationProp,
+    option: CUmemAllocationGranularity_flags,
+) -> Result<(), CUresult> {
+    let prop = allocation_prop_from_cuda(prop)?;
+    let option = match option {
+        CUmemAllocationGranularity_flags::CU_MEM_ALLOC_GRANULARITY_MINIMUM => {
+            hipMemAllocationGranularity_flags::hipMemAllocationGranularityMinimum
+        }
+        CUmemAllocationGranularity_flags::CU_MEM_ALLOC_GRANULARITY_RECOMMENDED => {
+            hipMemAllocationGranularity_flags::hipMemAllocationGranularityRecommended
+        }
+        _ => return Err(CUresult::CUDA_ERROR_INVALID_VALUE),
+    };
+    hip_call_cuda!(hipMemGetAllocationGranularity(granularity, &prop, option));
+    Ok(())
+}
+
+pub(crate) unsafe fn get_allocation_properties_from_handle(
+    prop: *mut CUmemAllocationProp,
+    handle: hipMemGenericAllocationHandle_t,
+) -> Result<(), CUresult> {
+    let prop = prop.as_mut().ok_or(CUresult::CUDA_ERROR_INVALID_VALUE)?;
+    let mut hip_prop = mem::zeroed::<hipMemAllocationProp>();
+    hip_call_cuda!(hipMemGetAllocationPropertiesFromHandle(
+        &mut hip_prop,
+        handle
+    ));
+    // values are compatible
+    *prop = CUmemAllocationProp {
+        type_: CUmemAllocationType(hip_prop.type_.0),
+        requestedHandleTypes: CUmemAllocationHandleType(hip_prop.requestedHandleType.0),
+        location: CUmemLocation {
+            type_: CUmemLocationType(hip_prop.location.type_.0),
+            id: hip_prop.location.id,
+        },
+        win32HandleMetaData: hip_prop.win32HandleMetaData,
+        allocFlags: CUmemAllocationProp_st__bindgen_ty_1 {
+            compressionType: hip_prop.allocFlags.compressionType,
+            gpuDirectRDMACapable: hip_prop.allocFlags.gpuDirectRDMACapable,
+            usage: hip_prop.allocFlags.usage,
+            reserved: [0; 4],
+        },
+    };
+    Ok(())
+}
+
+pub(crate) unsafe fn address_reserve(
+    ptr: *mut hipDeviceptr_t,
+    size: usize,
+    alignment: usize,
+    addr: hipDeviceptr_t,
+    flags: ::std::os::raw::c_ulonglong,
+) -> Result<(), CUresult> {
+    hip_call_cuda!(hipMemAddressReserve(
+        ptr.cast(),
+        size,
+        alignment,
+        addr.0,
+        flags
+    ));
+    Ok(())
+}
+
+// Only POSIX file descriptors are supported. The descriptor is written to an
+// int, same as in CUDA. On import CUDA passes the descriptor by value, while
+// HIP expects a pointer to an int holding it
+pub(crate) unsafe fn export_to_shareable_handle(
+    shareable_handle: *mut c_void,
+    handle: hipMemGenericAllocationHandle_t,
+    handle_type: hipMemAllocationHandleType,
+    flags: ::std::os::raw::c_ulonglong,
+) -> Result<(), CUresult> {
+    if handle_type != hipMemAllocationHandleType::hipMemHandleTypePosixFileDescriptor {
+        return Err(CUresult::CUDA_ERROR_NOT_SUPPORTED);
+    }
+    if shareable_handle == ptr::null_mut() {
+        return Err(CUresult::CUDA_ERROR_INVALID_VALUE);
+    }
+    hip_call_cuda!(hipMemExportToShareableHandle(
+        shareable_handle,
+        handle,
+        handle_type,
+        flags
+    ));
+    Ok(())
+}
+
+pub(crate) unsafe fn import_from_shareable_handle(
+    handle: *mut hipMemGenericAllocationHandle_t,
+    os_handle: *mut c_void,
+    handle_type: hipMemAllocationHandleType,
+) -> Result<(), CUresult> {
+    if handle_type != hipMemAllocationHandleType::hipMemHandleTypePosixFileDescriptor {
+        return Err(CUresult::CUDA_ERROR_NOT_SUPPORTED);
+    }
+    let mut fd = os_handle as usize as i32;
+    hip_call_cuda!(hipMemImportFromShareableHandle(
+        handle,
+        &mut fd as *mut i32 as *mut c_void,
+        handle_type
+    ));
+    Ok(())
+}
+
+unsafe fn allocation_prop_from_cuda(
+    prop: *const CUmemAllocationProp,
+) -> Result<hipMemAllocationProp, CUresult> {
+    let prop = prop.as_ref().ok_or(CUresult::CUDA_ERROR_INVALID_VALUE)?;
+    Ok(hipMemAllocationProp {
+        type_: FromCuda::from_cuda(prop.type_),
+        requestedHandleType: FromCuda::from_cuda(prop.requestedHandleTypes),
+        location: FromCuda::from_cuda(prop.location),
+        win32HandleMetaData: prop.win32HandleMetaData,
+        allocFlags: hipMemAllocationProp__bindgen_ty_1 {
+            compressionType: prop.allocFlags.compressionType,
+            gpuDirectRDMACapable: prop.allocFlags.gpuDirectRDMACapable,
+            usage: prop.allocFlags.usage,
+        },
+    })
+}
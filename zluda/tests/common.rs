@@ -76,6 +76,139 @@ pub const CU_STREAM_LEGACY: cuda_types::CUstream = 1 as *mut _;
 #[allow(dead_code)]
 pub const CU_STREAM_PER_THREAD: cuda_types::CUstream = 2 as *mut _;
 
+// Fatbin with one module, the way nvcc embeds it into host objects.
+// Payloads are padded with newlines, because uncompressed PTX is used
+// together with its padding
+#[allow(dead_code)]
+pub fn fatbin(files: &[(std::os::raw::c_ushort, &[u8])]) -> Vec<u8> {
+    use std::{mem, slice};
+    use zluda_dark_api::*;
+    let mut files_data = Vec::new();
+    for (kind, payload) in files {
+        let payload_size = payload.len();
+        let mut payload = payload.to_vec();
+        while payload.len() % 8 != 0 {
+            payload.push(b'\n');
+        }
+        let file_header = FatbinFileHeader {
+            kind: *kind,
+            version: FATBIN_FILE_HEADER_VERSION_CURRENT,
+            header_size: mem::size_of::<FatbinFileHeader>() as u32,
+            padded_payload_size: payload.len() as u32,
+            unknown0: 0,
+            payload_size: payload_size as u32,
+            unknown1: 0,
+            ptx_version: 0x60005,
+            sm_version: 60,
+            file_name_offset: 0,
+            file_name_len: 0,
+            flags: FatbinFileHeaderFlags::Is64Bit | FatbinFileHeaderFlags::Linux,
+            unknown6: 0,
+            uncompressed_payload: 0,
+        };
+        files_data.extend_from_slice(unsafe {
+            slice::from_raw_parts(
+                &file_header as *const _ as *const u8,
+                mem::size_of::<FatbinFileHeader>(),
+            )
+        });
+        files_data.extend_from_slice(&payload);
+    }
+    let mut result = Vec::new();
+    result.extend_from_slice(&FATBIN_MAGIC.to_le_bytes());
+    result.extend_from_slice(&FATBIN_VERSION.to_le_bytes());
+    result.extend_from_slice(&16u16.to_le_bytes());
+    result.extend_from_slice(&(files_data.len() as u64).to_le_bytes());
+    result.extend_from_slice(&files_data);
+    result
+}
+
+// ar archive with GNU-style short member names
+#[allow(dead_code)]
+pub fn archive(members: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut result = zluda_dark_api::ARCHIVE_MAGIC.to_vec();
+    for (name, data) in members {
+        let header = format!(
+            "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+            format!("{}/", name),
+            0,
+            0,
+            0,
+            644,
+            data.len()
+        );
+        result.extend_from_slice(header.as_bytes());
+        result.extend_from_slice(data);
+        if data.len() % 2 != 0 {
+            result.push(b'\n');
+        }
+    }
+    result
+}
+
+// Minimal relocatable ELF64 with a single data section (and section name table)
+#[allow(dead_code)]
+pub fn elf_object(section_name: &str, section: &[u8]) -> Vec<u8> {
+    const EHDR_SIZE: usize = 64;
+    const SHDR_SIZE: usize = 64;
+    let mut shstrtab = vec![0u8];
+    shstrtab.extend_from_slice(section_name.as_bytes());
+    shstrtab.push(0);
+    shstrtab.extend_from_slice(b".shstrtab\0");
+    let section_offset = EHDR_SIZE;
+    let shstrtab_offset = section_offset + section.len();
+    let shdr_offset = (shstrtab_offset + shstrtab.len() + 7) / 8 * 8;
+    let mut result = Vec::new();
+    result.extend_from_slice(b"\x7FELF\x02\x01\x01\0\0\0\0\0\0\0\0\0");
+    result.extend_from_slice(&1u16.to_le_bytes()); // ET_REL
+    result.extend_from_slice(&62u16.to_le_bytes()); // EM_X86_64
+    result.extend_from_slice(&1u32.to_le_bytes());
+    result.extend_from_slice(&0u64.to_le_bytes()); // e_entry
+    result.extend_from_slice(&0u64.to_le_bytes()); // e_phoff
+    result.extend_from_slice(&(shdr_offset as u64).to_le_bytes());
+    result.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+    result.extend_from_slice(&(EHDR_SIZE as u16).to_le_bytes());
+    result.extend_from_slice(&0u16.to_le_bytes()); // e_phentsize
+    result.extend_from_slice(&0u16.to_le_bytes()); // e_phnum
+    result.extend_from_slice(&(SHDR_SIZE as u16).to_le_bytes());
+    result.extend_from_slice(&3u16.to_le_bytes()); // e_shnum
+    result.extend_from_slice(&2u16.to_le_bytes()); // e_shstrndx
+    result.extend_from_slice(section);
+    result.extend_from_slice(&shstrtab);
+    result.resize(shdr_offset, 0);
+    let mut section_header = |name: u32, type_: u32, offset: usize, size: usize, align: u64| {
+        result.extend_from_slice(&name.to_le_bytes());
+        result.extend_from_slice(&type_.to_le_bytes());
+        result.extend_from_slice(&0u64.to_le_bytes()); // sh_flags
+        result.extend_from_slice(&0u64.to_le_bytes()); // sh_addr
+        result.extend_from_slice(&(offset as u64).to_le_bytes());
+        result.extend_from_slice(&(size as u64).to_le_bytes());
+        result.extend_from_slice(&0u32.to_le_bytes()); // sh_link
+        result.extend_from_slice(&0u32.to_le_bytes()); // sh_info
+        result.extend_from_slice(&align.to_le_bytes());
+        result.extend_from_slice(&0u64.to_le_bytes()); // sh_entsize
+    };
+    section_header(0, 0, 0, 0, 0);
+    section_header(1, 1, section_offset, section.len(), 8); // SHT_PROGBITS
+    section_header(
+        section_name.len() as u32 + 2,
+        3, // SHT_STRTAB
+        shstrtab_offset,
+        shstrtab.len(),
+        1,
+    );
+    result
+}
+
+// Fatbin headers are read in place, so inputs must be 8-byte aligned
+#[allow(dead_code)]
+pub fn aligned(data: &[u8]) -> Vec<u64> {
+    let mut buffer = vec![0u64; (data.len() + 7) / 8];
+    unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr().cast::<u8>(), data.len()) }
+        .copy_from_slice(data);
+    buffer
+}
+
 #[cfg(windows)]
 mod os {
     use std::ffi::c_void;
@@ -0,0 +1,302 @@
+use crate::common::CudaDriverFns;
+use cuda_types::*;
+use std::{ffi::c_void, mem, ptr};
+
+mod common;
+
+unsafe extern "system" fn increment(counter: *mut c_void) {
+    *(counter as *mut u32) += 1;
+}
+
+fn memset_params(dst: CUdeviceptr, value: u32, width: usize) -> CUDA_MEMSET_NODE_PARAMS {
+    CUDA_MEMSET_NODE_PARAMS {
+        dst,
+        pitch: 0,
+        value,
+        elementSize: mem::size_of::<u32>() as u32,
+        width,
+        height: 1,
+    }
+}
+
+cuda_driver_test!(graph_memcpy_node_array);
+
+unsafe fn graph_memcpy_node_array<T: CudaDriverFns>(cuda: T) {
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let width = 4;
+    let height = 2;
+    let mut array = ptr::null_mut();
+    let array_desc = CUDA_ARRAY3D_DESCRIPTOR {
+        Width: width,
+        Height: height,
+        Depth: 0,
+        Format: CUarray_format::CU_AD_FORMAT_FLOAT,
+        NumChannels: 1,
+        Flags: 0,
+    };
+    assert_eq!(
+        cuda.cuArray3DCreate_v2(&mut array, &array_desc),
+        CUresult::CUDA_SUCCESS
+    );
+    let source = (0..width * height).map(|x| x as f32).collect::<Vec<_>>();
+    let mut dptr = mem::zeroed();
+    assert_eq!(
+        cuda.cuMemAlloc_v2(&mut dptr, width * height * mem::size_of::<f32>()),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut graph = ptr::null_mut();
+    assert_eq!(cuda.cuGraphCreate(&mut graph, 0), CUresult::CUDA_SUCCESS);
+    // Copy the second column onwards into the array, so the byte offset
+    // must be translated into an element offset
+    let mut to_array = mem::zeroed::<CUDA_MEMCPY3D>();
+    to_array.srcXInBytes = mem::size_of::<f32>();
+    to_array.srcMemoryType = CUmemorytype::CU_MEMORYTYPE_HOST;
+    to_array.srcHost = source.as_ptr().cast();
+    to_array.srcPitch = width * mem::size_of::<f32>();
+    to_array.srcHeight = height;
+    to_array.dstXInBytes = mem::size_of::<f32>();
+    to_array.dstMemoryType = CUmemorytype::CU_MEMORYTYPE_ARRAY;
+    to_array.dstArray = array;
+    to_array.WidthInBytes = (width - 1) * mem::size_of::<f32>();
+    to_array.Height = height;
+    to_array.Depth = 1;
+    let mut to_array_node = ptr::null_mut();
+    assert_eq!(
+        cuda.cuGraphAddMemcpyNode(&mut to_array_node, graph, ptr::null(), 0, &to_array, ctx),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut from_array = mem::zeroed::<CUDA_MEMCPY3D>();
+    from_array.srcMemoryType = CUmemorytype::CU_MEMORYTYPE_ARRAY;
+    from_array.srcArray = array;
+    from_array.dstMemoryType = CUmemorytype::CU_MEMORYTYPE_DEVICE;
+    from_array.dstDevice = dptr;
+    from_array.dstPitch = width * mem::size_of::<f32>();
+    from_array.dstHeight = height;
+    from_array.WidthInBytes = width * mem::size_of::<f32>();
+    from_array.Height = height;
+    from_array.Depth = 1;
+    let mut from_array_node = ptr::null_mut();
+    assert_eq!(
+        cuda.cuGraphAddMemcpyNode(
+            &mut from_array_node,
+            graph,
+            &to_array_node,
+            1,
+            &from_array,
+            ctx
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut node_type = CUgraphNodeType::CU_GRAPH_NODE_TYPE_EMPTY;
+    assert_eq!(
+        cuda.cuGraphNodeGetType(from_array_node, &mut node_type),
+        CUresult::CUDA_SUCCESS
+    );
+    assert!(node_type == CUgraphNodeType::CU_GRAPH_NODE_TYPE_MEMCPY);
+    assert_eq!(
+        cuda.cuMemsetD32_v2(dptr, 0, width * height),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut graph_exec = ptr::null_mut();
+    assert_eq!(
+        cuda.cuGraphInstantiateWithFlags(&mut graph_exec, graph, 0),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuGraphLaunch(graph_exec, ptr::null_mut()),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuStreamSynchronize(ptr::null_mut()),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut result = vec![0f32; width * height];
+    assert_eq!(
+        cuda.cuMemcpyDtoH_v2(
+            result.as_mut_ptr().cast(),
+            dptr,
+            width * height * mem::size_of::<f32>()
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(result[1..4], source[1..4]);
+    assert_eq!(result[5..8], source[5..8]);
+    assert_eq!(cuda.cuGraphExecDestroy(graph_exec), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuGraphDestroy(graph), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuArrayDestroy(array), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
+
+cuda_driver_test!(graph_memset_then_host_node);
+
+unsafe fn graph_memset_then_host_node<T: CudaDriverFns>(cuda: T) {
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut dptr = mem::zeroed();
+    assert_eq!(
+        cuda.cuMemAlloc_v2(&mut dptr, 4 * mem::size_of::<u32>()),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut graph = ptr::null_mut();
+    assert_eq!(cuda.cuGraphCreate(&mut graph, 0), CUresult::CUDA_SUCCESS);
+    let mut memset_node = ptr::null_mut();
+    assert_eq!(
+        cuda.cuGraphAddMemsetNode(
+            &mut memset_node,
+            graph,
+            ptr::null(),
+            0,
+            &memset_params(dptr, 0x11223344, 4),
+            ctx
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut counter = 0u32;
+    let host_params = CUDA_HOST_NODE_PARAMS {
+        fn_: Some(increment),
+        userData: &mut counter as *mut u32 as *mut c_void,
+    };
+    let mut host_node = ptr::null_mut();
+    assert_eq!(
+        cuda.cuGraphAddHostNode(&mut host_node, graph, &memset_node, 1, &host_params),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut node_type = CUgraphNodeType::CU_GRAPH_NODE_TYPE_EMPTY;
+    assert_eq!(
+        cuda.cuGraphNodeGetType(memset_node, &mut node_type),
+        CUresult::CUDA_SUCCESS
+    );
+    assert!(node_type == CUgraphNodeType::CU_GRAPH_NODE_TYPE_MEMSET);
+    assert_eq!(
+        cuda.cuGraphNodeGetType(host_node, &mut node_type),
+        CUresult::CUDA_SUCCESS
+    );
+    assert!(node_type == CUgraphNodeType::CU_GRAPH_NODE_TYPE_HOST);
+    let mut graph_exec = ptr::null_mut();
+    assert_eq!(
+        cuda.cuGraphInstantiateWithFlags(&mut graph_exec, graph, 0),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuGraphLaunch(graph_exec, ptr::null_mut()),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuStreamSynchronize(ptr::null_mut()),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(counter, 1);
+    let mut result = [0u32; 4];
+    assert_eq!(
+        cuda.cuMemcpyDtoH_v2(result.as_mut_ptr().cast(), dptr, mem::size_of_val(&result)),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(result, [0x11223344u32; 4]);
+    assert_eq!(cuda.cuGraphExecDestroy(graph_exec), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuGraphDestroy(graph), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
+
+cuda_driver_test!(graph_exec_update);
+
+unsafe fn graph_exec_update<T: CudaDriverFns>(cuda: T) {
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut dptr = mem::zeroed();
+    assert_eq!(
+        cuda.cuMemAlloc_v2(&mut dptr, 4 * mem::size_of::<u32>()),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut graphs = [ptr::null_mut(); 2];
+    for (graph, value) in graphs.iter_mut().zip([1u32, 2]) {
+        assert_eq!(cuda.cuGraphCreate(graph, 0), CUresult::CUDA_SUCCESS);
+        let mut node = ptr::null_mut();
+        assert_eq!(
+            cuda.cuGraphAddMemsetNode(
+                &mut node,
+                *graph,
+                ptr::null(),
+                0,
+                &memset_params(dptr, value, 4),
+                ctx
+            ),
+            CUresult::CUDA_SUCCESS
+        );
+    }
+    let mut graph_exec = ptr::null_mut();
+    assert_eq!(
+        cuda.cuGraphInstantiateWithFlags(&mut graph_exec, graphs[0], 0),
+        CUresult::CUDA_SUCCESS
+    );
+    // Same topology, different memset value
+    let mut result_info = mem::zeroed::<CUgraphExecUpdateResultInfo>();
+    assert_eq!(
+        cuda.cuGraphExecUpdate_v2(graph_exec, graphs[1], &mut result_info),
+        CUresult::CUDA_SUCCESS
+    );
+    assert!(result_info.result == CUgraphExecUpdateResult::CU_GRAPH_EXEC_UPDATE_SUCCESS);
+    assert_eq!(
+        cuda.cuGraphLaunch(graph_exec, ptr::null_mut()),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuStreamSynchronize(ptr::null_mut()),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut result = [0u32; 4];
+    assert_eq!(
+        cuda.cuMemcpyDtoH_v2(result.as_mut_ptr().cast(), dptr, mem::size_of_val(&result)),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(result, [2u32; 4]);
+    // Same topology, but the node is now a host node
+    let mut host_graph = ptr::null_mut();
+    assert_eq!(
+        cuda.cuGraphCreate(&mut host_graph, 0),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut counter = 0u32;
+    let host_params = CUDA_HOST_NODE_PARAMS {
+        fn_: Some(increment),
+        userData: &mut counter as *mut u32 as *mut c_void,
+    };
+    let mut host_node = ptr::null_mut();
+    assert_eq!(
+        cuda.cuGraphAddHostNode(&mut host_node, host_graph, ptr::null(), 0, &host_params),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut result_info = mem::zeroed::<CUgraphExecUpdateResultInfo>();
+    assert_eq!(
+        cuda.cuGraphExecUpdate_v2(graph_exec, host_graph, &mut result_info),
+        CUresult::CUDA_ERROR_GRAPH_EXEC_UPDATE_FAILURE
+    );
+    assert!(
+        result_info.result == CUgraphExecUpdateResult::CU_GRAPH_EXEC_UPDATE_ERROR_NODE_TYPE_CHANGED
+    );
+    assert_eq!(result_info.errorNode, host_node);
+    // HIP does not report the node of the instantiated graph
+    if T::is_nvidia() {
+        assert_ne!(result_info.errorFromNode, ptr::null_mut());
+    } else {
+        assert_eq!(result_info.errorFromNode, ptr::null_mut());
+    }
+    assert_eq!(cuda.cuGraphExecDestroy(graph_exec), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuGraphDestroy(host_graph), CUresult::CUDA_SUCCESS);
+    for graph in graphs {
+        assert_eq!(cuda.cuGraphDestroy(graph), CUresult::CUDA_SUCCESS);
+    }
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
@@ -0,0 +1,187 @@
+use crate::common::CudaDriverFns;
+use cuda_types::*;
+use std::{mem, ptr};
+
+mod common;
+
+fn attribute(id: CUlaunchAttributeID) -> CUlaunchAttribute {
+    let mut attribute = unsafe { mem::zeroed::<CUlaunchAttribute>() };
+    attribute.id = id;
+    attribute
+}
+
+// Launches the `set` kernel on `stream` in the current context and returns
+// the launch result together with the value the kernel wrote, if any
+unsafe fn launch_set<T: CudaDriverFns>(
+    cuda: &T,
+    stream: CUstream,
+    attributes: &mut [CUlaunchAttribute],
+) -> (CUresult, u32) {
+    let kernel = include_str!("launch_kernel_ex.ptx");
+    let mut module = ptr::null_mut();
+    assert_eq!(
+        cuda.cuModuleLoadData(&mut module, kernel.as_ptr() as _),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut kernel = ptr::null_mut();
+    assert_eq!(
+        cuda.cuModuleGetFunction(&mut kernel, module, b"set\0".as_ptr() as _),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut output = mem::zeroed();
+    assert_eq!(
+        cuda.cuMemAlloc_v2(&mut output, mem::size_of::<u32>()),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(cuda.cuMemsetD32_v2(output, 0, 1), CUresult::CUDA_SUCCESS);
+    let mut args = [&mut output as *mut _ as *mut _];
+    let config = CUlaunchConfig {
+        gridDimX: 1,
+        gridDimY: 1,
+        gridDimZ: 1,
+        blockDimX: 1,
+        blockDimY: 1,
+        blockDimZ: 1,
+        sharedMemBytes: 0,
+        hStream: stream,
+        attrs: attributes.as_mut_ptr(),
+        numAttrs: attributes.len() as u32,
+    };
+    let launch_result = cuda.cuLaunchKernelEx(&config, kernel, args.as_mut_ptr(), ptr::null_mut());
+    assert_eq!(cuda.cuStreamSynchronize(stream), CUresult::CUDA_SUCCESS);
+    let mut result = 0u32;
+    assert_eq!(
+        cuda.cuMemcpyDtoH_v2(
+            &mut result as *mut u32 as *mut _,
+            output,
+            mem::size_of::<u32>()
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(cuda.cuMemFree_v2(output), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuModuleUnload(module), CUresult::CUDA_SUCCESS);
+    (launch_result, result)
+}
+
+cuda_driver_test!(launch_kernel_ex_programmatic_event);
+
+unsafe fn launch_kernel_ex_programmatic_event<T: CudaDriverFns>(cuda: T) {
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut stream = ptr::null_mut();
+    assert_eq!(cuda.cuStreamCreate(&mut stream, 0), CUresult::CUDA_SUCCESS);
+    let mut event = ptr::null_mut();
+    assert_eq!(cuda.cuEventCreate(&mut event, 0), CUresult::CUDA_SUCCESS);
+    let mut programmatic_event =
+        attribute(CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_PROGRAMMATIC_EVENT);
+    programmatic_event.value.programmaticEvent.event = event;
+    assert_eq!(
+        launch_set(&cuda, stream, &mut [programmatic_event]),
+        (CUresult::CUDA_SUCCESS, 0x11223344)
+    );
+    // The event has been recorded by the launch, so it's complete now
+    assert_eq!(cuda.cuEventSynchronize(event), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuEventQuery(event), CUresult::CUDA_SUCCESS);
+    // Programmatic event without an event to record, NVIDIA driver does not
+    // document the error it returns in this case
+    if !T::is_nvidia() {
+        let mut no_event = attribute(CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_PROGRAMMATIC_EVENT);
+        no_event.value.programmaticEvent.event = ptr::null_mut();
+        assert_eq!(
+            launch_set(&cuda, stream, &mut [no_event]).0,
+            CUresult::CUDA_ERROR_INVALID_VALUE
+        );
+    }
+    assert_eq!(cuda.cuEventDestroy_v2(event), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuStreamDestroy_v2(stream), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
+
+cuda_driver_test!(launch_kernel_ex_ignored_attributes);
+
+unsafe fn launch_kernel_ex_ignored_attributes<T: CudaDriverFns>(cuda: T) {
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut serialization =
+        attribute(CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_PROGRAMMATIC_STREAM_SERIALIZATION);
+    serialization.value.programmaticStreamSerializationAllowed = 1;
+    let mut priority = attribute(CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_PRIORITY);
+    priority.value.priority = -1;
+    let mut attributes = [
+        attribute(CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_IGNORE),
+        attribute(CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_ACCESS_POLICY_WINDOW),
+        attribute(CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_SYNCHRONIZATION_POLICY),
+        attribute(CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_CLUSTER_SCHEDULING_POLICY_PREFERENCE),
+        serialization,
+        priority,
+        attribute(CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_MEM_SYNC_DOMAIN_MAP),
+        attribute(CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_MEM_SYNC_DOMAIN),
+    ];
+    assert_eq!(
+        launch_set(&cuda, ptr::null_mut(), &mut attributes),
+        (CUresult::CUDA_SUCCESS, 0x11223344)
+    );
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
+
+cuda_driver_test!(launch_kernel_ex_unsupported_attribute);
+
+unsafe fn launch_kernel_ex_unsupported_attribute<T: CudaDriverFns>(cuda: T) {
+    // NVIDIA driver rejects unknown attributes with a different error
+    if T::is_nvidia() {
+        return;
+    }
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        launch_set(
+            &cuda,
+            ptr::null_mut(),
+            &mut [attribute(CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_MAX)]
+        ),
+        (CUresult::CUDA_ERROR_NOT_SUPPORTED, 0)
+    );
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
+
+cuda_driver_test!(launch_kernel_ex_cluster_dimension);
+
+unsafe fn launch_kernel_ex_cluster_dimension<T: CudaDriverFns>(cuda: T) {
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut cluster = attribute(CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_CLUSTER_DIMENSION);
+    cluster.value.clusterDim.x = 1;
+    cluster.value.clusterDim.y = 1;
+    cluster.value.clusterDim.z = 1;
+    assert_eq!(
+        launch_set(&cuda, ptr::null_mut(), &mut [cluster]),
+        (CUresult::CUDA_SUCCESS, 0x11223344)
+    );
+    // Recent NVIDIA GPUs do support clusters bigger than a single block
+    if T::is_nvidia() {
+        assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+        return;
+    }
+    cluster.value.clusterDim.x = 2;
+    assert_eq!(
+        launch_set(&cuda, ptr::null_mut(), &mut [cluster]),
+        (CUresult::CUDA_ERROR_INVALID_CLUSTER_SIZE, 0)
+    );
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
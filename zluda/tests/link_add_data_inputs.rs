@@ -0,0 +1,184 @@
+use crate::common::{aligned, elf_object, fatbin, CudaDriverFns};
+use cuda_types::*;
+use std::{mem, ptr};
+use zluda_dark_api::FATBIN_FILE_HEADER_KIND_PTX;
+
+mod common;
+
+const KERNEL: &'static str = "
+.version 6.5
+.target sm_60
+.address_size 64
+
+.extern .func (.reg .u32 res) foobar();
+
+.visible .entry observer(.param .u64 output)
+{
+    .reg .u64 out_addr;
+    .reg .u32 constant;
+    ld.param.u64 out_addr, [output];
+    call (constant), foobar, ();
+    st.u32 [out_addr], constant;
+    ret;
+}";
+
+const FUNCTION: &'static str = "
+.version 6.5
+.target sm_60
+.address_size 64
+
+.visible .func (.reg .u32 result) foobar()
+{
+    mov.u32 result, 42;
+    ret;
+}";
+
+cuda_driver_test!(link_add_data_inputs);
+
+unsafe fn link_add_data_inputs<T: CudaDriverFns>(cuda: T) {
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let kernel_fatbin = fatbin(&[(FATBIN_FILE_HEADER_KIND_PTX, KERNEL.as_bytes())]);
+    let function_object = elf_object(
+        ".nv_fatbin",
+        &fatbin(&[(FATBIN_FILE_HEADER_KIND_PTX, FUNCTION.as_bytes())]),
+    );
+    let inputs = [
+        (
+            CUjitInputType::CU_JIT_INPUT_FATBINARY,
+            aligned(&kernel_fatbin),
+            kernel_fatbin.len(),
+        ),
+        (
+            CUjitInputType::CU_JIT_INPUT_OBJECT,
+            aligned(&function_object),
+            function_object.len(),
+        ),
+    ];
+    let mut linker = mem::zeroed();
+    assert_eq!(
+        cuda.cuLinkCreate_v2(0, ptr::null_mut(), ptr::null_mut(), &mut linker),
+        CUresult::CUDA_SUCCESS
+    );
+    for (input_type, data, size) in inputs.iter() {
+        assert_eq!(
+            cuda.cuLinkAddData_v2(
+                linker,
+                *input_type,
+                data.as_ptr().cast_mut().cast(),
+                *size,
+                ptr::null_mut(),
+                0,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            ),
+            CUresult::CUDA_SUCCESS
+        );
+    }
+    let mut binary = ptr::null_mut();
+    let mut size = 0;
+    assert_eq!(
+        cuda.cuLinkComplete(linker, &mut binary, &mut size),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut module = ptr::null_mut();
+    assert_eq!(
+        cuda.cuModuleLoadData(&mut module, binary),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(cuda.cuLinkDestroy(linker), CUresult::CUDA_SUCCESS);
+    let mut function = ptr::null_mut();
+    assert_eq!(
+        cuda.cuModuleGetFunction(&mut function, module, b"observer\0".as_ptr().cast()),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut dptr = mem::zeroed();
+    assert_eq!(
+        cuda.cuMemAlloc_v2(&mut dptr, mem::size_of::<u32>()),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut args = [&mut dptr];
+    assert_eq!(
+        cuda.cuLaunchKernel(
+            function,
+            1,
+            1,
+            1,
+            1,
+            1,
+            1,
+            0,
+            ptr::null_mut(),
+            args.as_mut_ptr().cast(),
+            ptr::null_mut(),
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut result = 0u32;
+    assert_eq!(
+        cuda.cuMemcpyDtoH_v2(&mut result as *mut _ as *mut _, dptr, mem::size_of::<u32>()),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(result, 42);
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
+
+cuda_driver_test!(link_add_data_ignores_unusable_library);
+
+unsafe fn link_add_data_ignores_unusable_library<T: CudaDriverFns>(cuda: T) {
+    // NVIDIA driver validates libraries, we accept anything that does not
+    // carry PTX as contributing no inputs
+    if T::is_nvidia() {
+        return;
+    }
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut linker = mem::zeroed();
+    assert_eq!(
+        cuda.cuLinkCreate_v2(0, ptr::null_mut(), ptr::null_mut(), &mut linker),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut not_an_archive = *b"not an ar archive";
+    assert_eq!(
+        cuda.cuLinkAddData_v2(
+            linker,
+            CUjitInputType::CU_JIT_INPUT_LIBRARY,
+            not_an_archive.as_mut_ptr().cast(),
+            not_an_archive.len(),
+            ptr::null_mut(),
+            0,
+            ptr::null_mut(),
+            ptr::null_mut(),
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuLinkAddData_v2(
+            linker,
+            CUjitInputType::CU_JIT_INPUT_PTX,
+            FUNCTION.as_ptr().cast_mut().cast(),
+            FUNCTION.len(),
+            ptr::null_mut(),
+            0,
+            ptr::null_mut(),
+            ptr::null_mut(),
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut binary = ptr::null_mut();
+    let mut size = 0;
+    assert_eq!(
+        cuda.cuLinkComplete(linker, &mut binary, &mut size),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(cuda.cuLinkDestroy(linker), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
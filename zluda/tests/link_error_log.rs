@@ -0,0 +1,73 @@
+use crate::common::CudaDriverFns;
+use cuda_types::*;
+use std::{ffi::c_void, mem, os::raw::c_char, ptr};
+
+mod common;
+
+const INVALID_PTX: &'static str = "
+.version 6.5
+.target sm_30
+.address_size 64
+
+.visible .entry invalid_ptx()
+{
+    not_an_instruction.u32 %r1;
+    ret;
+}\0";
+
+cuda_driver_test!(link_error_log);
+
+unsafe fn link_error_log<T: CudaDriverFns>(cuda: T) {
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut error_log = [0 as c_char; 1024];
+    let mut options = [
+        CUjit_option::CU_JIT_ERROR_LOG_BUFFER,
+        CUjit_option::CU_JIT_ERROR_LOG_BUFFER_SIZE_BYTES,
+    ];
+    let mut option_values = [
+        error_log.as_mut_ptr().cast::<c_void>(),
+        error_log.len() as *mut c_void,
+    ];
+    let mut linker = mem::zeroed();
+    assert_eq!(
+        cuda.cuLinkCreate_v2(
+            options.len() as u32,
+            options.as_mut_ptr(),
+            option_values.as_mut_ptr(),
+            &mut linker
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    // NVIDIA reports invalid PTX already in cuLinkAddData, we report it in cuLinkComplete
+    let mut result = cuda.cuLinkAddData_v2(
+        linker,
+        CUjitInputType::CU_JIT_INPUT_PTX,
+        INVALID_PTX.as_ptr().cast_mut().cast(),
+        INVALID_PTX.len(),
+        ptr::null_mut(),
+        0,
+        ptr::null_mut(),
+        ptr::null_mut(),
+    );
+    if result == CUresult::CUDA_SUCCESS {
+        let mut binary = mem::zeroed();
+        let mut size = 0;
+        result = cuda.cuLinkComplete(linker, &mut binary, &mut size);
+    }
+    // Release builds of ZLUDA fall back to an empty module instead of failing,
+    // but the error log is filled either way
+    if T::is_nvidia() || cfg!(debug_assertions) {
+        assert_eq!(result, CUresult::CUDA_ERROR_INVALID_PTX);
+    }
+    let log_size = option_values[1] as usize;
+    assert_ne!(log_size, 0);
+    assert!(log_size < error_log.len());
+    assert_ne!(error_log[0], 0);
+    assert_eq!(cuda.cuLinkDestroy(linker), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
@@ -0,0 +1,75 @@
+use crate::common::CudaDriverFns;
+use cuda_types::*;
+use std::{ffi::c_void, mem, ptr};
+
+mod common;
+
+cuda_driver_test!(memory_pool_alloc_free_trim);
+
+unsafe fn memory_pool_alloc_free_trim<T: CudaDriverFns>(cuda: T) {
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut stream = ptr::null_mut();
+    assert_eq!(cuda.cuStreamCreate(&mut stream, 0), CUresult::CUDA_SUCCESS);
+    let mut pool = ptr::null_mut();
+    assert_eq!(
+        cuda.cuDeviceGetDefaultMemPool(&mut pool, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let size = 1024 * 1024;
+    let mut dptr = mem::zeroed();
+    assert_eq!(
+        cuda.cuMemAllocAsync(&mut dptr, size, stream),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuMemsetD32Async(dptr, 0x11223344, size / 4, stream),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut result = vec![0u32; size / 4];
+    assert_eq!(
+        cuda.cuMemcpyDtoHAsync_v2(result.as_mut_ptr().cast(), dptr, size, stream),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(cuda.cuStreamSynchronize(stream), CUresult::CUDA_SUCCESS);
+    assert!(result.iter().all(|x| *x == 0x11223344));
+    let mut used = 0u64;
+    assert_eq!(
+        cuda.cuMemPoolGetAttribute(
+            pool,
+            CUmemPool_attribute::CU_MEMPOOL_ATTR_USED_MEM_CURRENT,
+            &mut used as *mut u64 as *mut c_void
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    assert!(used >= size as u64);
+    assert_eq!(cuda.cuMemFreeAsync(dptr, stream), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuStreamSynchronize(stream), CUresult::CUDA_SUCCESS);
+    assert_eq!(
+        cuda.cuMemPoolGetAttribute(
+            pool,
+            CUmemPool_attribute::CU_MEMPOOL_ATTR_USED_MEM_CURRENT,
+            &mut used as *mut u64 as *mut c_void
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(used, 0);
+    // Nothing is in use anymore, so trimming releases all reserved memory
+    assert_eq!(cuda.cuMemPoolTrimTo(pool, 0), CUresult::CUDA_SUCCESS);
+    let mut reserved = u64::MAX;
+    assert_eq!(
+        cuda.cuMemPoolGetAttribute(
+            pool,
+            CUmemPool_attribute::CU_MEMPOOL_ATTR_RESERVED_MEM_CURRENT,
+            &mut reserved as *mut u64 as *mut c_void
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(reserved, 0);
+    assert_eq!(cuda.cuStreamDestroy_v2(stream), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
@@ -0,0 +1,201 @@
+use crate::common::{aligned, archive, fatbin, CudaDriverFns};
+use cuda_types::*;
+use std::{mem, ptr};
+use zluda_dark_api::{
+    FATBINC_MAGIC, FATBINC_VERSION_V2, FATBIN_FILE_HEADER_KIND_ELF, FATBIN_FILE_HEADER_KIND_PTX,
+};
+
+mod common;
+
+const KERNEL: &'static str = "
+.version 6.5
+.target sm_60
+.address_size 64
+
+.extern .func (.reg .u32 res) foobar();
+
+.visible .entry observer(.param .u64 output)
+{
+    .reg .u64 out_addr;
+    .reg .u32 constant;
+    ld.param.u64 out_addr, [output];
+    call (constant), foobar, ();
+    st.u32 [out_addr], constant;
+    ret;
+}";
+
+const FUNCTION: &'static str = "
+.version 6.5
+.target sm_60
+.address_size 64
+
+.visible .func (.reg .u32 result) foobar()
+{
+    mov.u32 result, 42;
+    ret;
+}";
+
+const UNNEEDED: &'static str = "
+.version 6.5
+.target sm_60
+.address_size 64
+
+.visible .entry unneeded()
+{
+    ret;
+}";
+
+cuda_driver_test!(module_fatbin_archive_links_needed_members);
+
+// NVIDIA driver does not accept device library archives in cuModuleLoadData
+unsafe fn module_fatbin_archive_links_needed_members<T: CudaDriverFns>(cuda: T) {
+    if T::is_nvidia() {
+        return;
+    }
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let library = archive(&[
+        ("unneeded.ptx", UNNEEDED.as_bytes()),
+        ("foobar.ptx", FUNCTION.as_bytes()),
+    ]);
+    let image = aligned(&fatbin(&[
+        (FATBIN_FILE_HEADER_KIND_PTX, KERNEL.as_bytes()),
+        (FATBIN_FILE_HEADER_KIND_ELF, &library),
+    ]));
+    let mut module = ptr::null_mut();
+    assert_eq!(
+        cuda.cuModuleLoadData(&mut module, image.as_ptr().cast()),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut function = ptr::null_mut();
+    assert_eq!(
+        cuda.cuModuleGetFunction(&mut function, module, b"unneeded\0".as_ptr().cast()),
+        CUresult::CUDA_ERROR_NOT_FOUND
+    );
+    assert_eq!(
+        cuda.cuModuleGetFunction(&mut function, module, b"observer\0".as_ptr().cast()),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut dptr = mem::zeroed();
+    assert_eq!(
+        cuda.cuMemAlloc_v2(&mut dptr, mem::size_of::<u32>()),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut args = [&mut dptr];
+    assert_eq!(
+        cuda.cuLaunchKernel(
+            function,
+            1,
+            1,
+            1,
+            1,
+            1,
+            1,
+            0,
+            ptr::null_mut(),
+            args.as_mut_ptr().cast(),
+            ptr::null_mut(),
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut result = 0u32;
+    assert_eq!(
+        cuda.cuMemcpyDtoH_v2(&mut result as *mut _ as *mut _, dptr, mem::size_of::<u32>()),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(result, 42);
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
+
+cuda_driver_test!(module_archive_links_all_members);
+
+// NVIDIA driver does not accept device library archives in cuModuleLoadData
+unsafe fn module_archive_links_all_members<T: CudaDriverFns>(cuda: T) {
+    if T::is_nvidia() {
+        return;
+    }
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut library = archive(&[
+        ("unneeded.ptx", UNNEEDED.as_bytes()),
+        ("foobar.ptx", FUNCTION.as_bytes()),
+    ]);
+    // Size of an archive passed by pointer is found by looking for the next
+    // member header, so make sure the memory past the archive is readable
+    library.resize(library.len() + 64, 0);
+    let image = aligned(&library);
+    let mut module = ptr::null_mut();
+    assert_eq!(
+        cuda.cuModuleLoadData(&mut module, image.as_ptr().cast()),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut function = ptr::null_mut();
+    assert_eq!(
+        cuda.cuModuleGetFunction(&mut function, module, b"unneeded\0".as_ptr().cast()),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
+
+// Layout of __fatBinC_Wrapper_t, as emitted by nvcc -rdc=true
+#[repr(C)]
+struct FatbincWrapper {
+    magic: u32,
+    version: u32,
+    data: *const u64,
+    pre_link: *const *const u64,
+}
+
+cuda_driver_test!(module_pre_link_archive_without_ptx);
+
+// NVIDIA driver does not accept device library archives in cuModuleLoadData
+unsafe fn module_pre_link_archive_without_ptx<T: CudaDriverFns>(cuda: T) {
+    if T::is_nvidia() {
+        return;
+    }
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let library = archive(&[
+        ("unneeded.ptx", UNNEEDED.as_bytes()),
+        ("foobar.ptx", FUNCTION.as_bytes()),
+    ]);
+    // Post-link module has nothing we can use, so the pre-link modules are
+    // linked. The second one carries only the device library
+    let post_link = aligned(&fatbin(&[(FATBIN_FILE_HEADER_KIND_ELF, b"SASS")]));
+    let kernel_module = aligned(&fatbin(&[(FATBIN_FILE_HEADER_KIND_PTX, KERNEL.as_bytes())]));
+    let library_module = aligned(&fatbin(&[(FATBIN_FILE_HEADER_KIND_ELF, &library)]));
+    let pre_link = [kernel_module.as_ptr(), library_module.as_ptr(), ptr::null()];
+    let wrapper = FatbincWrapper {
+        magic: FATBINC_MAGIC,
+        version: FATBINC_VERSION_V2,
+        data: post_link.as_ptr(),
+        pre_link: pre_link.as_ptr(),
+    };
+    let mut module = ptr::null_mut();
+    assert_eq!(
+        cuda.cuModuleLoadData(&mut module, (&wrapper as *const FatbincWrapper).cast()),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut function = ptr::null_mut();
+    assert_eq!(
+        cuda.cuModuleGetFunction(&mut function, module, b"unneeded\0".as_ptr().cast()),
+        CUresult::CUDA_ERROR_NOT_FOUND
+    );
+    assert_eq!(
+        cuda.cuModuleGetFunction(&mut function, module, b"observer\0".as_ptr().cast()),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
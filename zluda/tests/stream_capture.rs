@@ -0,0 +1,204 @@
+use crate::common::{CudaDriverFns, CU_STREAM_LEGACY};
+use cuda_types::*;
+use std::{ffi::c_void, mem, ptr};
+
+mod common;
+
+const CU_STREAM_NON_BLOCKING: u32 = 1;
+
+unsafe extern "system" fn increment(counter: *mut c_void) {
+    *(counter as *mut u32) += 1;
+}
+
+cuda_driver_test!(stream_capture_memset);
+
+unsafe fn stream_capture_memset<T: CudaDriverFns>(cuda: T) {
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut stream = ptr::null_mut();
+    assert_eq!(cuda.cuStreamCreate(&mut stream, 0), CUresult::CUDA_SUCCESS);
+    let mut dptr = mem::zeroed();
+    assert_eq!(
+        cuda.cuMemAlloc_v2(&mut dptr, 4 * mem::size_of::<u32>()),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuStreamBeginCapture_v2(stream, CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_GLOBAL),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut status = CUstreamCaptureStatus::CU_STREAM_CAPTURE_STATUS_NONE;
+    assert_eq!(
+        cuda.cuStreamGetCaptureInfo_v2(
+            stream,
+            &mut status,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut()
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    assert!(status == CUstreamCaptureStatus::CU_STREAM_CAPTURE_STATUS_ACTIVE);
+    assert_eq!(
+        cuda.cuMemsetD32Async(dptr, 0x11223344, 4, stream),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut graph = ptr::null_mut();
+    assert_eq!(
+        cuda.cuStreamEndCapture(stream, &mut graph),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuStreamGetCaptureInfo_v2(
+            stream,
+            &mut status,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut()
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    assert!(status == CUstreamCaptureStatus::CU_STREAM_CAPTURE_STATUS_NONE);
+    // Captured work is recorded, not executed
+    let mut result = [0u32; 4];
+    assert_eq!(
+        cuda.cuMemcpyDtoH_v2(result.as_mut_ptr().cast(), dptr, mem::size_of_val(&result)),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(result, [0u32; 4]);
+    let mut graph_exec = ptr::null_mut();
+    assert_eq!(
+        cuda.cuGraphInstantiateWithFlags(&mut graph_exec, graph, 0),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuGraphLaunch(graph_exec, stream),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(cuda.cuStreamSynchronize(stream), CUresult::CUDA_SUCCESS);
+    assert_eq!(
+        cuda.cuMemcpyDtoH_v2(result.as_mut_ptr().cast(), dptr, mem::size_of_val(&result)),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(result, [0x11223344u32; 4]);
+    assert_eq!(cuda.cuGraphExecDestroy(graph_exec), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuGraphDestroy(graph), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
+
+cuda_driver_test!(stream_capture_host_func);
+
+unsafe fn stream_capture_host_func<T: CudaDriverFns>(cuda: T) {
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut stream = ptr::null_mut();
+    assert_eq!(cuda.cuStreamCreate(&mut stream, 0), CUresult::CUDA_SUCCESS);
+    let mut counter = 0u32;
+    assert_eq!(
+        cuda.cuStreamBeginCapture_v2(stream, CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_GLOBAL),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuLaunchHostFunc(
+            stream,
+            Some(increment),
+            &mut counter as *mut u32 as *mut c_void
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut graph = ptr::null_mut();
+    assert_eq!(
+        cuda.cuStreamEndCapture(stream, &mut graph),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(counter, 0);
+    let mut node = ptr::null_mut();
+    let mut nodes_count = 1;
+    assert_eq!(
+        cuda.cuGraphGetNodes(graph, &mut node, &mut nodes_count),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(nodes_count, 1);
+    let mut node_type = CUgraphNodeType::CU_GRAPH_NODE_TYPE_EMPTY;
+    assert_eq!(
+        cuda.cuGraphNodeGetType(node, &mut node_type),
+        CUresult::CUDA_SUCCESS
+    );
+    assert!(node_type == CUgraphNodeType::CU_GRAPH_NODE_TYPE_HOST);
+    let mut graph_exec = ptr::null_mut();
+    assert_eq!(
+        cuda.cuGraphInstantiateWithFlags(&mut graph_exec, graph, 0),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuGraphLaunch(graph_exec, stream),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuGraphLaunch(graph_exec, stream),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(cuda.cuStreamSynchronize(stream), CUresult::CUDA_SUCCESS);
+    assert_eq!(counter, 2);
+    assert_eq!(cuda.cuGraphExecDestroy(graph_exec), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuGraphDestroy(graph), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
+
+cuda_driver_test!(stream_capture_allows_legacy_host_func);
+
+// Legacy default stream does not synchronize with non-blocking streams, so it
+// can be used while one of them is capturing
+unsafe fn stream_capture_allows_legacy_host_func<T: CudaDriverFns>(cuda: T) {
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut stream = ptr::null_mut();
+    assert_eq!(
+        cuda.cuStreamCreate(&mut stream, CU_STREAM_NON_BLOCKING),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut counter = 0u32;
+    assert_eq!(
+        cuda.cuStreamBeginCapture_v2(stream, CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_RELAXED),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuLaunchHostFunc(
+            CU_STREAM_LEGACY,
+            Some(increment),
+            &mut counter as *mut u32 as *mut c_void
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuStreamSynchronize(CU_STREAM_LEGACY),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(counter, 1);
+    let mut graph = ptr::null_mut();
+    assert_eq!(
+        cuda.cuStreamEndCapture(stream, &mut graph),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut nodes_count = 0;
+    assert_eq!(
+        cuda.cuGraphGetNodes(graph, ptr::null_mut(), &mut nodes_count),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(nodes_count, 0);
+    assert_eq!(cuda.cuGraphDestroy(graph), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
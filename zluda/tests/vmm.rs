@@ -0,0 +1,180 @@
+use crate::common::CudaDriverFns;
+use cuda_types::*;
+use std::{mem, ptr};
+
+mod common;
+
+cuda_driver_test!(vmm_map_memset_unmap);
+
+unsafe fn vmm_map_memset_unmap<T: CudaDriverFns>(cuda: T) {
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let location = CUmemLocation {
+        type_: CUmemLocationType::CU_MEM_LOCATION_TYPE_DEVICE,
+        id: 0,
+    };
+    let mut prop = mem::zeroed::<CUmemAllocationProp>();
+    prop.type_ = CUmemAllocationType::CU_MEM_ALLOCATION_TYPE_PINNED;
+    prop.location = location;
+    let mut granularity = 0;
+    assert_eq!(
+        cuda.cuMemGetAllocationGranularity(
+            &mut granularity,
+            &prop,
+            CUmemAllocationGranularity_flags::CU_MEM_ALLOC_GRANULARITY_MINIMUM
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_ne!(granularity, 0);
+    let size = granularity;
+    let mut dptr = mem::zeroed();
+    assert_eq!(
+        cuda.cuMemAddressReserve(&mut dptr, size, 0, mem::zeroed(), 0),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut handle = mem::zeroed();
+    assert_eq!(
+        cuda.cuMemCreate(&mut handle, size, &prop, 0),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuMemMap(dptr, size, 0, handle, 0),
+        CUresult::CUDA_SUCCESS
+    );
+    let access = CUmemAccessDesc {
+        location,
+        flags: CUmemAccess_flags::CU_MEM_ACCESS_FLAGS_PROT_READWRITE,
+    };
+    assert_eq!(
+        cuda.cuMemSetAccess(dptr, size, &access, 1),
+        CUresult::CUDA_SUCCESS
+    );
+    let elements = size / mem::size_of::<u32>();
+    assert_eq!(
+        cuda.cuMemsetD32_v2(dptr, 0x11223344, elements),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut result = vec![0u32; elements];
+    assert_eq!(
+        cuda.cuMemcpyDtoH_v2(result.as_mut_ptr().cast(), dptr, size),
+        CUresult::CUDA_SUCCESS
+    );
+    assert!(result.iter().all(|x| *x == 0x11223344));
+    assert_eq!(cuda.cuMemUnmap(dptr, size), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuMemRelease(handle), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuMemAddressFree(dptr, size), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
+
+// CUDA and ZLUDA support only POSIX file descriptors as shareable handles
+#[cfg(not(windows))]
+cuda_driver_test!(vmm_export_import);
+
+#[cfg(not(windows))]
+unsafe fn vmm_export_import<T: CudaDriverFns>(cuda: T) {
+    assert_eq!(cuda.cuInit(0), CUresult::CUDA_SUCCESS);
+    let mut ctx = ptr::null_mut();
+    assert_eq!(
+        cuda.cuCtxCreate_v2(&mut ctx, 0, CUdevice_v1(0)),
+        CUresult::CUDA_SUCCESS
+    );
+    let location = CUmemLocation {
+        type_: CUmemLocationType::CU_MEM_LOCATION_TYPE_DEVICE,
+        id: 0,
+    };
+    let mut prop = mem::zeroed::<CUmemAllocationProp>();
+    prop.type_ = CUmemAllocationType::CU_MEM_ALLOCATION_TYPE_PINNED;
+    prop.requestedHandleTypes = CUmemAllocationHandleType::CU_MEM_HANDLE_TYPE_POSIX_FILE_DESCRIPTOR;
+    prop.location = location;
+    let mut size = 0;
+    assert_eq!(
+        cuda.cuMemGetAllocationGranularity(
+            &mut size,
+            &prop,
+            CUmemAllocationGranularity_flags::CU_MEM_ALLOC_GRANULARITY_MINIMUM
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    let access = CUmemAccessDesc {
+        location,
+        flags: CUmemAccess_flags::CU_MEM_ACCESS_FLAGS_PROT_READWRITE,
+    };
+    let mut handle = mem::zeroed();
+    assert_eq!(
+        cuda.cuMemCreate(&mut handle, size, &prop, 0),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut dptr = mem::zeroed();
+    assert_eq!(
+        cuda.cuMemAddressReserve(&mut dptr, size, 0, mem::zeroed(), 0),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuMemMap(dptr, size, 0, handle, 0),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuMemSetAccess(dptr, size, &access, 1),
+        CUresult::CUDA_SUCCESS
+    );
+    let elements = size / mem::size_of::<u32>();
+    assert_eq!(
+        cuda.cuMemsetD32_v2(dptr, 0x11223344, elements),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut fd = -1i32;
+    assert_eq!(
+        cuda.cuMemExportToShareableHandle(
+            &mut fd as *mut i32 as *mut _,
+            handle,
+            CUmemAllocationHandleType::CU_MEM_HANDLE_TYPE_POSIX_FILE_DESCRIPTOR,
+            0
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    assert!(fd >= 0);
+    // The descriptor is passed by value, not through a pointer
+    let mut imported = mem::zeroed();
+    assert_eq!(
+        cuda.cuMemImportFromShareableHandle(
+            &mut imported,
+            fd as usize as *mut _,
+            CUmemAllocationHandleType::CU_MEM_HANDLE_TYPE_POSIX_FILE_DESCRIPTOR
+        ),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut imported_dptr = mem::zeroed();
+    assert_eq!(
+        cuda.cuMemAddressReserve(&mut imported_dptr, size, 0, mem::zeroed(), 0),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuMemMap(imported_dptr, size, 0, imported, 0),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(
+        cuda.cuMemSetAccess(imported_dptr, size, &access, 1),
+        CUresult::CUDA_SUCCESS
+    );
+    let mut result = vec![0u32; elements];
+    assert_eq!(
+        cuda.cuMemcpyDtoH_v2(result.as_mut_ptr().cast(), imported_dptr, size),
+        CUresult::CUDA_SUCCESS
+    );
+    assert!(result.iter().all(|x| *x == 0x11223344));
+    assert_eq!(cuda.cuMemUnmap(imported_dptr, size), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuMemRelease(imported), CUresult::CUDA_SUCCESS);
+    assert_eq!(
+        cuda.cuMemAddressFree(imported_dptr, size),
+        CUresult::CUDA_SUCCESS
+    );
+    assert_eq!(cuda.cuMemUnmap(dptr, size), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuMemRelease(handle), CUresult::CUDA_SUCCESS);
+    assert_eq!(cuda.cuMemAddressFree(dptr, size), CUresult::CUDA_SUCCESS);
+    assert_eq!(libc::close(fd), 0);
+    assert_eq!(cuda.cuCtxDestroy_v2(ctx), CUresult::CUDA_SUCCESS);
+}
@@ -0,0 +1,325 @@
+use argh::FromArgs;
+use hip_common::cache;
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{self, SystemTime};
+
+type DynError = Box<dyn Error>;
+
+#[derive(FromArgs)]
+/// Inspect and clean up the cache of GPU code compiled by ZLUDA
+struct Arguments {
+    /// path to the cache file, defaults to the ZLUDA compute cache (zluda.db)
+    #[argh(option, short = 'f')]
+    file: Option<PathBuf>,
+    /// use the ZLUDA OptiX cache (zluda_optix6.db) instead of the compute cache
+    #[argh(switch)]
+    optix: bool,
+    #[argh(subcommand)]
+    command: Subcommand,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum Subcommand {
+    List(ListCommand),
+    Stats(StatsCommand),
+    Vacuum(VacuumCommand),
+    Export(ExportCommand),
+    Purge(PurgeCommand),
+}
+
+#[derive(FromArgs)]
+/// List cached binaries, most recently used first
+#[argh(subcommand, name = "list")]
+struct ListCommand {}
+
+#[derive(FromArgs)]
+/// Print cache size, grouped by ZLUDA build, compiler and device
+#[argh(subcommand, name = "stats")]
+struct StatsCommand {}
+
+#[derive(FromArgs)]
+/// Give space freed by removed binaries back to the filesystem
+#[argh(subcommand, name = "vacuum")]
+struct VacuumCommand {}
+
+#[derive(FromArgs)]
+/// Write cached binaries to files
+#[argh(subcommand, name = "export")]
+struct ExportCommand {
+    /// directory with output, will be created if does not exist
+    #[argh(option, short = 'o')]
+    output: PathBuf,
+    /// ids of binaries to export (as printed by `list`), all binaries if empty
+    #[argh(positional)]
+    ids: Vec<i64>,
+}
+
+#[derive(FromArgs)]
+/// Remove cached binaries
+#[argh(subcommand, name = "purge")]
+struct PurgeCommand {
+    /// remove all binaries
+    #[argh(switch)]
+    all: bool,
+    /// remove binaries not used in the last N days
+    #[argh(option)]
+    older_than: Option<u64>,
+    /// remove least recently used binaries until the cache is smaller than N MiB
+    #[argh(option)]
+    max_size: Option<u64>,
+    /// remove binaries from ZLUDA builds or compilers other than the most recently used one
+    #[argh(switch)]
+    other_versions: bool,
+}
+
+fn main() -> Result<(), DynError> {
+    let args: Arguments = argh::from_env();
+    let file = match args.file {
+        Some(file) => file,
+        None => default_cache_file(args.optix)?,
+    };
+    let connection = Connection::open_with_flags(&file, OpenFlags::SQLITE_OPEN_READ_WRITE)
+        .map_err(|err| format!("Could not open {}: {}", file.display(), err))?;
+    match args.command {
+        Subcommand::List(_) => list(&connection),
+        Subcommand::Stats(_) => stats(&connection, &file),
+        Subcommand::Vacuum(_) => vacuum(&connection, &file),
+        Subcommand::Export(command) => export(&connection, command),
+        Subcommand::Purge(command) => {
+            purge(&connection, command)?;
+            vacuum(&connection, &file)
+        }
+    }
+}
+
+fn default_cache_file(optix: bool) -> Result<PathBuf, DynError> {
+    let mut file = dirs::cache_dir().ok_or("Could not find the cache directory")?;
+    file.push("ZLUDA");
+    if optix {
+        file.push("OptixCache");
+        file.push("zluda_optix6.db");
+    } else {
+        file.push("ComputeCache");
+        file.push(cache::COMPUTE_CACHE_FILE);
+    }
+    Ok(file)
+}
+
+fn list(connection: &Connection) -> Result<(), DynError> {
+    let now = now()?;
+    let mut statement = connection.prepare(
+        "
+        SELECT
+            id, length(binary), last_used, device, git_hash, compiler_version, hash
+        FROM
+            kernels
+        ORDER BY
+            last_used DESC, id DESC;",
+    )?;
+    let mut rows = statement.query([])?;
+    println!(
+        "      ID        SIZE   LAST USED  DEVICE                        BUILD       HASH              COMPILER"
+    );
+    while let Some(row) = rows.next()? {
+        let id: i64 = row.get(0)?;
+        let size: u64 = row.get(1)?;
+        let last_used: i64 = row.get(2)?;
+        let device: String = row.get(3)?;
+        let git_hash: String = row.get(4)?;
+        let compiler_version: String = row.get(5)?;
+        let hash: String = row.get(6)?;
+        println!(
+            "{:>8}  {:>10}  {:>10}  {:<28}  {:<10}  {:<16}  {}",
+            id,
+            format_size(size),
+            format_age(now - last_used),
+            device,
+            shorten(&git_hash, 10),
+            shorten(&hash, 16),
+            compiler_version
+        );
+    }
+    Ok(())
+}
+
+fn stats(connection: &Connection, file: &Path) -> Result<(), DynError> {
+    let now = now()?;
+    let count: u64 = connection.query_row("SELECT COUNT(*) FROM kernels;", [], |row| row.get(0))?;
+    println!("File:           {}", file.display());
+    println!("File size:      {}", format_size(fs::metadata(file)?.len()));
+    println!("Binaries:       {}", count);
+    println!(
+        "Binaries size:  {}",
+        format_size(cache::total_size(connection)?)
+    );
+    println!("Size limit:     {}", max_size_description());
+    let mut statement = connection.prepare(
+        "
+        SELECT
+            git_hash, compiler_version, device, COUNT(*), SUM(length(binary)), MAX(last_used)
+        FROM
+            kernels
+        GROUP BY
+            git_hash, compiler_version, device
+        ORDER BY
+            MAX(last_used) DESC;",
+    )?;
+    let mut rows = statement.query([])?;
+    println!();
+    println!(
+        "BUILD       DEVICE                        BINARIES        SIZE   LAST USED  COMPILER"
+    );
+    while let Some(row) = rows.next()? {
+        let git_hash: String = row.get(0)?;
+        let compiler_version: String = row.get(1)?;
+        let device: String = row.get(2)?;
+        let count: u64 = row.get(3)?;
+        let size: u64 = row.get(4)?;
+        let last_used: i64 = row.get(5)?;
+        println!(
+            "{:<10}  {:<28}  {:>8}  {:>10}  {:>10}  {}",
+            shorten(&git_hash, 10),
+            device,
+            count,
+            format_size(size),
+            format_age(now - last_used),
+            compiler_version
+        );
+    }
+    Ok(())
+}
+
+fn vacuum(connection: &Connection, file: &Path) -> Result<(), DynError> {
+    let size_before = fs::metadata(file)?.len();
+    connection.execute_batch("VACUUM;")?;
+    let size_after = fs::metadata(file)?.len();
+    println!(
+        "File size: {} -> {}",
+        format_size(size_before),
+        format_size(size_after)
+    );
+    Ok(())
+}
+
+fn export(connection: &Connection, command: ExportCommand) -> Result<(), DynError> {
+    fs::create_dir_all(&command.output)?;
+    let mut statement = connection.prepare("SELECT id, binary FROM kernels;")?;
+    let mut rows = statement.query([])?;
+    let mut exported = 0usize;
+    while let Some(row) = rows.next()? {
+        let id: i64 = row.get(0)?;
+        if !command.ids.is_empty() && !command.ids.contains(&id) {
+            continue;
+        }
+        let binary: Vec<u8> = row.get(1)?;
+        let mut output_path = command.output.clone();
+        output_path.push(format!("{}.elf", id));
+        fs::write(&output_path, &binary)?;
+        exported += 1;
+    }
+    if exported < command.ids.len() {
+        return Err(format!(
+            "Exported {} out of {} requested binaries",
+            exported,
+            command.ids.len()
+        )
+        .into());
+    }
+    println!(
+        "Exported {} binaries to {}",
+        exported,
+        command.output.display()
+    );
+    Ok(())
+}
+
+fn purge(connection: &Connection, command: PurgeCommand) -> Result<(), DynError> {
+    if !command.all
+        && command.older_than.is_none()
+        && command.max_size.is_none()
+        && !command.other_versions
+    {
+        return Err(
+            "Nothing to purge, use one of: --all, --older-than, --max-size, --other-versions"
+                .into(),
+        );
+    }
+    let mut removed = 0;
+    if command.all {
+        removed += connection.execute("DELETE FROM kernels;", [])?;
+    }
+    if command.other_versions {
+        let latest = connection
+            .query_row(
+                "SELECT compiler_version, git_hash FROM kernels ORDER BY last_used DESC LIMIT 1;",
+                [],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()?;
+        if let Some((compiler_version, git_hash)) = latest {
+            removed += cache::evict_other_versions(connection, &compiler_version, &git_hash)?;
+        }
+    }
+    if let Some(days) = command.older_than {
+        let cutoff = now()? - (days * 24 * 60 * 60 * 1000) as i64;
+        removed += connection.execute(
+            "DELETE FROM kernels WHERE last_used < ?1;",
+            rusqlite::params![cutoff],
+        )?;
+    }
+    if let Some(max_size) = command.max_size {
+        removed += cache::evict_least_recently_used(connection, max_size * 1024 * 1024)?;
+    }
+    println!("Removed {} binaries", removed);
+    Ok(())
+}
+
+fn max_size_description() -> String {
+    match cache::max_size_from_env() {
+        Some(max_size) => format_size(max_size),
+        None => "none".to_string(),
+    }
+}
+
+fn now() -> Result<i64, time::SystemTimeError> {
+    let now = SystemTime::now().duration_since(time::UNIX_EPOCH)?;
+    Ok(now.as_millis() as i64)
+}
+
+fn shorten(text: &str, length: usize) -> &str {
+    match text.char_indices().nth(length) {
+        Some((index, _)) => &text[..index],
+        None => text,
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+fn format_age(milliseconds: i64) -> String {
+    let seconds = milliseconds.max(0) / 1000;
+    if seconds < 60 {
+        format!("{}s ago", seconds)
+    } else if seconds < 60 * 60 {
+        format!("{}m ago", seconds / 60)
+    } else if seconds < 24 * 60 * 60 {
+        format!("{}h ago", seconds / (60 * 60))
+    } else {
+        format!("{}d ago", seconds / (24 * 60 * 60))
+    }
+}
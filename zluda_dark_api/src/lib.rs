@@ -414,6 +414,8 @@ dark_api_table!(
 );
 
 pub const ELF_MAGIC: c_uint = unsafe { std::mem::transmute(*b"\x7FELF") };
+pub const ARCHIVE_MAGIC: [u8; 8] = *b"!<arch>\n";
+const ARCHIVE_MEMBER_HEADER_SIZE: usize = 60;
 pub const FATBINC_MAGIC: c_uint = 0x466243B1;
 pub const FATBINC_VERSION_V1: c_uint = 0x1;
 pub const FATBINC_VERSION_V2: c_uint = 0x2;
@@ -476,6 +478,7 @@ bitflags! {
         const OptMask = 0x0000000000000f00;
         const CompressedOld = 0x0000000000001000;
         const CompressedNew = 0x0000000000002000;
+        const CompressedZstd = 0x0000000000008000;
 
         const _ = !0;
     }
@@ -612,12 +615,36 @@ impl CUmoduleContent {
             CUmoduleContent::Fatbin(CudaFatbin::from_header(ptr as *const FatbinHeader))
         } else if *(ptr as *const u32) == ELF_MAGIC {
             CUmoduleContent::Elf(ptr)
+        } else if *(ptr as *const [u8; 8]) == ARCHIVE_MAGIC {
+            CUmoduleContent::Archive(archive_as_slice(ptr))
         } else {
             CUmoduleContent::RawText(ptr)
         })
     }
 }
 
+// Archives passed by pointer come without a size, so it is recovered from
+// member headers: the archive ends where the next member header is malformed
+pub unsafe fn archive_as_slice(ptr: *const u8) -> &'static [u8] {
+    let mut size = ARCHIVE_MAGIC.len();
+    loop {
+        let header = slice::from_raw_parts(ptr.add(size), ARCHIVE_MEMBER_HEADER_SIZE);
+        if &header[58..] != b"`\n" {
+            break;
+        }
+        let member_size = match std::str::from_utf8(&header[48..58])
+            .ok()
+            .and_then(|member_size| member_size.trim_end().parse::<usize>().ok())
+        {
+            Some(member_size) => member_size,
+            None => break,
+        };
+        // Members are 2-byte aligned
+        size += ARCHIVE_MEMBER_HEADER_SIZE + member_size + (member_size % 2);
+    }
+    slice::from_raw_parts(ptr, size)
+}
+
 unsafe fn slice_length(ptr: *const *const c_void) -> usize {
     let mut current = ptr;
     while *current != ptr::null() {
@@ -654,6 +681,28 @@ impl FatbinModuleHandle {
         let end = current.add(fatbin_header.files_size as usize);
         Ok(FatbinModule::Files(FatbinModuleFiles { current, end }))
     }
+
+    // Host objects store fatbins (one per translation unit) back-to-back
+    // in .nv_fatbin or __nv_relfatbin section
+    pub unsafe fn split_section(section: &[u8]) -> Vec<FatbinModuleHandle> {
+        let mut result = Vec::new();
+        let mut current = section.as_ptr();
+        let end = current.add(section.len());
+        while (end as usize - current as usize) >= mem::size_of::<FatbinHeader>() {
+            let fatbin_header = &*(current as *const FatbinHeader);
+            if fatbin_header.magic != FATBIN_MAGIC {
+                break;
+            }
+            let next =
+                current.add(fatbin_header.header_size as usize + fatbin_header.files_size as usize);
+            if next > end {
+                break;
+            }
+            result.push(FatbinModuleHandle(fatbin_header));
+            current = next;
+        }
+        result
+    }
 }
 
 pub struct FatbinModuleFiles {
@@ -661,6 +710,50 @@ pub struct FatbinModuleFiles {
     end: *const u8,
 }
 
+impl FatbinModuleFiles {
+    // Returns PTX files with their sm version, highest version first.
+    // Files that fail to decompress or are not valid UTF-8 are skipped
+    pub fn ptx_files(self) -> Vec<(Cow<'static, str>, u32)> {
+        let mut ptx_files = self
+            .filter_map(|file| {
+                let file = file.ok()?;
+                if file.kind != FatbinFileKind::Ptx {
+                    return None;
+                }
+                let text = match unsafe { file.get_or_decompress() }.ok()? {
+                    Cow::Borrowed(slice) => Cow::Borrowed(std::str::from_utf8(slice).ok()?),
+                    Cow::Owned(vec) => Cow::Owned(String::from_utf8(vec).ok()?),
+                };
+                Some((text, file.sm_version))
+            })
+            .collect::<Vec<_>>();
+        ptx_files.sort_unstable_by_key(|(_, sm_version)| std::cmp::Reverse(*sm_version));
+        ptx_files
+    }
+
+    // Returns device library archives. Compressed archives are only
+    // recognized after decompression, hence the check of every non-PTX file
+    pub fn archive_files(self) -> Vec<Cow<'static, [u8]>> {
+        self.filter_map(|file| {
+            let file = file.ok()?;
+            if file.kind == FatbinFileKind::Ptx {
+                return None;
+            }
+            // Padding of uncompressed payloads would be taken for a malformed member
+            let data = match unsafe { file.get_or_decompress() }.ok()? {
+                Cow::Borrowed(data) => Cow::Borrowed(&data[..file.payload_size.min(data.len())]),
+                data => data,
+            };
+            if data.starts_with(&ARCHIVE_MAGIC) {
+                Some(data)
+            } else {
+                None
+            }
+        })
+        .collect()
+    }
+}
+
 impl Iterator for FatbinModuleFiles {
     type Item = Result<FatbinFile, UnexpectedFieldError>;
 
@@ -694,10 +787,23 @@ pub struct FatbinFile {
     uncompressed_payload: usize,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum FatbinCompression {
     None,
     Zlib,
     Lz4,
+    Zstd,
+}
+
+impl FatbinCompression {
+    pub fn name(self) -> &'static str {
+        match self {
+            FatbinCompression::None => "uncompressed",
+            FatbinCompression::Zlib => "zlib",
+            FatbinCompression::Lz4 => "LZ4",
+            FatbinCompression::Zstd => "zstd",
+        }
+    }
 }
 
 impl FatbinFile {
@@ -735,11 +841,25 @@ impl FatbinFile {
             .contains(FatbinFileHeaderFlags::CompressedNew)
         {
             FatbinCompression::Lz4
+        } else if fatbin_file
+            .flags
+            .contains(FatbinFileHeaderFlags::CompressedZstd)
+        {
+            FatbinCompression::Zstd
         } else {
             FatbinCompression::None
         };
         let data = (fatbin_file as *const _ as *const u8).add(fatbin_file.header_size as usize);
         let padded_payload_size = fatbin_file.padded_payload_size as usize;
+        let kind = if kind == FatbinFileKind::Elf
+            && compression == FatbinCompression::None
+            && padded_payload_size >= ARCHIVE_MAGIC.len()
+            && *(data as *const [u8; 8]) == ARCHIVE_MAGIC
+        {
+            FatbinFileKind::Archive
+        } else {
+            kind
+        };
         let payload_size = fatbin_file.payload_size as usize;
         let uncompressed_payload = fatbin_file.uncompressed_payload as usize;
         let sm_version = fatbin_file.sm_version;
@@ -766,16 +886,26 @@ impl FatbinFile {
                         }
                         Ok(Cow::Owned(decompressed))
                     }
-                    None => Err(DecompressionFailure),
+                    None => Err(DecompressionFailure(self.compression)),
                 }
             }
             FatbinCompression::Zlib => {
                 let compressed =
                     std::slice::from_raw_parts(self.data.cast(), self.padded_payload_size);
                 Ok(Cow::Owned(
-                    cloudflare_zlib::inflate(compressed).map_err(|_| DecompressionFailure)?,
+                    cloudflare_zlib::inflate(compressed)
+                        .map_err(|_| DecompressionFailure(self.compression))?,
                 ))
             }
+            FatbinCompression::Zstd => {
+                let compressed = std::slice::from_raw_parts(self.data, self.payload_size);
+                let mut decompressed = zstd::stream::decode_all(compressed)
+                    .map_err(|_| DecompressionFailure(self.compression))?;
+                if self.kind == FatbinFileKind::Ptx && decompressed.last() == Some(&0) {
+                    decompressed.pop(); // remove trailing zero
+                }
+                Ok(Cow::Owned(decompressed))
+            }
             FatbinCompression::None => Ok(Cow::Borrowed(slice::from_raw_parts(
                 self.data,
                 self.padded_payload_size as usize,
@@ -812,7 +942,7 @@ impl FatbinFile {
 }
 
 #[derive(Debug)]
-pub struct DecompressionFailure;
+pub struct DecompressionFailure(pub FatbinCompression);
 
 pub fn anti_zluda_hash<F: FnMut(u32) -> AntiZludaHashInputDevice>(
     return_known_value: bool,
@@ -1123,8 +1253,148 @@ mod tests {
 
     use crate::{
         anti_zluda_hash_impl, anti_zluda_hash_round_v1, AntiZludaHashInput,
-        AntiZludaHashInputDevice,
+        AntiZludaHashInputDevice, CUmoduleContent, FatbinCompression, FatbinFileHeader,
+        FatbinFileHeaderFlags, FatbinFileKind, FatbinHeader, FatbinModule, FatbinModuleHandle,
+        ARCHIVE_MAGIC, FATBIN_FILE_HEADER_KIND_ELF, FATBIN_FILE_HEADER_KIND_PTX,
+        FATBIN_FILE_HEADER_VERSION_CURRENT, FATBIN_MAGIC, FATBIN_VERSION,
     };
+    use std::{mem, slice};
+
+    fn archive(members: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut result = ARCHIVE_MAGIC.to_vec();
+        for (name, data) in members {
+            let header = format!(
+                "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+                format!("{}/", name),
+                0,
+                0,
+                0,
+                644,
+                data.len()
+            );
+            result.extend_from_slice(header.as_bytes());
+            result.extend_from_slice(data);
+            if data.len() % 2 != 0 {
+                result.push(b'\n');
+            }
+        }
+        result
+    }
+
+    fn aligned(data: &[u8]) -> Vec<u64> {
+        let mut buffer = vec![0u64; (data.len() + 7) / 8];
+        unsafe { slice::from_raw_parts_mut(buffer.as_mut_ptr().cast::<u8>(), data.len()) }
+            .copy_from_slice(data);
+        buffer
+    }
+
+    #[test]
+    fn archive_size_is_recovered_from_member_headers() {
+        let library = archive(&[("a.ptx", b"abc"), ("b.ptx", b"defg")]);
+        // Garbage after the archive must not be mistaken for a member
+        let mut image = library.clone();
+        image.extend_from_slice(&[0xCC; 64]);
+        let image = aligned(&image);
+        match unsafe { CUmoduleContent::from_ptr(image.as_ptr().cast()) }.unwrap() {
+            CUmoduleContent::Archive(slice) => assert_eq!(slice, &library[..]),
+            _ => panic!(),
+        }
+    }
+
+    // Single file fatbin, payload is padded with the given byte
+    fn fatbin(kind: u16, flags: FatbinFileHeaderFlags, payload: &[u8], padding: u8) -> Vec<u64> {
+        let mut padded_payload = payload.to_vec();
+        while padded_payload.len() % 8 != 0 {
+            padded_payload.push(padding);
+        }
+        let file_header = FatbinFileHeader {
+            kind,
+            version: FATBIN_FILE_HEADER_VERSION_CURRENT,
+            header_size: mem::size_of::<FatbinFileHeader>() as u32,
+            padded_payload_size: padded_payload.len() as u32,
+            unknown0: 0,
+            payload_size: payload.len() as u32,
+            unknown1: 0,
+            ptx_version: 0,
+            sm_version: 60,
+            file_name_offset: 0,
+            file_name_len: 0,
+            flags,
+            unknown6: 0,
+            uncompressed_payload: 0,
+        };
+        let header = FatbinHeader {
+            magic: FATBIN_MAGIC,
+            version: FATBIN_VERSION,
+            header_size: mem::size_of::<FatbinHeader>() as u16,
+            files_size: (mem::size_of::<FatbinFileHeader>() + padded_payload.len()) as _,
+        };
+        let mut image = Vec::new();
+        image.extend_from_slice(unsafe {
+            slice::from_raw_parts(
+                &header as *const _ as *const u8,
+                mem::size_of::<FatbinHeader>(),
+            )
+        });
+        image.extend_from_slice(unsafe {
+            slice::from_raw_parts(
+                &file_header as *const _ as *const u8,
+                mem::size_of::<FatbinFileHeader>(),
+            )
+        });
+        image.extend_from_slice(&padded_payload);
+        aligned(&image)
+    }
+
+    #[test]
+    fn fatbin_archive_file_is_detected() {
+        let library = archive(&[("a.ptx", b"abcde")]);
+        let image = fatbin(
+            FATBIN_FILE_HEADER_KIND_ELF,
+            FatbinFileHeaderFlags::Is64Bit,
+            &library,
+            b'\n',
+        );
+        let module = FatbinModuleHandle(image.as_ptr().cast());
+        let kinds = match unsafe { module.get() }.unwrap() {
+            FatbinModule::Files(files) => files.map(|file| file.unwrap().kind).collect::<Vec<_>>(),
+            FatbinModule::Elf(_) => panic!(),
+        };
+        assert_eq!(kinds, vec![FatbinFileKind::Archive]);
+        let archives = match unsafe { module.get() }.unwrap() {
+            FatbinModule::Files(files) => files.archive_files(),
+            FatbinModule::Elf(_) => panic!(),
+        };
+        assert_eq!(archives.len(), 1);
+        assert_eq!(&*archives[0], &library[..]);
+    }
+
+    #[test]
+    fn fatbin_zstd_file_is_decompressed() {
+        let ptx = ".version 6.5\n.target sm_60\n.address_size 64\n";
+        let mut uncompressed = ptx.as_bytes().to_vec();
+        uncompressed.push(0);
+        let compressed = zstd::stream::encode_all(&uncompressed[..], 0).unwrap();
+        // zstd rejects trailing garbage, so this checks that padding is not decompressed
+        let image = fatbin(
+            FATBIN_FILE_HEADER_KIND_PTX,
+            FatbinFileHeaderFlags::Is64Bit | FatbinFileHeaderFlags::CompressedZstd,
+            &compressed,
+            0xCC,
+        );
+        let module = FatbinModuleHandle(image.as_ptr().cast());
+        let mut files = match unsafe { module.get() }.unwrap() {
+            FatbinModule::Files(files) => files,
+            FatbinModule::Elf(_) => panic!(),
+        };
+        let file = files.next().unwrap().unwrap();
+        assert!(files.next().is_none());
+        assert_eq!(file.compression, FatbinCompression::Zstd);
+        assert_eq!(
+            &*unsafe { file.get_or_decompress() }.unwrap(),
+            ptx.as_bytes()
+        );
+    }
 
     #[test]
     fn anti_zluda_hash_round_sample() {
@@ -624,9 +624,91 @@ impl CudaDisplay for cuda_types::CUlaunchAttribute_st {
         &self,
         _fn_name: &'static str,
         _index: usize,
-        _writer: &mut (impl std::io::Write + ?Sized),
+        writer: &mut (impl std::io::Write + ?Sized),
     ) -> std::io::Result<()> {
-        todo!()
+        writer.write_all(b"{ id: ")?;
+        CudaDisplay::write(&self.id, "", 0, writer)?;
+        writer.write_all(b", value: ")?;
+        match self.id {
+            cuda_types::CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_ACCESS_POLICY_WINDOW => {
+                CudaDisplay::write(unsafe { &self.value.accessPolicyWindow }, "", 0, writer)?;
+            }
+            cuda_types::CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_COOPERATIVE => {
+                CudaDisplay::write(unsafe { &self.value.cooperative }, "", 0, writer)?;
+            }
+            cuda_types::CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_SYNCHRONIZATION_POLICY => {
+                CudaDisplay::write(unsafe { &self.value.syncPolicy }, "", 0, writer)?;
+            }
+            cuda_types::CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_CLUSTER_DIMENSION => {
+                CudaDisplay::write(unsafe { &self.value.clusterDim }, "", 0, writer)?;
+            }
+            cuda_types::CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_CLUSTER_SCHEDULING_POLICY_PREFERENCE => {
+                CudaDisplay::write(
+                    unsafe { &self.value.clusterSchedulingPolicyPreference },
+                    "",
+                    0,
+                    writer,
+                )?;
+            }
+            cuda_types::CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_PROGRAMMATIC_STREAM_SERIALIZATION => {
+                CudaDisplay::write(
+                    unsafe { &self.value.programmaticStreamSerializationAllowed },
+                    "",
+                    0,
+                    writer,
+                )?;
+            }
+            cuda_types::CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_PROGRAMMATIC_EVENT => {
+                CudaDisplay::write(unsafe { &self.value.programmaticEvent }, "", 0, writer)?;
+            }
+            cuda_types::CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_PRIORITY => {
+                CudaDisplay::write(unsafe { &self.value.priority }, "", 0, writer)?;
+            }
+            cuda_types::CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_MEM_SYNC_DOMAIN_MAP => {
+                CudaDisplay::write(unsafe { &self.value.memSyncDomainMap }, "", 0, writer)?;
+            }
+            cuda_types::CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_MEM_SYNC_DOMAIN => {
+                CudaDisplay::write(unsafe { &self.value.memSyncDomain }, "", 0, writer)?;
+            }
+            _ => writer.write_all(b"...")?,
+        }
+        writer.write_all(b" }")
+    }
+}
+
+impl CudaDisplay for cuda_types::CUlaunchConfig_st {
+    fn write(
+        &self,
+        _fn_name: &'static str,
+        _index: usize,
+        writer: &mut (impl std::io::Write + ?Sized),
+    ) -> std::io::Result<()> {
+        write!(
+            writer,
+            "{{ gridDimX: {}, gridDimY: {}, gridDimZ: {}, blockDimX: {}, blockDimY: {}, blockDimZ: {}, sharedMemBytes: {}, hStream: ",
+            self.gridDimX,
+            self.gridDimY,
+            self.gridDimZ,
+            self.blockDimX,
+            self.blockDimY,
+            self.blockDimZ,
+            self.sharedMemBytes
+        )?;
+        CudaDisplay::write(&self.hStream, "", 0, writer)?;
+        writer.write_all(b", attrs: ")?;
+        if self.numAttrs == 0 || self.attrs == ptr::null_mut() {
+            CudaDisplay::write(&self.attrs, "", 0, writer)?;
+        } else {
+            writer.write_all(b"[")?;
+            for i in 0..self.numAttrs as usize {
+                CudaDisplay::write(unsafe { &*self.attrs.add(i) }, "", 0, writer)?;
+                if i != self.numAttrs as usize - 1 {
+                    writer.write_all(b", ")?;
+                }
+            }
+            writer.write_all(b"]")?;
+        }
+        write!(writer, ", numAttrs: {} }}", self.numAttrs)
     }
 }
 
@@ -919,7 +1001,8 @@ cuda_derive_display_trait!(
         CUuuid_st,
         HGPUNV,
         CUgraphNodeParams_st,
-        CUlaunchAttribute_st
+        CUlaunchAttribute_st,
+        CUlaunchConfig_st
     ],
     [
         cuCtxCreate_v3,
@@ -18,8 +18,9 @@ use std::io::Write;
 use std::path::PathBuf;
 use std::str::Utf8Error;
 use zluda_dark_api::AnyUInt;
-use zluda_dark_api::FatbinFileKind;
 use zluda_dark_api::DecompressionFailure;
+use zluda_dark_api::FatbinCompression;
+use zluda_dark_api::FatbinFileKind;
 use zluda_dark_api::UnexpectedFieldError;
 
 const LOG_PREFIX: &[u8] = b"[ZLUDA_DUMP] ";
@@ -370,7 +371,11 @@ pub(crate) enum LogEntry {
     MalformedModulePath(Utf8Error),
     NonUtf8ModuleText(Utf8Error),
     ModuleParsingError(String),
-    Lz4DecompressionFailure,
+    DecompressionFailure(FatbinCompression),
+    CompressedModuleFile {
+        kind: FatbinFileKind,
+        compression: FatbinCompression,
+    },
     UnknownExportTableFn,
     UnexpectedBinaryField {
         field_name: &'static str,
@@ -447,7 +452,17 @@ impl Display for LogEntry {
                     file_name
                 )
             }
-            LogEntry::Lz4DecompressionFailure => write!(f, "Decompression failure"),
+            LogEntry::DecompressionFailure(compression) => {
+                write!(f, "Decompression failure ({})", compression.name())
+            }
+            LogEntry::CompressedModuleFile { kind, compression } => {
+                write!(
+                    f,
+                    "Found {} {} module file",
+                    compression.name(),
+                    human_readable(*kind)
+                )
+            }
             LogEntry::UnknownExportTableFn => write!(f, "Unknown export table function"),
             LogEntry::UnexpectedBinaryField {
                 field_name,
@@ -592,8 +607,8 @@ impl From<io::Error> for LogEntry {
 }
 
 impl From<DecompressionFailure> for LogEntry {
-    fn from(_err: DecompressionFailure) -> Self {
-        LogEntry::Lz4DecompressionFailure
+    fn from(err: DecompressionFailure) -> Self {
+        LogEntry::DecompressionFailure(err.0)
     }
 }
 
@@ -15,7 +15,7 @@ use std::{
     path::PathBuf,
     rc::Rc,
 };
-use zluda_dark_api::{CUmoduleContent, FatbinFileKind};
+use zluda_dark_api::{CUmoduleContent, FatbinCompression, FatbinFileKind};
 
 // This struct is the heart of CUDA state tracking, it:
 // * receives calls from the probes about changes to CUDA state
@@ -146,6 +146,12 @@ fn visit_module(
             zluda_dark_api::FatbinModule::Files(files) => {
                 for (index, maybe_file) in files.enumerate() {
                     if let Some(file) = fn_logger.log_unwrap(maybe_file.map_err(Into::into)) {
+                        if file.compression != FatbinCompression::None {
+                            fn_logger.log(LogEntry::CompressedModuleFile {
+                                kind: file.kind,
+                                compression: file.compression,
+                            });
+                        }
                         if let Some(buffer) = fn_logger
                             .log_unwrap(unsafe { file.get_or_decompress() }.map_err(Into::into))
                         {
@@ -624,9 +630,11 @@ impl DumpWriter {
             Some(d) => d.clone(),
         };
         log_file.push(Self::get_file_name2(cu_module_index, module_file, "log"));
+        let module_file_name = Self::get_file_name2(cu_module_index, module_file, "ptx");
         let mut file = File::create(log_file)?;
         for error in errors {
-            let pretty_print_error = unsafe { DisplayParseError::new(error, module_text) };
+            let pretty_print_error = unsafe { DisplayParseError::new(error, module_text) }
+                .with_file_name(&module_file_name);
             writeln!(file, "{}", pretty_print_error)?;
         }
         Ok(())